@@ -1,12 +1,143 @@
-use crate::config::Config;
-use crate::services::radio::{self, RadioController, RadioMode};
-use crate::services::{RbnClient, RbnMessage, SpotStore, VfdDisplay};
+use crate::config::{parse_calls, settings, AgeIndicator, Config, Theme};
+use crate::error::Error;
+use crate::models::{
+    bearing, locator_center, to_locator, AggregatedSpot, Band, BandPlan, BustedCallPolicy,
+    FrequencyUnit, RawSpot, SourcePolicy, SpotSource, Trend, UtcTime,
+};
+use crate::pipeline;
+use crate::plugins;
+use crate::services::radio::{
+    self, slice_letter, AntennaOutput, RadioController, RadioError, RadioMode,
+};
+use crate::services::{
+    beacon_lines, follow_lines, heading_lines, heading_text, parse_manual_spots, run_detached,
+    write_spots, Alert, AlertCenter, AlertDelivery, Contest, ContestLog, DataFileKind,
+    DataFileMessage, DataFileUpdater, DataFiles, DisplayGeometry, ExportFormat, HistoryRow,
+    HttpApi, IdleContent, ModeClass, MqttPublisher, Multiplier, NeedsMatrix, Notification,
+    Notifications, PropagationFetcher, PropagationMessage, ProtocolKind, RadioInfo,
+    RadioInfoListener, RbnClient, RbnMessage, ScreensaverStyle, ScriptAction, ScriptHost,
+    SelfSpots, SessionEvent, SessionPlayer, SessionRecorder, Severity, SolarData, SpotDb,
+    SpotStore, VfdContent, VfdDisplay, Watchdog,
+};
+use crate::ui::{MapMarker, Phosphor, VfdPreview, WorldMap};
 use eframe::egui;
-use std::time::{Duration, Instant};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
 
 /// Max lines to keep in raw data log
 const RAW_DATA_LOG_MAX_LINES: usize = 500;
 
+/// How long an alert stays on the VFD and in the banner
+const ALERT_DISPLAY_TIME: Duration = Duration::from_secs(10);
+
+/// How far from the logger's frequency the follow page lists spots
+const FOLLOW_RANGE_KHZ: f64 = 5.0;
+
+/// How long the previous frequency can be restored after tuning to a spot
+const TUNE_UNDO_TIME: Duration = Duration::from_secs(10);
+
+/// How long a new or changed spot row stays highlighted
+const ROW_HIGHLIGHT_TIME: Duration = Duration::from_secs(3);
+
+/// Spots within this many kHz of the watched frequency are listed
+const WATCH_RANGE_KHZ: f64 = 1.0;
+
+/// How long "Ignore for 1 hour" drops a call's spots
+const SNOOZE_TIME: Duration = Duration::from_secs(60 * 60);
+
+/// How long the startup health page stays on the VFD
+const STARTUP_PAGE_TIME: Duration = Duration::from_secs(5);
+
+/// How often the VFD handoff flag file is checked
+const HANDOFF_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often settings.toml is checked for edits made outside the app
+const SETTINGS_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long settings.toml must go unwritten before an edit is read, so a
+/// half-saved file isn't picked up
+const SETTINGS_SETTLE_TIME: Duration = Duration::from_secs(1);
+
+/// How often the settings are checked for changes made in the app
+const AUTOSAVE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long after a change the settings are written, so a run of edits
+/// (e.g. dragging a slider) is saved once
+const AUTOSAVE_DELAY: Duration = Duration::from_secs(2);
+
+/// How often the watchdog checks the feed and serial writer
+const WATCHDOG_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A single serial write taking this long counts as a wedged writer
+const SERIAL_STALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often the radio's VFO is read to detect band changes
+const VFO_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Window for the spot count shown by the ticker screensaver
+const SPOT_TICKER_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Number of frequency bins per band in the band scope
+const BAND_SCOPE_BINS: usize = 64;
+
+/// Skimmers listed in the Skimmers section
+const SKIMMER_LEADERBOARD_SIZE: usize = 25;
+
+/// Initial and smallest size of the full window
+pub const WINDOW_SIZE: egui::Vec2 = egui::vec2(500.0, 600.0);
+pub const MIN_WINDOW_SIZE: egui::Vec2 = egui::vec2(400.0, 400.0);
+
+/// Size of the always-on-top mini window
+const MINI_WINDOW_SIZE: egui::Vec2 = egui::vec2(280.0, 130.0);
+
+/// File in the data directory holding the confirmed DXCC slots
+const NEEDS_FILE: &str = "needs.txt";
+
+/// Last seen values of a spot row, for change highlighting
+struct RowState {
+    snr: i32,
+    frequency_khz: f64,
+    changed: Instant,
+}
+
+/// Options given on the command line
+#[derive(Debug, Default)]
+pub struct LaunchOptions {
+    /// Record pipeline events to this file from startup
+    pub record_session: Option<PathBuf>,
+    /// Play back a recorded session instead of connecting to RBN
+    pub replay_session: Option<PathBuf>,
+    /// Run without the GUI from the saved settings
+    pub headless: bool,
+    /// Keep settings and data next to the executable
+    pub portable: bool,
+}
+
+impl LaunchOptions {
+    /// Parse command-line arguments (excluding the program name)
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            let mut path = || {
+                args.next()
+                    .map(PathBuf::from)
+                    .ok_or_else(|| format!("{} requires a file argument", arg))
+            };
+            match arg.as_str() {
+                "--record-session" => options.record_session = Some(path()?),
+                "--replay-session" => options.replay_session = Some(path()?),
+                "--headless" => options.headless = true,
+                "--portable" => options.portable = true,
+                _ => return Err(format!("Unknown argument: {}", arg)),
+            }
+        }
+        Ok(options)
+    }
+}
+
 /// Main application state
 pub struct RbnVfdApp {
     config: Config,
@@ -16,10 +147,62 @@ pub struct RbnVfdApp {
     callsign_input: String,
     selected_port: String,
     available_ports: Vec<String>,
-    status_message: String,
+    notifications: Notifications,
+    /// Whether the notification history drawer is open
+    show_notification_history: bool,
     is_connected: bool,
     last_purge: Instant,
     last_port_refresh: Instant,
+    last_handoff_check: Instant,
+    last_settings_check: Instant,
+    /// settings.toml's modification time and setting values when last read or written
+    settings_file: (Option<SystemTime>, Vec<String>),
+    last_autosave_check: Instant,
+    /// Settings as last written to settings.toml
+    saved_config: Config,
+    /// When the settings first differed from `saved_config` since the last save
+    config_changed_at: Option<Instant>,
+    /// settings.toml couldn't be read at startup, so it is never written
+    /// over until it reloads cleanly
+    settings_unreadable: bool,
+    /// Present while MQTT publishing is enabled
+    mqtt: Option<MqttPublisher>,
+    /// Frame last published over MQTT
+    last_published_frame: Vec<String>,
+    /// Present while the HTTP control API is enabled and listening
+    http_api: Option<HttpApi>,
+    /// Present while following N1MM's RadioInfo broadcasts
+    radio_info: Option<RadioInfoListener>,
+    /// The logger's active radio, from RadioInfo
+    logger_radio: Option<RadioInfo>,
+    /// Present while solar data refreshes are on
+    propagation: Option<PropagationFetcher>,
+    /// Latest solar indices, kept through fetch failures
+    solar: Option<SolarData>,
+    /// Skimmers hearing our own callsign, kept out of the spot list
+    self_spots: SelfSpots,
+    /// Present while the spot history database is enabled
+    spot_db: Option<SpotDb>,
+    /// Callsign searched for in the History section
+    history_callsign: String,
+    /// How far back the history search looks
+    history_hours: u32,
+    history_results: Vec<HistoryRow>,
+    /// Format chosen in the Export section
+    export_format: ExportFormat,
+    /// Export everything heard this session rather than the shown spots
+    export_history: bool,
+    /// Present while a hook script is configured and running
+    script_host: Option<ScriptHost>,
+    /// Callsigns the hook script asked to keep out of the spot list
+    script_hidden: HashSet<String>,
+    /// Whether the spot list was empty on the last update, to send `idle` once
+    spots_idle: bool,
+    /// Present in service mode
+    watchdog: Option<Watchdog>,
+    last_watchdog_check: Instant,
+    /// Last time anything arrived from the RBN feed
+    last_feed_activity: Instant,
     /// Raw telnet data log for debugging
     raw_data_log: Vec<String>,
     /// Currently selected spot for tuning
@@ -32,26 +215,175 @@ pub struct RbnVfdApp {
     show_radio_settings: bool,
     /// Temporary radio config for settings dialog
     temp_radio_config: Option<crate::config::RadioConfig>,
+    /// Tune steps being edited in the radio settings dialog
+    tune_steps_input: String,
+    /// Antennas being edited in the radio settings dialog
+    antennas_input: String,
+    /// Profile name being edited in the radio settings dialog
+    radio_profile_name: String,
+    /// Name being edited in the configuration profile menu
+    profile_name: String,
+    /// File to export settings to or import them from
+    settings_file_path: String,
+    /// Shrunk to an always-on-top strip with just the VFD preview and newest spot
+    mini_mode: bool,
+    /// Window size to return to when leaving mini mode
+    full_window_size: Option<egui::Vec2>,
+    /// Theme, accent color and scale last applied to the egui context
+    applied_theme: Option<(Theme, Option<[u8; 3]>, f32)>,
+    /// Manual QSY entry ("14025.5" or "7185 lsb")
+    qsy_input: String,
+    /// Watchlist being edited, calls separated by commas or spaces
+    watchlist_input: String,
+    /// Pasted manual spots, one per line
+    manual_spot_input: String,
+    /// Per-row state used to highlight new and changed spots
+    row_states: HashMap<String, RowState>,
+    /// Frequency segment (low, high kHz) picked in the band scope to filter the table
+    scope_segment: Option<(f64, f64)>,
+    /// Band sections folded away in the grouped spot table
+    collapsed_bands: HashSet<String>,
+    /// Whether to show the "All Settings" window
+    show_all_settings: bool,
+    /// Edit buffers for the "All Settings" window, one per entry in `settings()`
+    setting_edits: Vec<String>,
+    /// Directory holding downloaded data files
+    data_dir: Option<PathBuf>,
+    /// Reference data (country file, LoTW users, most wanted)
+    data_files: DataFiles,
+    /// Background updater for the data files
+    data_updater: Option<DataFileUpdater>,
+    /// Presence tracking and alert routing
+    alert_center: AlertCenter,
+    /// Alert currently shown in the banner
+    active_alert: Option<Alert>,
+    /// Bytes exchanged with the RBN server this session
+    rbn_traffic: Arc<AtomicU64>,
+    /// Active session recording
+    session_recorder: Option<SessionRecorder>,
+    /// Session being replayed instead of live RBN data
+    session_player: Option<SessionPlayer>,
+    /// Last VFD frame written to the session recording
+    last_recorded_frame: Vec<String>,
+    /// Last filter settings written to the session recording
+    last_recorded_filters: Option<(i32, u32)>,
+    last_vfo_poll: Instant,
+    /// Band the radio was last seen on
+    radio_band: Option<String>,
+    /// Antenna last selected through the antenna switch
+    antenna: Option<u8>,
+    /// Mode of the last tune, restored by undo
+    tuned_mode: Option<RadioMode>,
+    /// Frequency and mode before the last spot tune, and when it was left
+    tune_undo: Option<(f64, RadioMode, Instant)>,
+    /// Spot tune waiting for confirmation: target, mode and the radio's frequency
+    pending_tune: Option<(f64, RadioMode, f64)>,
+    /// Band whose spots are moved to the top, and until when
+    band_boost: Option<(String, Instant)>,
+    /// QSOs and worked multipliers for contest mode
+    contest_log: ContestLog,
+    /// Path of the ADIF or Cabrillo log to import into contest mode
+    contest_import_path: String,
+    /// Confirmed DXCC slots; spots outside them are marked NEW
+    needs: NeedsMatrix,
+    /// Path of the LoTW or Club Log ADIF export to import into the needs matrix
+    needs_import_path: String,
+    /// Slot being entered by hand in the DXCC Needs section
+    needs_entry: (String, String, ModeClass),
+    /// Frequency being run on, watched for other stations moving in
+    run_frequency_khz: Option<f64>,
+    /// Run frequency entry in kHz
+    run_frequency_input: String,
+    /// Frequency watch entry in kHz
+    watch_frequency_input: String,
+    /// Band shown on the My Signal map (None = all bands)
+    my_signal_band: Option<String>,
+    /// Show spots on a world map instead of the table
+    spot_map_view: bool,
+    /// Plot the skimmers heard this session on the spot map
+    map_show_skimmers: bool,
 }
 
 impl RbnVfdApp {
     /// Create a new application instance
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        let config = Config::load();
+    pub fn new(cc: &eframe::CreationContext<'_>, launch: LaunchOptions) -> Self {
+        let config_found = Config::exists();
+        let (config, load_error) = match Config::load() {
+            Ok(config) => (config, None),
+            Err(e) => (Config::default(), Some(e)),
+        };
         let radio_controller = radio::create_controller(&config.radio);
         let spot_store = SpotStore::new();
         let mut vfd_display = VfdDisplay::new();
         vfd_display.set_scroll_interval(config.scroll_interval_seconds);
+        vfd_display.set_marquee(config.marquee_rate());
+        vfd_display.set_frequency_format(config.frequency_format());
         vfd_display.set_random_char_percent(config.random_char_percent);
+        vfd_display.set_screensaver(config.screensaver);
+        vfd_display.set_callsign(&config.callsign);
+        vfd_display.set_flash_new_spots(config.flash_new_spots);
+        vfd_display.set_idle_content(config.idle_content);
+        vfd_display.set_content(config.vfd_content);
+        vfd_display.set_watchlist(&config.watchlist);
+        vfd_display.set_station_message(&config.station_message);
+        vfd_display.set_local_clock(config.local_clock());
+        vfd_display.set_geometry(config.display_geometry);
+        vfd_display.set_protocol(config.display_protocol);
+        vfd_display.set_brightness(config.display_brightness as u8);
+        vfd_display.set_pages(config.carousel_pages());
+        vfd_display.set_band_plan(config.band_plan.clone());
+
+        let data_dir = Config::data_dir();
+        let data_files = data_dir.as_deref().map(DataFiles::load).unwrap_or_default();
+        let needs = data_dir
+            .as_deref()
+            .map(|dir| NeedsMatrix::load(&dir.join(NEEDS_FILE)))
+            .unwrap_or_default();
+        let data_updater = data_dir.clone().map(|dir| {
+            DataFileUpdater::new(
+                dir,
+                Duration::from_secs(config.data_update_hours as u64 * 3600),
+            )
+        });
+
+        let watchdog = config.watchdog.then(|| {
+            let ctx = cc.egui_ctx.clone();
+            let log_path = data_dir.as_ref().map(|dir| dir.join("service.log"));
+            Watchdog::start(log_path, move || ctx.request_repaint())
+        });
+
+        let mut alert_center = AlertCenter::new();
+        alert_center.set_idle_timeout(idle_timeout(config.away_idle_minutes));
+
+        let mut notifications = Notifications::new("Ready".to_string());
+        let session_recorder = launch.record_session.as_deref().and_then(|path| {
+            SessionRecorder::create(path)
+                .map_err(|e| notifications.error(e))
+                .ok()
+        });
+        let session_player = launch.replay_session.as_deref().and_then(|path| {
+            SessionPlayer::load(path)
+                .map_err(|e| notifications.error(e))
+                .ok()
+        });
+        if let Some(path) = launch
+            .replay_session
+            .as_deref()
+            .filter(|_| session_player.is_some())
+        {
+            notifications.info(format!("Replaying {}", path.display()));
+        }
 
-        let available_ports = VfdDisplay::available_ports();
+        let available_ports = config.display_ports();
         let selected_port = if available_ports.contains(&config.serial_port) {
             config.serial_port.clone()
         } else {
             available_ports.first().cloned().unwrap_or_default()
         };
 
-        Self {
+        let settings_file = (Config::modified_time(), config.setting_values());
+        let saved_config = config.clone();
+        let mut app = Self {
             callsign_input: config.callsign.clone(),
             config,
             spot_store,
@@ -59,162 +391,2657 @@ impl RbnVfdApp {
             rbn_client: None,
             selected_port,
             available_ports,
-            status_message: "Ready".to_string(),
+            notifications,
+            show_notification_history: false,
             is_connected: false,
             last_purge: Instant::now(),
             last_port_refresh: Instant::now(),
+            last_handoff_check: Instant::now(),
+            last_settings_check: Instant::now(),
+            settings_file,
+            last_autosave_check: Instant::now(),
+            saved_config,
+            config_changed_at: None,
+            settings_unreadable: load_error.is_some(),
+            mqtt: None,
+            last_published_frame: Vec::new(),
+            http_api: None,
+            radio_info: None,
+            logger_radio: None,
+            propagation: None,
+            solar: None,
+            self_spots: SelfSpots::new(),
+            spot_db: None,
+            history_callsign: String::new(),
+            history_hours: 48,
+            history_results: Vec::new(),
+            export_format: ExportFormat::Csv,
+            export_history: false,
+            script_host: None,
+            script_hidden: HashSet::new(),
+            spots_idle: true,
+            watchdog,
+            last_watchdog_check: Instant::now(),
+            last_feed_activity: Instant::now(),
             raw_data_log: Vec::new(),
             selected_spot: None,
             radio_controller,
             radio_error: None,
             show_radio_settings: false,
             temp_radio_config: None,
+            tune_steps_input: String::new(),
+            antennas_input: String::new(),
+            radio_profile_name: String::new(),
+            profile_name: String::new(),
+            settings_file_path: String::new(),
+            mini_mode: false,
+            full_window_size: None,
+            applied_theme: None,
+            qsy_input: String::new(),
+            watchlist_input: String::new(),
+            manual_spot_input: String::new(),
+            row_states: HashMap::new(),
+            scope_segment: None,
+            collapsed_bands: HashSet::new(),
+            show_all_settings: false,
+            setting_edits: Vec::new(),
+            data_dir,
+            data_files,
+            data_updater,
+            alert_center,
+            active_alert: None,
+            run_frequency_khz: None,
+            run_frequency_input: String::new(),
+            watch_frequency_input: String::new(),
+            my_signal_band: None,
+            spot_map_view: false,
+            map_show_skimmers: false,
+            rbn_traffic: Arc::new(AtomicU64::new(0)),
+            session_recorder,
+            session_player,
+            last_recorded_frame: Vec::new(),
+            last_recorded_filters: None,
+            last_vfo_poll: Instant::now(),
+            radio_band: None,
+            antenna: None,
+            tuned_mode: None,
+            tune_undo: None,
+            pending_tune: None,
+            band_boost: None,
+            contest_log: ContestLog::new(Contest::CqWw),
+            contest_import_path: String::new(),
+            needs,
+            needs_import_path: String::new(),
+            needs_entry: (String::new(), "20m".to_string(), ModeClass::Cw),
+        };
+        if let Some(e) = load_error {
+            app.report_error(&e);
         }
+        app.startup(config_found);
+        app
     }
 
-    /// Connect to RBN server
-    fn connect_rbn(&mut self) {
-        if self.callsign_input.trim().is_empty() {
-            self.status_message = "Please enter a callsign".to_string();
-            return;
+    /// Start, stop or restart the MQTT publisher to match the settings
+    fn update_mqtt(&mut self) {
+        let wanted = self.config.mqtt.enabled && self.session_player.is_none();
+        if !wanted {
+            self.mqtt = None;
+        } else if self.mqtt.as_ref().map(|m| m.config()) != Some(&self.config.mqtt) {
+            self.mqtt = Some(MqttPublisher::start(self.config.mqtt.clone()));
+            self.last_published_frame.clear();
         }
+    }
 
-        let callsign = self.callsign_input.trim().to_uppercase();
-        self.config.callsign = callsign.clone();
+    /// Start, stop or rebind the HTTP control API to match the settings
+    fn update_http_api(&mut self) {
+        if !self.config.api_enabled {
+            self.http_api = None;
+        } else if self.http_api.as_ref().map(|api| api.bind_address())
+            != Some(self.config.api_bind.as_str())
+        {
+            self.http_api = None;
+            match HttpApi::start(&self.config.api_bind) {
+                Ok(api) => self.http_api = Some(api),
+                Err(e) => self.report_error(&e),
+            }
+        }
+    }
 
-        let client = RbnClient::new();
-        client.connect(callsign);
+    /// Start, rebind or stop the N1MM RadioInfo listener to match the settings
+    fn update_radio_info(&mut self) {
+        if !self.config.n1mm_enabled {
+            self.radio_info = None;
+            self.logger_radio = None;
+        } else if self.radio_info.as_ref().map(|l| l.port()) != Some(self.config.n1mm_port) {
+            self.radio_info = None;
+            match RadioInfoListener::start(self.config.n1mm_port) {
+                Ok(listener) => self.radio_info = Some(listener),
+                Err(e) => self.report_error(&e),
+            }
+        }
+    }
 
-        self.rbn_client = Some(client);
-        self.is_connected = true;
-        self.status_message = "Connecting...".to_string();
+    /// Take in the logger's frequency changes
+    fn process_radio_info(&mut self) {
+        let Some(ref listener) = self.radio_info else {
+            return;
+        };
+        while let Some(info) = listener.try_recv() {
+            if info.active {
+                self.logger_radio = Some(info);
+            }
+        }
     }
 
-    /// Disconnect from RBN server
-    fn disconnect_rbn(&mut self) {
-        if let Some(ref client) = self.rbn_client {
-            client.disconnect();
+    /// Frequency of the logger's active radio, once N1MM has reported it
+    fn logger_frequency_khz(&self) -> Option<f64> {
+        self.logger_radio.as_ref().map(|radio| radio.frequency_khz)
+    }
+
+    /// Start, retime or stop the solar data fetcher to match the settings
+    fn update_propagation(&mut self) {
+        let minutes = self.config.effective_solar_refresh_minutes();
+        let interval = Duration::from_secs(minutes as u64 * 60);
+        if minutes == 0 {
+            self.propagation = None;
+        } else if let Some(ref fetcher) = self.propagation {
+            fetcher.set_interval(interval);
+        } else {
+            let cache = self.data_dir.as_ref().map(|dir| dir.join("solar.xml"));
+            self.propagation = Some(PropagationFetcher::new(interval, cache));
         }
-        self.rbn_client = None;
-        self.is_connected = false;
-        self.status_message = "Disconnected".to_string();
     }
 
-    /// Open VFD on selected port
-    fn open_vfd(&mut self) {
-        if self.selected_port.is_empty() {
-            self.status_message = "No serial port selected".to_string();
+    /// Take in new solar data from the fetcher
+    fn process_propagation_messages(&mut self) {
+        let Some(ref fetcher) = self.propagation else {
             return;
+        };
+        let messages: Vec<PropagationMessage> = std::iter::from_fn(|| fetcher.try_recv()).collect();
+
+        for msg in messages {
+            match msg {
+                PropagationMessage::Updated(data) => self.solar = Some(data),
+                PropagationMessage::Failed(e) => self.notifications.warning(e),
+            }
         }
+    }
 
-        match self.vfd_display.open(&self.selected_port) {
-            Ok(()) => {
-                self.config.serial_port = self.selected_port.clone();
-                self.status_message = format!("VFD opened on {}", self.selected_port);
+    /// Open or close the spot history database to match the settings
+    fn update_spot_db(&mut self) {
+        if !self.config.history_enabled {
+            self.spot_db = None;
+        } else if self.spot_db.is_none() {
+            let Some(ref dir) = self.data_dir else {
+                return;
+            };
+            match SpotDb::open(&dir.join("history.sqlite")) {
+                Ok(db) => self.spot_db = Some(db),
+                Err(e) => self.report_error(&e),
             }
-            Err(e) => {
-                self.status_message = format!("Failed to open VFD: {}", e);
+        }
+    }
+
+    /// Look up the History section's callsign in the database
+    fn search_history(&mut self) {
+        let Some(ref db) = self.spot_db else {
+            self.notifications.warning("Spot history is turned off");
+            return;
+        };
+        let window = Duration::from_secs(u64::from(self.history_hours) * 3600);
+        match db.spots_for(&self.history_callsign, window) {
+            Ok(rows) => {
+                self.notifications.info(format!(
+                    "{} spots of {} in the last {}h",
+                    rows.len(),
+                    self.history_callsign.trim().to_uppercase(),
+                    self.history_hours
+                ));
+                self.history_results = rows;
             }
+            Err(e) => self.report_error(&e),
         }
     }
 
-    /// Close VFD
-    fn close_vfd(&mut self) {
-        self.vfd_display.close();
-        self.status_message = "VFD closed".to_string();
+    /// Start, restart or stop the hook script to match the settings
+    fn update_script_host(&mut self) {
+        let path = self.config.script_file.trim();
+        if path.is_empty() {
+            self.script_host = None;
+            self.script_hidden.clear();
+        } else if self.script_host.as_ref().map(|s| s.path()) != Some(path) {
+            self.script_host = None;
+            self.script_hidden.clear();
+            match ScriptHost::start(path) {
+                Ok(host) => self.script_host = Some(host),
+                Err(e) => self.report_error(&e),
+            }
+        }
     }
 
-    /// Tune the radio to the selected spot
-    fn tune_to_selected(&mut self) {
-        let Some(spot) = &self.selected_spot else {
+    /// Carry out what the hook script asked for
+    fn process_script_actions(&mut self) {
+        let Some(ref host) = self.script_host else {
             return;
         };
+        let actions: Vec<_> = std::iter::from_fn(|| host.try_recv()).collect();
+        let error = host.take_error();
 
-        let mode = RadioMode::from_rbn_mode(&spot.mode);
-
-        match self.radio_controller.tune(spot.frequency_khz, mode) {
-            Ok(()) => {
-                self.status_message = format!(
-                    "Tuned to {:.1} kHz {}",
-                    spot.frequency_khz,
-                    mode.to_rigctld_mode()
-                );
-            }
-            Err(e) => {
-                self.radio_error = Some(e.to_string());
+        for action in actions {
+            match action {
+                ScriptAction::Alert(text) => {
+                    self.raise_alert(&format!("script|{}", text), "Script", text)
+                }
+                ScriptAction::Display { lines, duration } => {
+                    self.vfd_display.interrupt(lines, duration);
+                }
+                ScriptAction::Hide(callsign) => {
+                    self.script_hidden.insert(callsign);
+                }
+                ScriptAction::Show(callsign) => {
+                    self.script_hidden.remove(&callsign);
+                }
             }
         }
+        if let Some(error) = error {
+            self.report_error(&error);
+        }
     }
 
-    /// Process incoming RBN messages
-    fn process_rbn_messages(&mut self) {
-        // Collect messages first to avoid borrow conflicts
-        let messages: Vec<RbnMessage> = if let Some(ref mut client) = self.rbn_client {
-            let mut msgs = Vec::new();
-            while let Some(msg) = client.try_recv() {
-                msgs.push(msg);
+    /// Answer requests waiting on the HTTP control API
+    fn process_api_requests(&mut self) {
+        let Some(ref api) = self.http_api else {
+            return;
+        };
+        let calls: Vec<_> = std::iter::from_fn(|| api.try_recv()).collect();
+
+        for call in calls {
+            let response = pipeline::answer_api(self, &call.request);
+            call.respond(response);
+        }
+    }
+
+    /// Apply the user's character mapping file to the display
+    fn load_charset(&mut self) {
+        match self.config.charset_overrides() {
+            Ok(overrides) => self.vfd_display.set_charset_overrides(overrides),
+            Err(e) => self.report_error(&e),
+        }
+    }
+
+    /// Hand the aggregation settings to the spot store
+    fn configure_spot_store(&mut self) {
+        pipeline::configure_spot_store(&self.spot_store, &self.config);
+    }
+
+    /// Bring up the VFD, radio and feed from saved settings, then show how that went
+    /// on the VFD so a headless setup reports its boot status
+    fn startup(&mut self, config_found: bool) {
+        self.load_charset();
+        self.configure_spot_store();
+        self.update_mqtt();
+        self.update_http_api();
+        self.update_radio_info();
+        self.update_script_host();
+        self.update_spot_db();
+        self.update_propagation();
+        self.update_contest();
+        if self.available_ports.contains(&self.config.serial_port) {
+            self.open_vfd();
+        }
+
+        let replaying = self.session_player.is_some();
+        let radio = if !self.config.radio.enabled || replaying {
+            "Radio off"
+        } else {
+            match self.radio_controller.connect() {
+                Ok(()) => "Radio OK",
+                Err(e) => {
+                    self.report_error(&e.into());
+                    "Radio FAIL"
+                }
             }
-            msgs
+        };
+
+        let feed =
+            if self.config.auto_connect && !self.callsign_input.trim().is_empty() && !replaying {
+                self.connect_rbn();
+                "RBN connecting"
+            } else {
+                "RBN idle"
+            };
+
+        let config = if self.settings_unreadable {
+            "Config FAIL"
+        } else if config_found {
+            "Config OK"
         } else {
-            Vec::new()
+            "Config default"
         };
+        self.vfd_display
+            .show_status_page(&[config, feed, radio], STARTUP_PAGE_TIME);
+    }
 
-        // Process collected messages
-        let mut should_disconnect = false;
-        for msg in messages {
-            match msg {
-                RbnMessage::Status(s) => {
-                    self.status_message = s;
-                }
-                RbnMessage::Spot(raw) => {
-                    self.spot_store.add_spot(raw);
-                }
-                RbnMessage::Disconnected => {
-                    self.is_connected = false;
-                    should_disconnect = true;
+    /// Append an event to the session recording, if one is active
+    fn record_event(&mut self, event: SessionEvent) {
+        if let Some(ref mut recorder) = self.session_recorder {
+            recorder.record(&event);
+        }
+    }
+
+    /// Start or stop recording a session into the data directory
+    fn toggle_session_recording(&mut self) {
+        if self.session_recorder.take().is_some() {
+            self.notifications.info("Session recording stopped");
+            return;
+        }
+
+        let Some(ref dir) = self.data_dir else {
+            self.notifications
+                .warning("No data directory for session recordings");
+            return;
+        };
+        let stamp = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = dir.join("sessions").join(format!("session-{}.log", stamp));
+        match SessionRecorder::create(&path) {
+            Ok(recorder) => {
+                self.session_recorder = Some(recorder);
+                self.last_recorded_frame.clear();
+                self.last_recorded_filters = None;
+                self.notifications
+                    .info(format!("Recording session to {}", path.display()));
+            }
+            Err(e) => {
+                self.notifications.error(e);
+            }
+        }
+    }
+
+    /// Session statistics: totals, standout stations and spots per band, mode and hour
+    /// Skimmers hearing our callsign, per band
+    fn show_my_signal(&mut self, ui: &mut egui::Ui) {
+        if self.self_spots.is_empty() {
+            ui.label(format!(
+                "No skimmer has heard {} recently.",
+                if self.config.callsign.trim().is_empty() {
+                    "your callsign"
+                } else {
+                    self.config.callsign.trim()
                 }
-                RbnMessage::RawData { data, received } => {
-                    let prefix = if received { "<<" } else { ">>" };
-                    let line = format!("{} {}", prefix, data.trim_end());
-                    self.raw_data_log.push(line);
-                    // Keep log from growing too large
-                    if self.raw_data_log.len() > RAW_DATA_LOG_MAX_LINES {
-                        self.raw_data_log.remove(0);
+            ));
+            return;
+        }
+
+        self.show_my_signal_map(ui);
+
+        for (band, reports) in self.self_spots.by_band(&self.config.band_plan) {
+            ui.label(
+                egui::RichText::new(format!("{} ({} skimmers)", band, reports.len())).strong(),
+            );
+            egui::Grid::new(format!("my_signal_{}", band))
+                .num_columns(5)
+                .striped(true)
+                .show(ui, |ui| {
+                    for report in reports {
+                        ui.label(egui::RichText::new(&report.spotter).monospace());
+                        ui.label(self.config.frequency_format().format(report.frequency_khz));
+                        ui.label(format!("{} dB", report.snr));
+                        ui.label(format!("{} wpm", report.speed_wpm));
+                        let heard = UtcTime::from_system(report.heard_at);
+                        ui.label(format!("{:02}{:02}Z", heard.hour, heard.minute));
+                        ui.end_row();
+                    }
+                });
+        }
+    }
+
+    /// Heat map of the grid squares hearing us, by skimmer location (cty.dat
+    /// entity centers, so every skimmer in an entity lands on one square)
+    fn show_my_signal_map(&mut self, ui: &mut egui::Ui) {
+        let groups = self.self_spots.by_band(&self.config.band_plan);
+        ui.horizontal(|ui| {
+            ui.label("Map:");
+            egui::ComboBox::from_id_salt("my_signal_map_band")
+                .selected_text(self.my_signal_band.as_deref().unwrap_or("All bands"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.my_signal_band, None, "All bands");
+                    for (band, _) in &groups {
+                        ui.selectable_value(&mut self.my_signal_band, Some(band.clone()), band);
                     }
+                });
+        });
+
+        // Skimmer count and best SNR per grid square
+        let mut squares: HashMap<String, (usize, i32)> = HashMap::new();
+        for (band, reports) in &groups {
+            if self.my_signal_band.as_ref().is_some_and(|b| b != band) {
+                continue;
+            }
+            for report in reports {
+                let Some(entity) = self.data_files.country_db.lookup(&report.spotter) else {
+                    continue;
+                };
+                let square = to_locator(entity.latitude, entity.longitude, 4);
+                let (count, best) = squares.entry(square).or_insert((0, i32::MIN));
+                *count += 1;
+                *best = (*best).max(report.snr);
+            }
+        }
+        let markers: Vec<MapMarker> = squares
+            .into_iter()
+            .filter_map(|(square, (count, best))| {
+                let (latitude, longitude) = locator_center(&square)?;
+                Some(MapMarker {
+                    latitude,
+                    longitude,
+                    radius: 3.0 + 2.0 * (count as f32).sqrt(),
+                    color: heat_color(best),
+                    label: format!("{}: {} skimmers, best {} dB", square, count, best),
+                })
+            })
+            .collect();
+        WorldMap::new(&markers).show(ui);
+    }
+
+    /// Spots (and optionally skimmers) placed at their cty.dat entity, colored
+    /// by band; clicking a spot selects it
+    fn show_spot_map(&mut self, ui: &mut egui::Ui) {
+        let spots = self.filtered_spots();
+        let bands = self.config.band_plan.bands();
+        let band_index = |khz: f64| bands.iter().position(|b| b.contains(khz));
+
+        ui.horizontal_wrapped(|ui| {
+            ui.checkbox(&mut self.map_show_skimmers, "Show skimmers");
+            for (i, band) in bands.iter().enumerate() {
+                if spots.iter().any(|s| band.contains(s.frequency_khz)) {
+                    ui.label(egui::RichText::new(format!("● {}", band.name)).color(band_color(i)));
                 }
             }
+        });
+
+        let mut markers = Vec::new();
+        let mut marker_spots = Vec::new();
+        // Spread markers sharing an entity's coordinates out in a small spiral
+        let mut per_entity: HashMap<String, usize> = HashMap::new();
+        let mut place = |prefix: &str, latitude: f64, longitude: f64| {
+            let n = per_entity.entry(prefix.to_string()).or_default();
+            let angle = *n as f64 * 2.4;
+            let distance = 1.5 * (*n as f64).sqrt();
+            *n += 1;
+            (
+                (latitude + distance * angle.sin()).clamp(-90.0, 90.0),
+                (longitude + distance * angle.cos() + 540.0) % 360.0 - 180.0,
+            )
+        };
+
+        if self.map_show_skimmers {
+            let spotters = self
+                .spot_store
+                .with_stats(|stats| stats.spotters_by_count())
+                .unwrap_or_default();
+            for (spotter, count) in spotters {
+                let Some(entity) = self.data_files.country_db.lookup(&spotter) else {
+                    continue;
+                };
+                let (latitude, longitude) =
+                    place(&entity.primary_prefix, entity.latitude, entity.longitude);
+                markers.push(MapMarker {
+                    latitude,
+                    longitude,
+                    radius: 2.0,
+                    color: egui::Color32::from_gray(140),
+                    label: format!("Skimmer {} ({} spots)", spotter, count),
+                });
+                marker_spots.push(None);
+            }
         }
 
-        if should_disconnect {
-            self.rbn_client = None;
+        for spot in spots {
+            let Some(entity) = self.data_files.country_db.lookup(&spot.callsign) else {
+                continue;
+            };
+            let (latitude, longitude) =
+                place(&entity.primary_prefix, entity.latitude, entity.longitude);
+            let selected = self.selected_spot.as_ref().is_some_and(|s| {
+                s.callsign == spot.callsign && (s.frequency_khz - spot.frequency_khz).abs() < 0.5
+            });
+            markers.push(MapMarker {
+                latitude,
+                longitude,
+                radius: if selected { 6.0 } else { 4.0 },
+                color: if selected {
+                    egui::Color32::WHITE
+                } else {
+                    band_index(spot.frequency_khz)
+                        .map(band_color)
+                        .unwrap_or(egui::Color32::GRAY)
+                },
+                label: format!(
+                    "{} {:.1} {} dB ({})",
+                    spot.callsign, spot.frequency_khz, spot.highest_snr, entity.name
+                ),
+            });
+            marker_spots.push(Some(spot));
         }
-    }
 
-    /// Perform periodic updates
-    fn update_periodic(&mut self) {
-        let now = Instant::now();
+        if let Some(spot) = WorldMap::new(&markers)
+            .show(ui)
+            .and_then(|i| marker_spots[i].take())
+        {
+            self.selected_spot = Some(spot);
+        }
+    }
 
-        // Purge old spots every 5 seconds
-        if now.duration_since(self.last_purge) >= Duration::from_secs(5) {
-            self.spot_store.purge_old_spots();
-            self.last_purge = now;
+    /// Drop a spotted call's spots for an hour, or for good
+    fn ignore_call(&mut self, call: &str, permanently: bool) {
+        if permanently {
+            self.config.set_call_ignored(call, true);
+            self.spot_store
+                .set_ignored_calls(self.config.ignored_calls.clone());
+            self.notifications.info(format!("Ignoring {}", call));
+        } else {
+            self.spot_store.snooze_call(call, SNOOZE_TIME);
+            self.notifications
+                .info(format!("Ignoring {} for 1 hour", call));
         }
+    }
 
-        // Refresh available ports every 5 seconds
-        if now.duration_since(self.last_port_refresh) >= Duration::from_secs(5) {
-            self.available_ports = VfdDisplay::available_ports();
-            self.last_port_refresh = now;
+    /// Ignored and snoozed calls, with buttons to let them back in
+    fn show_ignored(&mut self, ui: &mut egui::Ui) {
+        let ignored = self.config.ignored_calls.clone();
+        let snoozed = self.spot_store.snoozed_calls();
+        if ignored.is_empty() && snoozed.is_empty() {
+            return;
+        }
+        let mut unignore = None;
+        let mut unsnooze = None;
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Ignored:");
+            for call in ignored {
+                if ui
+                    .small_button(format!("{} \u{2715}", call))
+                    .on_hover_text("Stop ignoring")
+                    .clicked()
+                {
+                    unignore = Some(call);
+                }
+            }
+            for (call, until) in snoozed {
+                let minutes = until.saturating_duration_since(Instant::now()).as_secs() / 60 + 1;
+                if ui
+                    .small_button(format!("{} ({}m) \u{2715}", call, minutes))
+                    .on_hover_text("End the snooze")
+                    .clicked()
+                {
+                    unsnooze = Some(call);
+                }
+            }
+        });
+        if let Some(call) = unignore {
+            self.config.set_call_ignored(&call, false);
+            self.spot_store
+                .set_ignored_calls(self.config.ignored_calls.clone());
+            self.notifications
+                .info(format!("No longer ignoring {}", call));
+        }
+        if let Some(call) = unsnooze {
+            self.spot_store.unsnooze_call(&call);
+            self.notifications
+                .info(format!("No longer ignoring {}", call));
         }
+    }
 
-        // Update VFD display
-        let max_age = Duration::from_secs(self.config.max_age_minutes as u64 * 60);
-        let spots = self
+    /// Skimmers by spot count, with buttons to block noisy ones
+    fn show_skimmers(&mut self, ui: &mut egui::Ui) {
+        let spotters = self
             .spot_store
-            .get_filtered_spots(self.config.min_snr, max_age);
-        self.vfd_display.update(&spots);
+            .with_stats(|stats| stats.spotters_by_count())
+            .unwrap_or_default();
+        let mut toggle = None;
+
+        if spotters.is_empty() {
+            ui.label("No spots yet.");
+        } else {
+            egui::ScrollArea::vertical()
+                .id_salt("skimmer_leaderboard")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("skimmer_grid")
+                        .num_columns(5)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label(egui::RichText::new("Skimmer").strong());
+                            ui.label(egui::RichText::new("Spots").strong());
+                            ui.label(egui::RichText::new("Of me").strong());
+                            ui.label(egui::RichText::new("Offset").strong())
+                                .on_hover_text("Frequency correction applied to its spots");
+                            ui.label("");
+                            ui.end_row();
+                            for (spotter, count) in spotters.iter().take(SKIMMER_LEADERBOARD_SIZE) {
+                                ui.label(egui::RichText::new(spotter).monospace());
+                                ui.label(count.to_string());
+                                ui.label(self.self_spots.count_from(spotter).to_string());
+                                ui.label(
+                                    self.spot_store
+                                        .spotter_offset_hz(spotter)
+                                        .map(|hz| format!("{:+.0} Hz", hz))
+                                        .unwrap_or_default(),
+                                );
+                                if ui
+                                    .small_button("Block")
+                                    .on_hover_text("Ignore this skimmer's spots")
+                                    .clicked()
+                                {
+                                    toggle = Some((spotter.clone(), true));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+        }
+
+        let blocked = self.config.blocked_spotters.clone();
+        if !blocked.is_empty() {
+            ui.separator();
+            ui.label("Blocked:");
+            ui.horizontal_wrapped(|ui| {
+                for spotter in blocked {
+                    if ui
+                        .small_button(format!("{} \u{2715}", spotter))
+                        .on_hover_text("Unblock")
+                        .clicked()
+                    {
+                        toggle = Some((spotter, false));
+                    }
+                }
+            });
+        }
+
+        if let Some((spotter, blocked)) = toggle {
+            self.config.set_spotter_blocked(&spotter, blocked);
+            self.notifications.info(if blocked {
+                format!("Blocked skimmer {}", spotter)
+            } else {
+                format!("Unblocked skimmer {}", spotter)
+            });
+        }
+    }
+
+    fn show_statistics(&self, ui: &mut egui::Ui) {
+        let plan = &self.config.band_plan;
+        let Some((total, unique, loudest, fastest, bands, modes, hours)) =
+            self.spot_store.with_stats(|stats| {
+                (
+                    stats.total_spots,
+                    stats.unique_calls.len(),
+                    stats.loudest.clone(),
+                    stats.fastest.clone(),
+                    stats.per_band(plan),
+                    stats.modes_by_count(),
+                    stats.per_hour,
+                )
+            })
+        else {
+            return;
+        };
+        if total == 0 {
+            ui.label("No spots yet.");
+            return;
+        }
+
+        egui::Grid::new("stats_summary")
+            .num_columns(2)
+            .show(ui, |ui| {
+                ui.label("Spots:");
+                ui.label(total.to_string());
+                ui.end_row();
+                ui.label("Unique callsigns:");
+                ui.label(unique.to_string());
+                ui.end_row();
+                if let Some(s) = loudest {
+                    ui.label("Loudest:");
+                    ui.label(format!(
+                        "{} {} dB on {:.1}",
+                        s.callsign, s.value, s.frequency_khz
+                    ));
+                    ui.end_row();
+                }
+                if let Some(s) = fastest {
+                    ui.label("Fastest:");
+                    ui.label(format!(
+                        "{} {} WPM on {:.1}",
+                        s.callsign, s.value, s.frequency_khz
+                    ));
+                    ui.end_row();
+                }
+            });
+
+        ui.add_space(4.0);
+        let bar_rows = |ui: &mut egui::Ui, id: &str, counts: &[(String, u32)]| {
+            let max = counts.iter().map(|(_, n)| *n).max().unwrap_or(1).max(1);
+            egui::Grid::new(id).num_columns(2).show(ui, |ui| {
+                for (name, count) in counts {
+                    ui.label(egui::RichText::new(name).monospace());
+                    ui.add(
+                        egui::ProgressBar::new(*count as f32 / max as f32)
+                            .desired_width(160.0)
+                            .text(count.to_string()),
+                    );
+                    ui.end_row();
+                }
+            });
+        };
+
+        ui.label(egui::RichText::new("Per band").strong());
+        bar_rows(ui, "stats_bands", &bands);
+        ui.add_space(4.0);
+        ui.label(egui::RichText::new("Per mode").strong());
+        bar_rows(ui, "stats_modes", &modes);
+        ui.add_space(4.0);
+        ui.label(egui::RichText::new("Per hour (UTC)").strong());
+        let hours: Vec<(String, u32)> = hours
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(hour, count)| (format!("{:02}Z", hour), *count))
+            .collect();
+        bar_rows(ui, "stats_hours", &hours);
+    }
+
+    /// Write the shown spots (or the session history) into the data directory
+    fn export_spots(&mut self) {
+        let Some(ref dir) = self.data_dir else {
+            self.notifications.warning("No data directory for exports");
+            return;
+        };
+        let (spots, name) = if self.export_history {
+            (self.spot_store.session_history(), "history")
+        } else {
+            (self.filtered_spots(), "spots")
+        };
+        let stamp = SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let path = dir.join("exports").join(format!(
+            "{}-{}.{}",
+            name,
+            stamp,
+            self.export_format.extension()
+        ));
+        match write_spots(&path, &spots, self.export_format) {
+            Ok(()) => {
+                self.notifications.info(format!(
+                    "Exported {} spots to {}",
+                    spots.len(),
+                    path.display()
+                ));
+            }
+            Err(e) => self.report_error(&e),
+        }
+    }
+
+    /// Keep the contest log's multiplier rules in step with the settings
+    fn update_contest(&mut self) {
+        if let Some(contest) = self.config.contest {
+            if contest != self.contest_log.contest() {
+                self.contest_log
+                    .set_contest(contest, &self.data_files.country_db);
+            }
+        }
+    }
+
+    /// Multipliers working `spot` would add (none when contest mode is off)
+    fn new_multipliers(&self, spot: &AggregatedSpot) -> Vec<Multiplier> {
+        if self.config.contest.is_none() {
+            return Vec::new();
+        }
+        let Some(band) = self.config.band_plan.for_frequency(spot.frequency_khz) else {
+            return Vec::new();
+        };
+        self.contest_log
+            .new_multipliers(&spot.callsign, &band.name, &self.data_files.country_db)
+    }
+
+    /// Great-circle short-path bearing from our locator to the spot's country
+    fn spot_bearing(&self, spot: &AggregatedSpot) -> Option<f64> {
+        let home = locator_center(&self.config.locator)?;
+        let entity = self.data_files.country_db.lookup(&spot.callsign)?;
+        Some(bearing(home, (entity.latitude, entity.longitude)))
+    }
+
+    /// Whether the spot's call was already logged on its band this session
+    fn is_dupe(&self, spot: &AggregatedSpot) -> bool {
+        self.config
+            .band_plan
+            .for_frequency(spot.frequency_khz)
+            .is_some_and(|band| self.contest_log.is_dupe(&spot.callsign, &band.name))
+    }
+
+    /// Log a QSO with the selected spot in the session log
+    fn log_selected_qso(&mut self) {
+        let Some(spot) = &self.selected_spot else {
+            return;
+        };
+        let Some(band) = self.config.band_plan.for_frequency(spot.frequency_khz) else {
+            self.notifications.warning(format!(
+                "{:.1} kHz is outside the band plan",
+                spot.frequency_khz
+            ));
+            return;
+        };
+        if self.contest_log.is_dupe(&spot.callsign, &band.name) {
+            self.notifications
+                .warning(format!("{} is a dupe on {}", spot.callsign, band.name));
+            return;
+        }
+        self.contest_log
+            .log_qso(&spot.callsign, &band.name, &self.data_files.country_db);
+        self.notifications
+            .info(format!("Logged {} on {}", spot.callsign, band.name));
+    }
+
+    /// Read the QSOs of an ADIF or Cabrillo log into the contest log
+    fn import_contest_log(&mut self) {
+        let path = self.contest_import_path.trim();
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) => {
+                self.notifications
+                    .error(format!("Could not read {}: {}", path, e));
+                return;
+            }
+        };
+        match self
+            .contest_log
+            .import(&text, &self.config.band_plan, &self.data_files.country_db)
+        {
+            Ok(count) => self
+                .notifications
+                .info(format!("Imported {} QSOs from {}", count, path)),
+            Err(e) => self.notifications.error(format!("{}: {}", path, e)),
+        }
+    }
+
+    /// Contest picker, log import and running totals
+    fn show_contest(&mut self, ui: &mut egui::Ui) {
+        let mut contest = self.config.contest;
+        egui::ComboBox::from_label("Contest")
+            .selected_text(contest.map(Contest::label).unwrap_or("Off"))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut contest, None, "Off");
+                for c in Contest::ALL {
+                    ui.selectable_value(&mut contest, Some(c), c.label());
+                }
+            });
+        if contest != self.config.contest {
+            self.config.contest = contest;
+            self.update_contest();
+        }
+        if self.config.contest.is_none() {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.contest_import_path)
+                    .hint_text("Path to ADIF or Cabrillo log")
+                    .desired_width(220.0),
+            );
+            if ui
+                .add_enabled(
+                    !self.contest_import_path.trim().is_empty(),
+                    egui::Button::new("Import log"),
+                )
+                .clicked()
+            {
+                self.import_contest_log();
+            }
+        });
+        ui.label(format!(
+            "{} QSOs, {} multipliers worked",
+            self.contest_log.qso_count(),
+            self.contest_log.multiplier_count()
+        ));
+    }
+
+    /// Apply due events from the session being replayed
+    fn process_session_replay(&mut self) {
+        let Some(ref mut player) = self.session_player else {
+            return;
+        };
+        let events = player.poll();
+        let finished = player.is_finished();
+
+        for event in events {
+            match event {
+                SessionEvent::Spot(raw) => {
+                    self.spot_store.add_spot(raw, self.config.source_policy);
+                }
+                SessionEvent::Filters {
+                    min_snr,
+                    max_age_minutes,
+                } => {
+                    self.config.min_snr = min_snr;
+                    self.config.max_age_minutes = max_age_minutes;
+                }
+                SessionEvent::Tune {
+                    frequency_khz,
+                    mode,
+                } => {
+                    // Never touch the real radio during replay
+                    self.notifications.info(format!(
+                        "Replay: tuned to {:.1} kHz {}",
+                        frequency_khz,
+                        mode.to_rigctld_mode()
+                    ));
+                }
+                SessionEvent::Frame(lines) => {
+                    self.vfd_display.show_frame(lines);
+                }
+            }
+        }
+
+        if finished {
+            self.session_player = None;
+            self.notifications.info("Replay finished");
+        }
+    }
+
+    /// Spots passing the SNR (fixed or adaptive) and age filters, sorted by frequency
+    fn filtered_spots(&self) -> Vec<AggregatedSpot> {
+        let mut spots = pipeline::filtered_spots(&self.spot_store, &self.config);
+        if !self.script_hidden.is_empty() {
+            spots.retain(|s| !self.script_hidden.contains(&s.callsign));
+        }
+        if self.config.hide_dupes {
+            spots.retain(|s| !self.is_dupe(s));
+        }
+        spots
+    }
+
+    /// Push config values into the running services after a bulk change
+    fn apply_config(&mut self, radio_changed: bool) {
+        self.vfd_display
+            .set_scroll_interval(self.config.scroll_interval_seconds);
+        self.vfd_display.set_marquee(self.config.marquee_rate());
+        self.vfd_display
+            .set_frequency_format(self.config.frequency_format());
+        self.vfd_display
+            .set_random_char_percent(self.config.random_char_percent);
+        self.vfd_display.set_screensaver(self.config.screensaver);
+        self.vfd_display.set_callsign(&self.config.callsign);
+        self.vfd_display
+            .set_flash_new_spots(self.config.flash_new_spots);
+        self.vfd_display.set_idle_content(self.config.idle_content);
+        self.vfd_display.set_content(self.config.vfd_content);
+        self.vfd_display.set_watchlist(&self.config.watchlist);
+        self.vfd_display
+            .set_station_message(&self.config.station_message);
+        self.vfd_display.set_local_clock(self.config.local_clock());
+        self.vfd_display.set_geometry(self.config.display_geometry);
+        self.vfd_display.set_protocol(self.config.display_protocol);
+        self.vfd_display
+            .set_brightness(self.config.display_brightness as u8);
+        self.vfd_display.set_pages(self.config.carousel_pages());
+        self.vfd_display
+            .set_band_plan(self.config.band_plan.clone());
+        self.load_charset();
+        self.configure_spot_store();
+        self.update_mqtt();
+        self.update_http_api();
+        self.update_radio_info();
+        self.update_script_host();
+        self.update_spot_db();
+        self.update_propagation();
+        self.update_contest();
+
+        self.alert_center
+            .set_idle_timeout(idle_timeout(self.config.away_idle_minutes));
+
+        if let Some(ref updater) = self.data_updater {
+            updater.set_interval(Duration::from_secs(
+                self.config.effective_data_update_hours() as u64 * 3600,
+            ));
+        }
+
+        if radio_changed {
+            self.radio_controller = radio::create_controller(&self.config.radio);
+            self.radio_band = None;
+            self.antenna = None;
+            if self.config.radio.enabled {
+                let _ = self.radio_controller.connect();
+            }
+        }
+    }
+
+    /// Switch to a saved radio profile and reconnect
+    fn switch_radio_profile(&mut self, name: &str) {
+        match self.config.select_radio_profile(name) {
+            Ok(()) => {
+                self.apply_config(true);
+                self.notifications.info(format!("Radio profile: {}", name));
+            }
+            Err(e) => self.report_error(&e),
+        }
+    }
+
+    /// Apply a saved configuration profile (filters, display pages, radio)
+    fn switch_profile(&mut self, name: &str) {
+        let radio = self.config.radio.clone();
+        let result = self.config.select_profile(name);
+        self.apply_config(self.config.radio != radio);
+        match result {
+            Ok(()) => self.notifications.info(format!("Profile: {}", name)),
+            Err(e) => self.report_error(&e),
+        }
+    }
+
+    /// Apply the configured theme, accent color and UI scale if they changed
+    fn apply_theme(&mut self, ctx: &egui::Context) {
+        let wanted = (
+            self.config.theme,
+            self.config.accent_color,
+            self.config.ui_scale,
+        );
+        if self.applied_theme == Some(wanted) {
+            return;
+        }
+        let mut visuals = match self.config.theme {
+            Theme::Dark => egui::Visuals::dark(),
+            Theme::Light => egui::Visuals::light(),
+        };
+        if let Some([r, g, b]) = self.config.accent_color {
+            let accent = egui::Color32::from_rgb(r, g, b);
+            visuals.selection.bg_fill = accent;
+            visuals.hyperlink_color = accent;
+        }
+        ctx.set_visuals(visuals);
+        ctx.set_zoom_factor(self.config.ui_scale);
+        self.applied_theme = Some(wanted);
+    }
+
+    /// Theme, accent color and UI scale controls
+    fn show_appearance(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Theme:");
+            egui::ComboBox::from_id_salt("ui_theme")
+                .selected_text(self.config.theme.label())
+                .show_ui(ui, |ui| {
+                    for theme in Theme::ALL {
+                        ui.selectable_value(&mut self.config.theme, theme, theme.label());
+                    }
+                });
+        });
+
+        ui.horizontal(|ui| {
+            let mut custom = self.config.accent_color.is_some();
+            if ui.checkbox(&mut custom, "Accent color").changed() {
+                self.config.accent_color = custom.then(|| {
+                    let fill = ui.visuals().selection.bg_fill;
+                    [fill.r(), fill.g(), fill.b()]
+                });
+            }
+            if let Some(ref mut accent) = self.config.accent_color {
+                ui.color_edit_button_srgb(accent);
+            }
+        });
+
+        // Presets rather than a slider: rescaling mid-drag moves the slider
+        ui.horizontal(|ui| {
+            ui.label("UI scale:");
+            egui::ComboBox::from_id_salt("ui_scale")
+                .selected_text(format!("{:.0}%", self.config.ui_scale * 100.0))
+                .show_ui(ui, |ui| {
+                    for scale in [0.75, 0.9, 1.0, 1.1, 1.25, 1.5, 1.75, 2.0] {
+                        ui.selectable_value(
+                            &mut self.config.ui_scale,
+                            scale,
+                            format!("{:.0}%", scale * 100.0),
+                        );
+                    }
+                });
+        });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("VFD preview:");
+            egui::ComboBox::from_id_salt("preview_phosphor")
+                .selected_text(self.config.preview_phosphor.label())
+                .show_ui(ui, |ui| {
+                    for phosphor in Phosphor::ALL {
+                        ui.selectable_value(
+                            &mut self.config.preview_phosphor,
+                            phosphor,
+                            phosphor.label(),
+                        );
+                    }
+                });
+            ui.checkbox(&mut self.config.preview_dot_matrix, "Dot matrix");
+            ui.checkbox(&mut self.config.preview_glow, "Glow");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Spot rows:");
+            ui.checkbox(&mut self.config.row_color_age, "Fade with age");
+            ui.checkbox(&mut self.config.row_color_snr, "Dim weak spots");
+            ui.checkbox(&mut self.config.row_color_mode, "Color by mode");
+            ui.checkbox(&mut self.config.sort_by_trend, "Sort by trend");
+            ui.checkbox(&mut self.config.group_by_band, "Group by band");
+            egui::ComboBox::from_id_salt("age_indicator")
+                .selected_text(self.config.age_indicator.label())
+                .show_ui(ui, |ui| {
+                    for indicator in AgeIndicator::ALL {
+                        ui.selectable_value(
+                            &mut self.config.age_indicator,
+                            indicator,
+                            indicator.label(),
+                        );
+                    }
+                })
+                .response
+                .on_hover_text("Time left before a spot expires");
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Frequencies:");
+            let unit = self.config.frequency_unit;
+            egui::ComboBox::from_id_salt("frequency_unit")
+                .selected_text(self.config.frequency_unit.label())
+                .show_ui(ui, |ui| {
+                    for unit in FrequencyUnit::ALL {
+                        ui.selectable_value(&mut self.config.frequency_unit, unit, unit.label());
+                    }
+                });
+            ui.label("Locale:");
+            let locale = ui
+                .add(
+                    egui::TextEdit::singleline(&mut self.config.number_locale)
+                        .desired_width(80.0)
+                        .hint_text("system"),
+                )
+                .on_hover_text("e.g. de_DE for a decimal comma; empty uses the system's");
+            if self.config.frequency_unit != unit || locale.changed() {
+                self.vfd_display
+                    .set_frequency_format(self.config.frequency_format());
+            }
+        });
+    }
+
+    /// Switch between the full window and the always-on-top mini window
+    fn set_mini_mode(&mut self, ctx: &egui::Context, mini: bool) {
+        self.mini_mode = mini;
+        if mini {
+            self.full_window_size = ctx.input(|i| i.viewport().inner_rect.map(|r| r.size()));
+            ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(MINI_WINDOW_SIZE));
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(MINI_WINDOW_SIZE));
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(
+                egui::WindowLevel::AlwaysOnTop,
+            ));
+        } else {
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(
+                egui::WindowLevel::Normal,
+            ));
+            ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(MIN_WINDOW_SIZE));
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(
+                self.full_window_size.take().unwrap_or(WINDOW_SIZE),
+            ));
+        }
+    }
+
+    /// What the VFD is showing right now
+    fn show_vfd_preview(&self, ui: &mut egui::Ui) {
+        let style = self.config.preview_style();
+        match self.vfd_display.render_bitmap() {
+            Some(bitmap) => ui.add(VfdPreview::bitmap(&bitmap, style)),
+            None => ui.add(VfdPreview::lines(
+                &self.vfd_display.get_preview(),
+                self.vfd_display.geometry().width,
+                style,
+            )),
+        };
+    }
+
+    /// The mini window: VFD preview and the newest spot
+    fn show_mini_window(&mut self, ctx: &egui::Context) {
+        let mut restore = false;
+        egui::CentralPanel::default().show(ctx, |ui| {
+            self.show_vfd_preview(ui);
+            ui.horizontal(|ui| {
+                let newest = self
+                    .filtered_spots()
+                    .into_iter()
+                    .max_by_key(|s| s.first_spotted_at);
+                match newest {
+                    Some(spot) => ui.label(
+                        egui::RichText::new(format!(
+                            "{:.1} {} {} dB",
+                            spot.frequency_khz, spot.callsign, spot.highest_snr
+                        ))
+                        .monospace(),
+                    ),
+                    None => ui.label("No spots"),
+                };
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    restore = ui
+                        .small_button("⤢")
+                        .on_hover_text("Back to the full window")
+                        .clicked();
+                });
+            });
+        });
+        if restore {
+            self.set_mini_mode(ctx, false);
+        }
+    }
+
+    /// Write the current settings to the chosen file
+    fn export_settings(&mut self) {
+        let path = PathBuf::from(self.settings_file_path.trim());
+        match self.config.save_file(&path) {
+            Ok(()) => self
+                .notifications
+                .info(format!("Exported settings to {}", path.display())),
+            Err(e) => self.report_error(&e),
+        }
+    }
+
+    /// Replace the current settings with those in the chosen file
+    fn import_settings(&mut self) {
+        let path = PathBuf::from(self.settings_file_path.trim());
+        match Config::load_file(&path) {
+            Ok(config) => {
+                self.config = config;
+                self.callsign_input = self.config.callsign.clone();
+                self.apply_config(true);
+                self.refresh_setting_edits();
+                self.notifications
+                    .info(format!("Imported settings from {}", path.display()));
+            }
+            Err(e) => self.report_error(&e),
+        }
+    }
+
+    /// Apply the settings changed in settings.toml by hand or by a script
+    fn reload_edited_settings(&mut self) {
+        let modified = Config::modified_time();
+        if modified.is_none()
+            || modified == self.settings_file.0
+            || modified
+                .and_then(|m| m.elapsed().ok())
+                .is_some_and(|age| age < SETTINGS_SETTLE_TIME)
+        {
+            return;
+        }
+        match self.config.reload_changed(&self.settings_file.1) {
+            Ok((changed, values)) => {
+                self.settings_file = (modified, values);
+                if self.settings_unreadable {
+                    // Fixed by hand; it is safe to save over again
+                    self.settings_unreadable = false;
+                    self.saved_config = self.config.clone();
+                }
+                if changed.is_empty() {
+                    return;
+                }
+                self.apply_config(changed.iter().any(|&i| settings()[i].section() == "radio"));
+                self.callsign_input = self.config.callsign.clone();
+                self.refresh_setting_edits();
+                self.notifications.info(format!(
+                    "Reloaded settings.toml: {} setting(s) changed",
+                    changed.len()
+                ));
+            }
+            Err(e) => {
+                self.settings_file.0 = modified;
+                self.report_error(&e);
+            }
+        }
+    }
+
+    /// Write the settings to settings.toml now, returning whether that worked
+    fn save_settings(&mut self) -> bool {
+        if self.settings_unreadable {
+            self.notifications.warning(
+                "Not saving: settings.toml couldn't be read at startup; fix or remove it first",
+            );
+            return false;
+        }
+        match self.config.save() {
+            Ok(()) => {
+                self.saved_config = self.config.clone();
+                self.config_changed_at = None;
+                // Our own write isn't an outside edit to reload
+                self.settings_file = (Config::modified_time(), self.config.setting_values());
+                true
+            }
+            Err(e) => {
+                // Try again after another delay rather than every check
+                self.config_changed_at = Some(Instant::now());
+                self.report_error(&e);
+                false
+            }
+        }
+    }
+
+    /// Save the settings once they have been changed in the app and
+    /// `AUTOSAVE_DELAY` has passed
+    fn autosave_settings(&mut self) {
+        if self.settings_unreadable || !self.config.differs_from(&self.saved_config) {
+            self.config_changed_at = None;
+            return;
+        }
+        let changed_at = *self.config_changed_at.get_or_insert_with(Instant::now);
+        if changed_at.elapsed() >= AUTOSAVE_DELAY {
+            self.save_settings();
+        }
+    }
+
+    /// Reload the "All Settings" edit buffers from the current config
+    fn refresh_setting_edits(&mut self) {
+        self.setting_edits = self.config.setting_values();
+    }
+
+    /// Connect to RBN server
+    fn connect_rbn(&mut self) {
+        if self.callsign_input.trim().is_empty() {
+            self.notifications.warning("Please enter a callsign");
+            return;
+        }
+
+        let callsign = self.callsign_input.trim().to_uppercase();
+        self.config.callsign = callsign.clone();
+        self.vfd_display.set_callsign(&callsign);
+
+        let client = RbnClient::new(self.rbn_traffic.clone());
+        client.connect(callsign, self.config.login_commands());
+
+        self.rbn_client = Some(client);
+        self.is_connected = true;
+        self.last_feed_activity = Instant::now();
+        self.notifications.info("Connecting...");
+    }
+
+    /// Disconnect from RBN server
+    fn disconnect_rbn(&mut self) {
+        if let Some(ref client) = self.rbn_client {
+            client.disconnect();
+        }
+        self.rbn_client = None;
+        self.is_connected = false;
+        self.notifications.info("Disconnected");
+    }
+
+    /// Open VFD on selected port
+    fn open_vfd(&mut self) {
+        if self.selected_port.is_empty() {
+            self.notifications.warning("No serial port selected");
+            return;
+        }
+
+        match self.vfd_display.open(&self.selected_port) {
+            Ok(()) => {
+                self.config.serial_port = self.selected_port.clone();
+                self.notifications
+                    .info(format!("VFD opened on {}", self.selected_port));
+            }
+            Err(e) => {
+                self.notifications
+                    .error(format!("Failed to open VFD: {}", e));
+            }
+        }
+    }
+
+    /// Restart the feed or serial writer if it has wedged (service mode)
+    fn check_subsystems(&mut self) {
+        let Some(ref watchdog) = self.watchdog else {
+            return;
+        };
+
+        if let Some(ref client) = self.rbn_client {
+            let feed_timeout = Duration::from_secs(self.config.feed_stall_minutes as u64 * 60);
+            let problem = if !client.is_alive() {
+                Some("RBN feed task died".to_string())
+            } else if self.last_feed_activity.elapsed() >= feed_timeout {
+                Some(format!(
+                    "No RBN traffic for {} min",
+                    self.config.feed_stall_minutes
+                ))
+            } else {
+                None
+            };
+            if let Some(problem) = problem {
+                watchdog.log(&format!("{}, reconnecting", problem));
+                self.disconnect_rbn();
+                self.connect_rbn();
+            }
+        }
+
+        let Some(ref watchdog) = self.watchdog else {
+            return;
+        };
+        if let Some(stalled) = self
+            .vfd_display
+            .write_stalled_for()
+            .filter(|s| *s >= SERIAL_STALL_TIMEOUT)
+        {
+            watchdog.log(&format!(
+                "VFD write stuck for {}s, restarting serial writer",
+                stalled.as_secs()
+            ));
+            if let Err(e) = self.vfd_display.restart_writer() {
+                watchdog.log(&e.to_string());
+                self.report_error(&e);
+            }
+        }
+    }
+
+    /// Release the VFD port while the handoff flag file exists and take it back
+    /// once the other program removes it
+    fn check_handoff(&mut self) {
+        let requested = !self.config.handoff_file.is_empty()
+            && std::path::Path::new(&self.config.handoff_file).exists();
+        if requested && self.vfd_display.is_open() {
+            self.vfd_display.release();
+            self.notifications.info("VFD released to another program");
+        } else if !requested && self.vfd_display.is_released() {
+            match self.vfd_display.reclaim() {
+                Ok(()) => self.notifications.info("VFD handed back"),
+                Err(e) => self.report_error(&e),
+            }
+        }
+    }
+
+    /// Close VFD
+    fn close_vfd(&mut self) {
+        self.vfd_display.close();
+        self.notifications.info("VFD closed");
+    }
+
+    /// Tune the radio to the selected spot
+    fn tune_to_selected(&mut self) {
+        let Some(spot) = &self.selected_spot else {
+            return;
+        };
+
+        // Digital spots are heard at dial + audio offset; tune the dial so the
+        // decoder keeps them in its passband
+        let (frequency_khz, mode) = match self.config.dial_frequency(&spot.mode, spot.frequency_khz)
+        {
+            Some(dial) => (dial, RadioMode::Data),
+            None => (
+                spot.frequency_khz,
+                RadioMode::from_rbn_mode(&spot.mode, spot.frequency_khz),
+            ),
+        };
+        if let Some(reason) = self.config.tune_refusal(frequency_khz) {
+            self.radio_error = Some(RadioError::Refused(reason).to_string());
+            return;
+        }
+
+        let current = self.radio_controller.get_frequency().ok();
+        if let Some(current) = current {
+            let limit = self.config.confirm_qsy_khz as f64;
+            if limit > 0.0 && (frequency_khz - current).abs() > limit {
+                self.pending_tune = Some((frequency_khz, mode, current));
+                return;
+            }
+        }
+        self.tune_with_undo(frequency_khz, mode, current);
+    }
+
+    /// Tune to a spot, keeping the frequency left behind so it can be restored
+    fn tune_with_undo(&mut self, frequency_khz: f64, mode: RadioMode, previous: Option<f64>) {
+        let previous_mode = self.tuned_mode.unwrap_or(mode);
+        match self.tune_radio(frequency_khz, mode) {
+            Ok(()) => {
+                self.tune_undo = previous.map(|khz| (khz, previous_mode, Instant::now()));
+            }
+            Err(e) => self.radio_error = Some(e.to_string()),
+        }
+    }
+
+    /// Go back to the frequency left by the last spot tune
+    fn undo_tune(&mut self) {
+        let Some((frequency_khz, mode, _)) = self.tune_undo.take() else {
+            return;
+        };
+        if let Err(e) = self.tune_radio(frequency_khz, mode) {
+            self.radio_error = Some(e.to_string());
+        }
+    }
+
+    /// Tune the radio, recording the tune and reporting it in the status line
+    fn tune_radio(&mut self, frequency_khz: f64, mode: RadioMode) -> Result<(), RadioError> {
+        let antenna_error = pipeline::tune(
+            self.radio_controller.as_mut(),
+            &self.config,
+            &mut self.antenna,
+            frequency_khz,
+            mode,
+        )?;
+        self.tuned_mode = Some(mode);
+        if let Some(error) = antenna_error {
+            self.radio_error = Some(error);
+        }
+        self.record_event(SessionEvent::Tune {
+            frequency_khz,
+            mode,
+        });
+        if let Some(ref host) = self.script_host {
+            host.on_tune(frequency_khz, mode);
+        }
+        self.notifications.info(format!(
+            "Tuned to {:.1} kHz {}",
+            frequency_khz,
+            mode.to_rigctld_mode()
+        ));
+        Ok(())
+    }
+
+    /// Switch to the antenna configured for the frequency's band, if it is not
+    /// already selected
+    fn select_antenna(&mut self, frequency_khz: f64) {
+        if let Err(e) = pipeline::select_antenna(
+            self.radio_controller.as_mut(),
+            &self.config,
+            &mut self.antenna,
+            frequency_khz,
+        ) {
+            self.radio_error = Some(e);
+        }
+    }
+
+    /// Show an error in the status bar, labeled with the subsystem it came from
+    fn report_error(&mut self, error: &Error) {
+        self.notifications
+            .error(format!("{} error: {}", error.category(), error));
+    }
+
+    /// Take a batch of spots into the store as if they came from the feed
+    fn accept_spots(&mut self, raws: Vec<RawSpot>) {
+        let mut accepted = Vec::with_capacity(raws.len());
+        // Calls first heard in this batch, which the store hasn't recorded yet
+        let mut heard_in_batch = HashSet::new();
+        for raw in raws {
+            self.record_event(SessionEvent::Spot(raw.clone()));
+            if let Some(ref db) = self.spot_db {
+                db.record(&raw);
+            }
+            if !pipeline::keep_report(&self.config, &self.spot_store, &mut self.self_spots, &raw) {
+                continue;
+            }
+            // A station back after dropping out isn't news
+            let first_report = self
+                .spot_store
+                .first_heard(&raw.spotted_callsign, raw.frequency_khz)
+                .is_none()
+                && heard_in_batch.insert((
+                    raw.spotted_callsign.clone(),
+                    self.spot_store.band_of(raw.frequency_khz),
+                ));
+            if first_report {
+                self.check_spot_alerts(&raw);
+                self.check_needed_alert(&raw);
+            }
+            self.check_run_frequency(&raw);
+            accepted.push(raw);
+        }
+
+        for spot in self
+            .spot_store
+            .add_spots(accepted, self.config.source_policy)
+        {
+            if let Some(ref mqtt) = self.mqtt {
+                mqtt.publish_spot(&spot);
+            }
+            if let Some(ref api) = self.http_api {
+                api.publish_spot(&spot);
+            }
+            if let Some(ref host) = self.script_host {
+                host.on_spot(&spot);
+            }
+        }
+    }
+
+    /// Inject the spots pasted into the manual spot box, leaving unparsable lines
+    fn add_manual_spots(&mut self) {
+        let spotter = if self.config.callsign.is_empty() {
+            "MANUAL".to_string()
+        } else {
+            self.config.callsign.clone()
+        };
+        let (spots, rejected) = parse_manual_spots(&self.manual_spot_input, &spotter);
+        let count = spots.len();
+        self.accept_spots(spots);
+
+        self.notifications.info(if rejected.is_empty() {
+            format!("Added {} manual spot(s)", count)
+        } else {
+            format!(
+                "Added {} manual spot(s), {} line(s) not understood",
+                count,
+                rejected.len()
+            )
+        });
+        self.manual_spot_input = rejected.join("\n");
+    }
+
+    /// Tune the radio to the frequency typed in the QSY box
+    fn tune_to_qsy_input(&mut self) {
+        let Some((frequency_khz, mode)) = parse_qsy(&self.qsy_input) else {
+            self.notifications
+                .warning(format!("Invalid QSY entry: {}", self.qsy_input.trim()));
+            return;
+        };
+
+        match self.tune_radio(frequency_khz, mode) {
+            Ok(()) => self.qsy_input.clear(),
+            Err(e) => self.radio_error = Some(e.to_string()),
+        }
+    }
+
+    /// Raise an alert: shown now on the banner and VFD, or held while away
+    fn raise_alert(&mut self, key: &str, title: &str, detail: String) {
+        let message = format!("{}: {}", title, detail);
+        if self.alert_center.raise(key, message.clone()) == AlertDelivery::Immediate {
+            self.vfd_display
+                .interrupt(vec![title.to_uppercase(), detail], ALERT_DISPLAY_TIME);
+            self.active_alert = Some(Alert {
+                message,
+                raised: Instant::now(),
+            });
+        }
+    }
+
+    /// Check a newly received spot against the alert rules
+    fn check_spot_alerts(&mut self, raw: &RawSpot) {
+        let rank_limit = self.config.most_wanted_alert_rank as usize;
+        if rank_limit == 0 {
+            return;
+        }
+        let Some(entity) = self.data_files.country_db.lookup(&raw.spotted_callsign) else {
+            return;
+        };
+        let prefix = entity.primary_prefix.to_uppercase();
+        let Some(rank) = self
+            .data_files
+            .most_wanted
+            .iter()
+            .take(rank_limit)
+            .position(|p| *p == prefix)
+        else {
+            return;
+        };
+
+        let entity_name = entity.name.clone();
+        self.raise_alert(
+            &format!("most_wanted|{}", raw.spotted_callsign),
+            "Most wanted",
+            format!(
+                "#{} {} {:.1} {}",
+                rank + 1,
+                raw.spotted_callsign,
+                raw.frequency_khz,
+                entity_name
+            ),
+        );
+    }
+
+    /// Alert on a spot of an entity still needed on its band and mode
+    fn check_needed_alert(&mut self, raw: &RawSpot) {
+        if !self.config.needed_alerts
+            || !self.is_needed(&raw.spotted_callsign, raw.frequency_khz, &raw.mode)
+        {
+            return;
+        }
+        let Some(entity) = self.data_files.country_db.lookup(&raw.spotted_callsign) else {
+            return;
+        };
+        let entity_name = entity.name.clone();
+        let mode = ModeClass::from_mode(&raw.mode);
+        self.raise_alert(
+            &format!("needed|{}|{}", raw.spotted_callsign, mode.as_str()),
+            "New one",
+            format!(
+                "{} {:.1} {} {}",
+                raw.spotted_callsign,
+                raw.frequency_khz,
+                mode.label(),
+                entity_name
+            ),
+        );
+    }
+
+    /// Alert when another station is spotted inside the run frequency's guard band
+    ///
+    /// The VFD flash and sound go off even while away, since that's when
+    /// someone moving in matters most; the banner waits for the away summary.
+    fn check_run_frequency(&mut self, raw: &RawSpot) {
+        let Some(offset_hz) = self.run_offset_hz(raw.frequency_khz) else {
+            return;
+        };
+        if raw.source == SpotSource::Manual {
+            return;
+        }
+        let detail = format!("{} {:+.0} Hz", raw.spotted_callsign, offset_hz);
+        let message = format!("Run frequency busy: {}", detail);
+        let delivery = self.alert_center.raise(
+            &format!("run_busy|{}", raw.spotted_callsign),
+            message.clone(),
+        );
+        match delivery {
+            AlertDelivery::Suppressed => return,
+            AlertDelivery::Immediate => {
+                self.active_alert = Some(Alert {
+                    message,
+                    raised: Instant::now(),
+                });
+            }
+            AlertDelivery::Deferred => {}
+        }
+        if self.config.run_busy_flash {
+            self.vfd_display
+                .interrupt(vec!["FREQ BUSY".to_string(), detail], ALERT_DISPLAY_TIME);
+        }
+        let sound = self.config.run_busy_sound.trim();
+        if !sound.is_empty() {
+            if let Err(e) = run_detached(sound) {
+                self.notifications
+                    .warning(format!("Could not play the busy alert sound: {}", e));
+            }
+        }
+    }
+
+    /// Offset of `frequency_khz` from the run frequency in Hz, if inside the guard band
+    fn run_offset_hz(&self, frequency_khz: f64) -> Option<f64> {
+        let offset_hz = (frequency_khz - self.run_frequency_khz?) * 1000.0;
+        (offset_hz.abs() <= self.config.run_guard_hz as f64).then_some(offset_hz)
+    }
+
+    /// Run frequency entry and the guard band, flash and sound settings
+    fn show_run_frequency(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.run_frequency_input)
+                    .hint_text("kHz")
+                    .desired_width(80.0),
+            );
+            let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            if ui.button("Set").clicked() || entered {
+                match self.run_frequency_input.trim().parse::<f64>() {
+                    Ok(khz) if khz > 0.0 => self.run_frequency_khz = Some(khz),
+                    _ => self.notifications.warning(format!(
+                        "Invalid run frequency: {}",
+                        self.run_frequency_input.trim()
+                    )),
+                }
+            }
+            if ui
+                .add_enabled(
+                    self.radio_controller.is_connected(),
+                    egui::Button::new("From radio"),
+                )
+                .clicked()
+            {
+                match self.radio_controller.get_frequency() {
+                    Ok(khz) => {
+                        self.run_frequency_khz = Some(khz);
+                        self.run_frequency_input = format!("{:.1}", khz);
+                    }
+                    Err(e) => self.radio_error = Some(e.to_string()),
+                }
+            }
+            if ui
+                .add_enabled(self.run_frequency_khz.is_some(), egui::Button::new("Clear"))
+                .clicked()
+            {
+                self.run_frequency_khz = None;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Guard band:");
+            ui.add(
+                egui::DragValue::new(&mut self.config.run_guard_hz)
+                    .range(0..=10_000)
+                    .suffix(" Hz"),
+            );
+            ui.checkbox(&mut self.config.run_busy_flash, "Flash VFD");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Sound command:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.config.run_busy_sound)
+                    .hint_text("e.g. aplay beep.wav")
+                    .desired_width(180.0),
+            );
+        });
+    }
+
+    /// Everything spotted within a kHz of the watched frequency, nearest first
+    fn show_frequency_watch(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Frequency:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.watch_frequency_input)
+                    .hint_text("kHz")
+                    .desired_width(80.0),
+            );
+        });
+        let input = self.watch_frequency_input.trim();
+        if input.is_empty() {
+            return;
+        }
+        let Ok(frequency_khz) = input.parse::<f64>() else {
+            ui.label(egui::RichText::new("Enter a frequency in kHz").weak());
+            return;
+        };
+
+        let max_age = Duration::from_secs(self.config.max_age_minutes as u64 * 60);
+        let spots = self
+            .spot_store
+            .get_spots_near(frequency_khz, WATCH_RANGE_KHZ, max_age);
+        if spots.is_empty() {
+            ui.label(format!(
+                "Nothing spotted within {:.0} kHz of {:.1}",
+                WATCH_RANGE_KHZ, frequency_khz
+            ));
+            return;
+        }
+        egui::Grid::new("frequency_watch_grid")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                for label in ["Freq", "Callsign", "SNR", "#", "Age"] {
+                    ui.label(egui::RichText::new(label).strong());
+                }
+                ui.end_row();
+                let frequency_format = self.config.frequency_format();
+                for spot in spots {
+                    ui.label(frequency_format.format(spot.frequency_khz));
+                    if ui
+                        .selectable_label(false, &spot.callsign)
+                        .on_hover_text("Select")
+                        .clicked()
+                    {
+                        self.selected_spot = Some(spot.clone());
+                    }
+                    ui.label(spot.highest_snr.to_string());
+                    ui.label(spot.spot_count.to_string());
+                    ui.label(format!("{}s", spot.age_seconds()));
+                    ui.end_row();
+                }
+            });
+    }
+
+    /// Whether working `callsign` here would fill a needed DXCC slot
+    fn is_needed(&self, callsign: &str, frequency_khz: f64, mode: &str) -> bool {
+        let Some(entity) = self.data_files.country_db.lookup(callsign) else {
+            return false;
+        };
+        let Some(band) = self.config.band_plan.for_frequency(frequency_khz) else {
+            return false;
+        };
+        self.needs.is_needed(
+            &entity.primary_prefix,
+            &band.name,
+            ModeClass::from_mode(mode),
+        )
+    }
+
+    /// Write the needs matrix back to the data directory
+    fn save_needs(&mut self) {
+        let Some(ref dir) = self.data_dir else {
+            return;
+        };
+        if let Err(e) = self.needs.save(&dir.join(NEEDS_FILE)) {
+            self.notifications
+                .error(format!("Could not save {}: {}", NEEDS_FILE, e));
+        }
+    }
+
+    /// Read a LoTW or Club Log ADIF export into the needs matrix
+    fn import_needs(&mut self) {
+        let path = self.needs_import_path.trim().to_string();
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) => {
+                self.notifications
+                    .error(format!("Could not read {}: {}", path, e));
+                return;
+            }
+        };
+        match self
+            .needs
+            .import_adif(&text, &self.config.band_plan, &self.data_files.country_db)
+        {
+            Ok(count) => {
+                self.notifications.info(format!(
+                    "Imported {} new confirmed slots from {}",
+                    count, path
+                ));
+                self.save_needs();
+            }
+            Err(e) => self.notifications.error(format!("{}: {}", path, e)),
+        }
+    }
+
+    /// Import and hand editing of the confirmed DXCC slots
+    fn show_needs(&mut self, ui: &mut egui::Ui) {
+        ui.label("Spots of entities not confirmed on that band and mode are marked NEW.");
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::TextEdit::singleline(&mut self.needs_import_path)
+                    .hint_text("Path to LoTW or Club Log ADIF")
+                    .desired_width(220.0),
+            );
+            if ui
+                .add_enabled(
+                    !self.needs_import_path.trim().is_empty(),
+                    egui::Button::new("Import"),
+                )
+                .clicked()
+            {
+                self.import_needs();
+            }
+        });
+        ui.checkbox(&mut self.config.needed_alerts, "Alert on needed entities");
+
+        ui.add_space(4.0);
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            let (prefix, band, mode) = &mut self.needs_entry;
+            ui.add(
+                egui::TextEdit::singleline(prefix)
+                    .hint_text("Prefix")
+                    .desired_width(50.0),
+            );
+            egui::ComboBox::from_id_salt("needs_band")
+                .selected_text(band.as_str())
+                .show_ui(ui, |ui| {
+                    for b in self.config.band_plan.bands() {
+                        ui.selectable_value(band, b.name.clone(), &b.name);
+                    }
+                });
+            egui::ComboBox::from_id_salt("needs_mode")
+                .selected_text(mode.label())
+                .show_ui(ui, |ui| {
+                    for m in ModeClass::ALL {
+                        ui.selectable_value(mode, m, m.label());
+                    }
+                });
+            if ui
+                .add_enabled(
+                    !prefix.trim().is_empty(),
+                    egui::Button::new("Mark confirmed"),
+                )
+                .clicked()
+            {
+                self.needs.set_confirmed(prefix, band, *mode, true);
+                prefix.clear();
+                changed = true;
+            }
+        });
+
+        ui.label(format!("{} confirmed slots", self.needs.len()));
+        let mut remove = None;
+        egui::ScrollArea::vertical()
+            .id_salt("needs_slots")
+            .max_height(150.0)
+            .show(ui, |ui| {
+                egui::Grid::new("needs_grid")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        for (prefix, band, mode) in self.needs.slots() {
+                            let name = self
+                                .data_files
+                                .country_db
+                                .lookup(prefix)
+                                .map(|e| e.name.as_str())
+                                .unwrap_or(prefix.as_str());
+                            ui.label(name);
+                            ui.label(band.as_str());
+                            ui.label(mode.label());
+                            if ui.small_button("Remove").clicked() {
+                                remove = Some((prefix.clone(), band.clone(), *mode));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        if let Some((prefix, band, mode)) = remove {
+            self.needs.set_confirmed(&prefix, &band, mode, false);
+            changed = true;
+        }
+        if changed {
+            self.save_needs();
+        }
+    }
+
+    /// Process incoming RBN messages
+    fn process_rbn_messages(&mut self) {
+        // Collect messages first to avoid borrow conflicts
+        let messages: Vec<RbnMessage> = if let Some(ref mut client) = self.rbn_client {
+            let mut msgs = Vec::new();
+            while let Some(msg) = client.try_recv() {
+                msgs.push(msg);
+            }
+            msgs
+        } else {
+            Vec::new()
+        };
+
+        if !messages.is_empty() {
+            self.last_feed_activity = Instant::now();
+        }
+
+        // Process collected messages; spots are stored in one batch
+        let mut should_disconnect = false;
+        let mut spots = Vec::new();
+        for msg in messages {
+            match msg {
+                RbnMessage::Status(s) => {
+                    self.notifications.info(s);
+                }
+                RbnMessage::Error(e) => self.report_error(&e),
+                RbnMessage::Spot(raw) => spots.push(raw),
+                RbnMessage::Disconnected => {
+                    self.is_connected = false;
+                    should_disconnect = true;
+                }
+                RbnMessage::RawData { data, received } => {
+                    let prefix = if received { "<<" } else { ">>" };
+                    let line = format!("{} {}", prefix, data.trim_end());
+                    self.raw_data_log.push(line);
+                    // Keep log from growing too large
+                    if self.raw_data_log.len() > RAW_DATA_LOG_MAX_LINES {
+                        self.raw_data_log.remove(0);
+                    }
+                }
+            }
+        }
+
+        self.accept_spots(spots);
+
+        if should_disconnect {
+            self.rbn_client = None;
+        }
+    }
+
+    /// Reload data files the background updater has replaced
+    fn process_data_file_messages(&mut self) {
+        let Some(ref updater) = self.data_updater else {
+            return;
+        };
+        let messages: Vec<DataFileMessage> = std::iter::from_fn(|| updater.try_recv()).collect();
+
+        for msg in messages {
+            match msg {
+                DataFileMessage::Updated(kind) => {
+                    if let Some(ref dir) = self.data_dir {
+                        self.data_files.reload(kind, dir);
+                    }
+                    if kind == DataFileKind::CtyDat {
+                        self.contest_log.recount(&self.data_files.country_db);
+                    }
+                    self.notifications.info(format!("Updated {}", kind.label()));
+                }
+                DataFileMessage::Failed(kind, e) => {
+                    self.notifications.error(format!("{}: {}", kind.label(), e));
+                }
+            }
+        }
+    }
+
+    /// Note which rows are new or changed since the last refresh
+    fn track_row_changes(&mut self, spots: &[AggregatedSpot]) {
+        let now = Instant::now();
+        for spot in spots {
+            let changed = match self.row_states.get(&spot.key()) {
+                Some(state) => {
+                    state.snr != spot.highest_snr
+                        || (state.frequency_khz - spot.frequency_khz).abs() >= 0.05
+                }
+                None => true,
+            };
+            if changed {
+                self.row_states.insert(
+                    spot.key(),
+                    RowState {
+                        snr: spot.highest_snr,
+                        frequency_khz: spot.frequency_khz,
+                        changed: now,
+                    },
+                );
+            }
+        }
+        // Forget rows that are no longer listed so they highlight if they return
+        self.row_states
+            .retain(|key, _| spots.iter().any(|s| s.key() == *key));
+    }
+
+    /// Highlight strength for a row: 1.0 just changed, fading to 0.0
+    fn row_highlight(&self, spot: &AggregatedSpot) -> f32 {
+        self.row_states
+            .get(&spot.key())
+            .map(|state| {
+                1.0 - (state.changed.elapsed().as_secs_f32() / ROW_HIGHLIGHT_TIME.as_secs_f32())
+                    .min(1.0)
+            })
+            .unwrap_or(0.0)
+    }
+
+    /// Text color of a spot row under the configured age and SNR coloring
+    /// (dupes are always greyed out)
+    fn row_color(&self, visuals: &egui::Visuals, spot: &AggregatedSpot) -> Option<egui::Color32> {
+        if self.is_dupe(spot) {
+            return Some(visuals.weak_text_color());
+        }
+        if !self.config.row_color_age && !self.config.row_color_snr && !self.config.row_color_mode {
+            return None;
+        }
+        let base = if self.config.row_color_mode {
+            mode_color(visuals, ModeClass::from_mode(&spot.mode))
+        } else {
+            visuals.text_color()
+        };
+        let mut color = if !self.config.row_color_age {
+            base
+        } else if spot.age_seconds() < 60 {
+            if self.config.row_color_mode {
+                base
+            } else {
+                visuals.strong_text_color()
+            }
+        } else {
+            let max_age = Duration::from_secs(self.config.max_age_minutes as u64 * 60);
+            base.lerp_to_gamma(egui::Color32::GRAY, spot.age_fraction(max_age))
+        };
+        if self.config.row_color_snr {
+            // Full strength from 30 dB, down to 40% at 5 dB and below
+            let intensity = ((spot.highest_snr - 5) as f32 / 25.0).clamp(0.4, 1.0);
+            color = color.gamma_multiply(intensity);
+        }
+        Some(color)
+    }
+
+    /// Read the radio's frequency and start a boost when it moves to another band
+    fn poll_vfo(&mut self) {
+        if self.session_player.is_some() || !self.radio_controller.is_connected() {
+            return;
+        }
+        let Ok(frequency_khz) = self.radio_controller.get_frequency() else {
+            return;
+        };
+        let band = self
+            .config
+            .band_plan
+            .for_frequency(frequency_khz)
+            .map(|b| b.name.clone());
+        if band.is_none() || band == self.radio_band {
+            return;
+        }
+
+        self.select_antenna(frequency_khz);
+
+        // The first reading after connecting is not a band change
+        if self.radio_band.is_some() && self.config.band_boost_seconds > 0 {
+            let until = Instant::now() + Duration::from_secs(self.config.band_boost_seconds as u64);
+            self.band_boost = band.clone().map(|name| (name, until));
+        }
+        self.radio_band = band;
+    }
+
+    /// Move spots on the boosted band to the top, keeping their order otherwise
+    fn boost_spots(&self, spots: &mut [AggregatedSpot]) {
+        let Some((name, _)) = &self.band_boost else {
+            return;
+        };
+        let Some(band) = self
+            .config
+            .band_plan
+            .bands()
+            .iter()
+            .find(|b| &b.name == name)
+        else {
+            return;
+        };
+        spots.sort_by_key(|s| !band.contains(s.frequency_khz));
+    }
+
+    /// Perform periodic updates
+    fn update_periodic(&mut self) {
+        let now = Instant::now();
+
+        self.alert_center.update();
+        self.process_script_actions();
+        let heading = self.selected_spot.as_ref().and_then(|spot| {
+            let bearing = self.spot_bearing(spot)?;
+            let note = if self.config.show_spot_note {
+                spot.note.as_str()
+            } else {
+                ""
+            };
+            Some(heading_lines(
+                &spot.callsign,
+                bearing,
+                note,
+                self.config.display_geometry.width,
+            ))
+        });
+        self.vfd_display.set_heading_lines(heading);
+        let follow = self.logger_frequency_khz().map(|khz| {
+            let max_age = Duration::from_secs(self.config.max_age_minutes as u64 * 60);
+            let spots = self
+                .spot_store
+                .get_spots_near(khz, FOLLOW_RANGE_KHZ, max_age);
+            follow_lines(khz, &spots, self.config.display_geometry.width)
+        });
+        self.vfd_display.set_follow_lines(follow);
+        if let Some(error) = self.spot_db.as_ref().and_then(|db| db.take_error()) {
+            self.report_error(&error);
+        }
+        if self
+            .active_alert
+            .as_ref()
+            .is_some_and(|a| a.raised.elapsed() >= ALERT_DISPLAY_TIME)
+        {
+            self.active_alert = None;
+        }
+
+        // Purge old spots every 5 seconds
+        if now.duration_since(self.last_purge) >= Duration::from_secs(5) {
+            self.spot_store.purge_old_spots();
+            self.self_spots
+                .purge(Duration::from_secs(self.config.max_age_minutes as u64 * 60));
+            self.vfd_display.set_my_signal_lines(
+                self.self_spots
+                    .vfd_lines(&self.config.band_plan, self.config.display_geometry.width),
+            );
+            self.vfd_display.set_solar_lines(
+                self.solar
+                    .as_ref()
+                    .filter(|s| !s.is_stale())
+                    .map(|s| s.vfd_lines(self.config.display_geometry.width)),
+            );
+            let max_age = Duration::from_secs(self.config.max_age_minutes as u64 * 60);
+            self.vfd_display.set_beacon_lines(beacon_lines(
+                &self.spot_store.get_filtered_spots(i32::MIN, max_age),
+                &self.config.band_plan,
+                self.config.display_geometry.width,
+            ));
+            self.last_purge = now;
+        }
+
+        // Refresh available ports every 5 seconds
+        if now.duration_since(self.last_port_refresh) >= Duration::from_secs(5) {
+            self.available_ports = self.config.display_ports();
+            self.last_port_refresh = now;
+        }
+
+        if now.duration_since(self.last_handoff_check) >= HANDOFF_POLL_INTERVAL {
+            self.last_handoff_check = now;
+            self.check_handoff();
+        }
+
+        if now.duration_since(self.last_settings_check) >= SETTINGS_POLL_INTERVAL {
+            self.last_settings_check = now;
+            self.reload_edited_settings();
+        }
+
+        if now.duration_since(self.last_autosave_check) >= AUTOSAVE_CHECK_INTERVAL {
+            self.last_autosave_check = now;
+            self.autosave_settings();
+        }
+
+        if self.watchdog.is_some()
+            && now.duration_since(self.last_watchdog_check) >= WATCHDOG_CHECK_INTERVAL
+        {
+            self.last_watchdog_check = now;
+            self.check_subsystems();
+        }
+
+        if now.duration_since(self.last_vfo_poll) >= VFO_POLL_INTERVAL {
+            self.last_vfo_poll = now;
+            self.poll_vfo();
+        }
+        if self
+            .band_boost
+            .as_ref()
+            .is_some_and(|(_, until)| now >= *until)
+        {
+            self.band_boost = None;
+        }
+
+        if let Some(error) = self.vfd_display.take_error() {
+            self.report_error(&error);
+        }
+
+        // Update VFD display (replayed sessions drive the display from recorded frames)
+        if self.session_player.is_none() {
+            self.vfd_display
+                .set_spots_24h(self.spot_store.count_heard_since(SPOT_TICKER_WINDOW));
+            let mut spots = self.filtered_spots();
+            self.boost_spots(&mut spots);
+            if self.config.contest.is_some() {
+                spots.sort_by_cached_key(|s| self.new_multipliers(s).is_empty());
+            }
+            // Dupes drop to the back of the rotation
+            spots.sort_by_cached_key(|s| self.is_dupe(s));
+            self.vfd_display.set_selected_spot(
+                self.selected_spot
+                    .as_ref()
+                    .map(|s| (s.callsign.clone(), s.frequency_khz)),
+            );
+            self.vfd_display.update(&spots);
+
+            if spots.is_empty() && !self.spots_idle {
+                if let Some(ref host) = self.script_host {
+                    host.on_idle();
+                }
+            }
+            self.spots_idle = spots.is_empty();
+        }
+
+        if let Some(error) = self.mqtt.as_ref().and_then(|m| m.take_error()) {
+            self.report_error(&error);
+        }
+        if let Some(ref mqtt) = self.mqtt {
+            let frame = self.vfd_display.get_preview();
+            if frame != self.last_published_frame {
+                mqtt.publish_frame(&frame);
+                self.last_published_frame = frame;
+            }
+        }
+
+        if self.session_recorder.is_some() {
+            let filters = (self.config.min_snr, self.config.max_age_minutes);
+            if self.last_recorded_filters != Some(filters) {
+                self.last_recorded_filters = Some(filters);
+                self.record_event(SessionEvent::Filters {
+                    min_snr: filters.0,
+                    max_age_minutes: filters.1,
+                });
+            }
+
+            let frame = self.vfd_display.get_preview();
+            if frame != self.last_recorded_frame {
+                self.last_recorded_frame = frame.clone();
+                self.record_event(SessionEvent::Frame(frame));
+            }
+        }
+    }
+}
+
+/// Parse a QSY entry: frequency in kHz, optionally followed by a mode (defaults
+/// to CW; SSB picks the sideband for the frequency)
+fn parse_qsy(input: &str) -> Option<(f64, RadioMode)> {
+    let mut parts = input.split_whitespace();
+    let frequency_khz: f64 = parts.next()?.parse().ok()?;
+    if !(100.0..=500_000.0).contains(&frequency_khz) {
+        return None;
+    }
+    let mode = match parts.next() {
+        Some(name) if name.eq_ignore_ascii_case("ssb") => RadioMode::sideband(frequency_khz),
+        Some(name) => RadioMode::from_name(name)?,
+        None => RadioMode::Cw,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+    Some((frequency_khz, mode))
+}
+
+/// Convert the configured idle minutes into a timeout (0 disables idle detection)
+fn idle_timeout(minutes: u32) -> Option<Duration> {
+    (minutes > 0).then(|| Duration::from_secs(minutes as u64 * 60))
+}
+
+/// Compact elapsed time, e.g. "45s" or "12m"
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3600 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}h", secs / 3600)
+    }
+}
+
+/// Format a byte count with a binary unit, e.g. "1.2 MB"
+fn format_bytes(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// Describe how long ago a file time was, e.g. "3 h ago"
+fn format_time_ago(time: SystemTime) -> String {
+    let secs = time.elapsed().unwrap_or_default().as_secs();
+    if secs < 60 {
+        "just now".to_string()
+    } else if secs < 3600 {
+        format!("{} min ago", secs / 60)
+    } else if secs < 86400 {
+        format!("{} h ago", secs / 3600)
+    } else {
+        format!("{} d ago", secs / 86400)
+    }
+}
+
+/// Names of the bands each callsign is currently spotted on, in band plan order
+fn bands_by_call(band_plan: &BandPlan, spots: &[AggregatedSpot]) -> HashMap<String, Vec<String>> {
+    let mut bands: HashMap<String, Vec<String>> = HashMap::new();
+    for band in band_plan.bands() {
+        for spot in spots.iter().filter(|s| band.contains(s.frequency_khz)) {
+            let names = bands.entry(spot.callsign.clone()).or_default();
+            if !names.contains(&band.name) {
+                names.push(band.name.clone());
+            }
+        }
+    }
+    bands
+}
+
+/// The same call's spot on the next higher band, wrapping to the lowest
+fn next_band_spot(
+    band_plan: &BandPlan,
+    spots: &[AggregatedSpot],
+    current: &AggregatedSpot,
+) -> Option<AggregatedSpot> {
+    let band_index = |freq: f64| band_plan.bands().iter().position(|b| b.contains(freq));
+    let current_band = band_index(current.frequency_khz);
+    let mut others: Vec<(usize, &AggregatedSpot)> = spots
+        .iter()
+        .filter(|s| s.callsign == current.callsign)
+        .filter_map(|s| Some((band_index(s.frequency_khz)?, s)))
+        .filter(|(band, _)| Some(*band) != current_band)
+        .collect();
+    others.sort_by_key(|(band, _)| *band);
+    others
+        .iter()
+        .find(|(band, _)| current_band.is_none_or(|current| *band > current))
+        .or(others.first())
+        .map(|(_, spot)| (*spot).clone())
+}
+
+/// Draw one activity strip per band that has spots; returns the segment of a clicked bin
+fn draw_band_scope(
+    ui: &mut egui::Ui,
+    band_plan: &BandPlan,
+    spots: &[AggregatedSpot],
+    selected: Option<(f64, f64)>,
+) -> Option<(f64, f64)> {
+    let mut clicked = None;
+
+    for band in band_plan.bands() {
+        let mut bins = [0u32; BAND_SCOPE_BINS];
+        let bin_width = (band.high_khz - band.low_khz) / BAND_SCOPE_BINS as f64;
+        for spot in spots.iter().filter(|s| band.contains(s.frequency_khz)) {
+            let bin = ((spot.frequency_khz - band.low_khz) / bin_width) as usize;
+            bins[bin.min(BAND_SCOPE_BINS - 1)] += spot.spot_count;
+        }
+        let max = bins.iter().copied().max().unwrap_or(0);
+        if max == 0 {
+            continue;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(format!("{:>4}", band.name)).monospace());
+            let (rect, response) = ui.allocate_exact_size(
+                egui::vec2(ui.available_width().min(384.0), 12.0),
+                egui::Sense::click(),
+            );
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(rect, 0.0, egui::Color32::from_rgb(10, 10, 10));
+
+            let column = rect.width() / BAND_SCOPE_BINS as f32;
+            for (i, &count) in bins.iter().enumerate() {
+                let low = band.low_khz + i as f64 * bin_width;
+                let is_selected = selected == Some((low, low + bin_width));
+                if count == 0 && !is_selected {
+                    continue;
+                }
+                let level = (count as f32 / max as f32).sqrt();
+                let color = if is_selected {
+                    egui::Color32::from_rgb(255, 200, 0)
+                } else {
+                    egui::Color32::from_rgb(0, (60.0 + 195.0 * level) as u8, 0)
+                };
+                let x = rect.left() + i as f32 * column;
+                painter.rect_filled(
+                    egui::Rect::from_min_max(
+                        egui::pos2(x, rect.top()),
+                        egui::pos2(x + column.max(1.0), rect.bottom()),
+                    ),
+                    0.0,
+                    color,
+                );
+            }
+
+            if let Some(pos) = response
+                .interact_pointer_pos()
+                .filter(|_| response.clicked())
+            {
+                let i = (((pos.x - rect.left()) / column) as usize).min(BAND_SCOPE_BINS - 1);
+                let low = band.low_khz + i as f64 * bin_width;
+                clicked = Some((low, low + bin_width));
+            }
+            response.on_hover_text(format!(
+                "{} ({:.0}-{:.0} kHz)",
+                band.name, band.low_khz, band.high_khz
+            ));
+        });
+    }
+
+    clicked
+}
+
+/// Host and port of the selected network radio backend
+fn radio_address_fields(ui: &mut egui::Ui, radio: &mut crate::config::RadioConfig) {
+    if radio.backend == "flex" {
+        ui.horizontal(|ui| {
+            ui.label("Radio:");
+            ui.text_edit_singleline(&mut radio.flex_host)
+                .on_hover_text("IP address of the radio");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Slice:");
+            for slice in 0..8 {
+                ui.radio_value(
+                    &mut radio.flex_slice,
+                    slice,
+                    slice_letter(slice).to_string(),
+                );
+            }
+        });
+        return;
+    }
+    let (host, port) = if radio.backend == "commander" {
+        (&mut radio.commander_host, &mut radio.commander_port)
+    } else {
+        (&mut radio.rigctld_host, &mut radio.rigctld_port)
+    };
+    ui.horizontal(|ui| {
+        ui.label("Host:");
+        ui.text_edit_singleline(host);
+    });
+    ui.horizontal(|ui| {
+        ui.label("Port:");
+        let mut port_str = port.to_string();
+        if ui.text_edit_singleline(&mut port_str).changed() {
+            if let Ok(value) = port_str.parse() {
+                *port = value;
+            }
+        }
+    });
+}
+
+/// Marker color for the band at `index` in the band plan
+fn band_color(index: usize) -> egui::Color32 {
+    const PALETTE: [egui::Color32; 10] = [
+        egui::Color32::from_rgb(230, 25, 75),
+        egui::Color32::from_rgb(245, 130, 48),
+        egui::Color32::from_rgb(255, 225, 25),
+        egui::Color32::from_rgb(60, 180, 75),
+        egui::Color32::from_rgb(70, 240, 240),
+        egui::Color32::from_rgb(0, 130, 200),
+        egui::Color32::from_rgb(145, 30, 180),
+        egui::Color32::from_rgb(240, 50, 230),
+        egui::Color32::from_rgb(210, 245, 60),
+        egui::Color32::from_rgb(250, 190, 212),
+    ];
+    PALETTE[index % PALETTE.len()]
+}
+
+/// Map color for a signal report: blue when weak through yellow to red when loud
+fn heat_color(snr: i32) -> egui::Color32 {
+    let t = (snr.clamp(0, 30) as f32) / 30.0;
+    let (cold, warm, hot) = (
+        egui::Color32::from_rgb(40, 120, 255),
+        egui::Color32::from_rgb(255, 220, 0),
+        egui::Color32::from_rgb(255, 40, 40),
+    );
+    if t < 0.5 {
+        cold.lerp_to_gamma(warm, t * 2.0)
+    } else {
+        warm.lerp_to_gamma(hot, t * 2.0 - 1.0)
+    }
+}
+
+/// Row tint for a mode group
+fn mode_color(visuals: &egui::Visuals, class: ModeClass) -> egui::Color32 {
+    match class {
+        ModeClass::Cw => visuals.text_color(),
+        ModeClass::Phone => egui::Color32::from_rgb(80, 180, 80),
+        ModeClass::Digital => egui::Color32::from_rgb(90, 140, 240),
+    }
+}
+
+/// Text color for a notification
+fn severity_color(visuals: &egui::Visuals, severity: Severity) -> egui::Color32 {
+    match severity {
+        Severity::Info => visuals.text_color(),
+        Severity::Warning => visuals.warn_fg_color,
+        Severity::Error => visuals.error_fg_color,
     }
 }
 
+/// A notification's time as "HH:MM:SSZ"
+fn notification_time(notification: &Notification) -> String {
+    let time = UtcTime::from_system(notification.time);
+    format!("{:02}:{:02}:{:02}Z", time.hour, time.minute, time.second)
+}
+
 /// Draw an age ring indicator
 fn draw_age_ring(ui: &mut egui::Ui, fraction: f32) {
     let size = 16.0;
@@ -261,15 +3088,255 @@ fn draw_age_ring(ui: &mut egui::Ui, fraction: f32) {
     }
 }
 
+impl RbnVfdApp {
+    /// Show every persisted setting with its current value, default and description
+    /// Warnings and errors in the corner until they time out or are dismissed
+    fn show_toasts(&mut self, ctx: &egui::Context) {
+        let mut dismissed = Vec::new();
+        egui::Area::new(egui::Id::new("notification_toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                for notification in self.notifications.toasts() {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.set_max_width(360.0);
+                        ui.horizontal(|ui| {
+                            let color = severity_color(ui.visuals(), notification.severity);
+                            ui.colored_label(
+                                color,
+                                format!(
+                                    "{} {}",
+                                    notification_time(notification),
+                                    notification.severity.label()
+                                ),
+                            );
+                            if ui.small_button("✕").clicked() {
+                                dismissed.push(notification.id);
+                            }
+                        });
+                        ui.label(&notification.message);
+                    });
+                }
+            });
+        for id in dismissed {
+            self.notifications.dismiss(id);
+        }
+    }
+
+    /// Drawer listing every notification, newest first
+    fn show_notification_history(&mut self, ctx: &egui::Context) {
+        let mut clear = false;
+        egui::SidePanel::right("notification_history")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Notifications");
+                    if ui.button("Clear").clicked() {
+                        clear = true;
+                    }
+                    if ui.button("Close").clicked() {
+                        self.show_notification_history = false;
+                    }
+                });
+                ui.separator();
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for notification in self.notifications.history().rev() {
+                        let color = severity_color(ui.visuals(), notification.severity);
+                        ui.horizontal_wrapped(|ui| {
+                            ui.monospace(notification_time(notification));
+                            ui.colored_label(color, &notification.message);
+                        });
+                    }
+                });
+            });
+        if clear {
+            self.notifications.clear_history();
+        }
+    }
+
+    fn show_all_settings_window(&mut self, ctx: &egui::Context) {
+        if self.setting_edits.len() != settings().len() {
+            self.refresh_setting_edits();
+        }
+        let current = self.config.setting_values();
+
+        let mut open = true;
+        let mut commit: Option<usize> = None;
+        let mut reset: Option<usize> = None;
+        let mut export = false;
+        let mut import = false;
+
+        egui::Window::new("All Settings")
+            .open(&mut open)
+            .default_width(600.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.settings_file_path)
+                            .hint_text("Path to a settings .toml file")
+                            .desired_width(260.0),
+                    );
+                    let has_path = !self.settings_file_path.trim().is_empty();
+                    export = ui
+                        .add_enabled(has_path, egui::Button::new("Export Settings"))
+                        .clicked();
+                    import = ui
+                        .add_enabled(has_path, egui::Button::new("Import Settings"))
+                        .on_hover_text("Replaces all current settings")
+                        .clicked();
+                });
+                ui.separator();
+                ui.label("Edit a value and press Enter to apply it.");
+                ui.add_space(4.0);
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("all_settings_grid")
+                            .striped(true)
+                            .num_columns(4)
+                            .show(ui, |ui| {
+                                ui.strong("Key");
+                                ui.strong("Value");
+                                ui.strong("Default");
+                                ui.strong("Description");
+                                ui.end_row();
+
+                                for (i, setting) in settings().iter().enumerate() {
+                                    ui.label(egui::RichText::new(&setting.key).monospace());
+                                    let response = ui.add(
+                                        egui::TextEdit::singleline(&mut self.setting_edits[i])
+                                            .desired_width(140.0),
+                                    );
+                                    if response.lost_focus() {
+                                        commit = Some(i);
+                                    }
+                                    let default = setting.default_value();
+                                    ui.horizontal(|ui| {
+                                        ui.label(egui::RichText::new(&default).monospace());
+                                        if current[i] != default
+                                            && ui.small_button("↺").on_hover_text("Reset").clicked()
+                                        {
+                                            reset = Some(i);
+                                        }
+                                    });
+                                    ui.label(&setting.description);
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+
+        if export {
+            self.export_settings();
+        }
+        if import {
+            self.import_settings();
+            commit = None;
+        }
+
+        if let Some(i) = reset {
+            self.setting_edits[i] = settings()[i].default_value();
+            commit = Some(i);
+        }
+
+        if let Some(i) = commit {
+            let setting = &settings()[i];
+            if self.setting_edits[i] != current[i] {
+                match setting.set(&mut self.config, &self.setting_edits[i]) {
+                    Ok(()) => {
+                        self.apply_config(setting.section() == "radio");
+                        self.callsign_input = self.config.callsign.clone();
+                        self.notifications.info(format!("Set {}", setting.key));
+                    }
+                    Err(e) => {
+                        self.notifications
+                            .warning(format!("{}: {}", setting.key, e));
+                    }
+                }
+                self.setting_edits[i] = setting.get(&self.config);
+            }
+        }
+
+        if !open {
+            self.show_all_settings = false;
+        }
+    }
+}
+
+impl pipeline::ApiHost for RbnVfdApp {
+    fn config(&self) -> &Config {
+        &self.config
+    }
+
+    fn shown_spots(&self) -> Vec<AggregatedSpot> {
+        self.filtered_spots()
+    }
+
+    fn status_json(&self) -> String {
+        serde_json::json!({
+            "rbn_connected": self.is_connected,
+            "spot_count": self.spot_store.count(),
+            "vfd_open": self.vfd_display.is_open(),
+            "vfd_port": self.vfd_display.port_name(),
+            "radio_connected": self.radio_controller.is_connected(),
+            "status": self.notifications.status(),
+            "display": self.vfd_display.get_preview(),
+        })
+        .to_string()
+    }
+
+    fn tune(&mut self, frequency_khz: f64, mode: RadioMode) -> Result<(), RadioError> {
+        if self.session_player.is_some() {
+            return Err(RadioError::Refused("replaying a session".to_string()));
+        }
+        self.tune_radio(frequency_khz, mode)
+    }
+
+    fn vfd_display(&mut self) -> &mut VfdDisplay {
+        &mut self.vfd_display
+    }
+}
+
 impl eframe::App for RbnVfdApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // Any keyboard or mouse input counts as operator presence
+        if ctx.input(|i| !i.events.is_empty() || i.pointer.delta() != egui::Vec2::ZERO) {
+            self.alert_center.note_activity();
+        }
+
+        if let Some(ref mut watchdog) = self.watchdog {
+            watchdog.frame();
+        }
+
+        self.apply_theme(ctx);
+
         // Process messages and periodic updates
         self.process_rbn_messages();
+        self.process_data_file_messages();
+        self.process_propagation_messages();
+        self.process_session_replay();
+        self.process_api_requests();
+        self.process_radio_info();
         self.update_periodic();
 
-        // Request repaint for continuous updates
-        ctx.request_repaint_after(Duration::from_millis(100));
+        // Request repaint for continuous updates (batched in low-bandwidth mode)
+        ctx.request_repaint_after(if self.config.low_bandwidth {
+            Duration::from_millis(1000)
+        } else {
+            Duration::from_millis(100)
+        });
+
+        if self.mini_mode {
+            self.show_mini_window(ctx);
+            return;
+        }
+
+        if self.show_notification_history {
+            self.show_notification_history(ctx);
+        }
+        self.show_toasts(ctx);
 
+        let mut go_mini = false;
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.heading("RBN VFD Display");
@@ -277,6 +3344,72 @@ impl eframe::App for RbnVfdApp {
                     if ui.button("✕").clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                     }
+                    go_mini = ui
+                        .button("▭")
+                        .on_hover_text("Mini window: VFD preview and newest spot, always on top")
+                        .clicked();
+                    if let Some(ref solar) = self.solar {
+                        let mut text = egui::RichText::new(solar.summary()).monospace();
+                        if solar.is_stale() {
+                            text = text.weak();
+                        }
+                        let mut details = format!("Updated {}", solar.updated);
+                        if let Some(ref field) = solar.geomag_field {
+                            details.push_str(&format!("\nGeomagnetic field: {}", field));
+                        }
+                        if let Some(sunspots) = solar.sunspots {
+                            details.push_str(&format!("\nSunspots: {}", sunspots));
+                        }
+                        if let Some(ref xray) = solar.xray {
+                            details.push_str(&format!("\nX-ray: {}", xray));
+                        }
+                        ui.label(text).on_hover_text(details);
+                    }
+
+                    // Configuration profiles (e.g. contest / casual / SWL)
+                    let active = self.config.active_profile.clone();
+                    let mut selected = active.clone();
+                    egui::ComboBox::from_id_salt("config_profile_selector")
+                        .selected_text(if selected.is_empty() {
+                            "(custom)"
+                        } else {
+                            selected.as_str()
+                        })
+                        .show_ui(ui, |ui| {
+                            for profile in &self.config.profiles {
+                                ui.selectable_value(
+                                    &mut selected,
+                                    profile.name.clone(),
+                                    &profile.name,
+                                );
+                            }
+                            ui.separator();
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.profile_name)
+                                    .hint_text("Profile name")
+                                    .desired_width(120.0),
+                            );
+                            let name = self.profile_name.trim().to_string();
+                            ui.horizontal(|ui| {
+                                if ui
+                                    .add_enabled(!name.is_empty(), egui::Button::new("Save current"))
+                                    .clicked()
+                                {
+                                    self.config.save_profile(&name);
+                                    self.notifications.info(format!("Saved profile {}", name));
+                                }
+                                let exists = self.config.profiles.iter().any(|p| p.name == name);
+                                if ui.add_enabled(exists, egui::Button::new("Delete")).clicked() {
+                                    self.config.delete_profile(&name);
+                                    self.notifications.info(format!("Deleted profile {}", name));
+                                }
+                            });
+                        })
+                        .response
+                        .on_hover_text("Settings profile: filters, display pages and radio");
+                    if selected != active {
+                        self.switch_profile(&selected);
+                    }
                 });
             });
             ui.separator();
@@ -296,9 +3429,35 @@ impl eframe::App for RbnVfdApp {
                     if ui.button("Disconnect").clicked() {
                         self.disconnect_rbn();
                     }
-                } else if ui.button("Connect").clicked() {
+                } else if ui
+                    .add_enabled(self.session_player.is_none(), egui::Button::new("Connect"))
+                    .clicked()
+                {
                     self.connect_rbn();
                 }
+
+                if let Some(ref player) = self.session_player {
+                    let (played, total) = player.progress();
+                    ui.label(format!("Replay {}/{}", played, total));
+                }
+            });
+
+            // Low-bandwidth mode and traffic counter
+            ui.horizontal(|ui| {
+                if ui
+                    .checkbox(&mut self.config.low_bandwidth, "Low-bandwidth mode")
+                    .on_hover_text(
+                        "Request server-side filters on connect, stop automatic data file \
+                         updates and refresh the UI once per second",
+                    )
+                    .changed()
+                {
+                    self.apply_config(false);
+                }
+                ui.label(format!(
+                    "RBN traffic: {}",
+                    format_bytes(self.rbn_traffic.load(Ordering::Relaxed))
+                ));
             });
 
             ui.add_space(4.0);
@@ -315,17 +3474,80 @@ impl eframe::App for RbnVfdApp {
                         }
                     });
 
-                if self.vfd_display.is_open() {
+                if self.vfd_display.is_released() {
+                    ui.label("Released");
+                    if ui.button("Close").clicked() {
+                        self.close_vfd();
+                    }
+                } else if self.vfd_display.is_open() {
                     if ui.button("Close").clicked() {
                         self.close_vfd();
                     }
                     if ui.button("Blank").clicked() {
                         self.vfd_display.clear();
-                        self.status_message = "Display blanked".to_string();
+                        self.notifications.info("Display blanked");
+                    }
+                    if ui
+                        .add_enabled(!self.vfd_display.is_testing(), egui::Button::new("Test"))
+                        .on_hover_text("Cycle test patterns to check wiring and the driver")
+                        .clicked()
+                    {
+                        self.vfd_display.start_test();
+                        self.notifications.info("Running display test");
                     }
                 } else if ui.button("Open").clicked() {
                     self.open_vfd();
                 }
+
+                let mut geometry = self.config.display_geometry;
+                egui::ComboBox::from_id_salt("display_geometry")
+                    .selected_text(geometry.to_string())
+                    .width(60.0)
+                    .show_ui(ui, |ui| {
+                        for preset in DisplayGeometry::PRESETS {
+                            ui.selectable_value(&mut geometry, preset, preset.to_string());
+                        }
+                    });
+                if geometry != self.config.display_geometry {
+                    self.config.display_geometry = geometry;
+                    self.vfd_display.set_geometry(geometry);
+                }
+
+                let mut protocol = self.config.display_protocol;
+                egui::ComboBox::from_id_salt("display_protocol")
+                    .selected_text(protocol.label())
+                    .show_ui(ui, |ui| {
+                        for kind in ProtocolKind::available() {
+                            ui.selectable_value(&mut protocol, kind, kind.label());
+                        }
+                    });
+                if protocol != self.config.display_protocol {
+                    self.config.display_protocol = protocol;
+                    self.vfd_display.set_protocol(protocol);
+                }
+            });
+
+            // Network display offered in the port list
+            ui.horizontal(|ui| {
+                ui.label("Network Display:");
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.config.network_display)
+                        .hint_text("tcp://host:port or udp://host:port"),
+                );
+                if response.lost_focus() {
+                    let address = self.config.network_display.trim().to_string();
+                    if !address.is_empty() && !VfdDisplay::is_network_port(&address) {
+                        self.report_error(&Error::Config(
+                            "Network display must start with tcp:// or udp://".to_string(),
+                        ));
+                    } else {
+                        self.config.network_display = address.clone();
+                        self.available_ports = self.config.display_ports();
+                        if !address.is_empty() {
+                            self.selected_port = address;
+                        }
+                    }
+                }
             });
 
             ui.add_space(4.0);
@@ -334,13 +3556,42 @@ impl eframe::App for RbnVfdApp {
             ui.horizontal(|ui| {
                 ui.label("Radio:");
                 ui.label(if self.radio_controller.is_connected() {
-                    format!("{} connected", self.radio_controller.backend_name())
+                    match self.radio_controller.rig_name() {
+                        Some(rig) => format!(
+                            "{} connected ({})",
+                            self.radio_controller.backend_name(),
+                            rig
+                        ),
+                        None => format!("{} connected", self.radio_controller.backend_name()),
+                    }
                 } else if self.config.radio.enabled {
                     format!("{} disconnected", self.radio_controller.backend_name())
                 } else {
                     "Not configured".to_string()
                 });
+                if !self.config.radio_profiles.is_empty() {
+                    let mut selected = self.config.active_radio_profile.clone();
+                    egui::ComboBox::from_id_salt("radio_profile_selector")
+                        .selected_text(if selected.is_empty() {
+                            "(custom)"
+                        } else {
+                            selected.as_str()
+                        })
+                        .show_ui(ui, |ui| {
+                            for profile in &self.config.radio_profiles {
+                                ui.selectable_value(
+                                    &mut selected,
+                                    profile.name.clone(),
+                                    &profile.name,
+                                );
+                            }
+                        });
+                    if selected != self.config.active_radio_profile {
+                        self.switch_radio_profile(&selected);
+                    }
+                }
                 if ui.button("Settings...").clicked() {
+                    self.radio_profile_name = self.config.active_radio_profile.clone();
                     self.show_radio_settings = true;
                 }
             });
@@ -350,9 +3601,69 @@ impl eframe::App for RbnVfdApp {
             // Status line
             ui.horizontal(|ui| {
                 ui.label("Status:");
-                ui.label(&self.status_message);
+                ui.label(self.notifications.status());
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    let errors = self.notifications.unread_errors();
+                    let history = if errors > 0 {
+                        format!("History ({} errors)", errors)
+                    } else {
+                        "History".to_string()
+                    };
+                    ui.toggle_value(&mut self.show_notification_history, history);
+                    let mut away = self.alert_center.is_away();
+                    if ui.checkbox(&mut away, "Away").changed() {
+                        self.alert_center.set_away(away);
+                    }
+                    let pending = self.alert_center.pending_count();
+                    if pending > 0 {
+                        ui.label(format!("{} held", pending));
+                    }
+                });
             });
 
+            if let Some(ref radio) = self.logger_radio {
+                ui.label(format!(
+                    "N1MM radio {}: {:.1} kHz {}",
+                    radio.radio_number, radio.frequency_khz, radio.mode
+                ));
+            }
+
+            // Run frequency watch, red while other stations are spotted on it
+            if let Some(run_khz) = self.run_frequency_khz {
+                let intruders: Vec<String> = self
+                    .filtered_spots()
+                    .into_iter()
+                    .filter(|s| !s.manual && self.run_offset_hz(s.frequency_khz).is_some())
+                    .map(|s| s.callsign)
+                    .collect();
+                ui.horizontal(|ui| {
+                    if intruders.is_empty() {
+                        ui.label(format!("Run {:.1} kHz: clear", run_khz));
+                    } else {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "Run {:.1} kHz: BUSY ({})",
+                                run_khz,
+                                intruders.join(", ")
+                            ))
+                            .strong()
+                            .color(egui::Color32::from_rgb(255, 60, 60)),
+                        );
+                    }
+                });
+            }
+
+            // Alert banner
+            if let Some(ref alert) = self.active_alert {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(&alert.message)
+                            .strong()
+                            .color(egui::Color32::from_rgb(255, 200, 0)),
+                    );
+                });
+            }
+
             if self.vfd_display.is_open() {
                 ui.horizontal(|ui| {
                     ui.label("VFD:");
@@ -369,13 +3680,57 @@ impl eframe::App for RbnVfdApp {
                     ui.label("Min SNR:");
                     let mut snr = self.config.min_snr;
                     if ui
-                        .add(egui::Slider::new(&mut snr, 0..=50).suffix(" dB"))
+                        .add_enabled(
+                            !self.config.adaptive_snr,
+                            egui::Slider::new(&mut snr, 0..=50).suffix(" dB"),
+                        )
                         .changed()
                     {
                         self.config.min_snr = snr;
                     }
+                    ui.checkbox(&mut self.config.adaptive_snr, "Adaptive")
+                        .on_hover_text("Per-band thresholds from the SNRs of recent spots");
                 });
 
+                if self.config.adaptive_snr {
+                    ui.horizontal(|ui| {
+                        ui.label("Keep strongest:");
+                        ui.add(
+                            egui::Slider::new(&mut self.config.adaptive_keep_percent, 1..=100)
+                                .suffix("% per band"),
+                        );
+                    });
+                    let max_age = Duration::from_secs(self.config.max_age_minutes as u64 * 60);
+                    let recent = self.spot_store.get_filtered_spots(i32::MIN, max_age);
+                    let thresholds = SpotStore::adaptive_thresholds(
+                        &recent,
+                        &self.config.band_plan,
+                        self.config.adaptive_keep_percent,
+                    );
+                    let summary: Vec<String> = self
+                        .config
+                        .band_plan
+                        .bands()
+                        .iter()
+                        .filter_map(|band| {
+                            thresholds
+                                .get(&band.name)
+                                .map(|snr| format!("{} ≥{} dB", band.name, snr))
+                        })
+                        .collect();
+                    if !summary.is_empty() {
+                        ui.label(egui::RichText::new(summary.join(", ")).small().weak());
+                    }
+                }
+
+                ui.checkbox(&mut self.config.show_beacons, "Show beacons")
+                    .on_hover_text("NCDXF/IARU beacons and /B calls; the beacon VFD page lists them either way");
+
+                ui.checkbox(&mut self.config.hide_dupes, "Hide dupes")
+                    .on_hover_text("Leave out calls already logged on that band this session");
+
+                self.show_ignored(ui);
+
                 ui.add_space(4.0);
 
                 // Max age radio buttons
@@ -414,74 +3769,465 @@ impl eframe::App for RbnVfdApp {
 
                 ui.add_space(4.0);
 
+                // Marquee scrolling instead of flipping
+                ui.horizontal(|ui| {
+                    let mut changed = ui.checkbox(&mut self.config.marquee, "Marquee").changed();
+                    changed |= ui
+                        .add_enabled(
+                            self.config.marquee,
+                            egui::Slider::new(&mut self.config.marquee_chars_per_second, 1..=20)
+                                .suffix(" chars/s"),
+                        )
+                        .changed();
+                    if changed {
+                        self.vfd_display.set_marquee(self.config.marquee_rate());
+                    }
+                });
+
+                ui.add_space(4.0);
+
+                // Brightness, for displays that support it
+                if self.vfd_display.supports_brightness() {
+                    ui.horizontal(|ui| {
+                        ui.label("Brightness:");
+                        let mut brightness = self.config.display_brightness;
+                        if ui
+                            .add(egui::Slider::new(&mut brightness, 0..=100).suffix("%"))
+                            .changed()
+                        {
+                            self.config.display_brightness = brightness;
+                            self.vfd_display.set_brightness(brightness as u8);
+                        }
+                    });
+
+                    ui.add_space(4.0);
+                }
+
                 // Force random mode checkbox
                 ui.horizontal(|ui| {
                     let mut force_random = self.vfd_display.is_in_random_mode();
                     if ui
-                        .checkbox(&mut force_random, "Force random mode")
+                        .checkbox(&mut force_random, "Force screensaver")
                         .clicked()
                     {
                         self.vfd_display.set_force_random_mode(force_random);
                     }
+                    if ui
+                        .checkbox(&mut self.config.flash_new_spots, "Flash new spots")
+                        .changed()
+                    {
+                        self.vfd_display
+                            .set_flash_new_spots(self.config.flash_new_spots);
+                    }
+                });
+
+                ui.add_space(4.0);
+
+                // What to show while there are no spots
+                ui.horizontal(|ui| {
+                    ui.label("When Idle:");
+                    let mut idle = self.config.idle_content;
+                    egui::ComboBox::from_id_salt("idle_content")
+                        .selected_text(idle.label())
+                        .show_ui(ui, |ui| {
+                            for content in IdleContent::ALL {
+                                ui.selectable_value(&mut idle, content, content.label());
+                            }
+                        });
+                    if idle != self.config.idle_content {
+                        self.config.idle_content = idle;
+                        self.vfd_display.set_idle_content(idle);
+                    }
+
+                    let mut style = self.config.screensaver;
+                    egui::ComboBox::from_id_salt("screensaver")
+                        .selected_text(style.label())
+                        .show_ui(ui, |ui| {
+                            for option in ScreensaverStyle::ALL {
+                                ui.selectable_value(&mut style, option, option.label());
+                            }
+                        });
+                    if style != self.config.screensaver {
+                        self.config.screensaver = style;
+                        self.vfd_display.set_screensaver(style);
+                    }
+                });
+
+                ui.add_space(4.0);
+
+                // Which spots the spot page shows
+                ui.horizontal(|ui| {
+                    ui.label("Show:");
+                    let mut content = self.config.vfd_content;
+                    egui::ComboBox::from_id_salt("vfd_content")
+                        .selected_text(content.label())
+                        .show_ui(ui, |ui| {
+                            for option in VfdContent::ALL {
+                                ui.selectable_value(&mut content, option, option.label());
+                            }
+                        });
+                    if content != self.config.vfd_content {
+                        self.config.vfd_content = content;
+                        self.vfd_display.set_content(content);
+                    }
+                    if content == VfdContent::Watchlist {
+                        ui.label("Watchlist:");
+                        // Follow changes made elsewhere (profiles, reloads)
+                        // without reformatting what is being typed
+                        if parse_calls(&self.watchlist_input) != self.config.watchlist {
+                            self.watchlist_input = self.config.watchlist.join(", ");
+                        }
+                        if ui
+                            .add(
+                                egui::TextEdit::singleline(&mut self.watchlist_input)
+                                    .desired_width(180.0)
+                                    .hint_text("W1AW, DL*"),
+                            )
+                            .changed()
+                        {
+                            self.config.watchlist = parse_calls(&self.watchlist_input);
+                            self.vfd_display.set_watchlist(&self.config.watchlist);
+                        }
+                    }
+                });
+
+                ui.add_space(4.0);
+
+                // Station ID message, shown as a page or when idle
+                ui.label("Station ID:");
+                let width = self.config.display_geometry.width;
+                let mut changed = false;
+                for line in self.config.station_message.iter_mut() {
+                    changed |= ui
+                        .add(egui::TextEdit::singleline(line).char_limit(width))
+                        .changed();
+                }
+                if changed {
+                    self.vfd_display
+                        .set_station_message(&self.config.station_message);
+                }
+                ui.add(VfdPreview::lines(
+                    &self.config.station_message,
+                    width,
+                    self.config.preview_style(),
+                ));
+
+                ui.add_space(4.0);
+
+                // Random char duty cycle slider
+                ui.horizontal(|ui| {
+                    ui.label("Random Duty Cycle:");
+                    let mut percent = self.config.random_char_percent;
+                    if ui
+                        .add(egui::Slider::new(&mut percent, 0..=100).suffix("%"))
+                        .changed()
+                    {
+                        self.config.random_char_percent = percent;
+                        self.vfd_display.set_random_char_percent(percent);
+                    }
+                });
+
+                ui.add_space(4.0);
+
+                // Skimmer vs human conflict policy
+                ui.horizontal(|ui| {
+                    ui.label("Source Conflicts:");
+                    egui::ComboBox::from_id_salt("source_policy")
+                        .selected_text(self.config.source_policy.label())
+                        .show_ui(ui, |ui| {
+                            for policy in SourcePolicy::ALL {
+                                ui.selectable_value(
+                                    &mut self.config.source_policy,
+                                    policy,
+                                    policy.label(),
+                                );
+                            }
+                        });
+                });
+
+                // Spots one character off a busier call on the same frequency
+                ui.horizontal(|ui| {
+                    ui.label("Busted Calls:");
+                    let mut policy = self.config.busted_calls;
+                    egui::ComboBox::from_id_salt("busted_calls")
+                        .selected_text(policy.label())
+                        .show_ui(ui, |ui| {
+                            for option in BustedCallPolicy::ALL {
+                                ui.selectable_value(&mut policy, option, option.label());
+                            }
+                        });
+                    if policy != self.config.busted_calls {
+                        self.config.busted_calls = policy;
+                        self.spot_store.set_busted_policy(policy);
+                    }
                 });
 
                 ui.add_space(4.0);
 
-                // Random char duty cycle slider
+                // Named frequency windows (e.g. licensed CW sub-bands)
+                ui.label("Frequency Windows:")
+                    .on_hover_text("Only spots inside one of these ranges are shown; none = everywhere");
+                let mut windows_changed = false;
+                let mut remove = None;
+                for (i, window) in self.config.frequency_windows.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add(egui::TextEdit::singleline(&mut window.name).desired_width(70.0))
+                            .changed()
+                        {
+                            // ':' and ';' delimit the settings.toml list
+                            window.name.retain(|c| c != ':' && c != ';');
+                            windows_changed = true;
+                        }
+                        let high = window.high_khz;
+                        windows_changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut window.low_khz)
+                                    .range(0.0..=high - 0.1)
+                                    .speed(1.0)
+                                    .suffix(" kHz"),
+                            )
+                            .changed();
+                        ui.label("–");
+                        let low = window.low_khz;
+                        windows_changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut window.high_khz)
+                                    .range(low + 0.1..=f64::MAX)
+                                    .speed(1.0)
+                                    .suffix(" kHz"),
+                            )
+                            .changed();
+                        if ui.small_button("✖").on_hover_text("Remove window").clicked() {
+                            remove = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = remove {
+                    self.config.frequency_windows.remove(i);
+                    windows_changed = true;
+                }
+                if ui.button("Add Window").clicked() {
+                    self.config.frequency_windows.push(Band {
+                        name: "20m CW".to_string(),
+                        low_khz: 14000.0,
+                        high_khz: 14070.0,
+                    });
+                    windows_changed = true;
+                }
+                if windows_changed {
+                    self.spot_store
+                        .set_frequency_windows(self.config.frequency_windows.clone());
+                }
+
+                ui.add_space(4.0);
+
                 ui.horizontal(|ui| {
-                    ui.label("Random Duty Cycle:");
-                    let mut percent = self.config.random_char_percent;
-                    if ui
-                        .add(egui::Slider::new(&mut percent, 0..=100).suffix("%"))
-                        .changed()
-                    {
-                        self.config.random_char_percent = percent;
-                        self.vfd_display.set_random_char_percent(percent);
+                    // Restore defaults button
+                    if ui.button("Restore Defaults").clicked() {
+                        self.config.reset_to_defaults();
+                        self.vfd_display
+                            .set_scroll_interval(self.config.scroll_interval_seconds);
+                        self.vfd_display.set_marquee(self.config.marquee_rate());
+                        self.vfd_display
+                            .set_random_char_percent(self.config.random_char_percent);
+                        self.vfd_display.set_screensaver(self.config.screensaver);
+                        self.vfd_display.set_idle_content(self.config.idle_content);
+                        self.vfd_display.set_content(self.config.vfd_content);
+                        self.vfd_display.set_watchlist(&self.config.watchlist);
+                        self.configure_spot_store();
+                    }
+                    if ui.button("All Settings...").clicked() {
+                        self.refresh_setting_edits();
+                        self.show_all_settings = true;
+                    }
+                    if ui.button("Save Settings").clicked() && self.save_settings() {
+                        self.notifications.info("Settings saved");
                     }
                 });
+            });
+
+            ui.separator();
+
+            // VFD Preview
+            ui.collapsing("VFD Preview", |ui| {
+                if self.config.carousel_pages().len() > 1 {
+                    ui.label(format!("Page: {}", self.vfd_display.current_page().label()));
+                }
+
+                self.show_vfd_preview(ui);
+            });
+
+            ui.separator();
+
+            ui.collapsing("Appearance", |ui| self.show_appearance(ui));
+
+            ui.separator();
+
+            // Reference data files
+            ui.collapsing("Data Files", |ui| {
+                if let Some(ref dir) = self.data_dir {
+                    egui::Grid::new("data_files_grid")
+                        .num_columns(3)
+                        .show(ui, |ui| {
+                            for kind in DataFileKind::ALL {
+                                ui.label(kind.label());
+                                ui.label(format!("{} entries", self.data_files.entry_count(kind)));
+                                ui.label(match kind.last_updated(dir) {
+                                    Some(time) => format!("updated {}", format_time_ago(time)),
+                                    None => "never downloaded".to_string(),
+                                });
+                                ui.end_row();
+                            }
+                        });
+                }
 
                 ui.add_space(4.0);
 
-                // Restore defaults button
-                if ui.button("Restore Defaults").clicked() {
-                    self.config.reset_to_defaults();
-                    self.vfd_display
-                        .set_scroll_interval(self.config.scroll_interval_seconds);
-                    self.vfd_display
-                        .set_random_char_percent(self.config.random_char_percent);
+                if let Some(ref updater) = self.data_updater {
+                    if ui.button("Update data files now").clicked() {
+                        updater.update_now();
+                        self.notifications.info("Updating data files...");
+                    }
                 }
             });
 
             ui.separator();
 
-            // VFD Preview
-            ui.collapsing("VFD Preview", |ui| {
-                let preview = self.vfd_display.get_preview();
+            // Manual spots (skeds arranged off-air)
+            ui.collapsing("Manual Spots", |ui| {
+                ui.label("Paste cluster lines or \"frequency, callsign[, mode]\", one per line:");
+                ui.add(
+                    egui::TextEdit::multiline(&mut self.manual_spot_input)
+                        .hint_text("14025.0, K1ABC\nDX de W1AW: 7030.0 DL1XYZ sked")
+                        .desired_rows(3)
+                        .desired_width(f32::INFINITY),
+                );
+                if ui
+                    .add_enabled(
+                        !self.manual_spot_input.trim().is_empty(),
+                        egui::Button::new("Add Spots"),
+                    )
+                    .clicked()
+                {
+                    self.add_manual_spots();
+                }
+            });
 
-                // Create a frame with green-on-black styling
-                egui::Frame::new()
-                    .fill(egui::Color32::BLACK)
-                    .inner_margin(egui::Margin::same(8))
-                    .corner_radius(egui::CornerRadius::same(4))
-                    .show(ui, |ui| {
-                        ui.style_mut().visuals.override_text_color =
-                            Some(egui::Color32::from_rgb(0, 255, 0));
+            ui.separator();
 
-                        // Use monospace font
-                        let line1 = if preview[0].is_empty() {
-                            " ".repeat(20)
-                        } else {
-                            format!("{:20}", preview[0])
-                        };
-                        let line2 = if preview[1].is_empty() {
-                            " ".repeat(20)
-                        } else {
-                            format!("{:20}", preview[1])
-                        };
+            // Session statistics
+            ui.collapsing("Statistics", |ui| {
+                self.show_statistics(ui);
+            });
+
+            ui.separator();
+
+            // Where our own signal is being heard
+            ui.collapsing("My Signal", |ui| {
+                self.show_my_signal(ui);
+            });
+
+            ui.separator();
+
+            // Skimmer leaderboard and blocking
+            ui.collapsing("Skimmers", |ui| {
+                self.show_skimmers(ui);
+            });
+
+            ui.separator();
+
+            // Entities still needed, from LoTW / Club Log
+            ui.collapsing("DXCC Needs", |ui| {
+                self.show_needs(ui);
+            });
+
+            ui.separator();
+
+            // Who is being spotted around a frequency
+            ui.collapsing("Frequency Watch", |ui| self.show_frequency_watch(ui));
+
+            ui.separator();
 
-                        ui.label(egui::RichText::new(&line1).monospace().size(16.0));
-                        ui.label(egui::RichText::new(&line2).monospace().size(16.0));
+            // Watch for stations moving in on the run frequency
+            ui.collapsing("Run Frequency", |ui| self.show_run_frequency(ui));
+
+            ui.separator();
+
+            // Contest mode and multiplier tracking
+            ui.collapsing("Contest", |ui| {
+                self.show_contest(ui);
+            });
+
+            ui.separator();
+
+            // Long-term spot history
+            if self.spot_db.is_some() {
+                ui.collapsing("History", |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Callsign:");
+                        let response = ui.add(
+                            egui::TextEdit::singleline(&mut self.history_callsign)
+                                .desired_width(90.0),
+                        );
+                        ui.label("Last");
+                        ui.add(
+                            egui::DragValue::new(&mut self.history_hours)
+                                .range(1..=24 * 365)
+                                .suffix(" h"),
+                        );
+                        let enter = response.lost_focus()
+                            && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                        if (ui.button("Search").clicked() || enter)
+                            && !self.history_callsign.trim().is_empty()
+                        {
+                            self.search_history();
+                        }
                     });
+                    egui::ScrollArea::vertical()
+                        .id_salt("history_results")
+                        .max_height(200.0)
+                        .show(ui, |ui| {
+                            for row in &self.history_results {
+                                let t = UtcTime::from_system(row.received_at);
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{}-{:02}-{:02} {:02}{:02}Z {:>9.1} {:<10} {:<10} {:>3} dB {:>2} {}",
+                                        t.year,
+                                        t.month,
+                                        t.day,
+                                        t.hour,
+                                        t.minute,
+                                        row.frequency_khz,
+                                        row.callsign,
+                                        row.spotter,
+                                        row.snr,
+                                        row.wpm,
+                                        row.mode
+                                    ))
+                                    .monospace(),
+                                );
+                            }
+                        });
+                });
+
+                ui.separator();
+            }
+
+            // Spot list export for spreadsheets and log analysis
+            ui.collapsing("Export", |ui| {
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut self.export_history, false, "Shown spots");
+                    ui.radio_value(&mut self.export_history, true, "Whole session");
+                });
+                ui.horizontal(|ui| {
+                    for format in ExportFormat::ALL {
+                        ui.radio_value(&mut self.export_format, format, format.label());
+                    }
+                    if ui.button("Export…").clicked() {
+                        self.export_spots();
+                    }
+                });
             });
 
             ui.separator();
@@ -493,6 +4239,14 @@ impl eframe::App for RbnVfdApp {
                     if ui.button("Clear").clicked() {
                         self.raw_data_log.clear();
                     }
+                    let label = if self.session_recorder.is_some() {
+                        "Stop Recording"
+                    } else {
+                        "Record Session"
+                    };
+                    if ui.button(label).clicked() {
+                        self.toggle_session_recording();
+                    }
                 });
 
                 egui::ScrollArea::vertical()
@@ -522,12 +4276,45 @@ impl eframe::App for RbnVfdApp {
 
             ui.separator();
 
+            // Band scope built from spot density
+            ui.collapsing("Band Scope", |ui| {
+                let spots = self.filtered_spots();
+                if let Some(segment) =
+                    draw_band_scope(ui, &self.config.band_plan, &spots, self.scope_segment)
+                {
+                    // Clicking the selected bin again clears the filter
+                    self.scope_segment = if self.scope_segment == Some(segment) {
+                        None
+                    } else {
+                        Some(segment)
+                    };
+                }
+                if let Some((low, high)) = self.scope_segment {
+                    ui.horizontal(|ui| {
+                        let band = self
+                            .config
+                            .band_plan
+                            .for_frequency(low)
+                            .map(|b| b.name.as_str())
+                            .unwrap_or("");
+                        ui.label(format!("Showing {} {:.1}-{:.1} kHz", band, low, high));
+                        if ui.small_button("Clear").clicked() {
+                            self.scope_segment = None;
+                        }
+                    });
+                }
+            });
+
+            ui.separator();
+
             // Active spots list
             ui.horizontal(|ui| {
                 ui.heading(format!("Active Spots ({})", self.spot_store.count()));
                 if ui.button("Clear").clicked() {
                     self.spot_store.clear();
                 }
+                ui.toggle_value(&mut self.spot_map_view, "Map")
+                    .on_hover_text("Show the spots on a world map instead of the table");
             });
 
             // Tune controls
@@ -553,22 +4340,128 @@ impl eframe::App for RbnVfdApp {
                     self.tune_to_selected();
                 }
 
+                // Way back to the run frequency after an accidental tune
+                if let Some((previous_khz, _, tuned)) = self.tune_undo {
+                    if tuned.elapsed() < TUNE_UNDO_TIME {
+                        ui.label(format!("Was {:.1}", previous_khz));
+                        if ui.add_enabled(connected, egui::Button::new("Undo")).clicked() {
+                            self.undo_tune();
+                        }
+                    } else {
+                        self.tune_undo = None;
+                    }
+                }
+
+                // Session log, for the dupe sheet and contest multipliers
+                if ui
+                    .add_enabled(self.selected_spot.is_some(), egui::Button::new("Log QSO"))
+                    .clicked()
+                {
+                    self.log_selected_qso();
+                }
+
+                // Manual QSY entry
+                ui.label("QSY:");
+                let response = ui.add_enabled(
+                    connected,
+                    egui::TextEdit::singleline(&mut self.qsy_input)
+                        .hint_text("kHz [mode]")
+                        .desired_width(90.0),
+                );
+                if response.lost_focus()
+                    && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                    && !self.qsy_input.trim().is_empty()
+                {
+                    self.tune_to_qsy_input();
+                }
+
+                if let Some((band, until)) = &self.band_boost {
+                    let remaining = until.saturating_duration_since(Instant::now());
+                    ui.label(
+                        egui::RichText::new(format!("{} first ({}s)", band, remaining.as_secs()))
+                            .color(egui::Color32::from_rgb(255, 200, 0)),
+                    );
+                }
+
                 // Show selected spot info
                 if let Some(spot) = &self.selected_spot {
-                    ui.label(format!("{} @ {:.1} kHz", spot.callsign, spot.frequency_khz));
+                    let frequency_format = self.config.frequency_format();
+                    let mut info = format!(
+                        "{} @ {} {}",
+                        spot.callsign,
+                        frequency_format.format(spot.frequency_khz),
+                        frequency_format.unit.symbol()
+                    );
+                    if let Some(band) = self.config.band_plan.for_frequency(spot.frequency_khz) {
+                        info.push_str(&format!(" [{}]", band.name));
+                    }
+                    if let Some(entity) = self.data_files.country_db.lookup(&spot.callsign) {
+                        info.push_str(&format!(" ({})", entity.name));
+                    }
+                    if let Some(bearing) = self.spot_bearing(spot) {
+                        info.push_str(&format!(" {}", heading_text(bearing)));
+                    }
+                    if spot.first_heard_at < spot.first_spotted_at {
+                        let first = UtcTime::from_system(spot.first_heard_at);
+                        info.push_str(&format!(
+                            " first heard {:02}{:02}Z",
+                            first.hour, first.minute
+                        ));
+                    }
+                    ui.label(info);
+                }
+                if let Some(spot) = &mut self.selected_spot {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut spot.note)
+                            .hint_text("Note, e.g. QSX 1.5 up")
+                            .desired_width(120.0),
+                    );
+                    if response.changed() {
+                        self.spot_store.set_note(&spot.key(), &spot.note);
+                    }
                 }
             });
 
+            if self.spot_map_view {
+                self.show_spot_map(ui);
+                return;
+            }
+
             egui::ScrollArea::vertical()
                 .auto_shrink([false, false])
                 .show(ui, |ui| {
-                    let max_age = Duration::from_secs(self.config.max_age_minutes as u64 * 60);
-                    let spots = self
-                        .spot_store
-                        .get_filtered_spots(self.config.min_snr, max_age);
+                    let all_spots = self.filtered_spots();
+                    let call_bands = bands_by_call(&self.config.band_plan, &all_spots);
+                    let mut spots = all_spots.clone();
+                    if let Some((low, high)) = self.scope_segment {
+                        spots.retain(|s| s.frequency_khz >= low && s.frequency_khz < high);
+                    }
+                    self.boost_spots(&mut spots);
+                    if self.config.sort_by_trend {
+                        spots.sort_by_cached_key(|s| s.trend());
+                    }
+                    spots.sort_by_key(|s| !s.pinned);
+                    // Band sections in band plan order, spots outside the plan last
+                    let band_plan = self.config.band_plan.clone();
+                    let band_of = |spot: &AggregatedSpot| {
+                        let bands = band_plan.bands();
+                        let index = bands.iter().position(|b| b.contains(spot.frequency_khz));
+                        (
+                            index.unwrap_or(bands.len()),
+                            index.map_or("Other".to_string(), |i| bands[i].name.clone()),
+                        )
+                    };
+                    let mut band_counts: HashMap<String, usize> = HashMap::new();
+                    if self.config.group_by_band {
+                        spots.sort_by_cached_key(|s| band_of(s).0);
+                        for spot in &spots {
+                            *band_counts.entry(band_of(spot).1).or_default() += 1;
+                        }
+                    }
                     if spots.is_empty() {
                         ui.label("No spots yet. Connect to RBN to receive spots.");
                     } else {
+                        let frequency_format = self.config.frequency_format();
                         // Header
                         ui.horizontal(|ui| {
                             ui.label(
@@ -591,11 +4484,30 @@ impl eframe::App for RbnVfdApp {
                                     .monospace()
                                     .strong(),
                             );
+                            ui.label(
+                                egui::RichText::new(format!("{:<5}", "Mode"))
+                                    .monospace()
+                                    .strong(),
+                            );
                             ui.label(
                                 egui::RichText::new(format!("{:>5}", "#"))
                                     .monospace()
                                     .strong(),
                             );
+                            ui.label(
+                                egui::RichText::new(format!("{:>4}", "Pile"))
+                                    .monospace()
+                                    .strong(),
+                            )
+                            .on_hover_text(
+                                "Pile-up estimate, 1-5, from skimmers hearing the station \
+                                 in the last 5 minutes",
+                            );
+                            ui.label(
+                                egui::RichText::new(format!("{:>6}", "UTC"))
+                                    .monospace()
+                                    .strong(),
+                            );
                             ui.label(
                                 egui::RichText::new(format!("{:>6}", "Age"))
                                     .monospace()
@@ -605,7 +4517,47 @@ impl eframe::App for RbnVfdApp {
 
                         ui.separator();
 
+                        self.track_row_changes(&spots);
+
+                        // The spot the logger is sitting on, if any
+                        let logger_key = self.logger_frequency_khz().and_then(|khz| {
+                            let distance = |s: &&AggregatedSpot| (s.frequency_khz - khz).abs();
+                            spots
+                                .iter()
+                                .filter(|s| distance(s) <= WATCH_RANGE_KHZ)
+                                .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+                                .map(|s| s.key())
+                        });
+
+                        let mut section: Option<String> = None;
                         for spot in &spots {
+                            if self.config.group_by_band {
+                                let band = band_of(spot).1;
+                                if section.as_ref() != Some(&band) {
+                                    let collapsed = self.collapsed_bands.contains(&band);
+                                    let header = format!(
+                                        "{} {} ({})",
+                                        if collapsed { "▶" } else { "▼" },
+                                        band,
+                                        band_counts.get(&band).copied().unwrap_or_default()
+                                    );
+                                    if ui
+                                        .selectable_label(false, egui::RichText::new(header).strong())
+                                        .clicked()
+                                        && !self.collapsed_bands.remove(&band)
+                                    {
+                                        self.collapsed_bands.insert(band.clone());
+                                    }
+                                    section = Some(band);
+                                }
+                                if section
+                                    .as_ref()
+                                    .is_some_and(|band| self.collapsed_bands.contains(band))
+                                {
+                                    continue;
+                                }
+                            }
+
                             let is_selected = self
                                 .selected_spot
                                 .as_ref()
@@ -622,47 +4574,241 @@ impl eframe::App for RbnVfdApp {
                             } else {
                                 format!("{:>3}m", age_secs / 60)
                             };
+                            let heard = UtcTime::from_system(spot.last_spotted_at);
                             let row_text = format!(
-                                "{:>10.1} {:<10} {:>4} {:>5} {:>5} {}",
-                                spot.frequency_khz,
+                                "{:>10} {:<10} {:>4} {:>5} {:<5} {:>5} {:>4}  {:02}{:02}Z {}",
+                                frequency_format.format(spot.frequency_khz),
                                 spot.callsign,
                                 spot.highest_snr,
                                 spot.average_speed.round() as i32,
+                                spot.mode,
                                 spot.spot_count,
+                                spot.pileup_level(),
+                                heard.hour,
+                                heard.minute,
                                 age_text
                             );
 
+                            // Fade out a highlight on new or changed rows
+                            let highlight = self.row_highlight(spot);
+                            let fill = if highlight > 0.0 {
+                                egui::Color32::from_rgba_unmultiplied(
+                                    255,
+                                    200,
+                                    0,
+                                    (highlight * 60.0) as u8,
+                                )
+                            } else if logger_key.as_ref() == Some(&spot.key()) {
+                                egui::Color32::from_rgba_unmultiplied(0, 160, 255, 50)
+                            } else {
+                                egui::Color32::TRANSPARENT
+                            };
+
+                            let mut row_label = egui::RichText::new(&row_text).monospace();
+                            if let Some(color) = self.row_color(ui.visuals(), spot) {
+                                row_label = row_label.color(color);
+                            }
+
+                            let new_mults = self.new_multipliers(spot);
+                            let needed =
+                                self.is_needed(&spot.callsign, spot.frequency_khz, &spot.mode);
+
                             // Use selectable_label for proper click handling
-                            let response = ui.horizontal(|ui| {
-                                let response = ui.selectable_label(
-                                    is_selected,
-                                    egui::RichText::new(&row_text).monospace(),
-                                );
+                            let response =
+                                egui::Frame::new().fill(fill).show(ui, |ui| {
+                                    ui.horizontal(|ui| {
+                                        let response =
+                                            ui.selectable_label(is_selected, row_label);
 
-                                // Ring indicator
-                                let max_age =
-                                    Duration::from_secs(self.config.max_age_minutes as u64 * 60);
-                                let fraction = spot.age_fraction(max_age);
-                                draw_age_ring(ui, fraction);
+                                        let trend = spot.trend();
+                                        let trend_color = match trend {
+                                            Trend::New => egui::Color32::from_rgb(255, 200, 0),
+                                            Trend::Rising => egui::Color32::from_rgb(80, 200, 80),
+                                            Trend::Fading => egui::Color32::from_rgb(255, 140, 0),
+                                            Trend::Steady | Trend::Gone => {
+                                                ui.visuals().weak_text_color()
+                                            }
+                                        };
+                                        ui.label(
+                                            egui::RichText::new(trend.glyph()).color(trend_color),
+                                        )
+                                        .on_hover_text(trend.label());
 
-                                response
-                            });
+                                        // Time left before expiry
+                                        let max_age = Duration::from_secs(
+                                            self.config.max_age_minutes as u64 * 60,
+                                        );
+                                        let fraction = spot.age_fraction(max_age);
+                                        match self.config.age_indicator {
+                                            AgeIndicator::Ring => draw_age_ring(ui, fraction),
+                                            AgeIndicator::Countdown => {
+                                                let left = max_age
+                                                    .saturating_sub(spot.last_spotted.elapsed())
+                                                    .as_secs();
+                                                ui.label(
+                                                    egui::RichText::new(format!(
+                                                        "{:02}:{:02}",
+                                                        left / 60,
+                                                        left % 60
+                                                    ))
+                                                    .monospace()
+                                                    .color(egui::Color32::from_rgb(0, 200, 0)),
+                                                );
+                                            }
+                                            AgeIndicator::Bar => {
+                                                ui.add(
+                                                    egui::ProgressBar::new(1.0 - fraction)
+                                                        .desired_width(40.0)
+                                                        .fill(egui::Color32::from_rgb(0, 160, 0)),
+                                                );
+                                            }
+                                        }
+
+                                        let toggle_pin = ui
+                                            .selectable_label(spot.pinned, "📌")
+                                            .on_hover_text(if spot.pinned {
+                                                "Unpin"
+                                            } else {
+                                                "Pin: keep past expiry and list first"
+                                            })
+                                            .clicked();
+
+                                        // Badge for calls active on several bands at once
+                                        let mut tune_next = false;
+                                        if let Some(bands) = call_bands
+                                            .get(&spot.callsign)
+                                            .filter(|bands| bands.len() >= 2)
+                                        {
+                                            ui.label(
+                                                egui::RichText::new(format!("{}B", bands.len()))
+                                                    .monospace()
+                                                    .color(egui::Color32::from_rgb(0, 200, 255)),
+                                            )
+                                            .on_hover_text(format!(
+                                                "{} heard on {}",
+                                                spot.callsign,
+                                                bands.join(", ")
+                                            ));
+                                            tune_next = ui
+                                                .small_button("⏭")
+                                                .on_hover_text("Tune next band for this call")
+                                                .clicked();
+                                        }
+
+                                        // Flag for a probable miscopy of a busier call
+                                        if let Some(ref real) = spot.busted_of {
+                                            ui.label(
+                                                egui::RichText::new("?")
+                                                    .monospace()
+                                                    .color(egui::Color32::from_rgb(255, 140, 0)),
+                                            )
+                                            .on_hover_text(format!("Probably a busted {}", real));
+                                        }
+
+                                        // Badge for an entity still needed on this band and mode
+                                        if needed {
+                                            ui.label(
+                                                egui::RichText::new("NEW")
+                                                    .monospace()
+                                                    .strong()
+                                                    .color(egui::Color32::from_rgb(255, 60, 60)),
+                                            )
+                                            .on_hover_text(
+                                                "Entity not yet confirmed on this band and mode",
+                                            );
+                                        }
+
+                                        // Badge for a new contest multiplier
+                                        if !new_mults.is_empty() {
+                                            let labels: Vec<String> =
+                                                new_mults.iter().map(Multiplier::label).collect();
+                                            ui.label(
+                                                egui::RichText::new("MULT")
+                                                    .monospace()
+                                                    .strong()
+                                                    .color(egui::Color32::from_rgb(255, 80, 200)),
+                                            )
+                                            .on_hover_text(format!(
+                                                "New multiplier: {}",
+                                                labels.join(", ")
+                                            ));
+                                        }
+
+                                        (response, tune_next, toggle_pin)
+                                    })
+                                    .inner
+                                });
+                            let (row_response, tune_next, toggle_pin) = response.inner;
+                            if toggle_pin {
+                                self.spot_store.set_pinned(&spot.key(), !spot.pinned);
+                            }
+                            if tune_next {
+                                if let Some(next) =
+                                    next_band_spot(&self.config.band_plan, &all_spots, spot)
+                                {
+                                    self.selected_spot = Some(next);
+                                    self.tune_to_selected();
+                                }
+                            }
 
                             // Handle click to select
-                            if response.inner.clicked() {
+                            if row_response.clicked() {
                                 self.selected_spot = Some(spot.clone());
                             }
 
                             // Handle double-click to tune
-                            if response.inner.double_clicked() {
+                            if row_response.double_clicked() {
                                 self.selected_spot = Some(spot.clone());
                                 self.tune_to_selected();
                             }
+
+                            row_response.context_menu(|ui| {
+                                if ui.button("Ignore for 1 hour").clicked() {
+                                    self.ignore_call(&spot.callsign, false);
+                                    ui.close_menu();
+                                }
+                                if ui.button("Ignore permanently").clicked() {
+                                    self.ignore_call(&spot.callsign, true);
+                                    ui.close_menu();
+                                }
+                            });
                         }
                     }
                 });
         });
 
+        // Confirmation for a spot far from the radio's frequency
+        if let Some((frequency_khz, mode, current)) = self.pending_tune {
+            let mut decision = None;
+            egui::Window::new("Confirm QSY")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "Move {:.1} kHz from {:.1} to {:.1} kHz {}?",
+                        (frequency_khz - current).abs(),
+                        current,
+                        frequency_khz,
+                        mode.to_rigctld_mode()
+                    ));
+                    ui.horizontal(|ui| {
+                        if ui.button("Tune").clicked() {
+                            decision = Some(true);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            decision = Some(false);
+                        }
+                    });
+                });
+            if let Some(tune) = decision {
+                self.pending_tune = None;
+                if tune {
+                    self.tune_with_undo(frequency_khz, mode, Some(current));
+                }
+            }
+        }
+
         // Error popup
         if let Some(error) = &self.radio_error.clone() {
             egui::Window::new("Radio Error")
@@ -682,12 +4828,16 @@ impl eframe::App for RbnVfdApp {
             // Initialize temp config if needed
             if self.temp_radio_config.is_none() {
                 self.temp_radio_config = Some(self.config.radio.clone());
+                self.tune_steps_input = self.config.radio.tune_steps_text();
+                self.antennas_input = self.config.radio.antennas_text();
             }
 
             let mut open = true;
             let mut apply_settings = false;
             let mut cancel_settings = false;
             let mut test_connection = false;
+            let mut save_profile = false;
+            let mut delete_profile = false;
 
             egui::Window::new("Radio Settings")
                 .collapsible(false)
@@ -705,12 +4855,39 @@ impl eframe::App for RbnVfdApp {
                             ui.horizontal(|ui| {
                                 ui.radio_value(&mut temp.backend, "omnirig".to_string(), "OmniRig");
                                 ui.radio_value(&mut temp.backend, "rigctld".to_string(), "rigctld");
+                                ui.radio_value(
+                                    &mut temp.backend,
+                                    "commander".to_string(),
+                                    "DXLab Commander",
+                                );
+                                ui.radio_value(&mut temp.backend, "flex".to_string(), "FlexRadio");
                             });
                         }
 
                         #[cfg(not(target_os = "windows"))]
                         {
-                            ui.label("Backend: rigctld");
+                            ui.label("Backend:");
+                            ui.horizontal(|ui| {
+                                ui.radio_value(&mut temp.backend, "rigctld".to_string(), "rigctld");
+                                ui.radio_value(
+                                    &mut temp.backend,
+                                    "commander".to_string(),
+                                    "DXLab Commander",
+                                );
+                                ui.radio_value(&mut temp.backend, "flex".to_string(), "FlexRadio");
+                            });
+                        }
+
+                        if !plugins::RADIO_PLUGINS.is_empty() {
+                            ui.horizontal(|ui| {
+                                for plugin in plugins::RADIO_PLUGINS {
+                                    ui.radio_value(
+                                        &mut temp.backend,
+                                        plugin.name.to_string(),
+                                        plugin.label,
+                                    );
+                                }
+                            });
                         }
 
                         ui.add_space(8.0);
@@ -723,36 +4900,84 @@ impl eframe::App for RbnVfdApp {
                                 ui.radio_value(&mut temp.omnirig_rig, 2, "Rig 2");
                             });
                         } else {
+                            radio_address_fields(ui, temp);
+                        }
+
+                        #[cfg(not(target_os = "windows"))]
+                        radio_address_fields(ui, temp);
+
+                        if temp.backend == "rigctld" {
                             ui.horizontal(|ui| {
-                                ui.label("Host:");
-                                ui.text_edit_singleline(&mut temp.rigctld_host);
-                            });
-                            ui.horizontal(|ui| {
-                                ui.label("Port:");
-                                let mut port_str = temp.rigctld_port.to_string();
-                                if ui.text_edit_singleline(&mut port_str).changed() {
-                                    if let Ok(port) = port_str.parse() {
-                                        temp.rigctld_port = port;
-                                    }
+                                ui.label("Passband (Hz):");
+                                let passbands = &mut temp.passbands;
+                                for (label, width) in [
+                                    ("CW", &mut passbands.cw),
+                                    ("SSB", &mut passbands.ssb),
+                                    ("RTTY", &mut passbands.rtty),
+                                    ("Data", &mut passbands.data),
+                                ] {
+                                    ui.label(label);
+                                    ui.add(egui::DragValue::new(width).range(0..=10_000).speed(10))
+                                        .on_hover_text("0 = the radio's default");
                                 }
                             });
                         }
 
-                        #[cfg(not(target_os = "windows"))]
-                        {
+                        ui.horizontal(|ui| {
+                            ui.label("Tune steps:");
+                            if ui
+                                .text_edit_singleline(&mut self.tune_steps_input)
+                                .on_hover_text(
+                                    "Backend commands run after each tune, separated by ';'. \
+                                     Prefix one with a mode to run it only in that mode, \
+                                     e.g. J 0;Z 0;CW:L AGC 6",
+                                )
+                                .changed()
+                            {
+                                temp.set_tune_steps_text(&self.tune_steps_input);
+                            }
+                        });
+
+                        ui.add_space(8.0);
+
+                        ui.horizontal(|ui| {
+                            ui.label("Antenna switch:");
+                            egui::ComboBox::from_id_salt("antenna_output")
+                                .selected_text(temp.antenna_output.label())
+                                .show_ui(ui, |ui| {
+                                    for output in AntennaOutput::ALL {
+                                        ui.selectable_value(
+                                            &mut temp.antenna_output,
+                                            output,
+                                            output.label(),
+                                        );
+                                    }
+                                });
+                        });
+                        if matches!(
+                            temp.antenna_output,
+                            AntennaOutput::Tcp | AntennaOutput::Serial
+                        ) {
                             ui.horizontal(|ui| {
-                                ui.label("Host:");
-                                ui.text_edit_singleline(&mut temp.rigctld_host);
+                                ui.label("Controller:");
+                                ui.text_edit_singleline(&mut temp.antenna_target)
+                                    .on_hover_text("host:port, or a serial port");
                             });
                             ui.horizontal(|ui| {
-                                ui.label("Port:");
-                                let mut port_str = temp.rigctld_port.to_string();
-                                if ui.text_edit_singleline(&mut port_str).changed() {
-                                    if let Ok(port) = port_str.parse() {
-                                        temp.rigctld_port = port;
-                                    }
-                                }
+                                ui.label("Command:");
+                                ui.text_edit_singleline(&mut temp.antenna_command)
+                                    .on_hover_text("{antenna} is replaced by the antenna number");
+                            });
+                        }
+                        if temp.antenna_output != AntennaOutput::None {
+                            ui.horizontal(|ui| {
+                                ui.label("Antennas:");
+                                ui.text_edit_singleline(&mut self.antennas_input)
+                                    .on_hover_text("band=number, e.g. 40m=1,20m=2");
                             });
+                            if let Err(e) = temp.set_antennas_text(&self.antennas_input) {
+                                ui.colored_label(ui.visuals().error_fg_color, e.to_string());
+                            }
                         }
 
                         ui.add_space(8.0);
@@ -764,6 +4989,30 @@ impl eframe::App for RbnVfdApp {
 
                         ui.add_space(8.0);
 
+                        // Profile management
+                        ui.horizontal(|ui| {
+                            ui.label("Profile:");
+                            ui.text_edit_singleline(&mut self.radio_profile_name);
+                        });
+                        ui.horizontal(|ui| {
+                            let name = self.radio_profile_name.trim();
+                            if ui
+                                .add_enabled(!name.is_empty(), egui::Button::new("Save as Profile"))
+                                .clicked()
+                            {
+                                save_profile = true;
+                            }
+                            let exists = self.config.radio_profiles.iter().any(|p| p.name == name);
+                            if ui
+                                .add_enabled(exists, egui::Button::new("Delete Profile"))
+                                .clicked()
+                            {
+                                delete_profile = true;
+                            }
+                        });
+
+                        ui.add_space(8.0);
+
                         ui.horizontal(|ui| {
                             if ui.button("OK").clicked() {
                                 apply_settings = true;
@@ -781,7 +5030,7 @@ impl eframe::App for RbnVfdApp {
                     let mut test_controller = radio::create_controller(temp);
                     match test_controller.connect() {
                         Ok(()) => {
-                            self.status_message = "Radio connection successful!".to_string();
+                            self.notifications.info("Radio connection successful!");
                         }
                         Err(e) => {
                             self.radio_error = Some(e.to_string());
@@ -790,13 +5039,46 @@ impl eframe::App for RbnVfdApp {
                 }
             }
 
+            if save_profile {
+                if let Some(ref temp) = self.temp_radio_config {
+                    self.config.radio = temp.clone();
+                    self.config.save_radio_profile(&self.radio_profile_name);
+                    self.apply_config(true);
+                    self.notifications.info(format!(
+                        "Saved radio profile: {}",
+                        self.config.active_radio_profile
+                    ));
+                }
+            }
+
+            if delete_profile {
+                self.config
+                    .delete_radio_profile(self.radio_profile_name.trim());
+                self.notifications.info(format!(
+                    "Deleted radio profile: {}",
+                    self.radio_profile_name.trim()
+                ));
+            }
+
             if apply_settings {
                 if let Some(temp) = self.temp_radio_config.take() {
+                    // Edits that diverge from the active profile make it a custom config
+                    let matches_profile = self
+                        .config
+                        .radio_profiles
+                        .iter()
+                        .any(|p| p.name == self.config.active_radio_profile && p.radio == temp);
+                    if !matches_profile {
+                        self.config.active_radio_profile.clear();
+                    }
                     self.config.radio = temp;
                     self.radio_controller = radio::create_controller(&self.config.radio);
+                    self.radio_band = None;
+                    self.antenna = None;
                     if self.config.radio.enabled {
                         let _ = self.radio_controller.connect();
                     }
+                    self.save_settings();
                 }
                 self.show_radio_settings = false;
             }
@@ -806,6 +5088,42 @@ impl eframe::App for RbnVfdApp {
                 self.temp_radio_config = None;
             }
         }
+
+        // While-you-were-away summary
+        if let Some(alerts) = self.alert_center.summary() {
+            let mut dismiss = false;
+            egui::Window::new("While You Were Away")
+                .collapsible(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    egui::ScrollArea::vertical()
+                        .max_height(300.0)
+                        .show(ui, |ui| {
+                            for alert in alerts {
+                                ui.label(format!(
+                                    "{:>4} ago  {}",
+                                    format_elapsed(alert.raised.elapsed()),
+                                    alert.message
+                                ));
+                            }
+                        });
+                    if ui.button("OK").clicked() {
+                        dismiss = true;
+                    }
+                });
+            if dismiss {
+                self.alert_center.dismiss_summary();
+            }
+        }
+
+        // All settings window
+        if self.show_all_settings {
+            self.show_all_settings_window(ctx);
+        }
+
+        if go_mini {
+            self.set_mini_mode(ctx, true);
+        }
     }
 
     fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
@@ -817,7 +5135,15 @@ impl eframe::App for RbnVfdApp {
         // Close VFD
         self.vfd_display.close();
 
-        // Save config
+        // Finish any session recording
+        if let Some(ref mut recorder) = self.session_recorder {
+            recorder.flush();
+        }
+
+        // Save config, unless that would replace a file we couldn't read
+        if self.settings_unreadable {
+            return;
+        }
         if let Err(e) = self.config.save() {
             eprintln!("Failed to save config: {}", e);
         }
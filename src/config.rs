@@ -1,28 +1,505 @@
-use configparser::ini::Ini;
+use crate::error::{Error, Result};
+use crate::models::{
+    locale_uses_decimal_comma, locator_center, system_locale, Band, BandPlan, BustedCallPolicy,
+    FrequencyFormat, FrequencyUnit, SourcePolicy,
+};
+use crate::services::radio::{AntennaOutput, Passbands, RadioMode};
+use crate::services::{
+    CharMap, Contest, DisplayGeometry, IdleContent, LocalClock, ProtocolKind, ScreensaverStyle,
+    VfdContent, VfdDisplay, VfdPage,
+};
+use crate::settings_file::{
+    self, AlertSettings, ApiSettings, BandEntry, BandSettings, CarouselPageSettings,
+    ConnectionSettings, ContestSettings, DataSettings, DisplaySettings, FilterSettings,
+    HistorySettings, MqttSettings, N1mmSettings, NetworkSettings, PagesSettings, ProfilesSettings,
+    PropagationSettings, RadioControlSettings, RadioProfileSettings, RadioSettings, SavedProfile,
+    ScriptingSettings, ServiceSettings, SettingsFile, UiSettings, SCHEMA_VERSION,
+};
+use crate::ui::{Phosphor, PreviewStyle};
 use directories::ProjectDirs;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime};
+use toml::{Table, Value};
+
+/// Directory next to the executable holding settings and data in portable mode
+static PORTABLE_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Server-side filters requested in low-bandwidth mode (CC Cluster syntax)
+const DEFAULT_LOW_BANDWIDTH_COMMANDS: &[&str] = &["set/nobeacon", "set/noft8", "set/noft4"];
+
+/// The API has no authentication, so it only listens on loopback addresses
+const DEFAULT_API_BIND: &str = "127.0.0.1:8073";
+
+/// Standard WSJT-X dial frequencies in kHz
+const DEFAULT_DIAL_FREQUENCIES: &[(&str, &[f64])] = &[
+    (
+        "FT8",
+        &[
+            1840.0, 3573.0, 5357.0, 7074.0, 10136.0, 14074.0, 18100.0, 21074.0, 24915.0, 28074.0,
+            50313.0,
+        ],
+    ),
+    (
+        "FT4",
+        &[
+            3575.0, 7047.5, 10140.0, 14080.0, 18104.0, 21140.0, 24919.0, 28180.0, 50318.0,
+        ],
+    ),
+];
+
+/// Widest audio offset above the dial a digital spot can be decoded at, in kHz
+const DIAL_PASSBAND_KHZ: f64 = 4.0;
 
 /// Application settings
 #[derive(Debug, Clone)]
 pub struct Config {
     pub callsign: String,
+    /// Our Maidenhead locator, for beam headings (empty = unknown)
+    pub locator: String,
+    /// Connect to the RBN feed on launch
+    pub auto_connect: bool,
+    /// Window color scheme
+    pub theme: Theme,
+    /// Highlight color for selections and links (None = the theme's own)
+    pub accent_color: Option<[u8; 3]>,
+    /// Overall size of the window contents (1.0 = normal)
+    pub ui_scale: f32,
+    /// Phosphor color of the on-screen VFD preview
+    pub preview_phosphor: Phosphor,
+    /// Draw the VFD preview in a 5x7 dot-matrix font
+    pub preview_dot_matrix: bool,
+    /// Glow and scanlines on the VFD preview
+    pub preview_glow: bool,
+    /// Spot rows bright while fresh, fading toward grey as they near expiry
+    pub row_color_age: bool,
+    /// Dim the rows of weak spots
+    pub row_color_snr: bool,
+    /// Tint spot rows by mode: CW, phone or digital
+    pub row_color_mode: bool,
+    /// List new and rising stations before steady and fading ones
+    pub sort_by_trend: bool,
+    /// Split the spot table into collapsible per-band sections
+    pub group_by_band: bool,
+    /// Time-to-expiry indicator on each spot row
+    pub age_indicator: AgeIndicator,
+    /// Show frequencies in kHz or MHz, in the UI and on the VFD
+    pub frequency_unit: FrequencyUnit,
+    /// Locale whose decimal mark frequencies use (e.g. "de_DE"; empty = the system's)
+    pub number_locale: String,
     pub serial_port: String,
     pub min_snr: i32,
+    /// Use per-band SNR thresholds from recent spots instead of `min_snr`
+    pub adaptive_snr: bool,
+    /// Percentage of each band's spots kept in adaptive mode (strongest first)
+    pub adaptive_keep_percent: u32,
     pub max_age_minutes: u32,
     pub scroll_interval_seconds: u32,
+    /// Scroll spots across the first VFD line instead of flipping between them
+    pub marquee: bool,
+    /// Marquee speed in characters per second
+    pub marquee_chars_per_second: u32,
     /// Percentage chance (0-100) to show random character when idle
     pub random_char_percent: u32,
+    /// Idle animation shown while there are no spots
+    pub screensaver: ScreensaverStyle,
+    /// Interrupt the VFD rotation to flash each brand-new spot
+    pub flash_new_spots: bool,
+    /// What the VFD shows while there are no spots
+    pub idle_content: IdleContent,
+    /// Which spots the VFD's spot page shows
+    pub vfd_content: VfdContent,
+    /// Calls shown in watchlist mode ("DL*" matches a prefix)
+    pub watchlist: Vec<String>,
+    /// Show local time next to UTC on the clock
+    pub show_local_time: bool,
+    /// Local time offset from UTC in minutes, overriding the system time zone
+    pub local_offset_minutes: Option<i32>,
+    /// While this file exists the VFD port is released to other programs (empty = off)
+    pub handoff_file: String,
+    /// Network display address offered alongside serial ports (tcp://host:port or udp://host:port)
+    pub network_display: String,
+    /// File of extra character mappings for the display's font (empty = none)
+    pub charset_file: String,
+    /// Character geometry of the attached display
+    pub display_geometry: DisplayGeometry,
+    /// Command set spoken by the attached display
+    pub display_protocol: ProtocolKind,
+    /// Display brightness (0-100%) for displays that support dimming
+    pub display_brightness: u32,
+    /// Conflict resolution between skimmer and human spots
+    pub source_policy: SourcePolicy,
+    /// Skimmers whose spots are ignored
+    pub blocked_spotters: Vec<String>,
+    /// Spotted calls whose spots are dropped
+    pub ignored_calls: Vec<String>,
+    /// List beacon spots alongside operators
+    pub show_beacons: bool,
+    /// Leave out calls already logged on the spot's band this session
+    pub hide_dupes: bool,
+    /// Handling of spots that look like a miscopy of a busier call nearby
+    pub busted_calls: BustedCallPolicy,
+    /// Reports of a call within this many Hz count as the same signal
+    pub merge_tolerance_hz: u32,
+    /// Named frequency ranges spots must fall in (empty = no restriction)
+    pub frequency_windows: Vec<Band>,
+    /// Milliseconds between rebuilds of the filtered spot list (0 = on every change)
+    pub spot_refresh_ms: u32,
+    /// Manual per-skimmer frequency corrections in Hz, keyed by callsign
+    pub calibration_offsets: BTreeMap<String, f64>,
+    /// Learn per-skimmer frequency corrections from the spots themselves
+    pub calibration_learning: bool,
+    /// Hours between automatic data file updates (0 = manual only)
+    pub data_update_hours: u32,
+    /// Minutes between solar data refreshes (0 = off)
+    pub solar_refresh_minutes: u32,
+    /// Contest whose new multipliers are highlighted (None = contest mode off)
+    pub contest: Option<Contest>,
+    /// Minutes without input before the operator counts as away (0 = manual only)
+    pub away_idle_minutes: u32,
+    /// Alert on spots of entities in the Club Log most-wanted top N (0 = off)
+    pub most_wanted_alert_rank: u32,
+    /// Alert on spots of entities still needed on that band and mode
+    pub needed_alerts: bool,
+    /// Spots of other stations within this many Hz of the run frequency raise an alert
+    pub run_guard_hz: u32,
+    /// Flash the busy alert on the VFD
+    pub run_busy_flash: bool,
+    /// Command run to play a sound for the busy alert (empty = silent)
+    pub run_busy_sound: String,
+    /// Minimize network use: server-side filters, no automatic downloads, slower UI refresh
+    pub low_bandwidth: bool,
+    /// Cluster commands sent after login in low-bandwidth mode
+    pub low_bandwidth_commands: Vec<String>,
+    /// Frequency ranges used to label spots and lay out the band scope
+    pub band_plan: BandPlan,
+    /// Seconds spots on a newly tuned band stay at the top after a band change (0 = off)
+    pub band_boost_seconds: u32,
+    /// Ranges tuning is allowed in, e.g. license-class segments (empty = anywhere)
+    pub tune_segments: Vec<Band>,
+    /// Band names never tuned to (e.g. an amplifier locked to one band)
+    pub locked_bands: Vec<String>,
+    /// Ask before tuning to a spot more than this many kHz away (0 = never ask)
+    pub confirm_qsy_khz: u32,
+    /// Dial frequencies in kHz digital spots snap to, keyed by mode
+    pub dial_frequencies: BTreeMap<String, Vec<f64>>,
+    /// Service mode: watch for wedged subsystems and restart them
+    pub watchdog: bool,
+    /// Minutes without feed traffic before the watchdog reconnects
+    pub feed_stall_minutes: u32,
+    /// VFD carousel settings, in `VfdPage::ALL` order
+    pub vfd_pages: [PageSettings; 9],
+    /// Lines of the station ID page
+    pub station_message: [String; 2],
+    /// Add the selected spot's note to the beam heading page
+    pub show_spot_note: bool,
     pub radio: RadioConfig,
+    /// Publishing of frames and spots to an MQTT broker
+    pub mqtt: MqttConfig,
+    /// Serve the HTTP control API
+    pub api_enabled: bool,
+    /// Address the HTTP control API listens on
+    pub api_bind: String,
+    /// Follow N1MM Logger+'s frequency from its RadioInfo broadcasts
+    pub n1mm_enabled: bool,
+    /// UDP port N1MM broadcasts RadioInfo to
+    pub n1mm_port: u16,
+    /// Record every spot in the SQLite history database
+    pub history_enabled: bool,
+    /// Rhai script run on spot, tune and idle events (empty = no script)
+    pub script_file: String,
+    /// Saved radio configurations that can be switched between
+    pub radio_profiles: Vec<RadioProfile>,
+    /// Name of the radio profile `radio` was last loaded from (empty if none)
+    pub active_radio_profile: String,
+    /// Saved filter, display page and radio setups that can be switched between
+    pub profiles: Vec<SavedProfile>,
+    /// Name of the configuration profile last applied (empty if none)
+    pub active_profile: String,
 }
 
-/// Radio control settings
+/// Values in a settings file that couldn't be used, keyed by setting path
+#[derive(Debug, Default)]
+struct Problems(Vec<(String, String)>);
+
+impl Problems {
+    /// Note why the value at `key` couldn't be used
+    fn note(&mut self, key: &str, problem: String) {
+        self.0.push((key.to_string(), problem));
+    }
+
+    /// `value`, or `fallback` with the problem noted
+    fn check<T>(&mut self, key: &str, value: std::result::Result<T, String>, fallback: T) -> T {
+        value.unwrap_or_else(|problem| {
+            self.note(key, problem);
+            fallback
+        })
+    }
+
+    /// The value named `name`, or `fallback` if no value has that name
+    fn name<T>(
+        &mut self,
+        key: &str,
+        name: &str,
+        from_name: impl Fn(&str) -> Option<T>,
+        fallback: T,
+    ) -> T {
+        let value = from_name(name).ok_or_else(|| format!("Unknown value: {}", name.trim()));
+        self.check(key, value, fallback)
+    }
+
+    /// The problem with the value at `key`, if there is one
+    fn about(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, problem)| problem.as_str())
+    }
+}
+
+/// Smallest and largest UI scale factor offered
+const UI_SCALE_RANGE: std::ops::RangeInclusive<f32> = 0.75..=2.0;
+
+/// Window color scheme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Dark,
+    /// Easier to read in bright light
+    Light,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 2] = [Theme::Dark, Theme::Light];
+
+    /// Name used in settings.toml
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Theme::Dark => "dark",
+            Theme::Light => "light",
+        }
+    }
+
+    /// Parse the settings.toml name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str() == name.trim())
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            Theme::Dark => "Dark",
+            Theme::Light => "Light",
+        }
+    }
+}
+
+/// How each spot row shows the time left before it expires
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AgeIndicator {
+    #[default]
+    Ring,
+    /// mm:ss until expiry
+    Countdown,
+    /// A shrinking bar
+    Bar,
+}
+
+impl AgeIndicator {
+    pub const ALL: [AgeIndicator; 3] = [
+        AgeIndicator::Ring,
+        AgeIndicator::Countdown,
+        AgeIndicator::Bar,
+    ];
+
+    /// Name used in settings.toml
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AgeIndicator::Ring => "ring",
+            AgeIndicator::Countdown => "countdown",
+            AgeIndicator::Bar => "bar",
+        }
+    }
+
+    /// Parse the settings.toml name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|a| a.as_str() == name.trim())
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            AgeIndicator::Ring => "Ring",
+            AgeIndicator::Countdown => "Countdown",
+            AgeIndicator::Bar => "Bar",
+        }
+    }
+}
+
+/// Parse a "#rrggbb" color (empty = none)
+fn parse_color(value: &str) -> Result<Option<[u8; 3]>> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.is_empty() {
+        return Ok(None);
+    }
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+    };
+    match (hex.len(), channel(0), channel(2), channel(4)) {
+        (6, Some(r), Some(g), Some(b)) => Ok(Some([r, g, b])),
+        _ => Err(Error::Config(format!("Invalid color: {}", value.trim()))),
+    }
+}
+
+/// Callsigns as stored: trimmed, uppercase, blanks dropped
+fn calls(list: Vec<String>) -> Vec<String> {
+    list.into_iter()
+        .map(|call| call.trim().to_uppercase())
+        .filter(|call| !call.is_empty())
+        .collect()
+}
+
+/// Callsigns typed into a text field, separated by commas or spaces
+pub fn parse_calls(text: &str) -> Vec<String> {
+    calls(text.split([',', ' ']).map(str::to_string).collect())
+}
+
+/// List entries trimmed, blanks dropped
+fn entries(list: Vec<String>) -> Vec<String> {
+    list.into_iter()
+        .map(|entry| entry.trim().to_string())
+        .filter(|entry| !entry.is_empty())
+        .collect()
+}
+
+/// Ranges as bands, or the first that isn't usable
+fn to_bands(entries: &[BandEntry]) -> std::result::Result<Vec<Band>, String> {
+    entries.iter().map(BandEntry::to_band).collect()
+}
+
+/// Format a color as written to settings.toml
+fn format_color(color: Option<[u8; 3]>) -> String {
+    color
+        .map(|[r, g, b]| format!("#{:02x}{:02x}{:02x}", r, g, b))
+        .unwrap_or_default()
+}
+
+/// Carousel settings for one VFD page
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PageSettings {
+    pub enabled: bool,
+    /// Seconds the page stays up before the next one
+    pub dwell_seconds: u32,
+}
+
+impl PageSettings {
+    fn default_for(page: VfdPage) -> Self {
+        match page {
+            VfdPage::Spots => Self {
+                enabled: true,
+                dwell_seconds: 30,
+            },
+            _ => Self {
+                enabled: false,
+                dwell_seconds: 5,
+            },
+        }
+    }
+}
+
+/// MQTT publisher settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct MqttConfig {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    /// Empty for anonymous access
+    pub username: String,
+    pub password: String,
+    /// Topic for the lines on the VFD (retained)
+    pub frame_topic: String,
+    /// Topic for each newly heard spot
+    pub spot_topic: String,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 1883,
+            username: String::new(),
+            password: String::new(),
+            frame_topic: "rbn-vfd/display".to_string(),
+            spot_topic: "rbn-vfd/spots".to_string(),
+        }
+    }
+}
+
+impl MqttConfig {
+    fn from_settings(settings: MqttSettings) -> Self {
+        Self {
+            enabled: settings.enabled,
+            host: settings.host,
+            port: settings.port,
+            username: settings.username,
+            password: settings.password,
+            frame_topic: settings.frame_topic,
+            spot_topic: settings.spot_topic,
+        }
+    }
+
+    fn to_settings(&self) -> MqttSettings {
+        MqttSettings {
+            enabled: self.enabled,
+            host: self.host.clone(),
+            port: self.port,
+            username: self.username.clone(),
+            password: self.password.clone(),
+            frame_topic: self.frame_topic.clone(),
+            spot_topic: self.spot_topic.clone(),
+        }
+    }
+}
+
+/// A named radio configuration
 #[derive(Debug, Clone)]
+pub struct RadioProfile {
+    pub name: String,
+    pub radio: RadioConfig,
+}
+
+/// Radio control settings
+#[derive(Debug, Clone, PartialEq)]
 pub struct RadioConfig {
     pub enabled: bool,
     pub backend: String,
     pub rigctld_host: String,
     pub rigctld_port: u16,
     pub omnirig_rig: u8,
+    /// Host running DXLab Commander
+    pub commander_host: String,
+    /// Commander's TCP server port
+    pub commander_port: u16,
+    /// Address of the FlexRadio
+    pub flex_host: String,
+    /// Slice tuned on the FlexRadio, 0 = A
+    pub flex_slice: u8,
+    /// Filter widths selected when tuning through rigctld
+    pub passbands: Passbands,
+    /// Backend commands run after every tune; a MODE: prefix limits one to
+    /// tunes in that mode (e.g. `J 0`, `CW:L AGC 6` for rigctld)
+    pub tune_steps: Vec<String>,
+    /// How antennas are switched on a band change
+    pub antenna_output: AntennaOutput,
+    /// Relay controller address: host:port for TCP, or a serial port
+    pub antenna_target: String,
+    /// Line sent to the relay controller, with {antenna} replaced by the number
+    pub antenna_command: String,
+    /// Antenna number per band, keyed by lowercase band name
+    pub antennas: BTreeMap<String, u8>,
 }
 
 impl Default for RadioConfig {
@@ -37,6 +514,144 @@ impl Default for RadioConfig {
             rigctld_host: "localhost".to_string(),
             rigctld_port: 4532,
             omnirig_rig: 1,
+            commander_host: "localhost".to_string(),
+            commander_port: 52002,
+            flex_host: String::new(),
+            flex_slice: 0,
+            passbands: Passbands::default(),
+            tune_steps: Vec::new(),
+            antenna_output: AntennaOutput::None,
+            antenna_target: String::new(),
+            antenna_command: "{antenna}".to_string(),
+            antennas: BTreeMap::new(),
+        }
+    }
+}
+
+impl RadioConfig {
+    /// Radio settings as read from settings.toml, noting values that can't
+    /// be used against keys under `prefix`
+    fn from_settings(
+        settings: RadioControlSettings,
+        problems: &mut Problems,
+        prefix: &str,
+    ) -> Self {
+        let defaults = Self::default();
+        let key = |name: &str| format!("{}.{}", prefix, name);
+        let backend = settings.backend.trim().to_lowercase();
+        Self {
+            enabled: settings.enabled,
+            backend: problems.check(
+                &key("backend"),
+                if ["rigctld", "omnirig", "commander", "flex"].contains(&backend.as_str())
+                    || crate::plugins::radio_plugin(&backend).is_some()
+                {
+                    Ok(backend.clone())
+                } else {
+                    Err(format!("Unknown backend: {}", backend))
+                },
+                defaults.backend.clone(),
+            ),
+            rigctld_host: settings.rigctld_host.trim().to_string(),
+            rigctld_port: settings.rigctld_port,
+            omnirig_rig: settings.omnirig_rig.clamp(1, 2),
+            commander_host: settings.commander_host.trim().to_string(),
+            commander_port: settings.commander_port,
+            flex_host: settings.flex_host.trim().to_string(),
+            flex_slice: settings.flex_slice.min(7),
+            passbands: Passbands {
+                cw: settings.passband_cw,
+                ssb: settings.passband_ssb,
+                rtty: settings.passband_rtty,
+                data: settings.passband_data,
+            },
+            tune_steps: entries(settings.tune_steps),
+            antenna_output: problems.name(
+                &key("antenna_output"),
+                &settings.antenna_output,
+                AntennaOutput::from_name,
+                defaults.antenna_output,
+            ),
+            antenna_target: settings.antenna_target.trim().to_string(),
+            antenna_command: settings.antenna_command.trim().to_string(),
+            antennas: settings
+                .antennas
+                .into_iter()
+                .map(|(band, antenna)| (band.trim().to_lowercase(), antenna))
+                .collect(),
+        }
+    }
+
+    /// Tune step commands that apply to a tune in `mode`
+    pub fn tune_steps_for(&self, mode: RadioMode) -> Vec<String> {
+        self.tune_steps
+            .iter()
+            .filter_map(|step| match step.split_once(':') {
+                Some((prefix, command)) => match RadioMode::from_name(prefix) {
+                    Some(step_mode) => (step_mode == mode).then(|| command.trim().to_string()),
+                    None => Some(step.to_string()),
+                },
+                None => Some(step.clone()),
+            })
+            .collect()
+    }
+
+    /// Tune steps as typed in the radio dialog, separated by ';'
+    pub fn tune_steps_text(&self) -> String {
+        self.tune_steps.join(";")
+    }
+
+    /// Take over tune steps typed in the radio dialog
+    pub fn set_tune_steps_text(&mut self, text: &str) {
+        self.tune_steps = entries(text.split(';').map(str::to_string).collect());
+    }
+
+    /// Antennas as typed in the radio dialog: band=number, separated by ','
+    pub fn antennas_text(&self) -> String {
+        self.antennas
+            .iter()
+            .map(|(band, antenna)| format!("{}={}", band, antenna))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Take over antennas typed in the radio dialog, unless an entry doesn't parse
+    pub fn set_antennas_text(&mut self, text: &str) -> Result<()> {
+        let mut antennas = BTreeMap::new();
+        for entry in text.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (band, antenna) = entry
+                .split_once('=')
+                .and_then(|(band, n)| Some((band.trim(), n.trim().parse::<u8>().ok()?)))
+                .ok_or_else(|| {
+                    Error::Config(format!("Antennas: expected band=number, got {}", entry))
+                })?;
+            antennas.insert(band.to_lowercase(), antenna);
+        }
+        self.antennas = antennas;
+        Ok(())
+    }
+
+    /// Radio settings as written to settings.toml
+    fn to_settings(&self) -> RadioControlSettings {
+        RadioControlSettings {
+            enabled: self.enabled,
+            backend: self.backend.clone(),
+            rigctld_host: self.rigctld_host.clone(),
+            rigctld_port: self.rigctld_port,
+            omnirig_rig: self.omnirig_rig,
+            commander_host: self.commander_host.clone(),
+            commander_port: self.commander_port,
+            flex_host: self.flex_host.clone(),
+            flex_slice: self.flex_slice,
+            passband_cw: self.passbands.cw,
+            passband_ssb: self.passbands.ssb,
+            passband_rtty: self.passbands.rtty,
+            passband_data: self.passbands.data,
+            tune_steps: self.tune_steps.clone(),
+            antenna_output: self.antenna_output.as_str().to_string(),
+            antenna_target: self.antenna_target.clone(),
+            antenna_command: self.antenna_command.clone(),
+            antennas: self.antennas.clone(),
         }
     }
 }
@@ -45,153 +660,1481 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             callsign: String::new(),
+            locator: String::new(),
+            auto_connect: false,
+            theme: Theme::Dark,
+            accent_color: None,
+            ui_scale: 1.0,
+            preview_phosphor: Phosphor::Green,
+            preview_dot_matrix: false,
+            preview_glow: false,
+            row_color_age: true,
+            row_color_snr: false,
+            row_color_mode: false,
+            sort_by_trend: false,
+            group_by_band: false,
+            age_indicator: AgeIndicator::Ring,
+            frequency_unit: FrequencyUnit::Khz,
+            number_locale: String::new(),
             serial_port: String::new(),
             min_snr: 10,
+            adaptive_snr: false,
+            adaptive_keep_percent: 40,
             max_age_minutes: 10,
             scroll_interval_seconds: 3,
+            marquee: false,
+            marquee_chars_per_second: 4,
             random_char_percent: 20,
+            screensaver: ScreensaverStyle::RandomChar,
+            flash_new_spots: false,
+            idle_content: IdleContent::Random,
+            vfd_content: VfdContent::All,
+            watchlist: Vec::new(),
+            show_local_time: true,
+            local_offset_minutes: None,
+            handoff_file: String::new(),
+            network_display: String::new(),
+            charset_file: String::new(),
+            display_geometry: DisplayGeometry::default(),
+            display_protocol: ProtocolKind::Elo,
+            display_brightness: 100,
+            source_policy: SourcePolicy::PreferSkimmer,
+            blocked_spotters: Vec::new(),
+            ignored_calls: Vec::new(),
+            show_beacons: true,
+            hide_dupes: false,
+            busted_calls: BustedCallPolicy::Off,
+            merge_tolerance_hz: 300,
+            frequency_windows: Vec::new(),
+            spot_refresh_ms: 1000,
+            calibration_offsets: BTreeMap::new(),
+            calibration_learning: false,
+            data_update_hours: 24,
+            solar_refresh_minutes: 60,
+            contest: None,
+            away_idle_minutes: 15,
+            most_wanted_alert_rank: 50,
+            needed_alerts: true,
+            run_guard_hz: 500,
+            run_busy_flash: true,
+            run_busy_sound: String::new(),
+            low_bandwidth: false,
+            low_bandwidth_commands: DEFAULT_LOW_BANDWIDTH_COMMANDS
+                .iter()
+                .map(|c| c.to_string())
+                .collect(),
+            band_plan: BandPlan::amateur(),
+            band_boost_seconds: 60,
+            tune_segments: Vec::new(),
+            locked_bands: Vec::new(),
+            confirm_qsy_khz: 0,
+            dial_frequencies: DEFAULT_DIAL_FREQUENCIES
+                .iter()
+                .map(|(mode, dials)| (mode.to_string(), dials.to_vec()))
+                .collect(),
+            watchdog: false,
+            feed_stall_minutes: 5,
+            vfd_pages: VfdPage::ALL.map(PageSettings::default_for),
+            station_message: [String::new(), String::new()],
+            show_spot_note: false,
             radio: RadioConfig::default(),
+            mqtt: MqttConfig::default(),
+            api_enabled: false,
+            api_bind: DEFAULT_API_BIND.to_string(),
+            n1mm_enabled: false,
+            n1mm_port: 12060,
+            history_enabled: false,
+            script_file: String::new(),
+            radio_profiles: Vec::new(),
+            active_radio_profile: String::new(),
+            profiles: Vec::new(),
+            active_profile: String::new(),
         }
     }
 }
 
 impl Config {
+    /// Directory holding the settings file
+    fn config_dir() -> Option<PathBuf> {
+        if let Some(dir) = PORTABLE_DIR.get() {
+            return Some(dir.clone());
+        }
+        ProjectDirs::from("com", "w6jsv", "rbn-vfd-display")
+            .map(|dirs| dirs.config_dir().to_path_buf())
+    }
+
     /// Get the config file path
     fn config_path() -> Option<PathBuf> {
+        Self::config_dir().map(|dir| dir.join("settings.toml"))
+    }
+
+    /// When settings.toml was last written, if it exists
+    pub fn modified_time() -> Option<SystemTime> {
+        std::fs::metadata(Self::config_path()?)
+            .ok()?
+            .modified()
+            .ok()
+    }
+
+    /// Every setting's value in `settings()` order, as edited
+    pub fn setting_values(&self) -> Vec<String> {
+        let table = self.file_table();
+        settings()
+            .iter()
+            .map(|s| lookup(&table, &s.key).map(edit_text).unwrap_or_default())
+            .collect()
+    }
+
+    /// settings.toml as a TOML table
+    fn file_table(&self) -> Table {
+        settings_file::to_table(&self.to_file()).unwrap_or_default()
+    }
+
+    /// Reread settings.toml and take over each setting whose value there differs
+    /// from `baseline` (the file's `setting_values` when last read), so changes
+    /// made in the app since are kept. Returns the indices into `settings()` of
+    /// the settings taken over and the file's values, the next baseline.
+    pub fn reload_changed(&mut self, baseline: &[String]) -> Result<(Vec<usize>, Vec<String>)> {
+        let path = Self::config_path()
+            .ok_or_else(|| Error::Config("Could not determine config path".to_string()))?;
+        let values = Self::load_file(&path)?.setting_values();
+        let mut changed = Vec::new();
+        for (i, (setting, value)) in settings().iter().zip(&values).enumerate() {
+            if baseline.get(i) != Some(value) && setting.set(self, value).is_ok() {
+                changed.push(i);
+            }
+        }
+        Ok((changed, values))
+    }
+
+    /// Whether a settings file exists (otherwise defaults are in use)
+    pub fn exists() -> bool {
+        Self::config_path().is_some_and(|path| path.exists())
+    }
+
+    /// Commands to send to the RBN server after login
+    pub fn login_commands(&self) -> Vec<String> {
+        if !self.low_bandwidth {
+            return Vec::new();
+        }
+        self.low_bandwidth_commands.clone()
+    }
+
+    /// Hours between data file updates, taking low-bandwidth mode into account
+    pub fn effective_data_update_hours(&self) -> u32 {
+        if self.low_bandwidth {
+            0
+        } else {
+            self.data_update_hours
+        }
+    }
+
+    /// Minutes between solar data refreshes, taking low-bandwidth mode into account
+    pub fn effective_solar_refresh_minutes(&self) -> u32 {
+        if self.low_bandwidth {
+            0
+        } else {
+            self.solar_refresh_minutes
+        }
+    }
+
+    /// Directory for downloaded data files (cty.dat etc.)
+    pub fn data_dir() -> Option<PathBuf> {
+        if let Some(dir) = PORTABLE_DIR.get() {
+            return Some(dir.join("data"));
+        }
         ProjectDirs::from("com", "w6jsv", "rbn-vfd-display")
-            .map(|dirs| dirs.config_dir().join("settings.ini"))
+            .map(|dirs| dirs.data_dir().to_path_buf())
     }
 
-    /// Load config from file, or return defaults if file doesn't exist
-    pub fn load() -> Self {
+    /// Keep settings.toml and the data directory next to the executable
+    /// (e.g. on a USB stick) instead of the OS config and data directories
+    pub fn enable_portable_mode() -> Result<()> {
+        let exe = std::env::current_exe()
+            .map_err(|e| Error::Config(format!("Could not locate the executable: {}", e)))?;
+        let dir = exe
+            .parent()
+            .ok_or_else(|| Error::Config("Executable has no parent directory".to_string()))?;
+        let _ = PORTABLE_DIR.set(dir.to_path_buf());
+        Ok(())
+    }
+
+    /// Load config from file, or return defaults if file doesn't exist.
+    /// Fails if the file can't be read; the caller must then not save over it
+    pub fn load() -> Result<Self> {
         let Some(path) = Self::config_path() else {
-            return Self::default();
+            return Ok(Self::default());
         };
 
         if !path.exists() {
-            return Self::default();
+            return Ok(Self::migrate_ini(&path)?.unwrap_or_default());
         }
 
-        let mut ini = Ini::new();
-        if ini.load(&path).is_err() {
-            return Self::default();
+        Self::load_existing(&path)
+    }
+
+    /// Load `path`, copying it to `<path>.bad` if it can't be read so the
+    /// user's settings survive whatever is written there next
+    fn load_existing(path: &Path) -> Result<Self> {
+        Self::load_file(path).map_err(|e| {
+            let backup = path.with_extension("toml.bad");
+            match std::fs::copy(path, &backup) {
+                Ok(_) => Error::Config(format!("{} (copied to {})", e, backup.display())),
+                Err(_) => e,
+            }
+        })
+    }
+
+    /// Convert a settings.ini left by an older version to `path`, keeping the
+    /// old file as settings.ini.bak
+    fn migrate_ini(path: &Path) -> Result<Option<Self>> {
+        let Some(legacy) = Self::config_dir().map(|dir| dir.join("settings.ini")) else {
+            return Ok(None);
+        };
+        if !legacy.exists() {
+            return Ok(None);
+        }
+        let config = Self::load_file(&legacy)?;
+        if config.save_file(path).is_ok() {
+            let _ = std::fs::rename(&legacy, legacy.with_extension("ini.bak"));
         }
+        Ok(Some(config))
+    }
 
-        let radio = RadioConfig {
-            enabled: ini
-                .getbool("radio", "enabled")
-                .ok()
-                .flatten()
-                .unwrap_or(false),
-            backend: ini.get("radio", "backend").unwrap_or_else(|| {
-                if cfg!(target_os = "windows") {
-                    "omnirig".to_string()
-                } else {
-                    "rigctld".to_string()
-                }
-            }),
-            rigctld_host: ini
-                .get("radio", "rigctld_host")
-                .unwrap_or_else(|| "localhost".to_string()),
-            rigctld_port: ini
-                .getint("radio", "rigctld_port")
-                .ok()
-                .flatten()
-                .unwrap_or(4532) as u16,
-            omnirig_rig: ini
-                .getint("radio", "omnirig_rig")
-                .ok()
-                .flatten()
-                .unwrap_or(1) as u8,
+    /// Load config from a settings file (e.g. one exported from another
+    /// machine): settings.toml, or a settings.ini from an older version
+    pub fn load_file(path: &Path) -> Result<Self> {
+        let defaults = Self::default().to_file();
+        let is_ini = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("ini"));
+        let settings = if is_ini {
+            settings_file::read_ini(path, &defaults)?
+        } else {
+            settings_file::read(path, &defaults)?
         };
+        Ok(Self::from_file(settings).0)
+    }
 
-        Self {
-            callsign: ini.get("connection", "callsign").unwrap_or_default(),
-            serial_port: ini.get("display", "serial_port").unwrap_or_default(),
-            min_snr: ini
-                .getint("filters", "min_snr")
-                .ok()
-                .flatten()
-                .unwrap_or(10) as i32,
-            max_age_minutes: ini
-                .getint("filters", "max_age_minutes")
-                .ok()
-                .flatten()
-                .unwrap_or(10) as u32,
-            scroll_interval_seconds: ini
-                .getint("filters", "scroll_interval_seconds")
-                .ok()
-                .flatten()
-                .unwrap_or(3) as u32,
-            random_char_percent: ini
-                .getint("display", "random_char_percent")
-                .ok()
-                .flatten()
-                .unwrap_or(20) as u32,
+    /// Config from the contents of a settings file, with out-of-range values
+    /// clamped and values that can't be used replaced by defaults. The
+    /// replaced values are returned as problems.
+    fn from_file(settings: SettingsFile) -> (Self, Problems) {
+        let defaults = Self::default();
+        let mut problems = Problems::default();
+        let p = &mut problems;
+        let SettingsFile {
+            connection,
+            ui,
+            display,
+            filters,
+            data,
+            propagation,
+            contest,
+            alerts,
+            network,
+            bands,
             radio,
-        }
+            service,
+            pages,
+            mqtt,
+            api,
+            n1mm,
+            history,
+            scripting,
+            profiles,
+            ..
+        } = settings;
+
+        let locator = connection.locator.trim().to_uppercase();
+        let network_display = display.network_display.trim().to_string();
+        let api_bind = api.bind.trim().to_string();
+        let radio_profiles: Vec<RadioProfile> = radio
+            .profiles
+            .into_iter()
+            .enumerate()
+            .map(|(i, profile)| RadioProfile {
+                name: profile.name,
+                radio: RadioConfig::from_settings(
+                    profile.control,
+                    p,
+                    &format!("radio.profiles.{}", i),
+                ),
+            })
+            .collect();
+        let active_radio_profile = radio.active_profile.trim().to_string();
+        let config = Self {
+            callsign: connection.callsign.trim().to_uppercase(),
+            locator: p.check(
+                "connection.locator",
+                if locator.is_empty() || locator_center(&locator).is_some() {
+                    Ok(locator.clone())
+                } else {
+                    Err(format!("Invalid locator: {}", locator))
+                },
+                defaults.locator,
+            ),
+            auto_connect: connection.auto_connect,
+            theme: p.name("ui.theme", &ui.theme, Theme::from_name, defaults.theme),
+            accent_color: p.check(
+                "ui.accent_color",
+                parse_color(&ui.accent_color).map_err(|e| e.to_string()),
+                defaults.accent_color,
+            ),
+            ui_scale: (ui.scale as f32).clamp(*UI_SCALE_RANGE.start(), *UI_SCALE_RANGE.end()),
+            preview_phosphor: p.name(
+                "ui.preview_color",
+                &ui.preview_color,
+                Phosphor::from_name,
+                defaults.preview_phosphor,
+            ),
+            preview_dot_matrix: ui.preview_dot_matrix,
+            preview_glow: ui.preview_glow,
+            row_color_age: ui.row_color_age,
+            row_color_snr: ui.row_color_snr,
+            row_color_mode: ui.row_color_mode,
+            sort_by_trend: ui.sort_by_trend,
+            group_by_band: ui.group_by_band,
+            age_indicator: p.name(
+                "ui.age_indicator",
+                &ui.age_indicator,
+                AgeIndicator::from_name,
+                defaults.age_indicator,
+            ),
+            frequency_unit: p.name(
+                "ui.frequency_unit",
+                &ui.frequency_unit,
+                FrequencyUnit::from_name,
+                defaults.frequency_unit,
+            ),
+            number_locale: ui.number_locale.trim().to_string(),
+            serial_port: display.serial_port.trim().to_string(),
+            min_snr: filters.min_snr,
+            adaptive_snr: filters.adaptive_snr,
+            adaptive_keep_percent: filters.adaptive_keep_percent.clamp(1, 100),
+            max_age_minutes: filters.max_age_minutes.max(1),
+            scroll_interval_seconds: filters.scroll_interval_seconds.max(1),
+            marquee: display.marquee,
+            marquee_chars_per_second: display.marquee_chars_per_second.clamp(1, 20),
+            random_char_percent: display.random_char_percent.min(100),
+            screensaver: p.name(
+                "display.screensaver",
+                &display.screensaver,
+                ScreensaverStyle::from_name,
+                defaults.screensaver,
+            ),
+            flash_new_spots: display.flash_new_spots,
+            idle_content: p.name(
+                "display.idle_content",
+                &display.idle_content,
+                IdleContent::from_name,
+                defaults.idle_content,
+            ),
+            vfd_content: p.name(
+                "display.content",
+                &display.content,
+                VfdContent::from_name,
+                defaults.vfd_content,
+            ),
+            watchlist: calls(display.watchlist),
+            show_local_time: display.show_local_time,
+            local_offset_minutes: display
+                .local_offset_minutes
+                .map(|m| m.clamp(-14 * 60, 14 * 60)),
+            handoff_file: display.handoff_file.trim().to_string(),
+            network_display: p.check(
+                "display.network_display",
+                if network_display.is_empty() || VfdDisplay::is_network_port(&network_display) {
+                    Ok(network_display.clone())
+                } else {
+                    Err(format!(
+                        "Network display must start with tcp:// or udp://: {}",
+                        network_display
+                    ))
+                },
+                defaults.network_display,
+            ),
+            charset_file: display.charset_file.trim().to_string(),
+            display_geometry: p.check(
+                "display.geometry",
+                DisplayGeometry::parse(&display.geometry)
+                    .ok_or_else(|| format!("Unsupported geometry: {}", display.geometry.trim())),
+                defaults.display_geometry,
+            ),
+            display_protocol: p.name(
+                "display.protocol",
+                &display.protocol,
+                ProtocolKind::from_name,
+                defaults.display_protocol,
+            ),
+            display_brightness: display.brightness.min(100),
+            source_policy: p.name(
+                "filters.source_policy",
+                &filters.source_policy,
+                SourcePolicy::from_name,
+                defaults.source_policy,
+            ),
+            blocked_spotters: calls(filters.blocked_spotters),
+            ignored_calls: calls(filters.ignored_calls),
+            show_beacons: filters.show_beacons,
+            hide_dupes: filters.hide_dupes,
+            busted_calls: p.name(
+                "filters.busted_calls",
+                &filters.busted_calls,
+                BustedCallPolicy::from_name,
+                defaults.busted_calls,
+            ),
+            merge_tolerance_hz: filters.merge_tolerance_hz.clamp(100, 2000),
+            frequency_windows: p.check(
+                "filters.frequency_windows",
+                to_bands(&filters.frequency_windows),
+                defaults.frequency_windows,
+            ),
+            spot_refresh_ms: filters.refresh_ms.min(10_000),
+            calibration_offsets: filters
+                .calibration_offsets
+                .into_iter()
+                .map(|(call, hz)| (call.trim().to_uppercase(), hz))
+                .collect(),
+            calibration_learning: filters.calibration_learning,
+            data_update_hours: data.update_interval_hours,
+            solar_refresh_minutes: propagation.refresh_minutes,
+            contest: match contest.name.trim() {
+                "" => None,
+                name => p.check(
+                    "contest.name",
+                    Contest::from_name(name)
+                        .map(Some)
+                        .ok_or_else(|| format!("Unknown contest: {}", name)),
+                    None,
+                ),
+            },
+            away_idle_minutes: alerts.away_idle_minutes,
+            most_wanted_alert_rank: alerts.most_wanted_rank,
+            needed_alerts: alerts.needed_entities,
+            run_guard_hz: alerts.run_guard_hz.min(10_000),
+            run_busy_flash: alerts.run_busy_flash,
+            run_busy_sound: alerts.run_busy_sound.trim().to_string(),
+            low_bandwidth: network.low_bandwidth,
+            low_bandwidth_commands: entries(network.low_bandwidth_commands),
+            band_plan: p.check(
+                "bands.table",
+                to_bands(&bands.table).map(BandPlan::from_bands),
+                defaults.band_plan,
+            ),
+            band_boost_seconds: radio.band_boost_seconds,
+            tune_segments: p.check(
+                "radio.tune_segments",
+                to_bands(&radio.tune_segments),
+                defaults.tune_segments,
+            ),
+            locked_bands: entries(radio.locked_bands),
+            confirm_qsy_khz: radio.confirm_qsy_khz,
+            dial_frequencies: radio
+                .dial_frequencies
+                .into_iter()
+                .map(|(mode, dials)| (mode.trim().to_uppercase(), dials))
+                .collect(),
+            watchdog: service.watchdog,
+            feed_stall_minutes: service.feed_stall_minutes.max(1),
+            vfd_pages: VfdPage::ALL.map(|page| match pages.carousel.get(page.as_str()) {
+                Some(settings) => PageSettings {
+                    enabled: settings.enabled,
+                    dwell_seconds: settings.dwell_seconds.max(1),
+                },
+                None => PageSettings::default_for(page),
+            }),
+            station_message: [pages.station_id_line1, pages.station_id_line2],
+            show_spot_note: pages.show_spot_note,
+            radio: RadioConfig::from_settings(radio.control, p, "radio"),
+            mqtt: MqttConfig::from_settings(mqtt),
+            api_enabled: api.enabled,
+            api_bind: p.check(
+                "api.bind",
+                match api_bind.parse::<std::net::SocketAddr>() {
+                    Ok(address) if address.ip().is_loopback() => Ok(api_bind.clone()),
+                    Ok(_) => Err(format!(
+                        "{} is not a loopback address; the API has no authentication",
+                        api_bind
+                    )),
+                    Err(_) => Err(format!("Invalid bind address: {}", api_bind)),
+                },
+                defaults.api_bind,
+            ),
+            n1mm_enabled: n1mm.enabled,
+            n1mm_port: n1mm.port,
+            history_enabled: history.enabled,
+            script_file: scripting.script.trim().to_string(),
+            active_radio_profile: p.check(
+                "radio.active_profile",
+                if active_radio_profile.is_empty()
+                    || radio_profiles
+                        .iter()
+                        .any(|r| r.name == active_radio_profile)
+                {
+                    Ok(active_radio_profile.clone())
+                } else {
+                    Err(format!("No radio profile named {}", active_radio_profile))
+                },
+                String::new(),
+            ),
+            radio_profiles,
+            profiles: profiles.saved,
+            active_profile: profiles.active,
+        };
+        (config, problems)
     }
 
     /// Save config to file
-    pub fn save(&self) -> Result<(), String> {
+    pub fn save(&self) -> Result<()> {
         let Some(path) = Self::config_path() else {
-            return Err("Could not determine config path".to_string());
+            return Err(Error::Config("Could not determine config path".to_string()));
         };
+        self.save_file(&path)
+    }
 
+    /// Write config to a settings file (the config file itself, or an export)
+    pub fn save_file(&self, path: &Path) -> Result<()> {
         // Create config directory if it doesn't exist
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create config directory: {}", e))?;
-        }
-
-        let mut ini = Ini::new();
-        ini.set("connection", "callsign", Some(self.callsign.clone()));
-        ini.set("display", "serial_port", Some(self.serial_port.clone()));
-        ini.set("filters", "min_snr", Some(self.min_snr.to_string()));
-        ini.set(
-            "filters",
-            "max_age_minutes",
-            Some(self.max_age_minutes.to_string()),
-        );
-        ini.set(
-            "filters",
-            "scroll_interval_seconds",
-            Some(self.scroll_interval_seconds.to_string()),
-        );
-        ini.set(
-            "display",
-            "random_char_percent",
-            Some(self.random_char_percent.to_string()),
-        );
-        ini.set("radio", "enabled", Some(self.radio.enabled.to_string()));
-        ini.set("radio", "backend", Some(self.radio.backend.clone()));
-        ini.set(
-            "radio",
-            "rigctld_host",
-            Some(self.radio.rigctld_host.clone()),
-        );
-        ini.set(
-            "radio",
-            "rigctld_port",
-            Some(self.radio.rigctld_port.to_string()),
-        );
-        ini.set(
-            "radio",
-            "omnirig_rig",
-            Some(self.radio.omnirig_rig.to_string()),
-        );
-
-        ini.write(&path)
-            .map_err(|e| format!("Failed to write config: {}", e))
+                .map_err(|e| Error::Config(format!("Failed to create config directory: {}", e)))?;
+        }
+        settings_file::write(&self.to_file(), path)
+    }
+
+    /// Whether anything that would be written to settings.toml differs
+    pub fn differs_from(&self, other: &Config) -> bool {
+        self.to_file() != other.to_file()
+    }
+
+    /// Every setting and profile as written to settings.toml
+    fn to_file(&self) -> SettingsFile {
+        SettingsFile {
+            version: SCHEMA_VERSION,
+            connection: ConnectionSettings {
+                callsign: self.callsign.clone(),
+                locator: self.locator.clone(),
+                auto_connect: self.auto_connect,
+            },
+            ui: UiSettings {
+                theme: self.theme.as_str().to_string(),
+                accent_color: format_color(self.accent_color),
+                // Whole percent, so 1.1 isn't written as 1.100000023841858
+                scale: (self.ui_scale as f64 * 100.0).round() / 100.0,
+                preview_color: self.preview_phosphor.as_str().to_string(),
+                preview_dot_matrix: self.preview_dot_matrix,
+                preview_glow: self.preview_glow,
+                row_color_age: self.row_color_age,
+                row_color_snr: self.row_color_snr,
+                row_color_mode: self.row_color_mode,
+                sort_by_trend: self.sort_by_trend,
+                group_by_band: self.group_by_band,
+                age_indicator: self.age_indicator.as_str().to_string(),
+                frequency_unit: self.frequency_unit.as_str().to_string(),
+                number_locale: self.number_locale.clone(),
+            },
+            display: DisplaySettings {
+                serial_port: self.serial_port.clone(),
+                marquee: self.marquee,
+                marquee_chars_per_second: self.marquee_chars_per_second,
+                random_char_percent: self.random_char_percent,
+                screensaver: self.screensaver.as_str().to_string(),
+                flash_new_spots: self.flash_new_spots,
+                idle_content: self.idle_content.as_str().to_string(),
+                content: self.vfd_content.as_str().to_string(),
+                watchlist: self.watchlist.clone(),
+                show_local_time: self.show_local_time,
+                local_offset_minutes: self.local_offset_minutes,
+                handoff_file: self.handoff_file.clone(),
+                network_display: self.network_display.clone(),
+                charset_file: self.charset_file.clone(),
+                geometry: self.display_geometry.to_string(),
+                protocol: self.display_protocol.as_str().to_string(),
+                brightness: self.display_brightness,
+            },
+            filters: FilterSettings {
+                min_snr: self.min_snr,
+                adaptive_snr: self.adaptive_snr,
+                adaptive_keep_percent: self.adaptive_keep_percent,
+                max_age_minutes: self.max_age_minutes,
+                scroll_interval_seconds: self.scroll_interval_seconds,
+                source_policy: self.source_policy.as_str().to_string(),
+                blocked_spotters: self.blocked_spotters.clone(),
+                ignored_calls: self.ignored_calls.clone(),
+                show_beacons: self.show_beacons,
+                hide_dupes: self.hide_dupes,
+                busted_calls: self.busted_calls.as_str().to_string(),
+                merge_tolerance_hz: self.merge_tolerance_hz,
+                frequency_windows: self
+                    .named_frequency_windows()
+                    .iter()
+                    .map(BandEntry::from)
+                    .collect(),
+                refresh_ms: self.spot_refresh_ms,
+                calibration_offsets: self.calibration_offsets.clone(),
+                calibration_learning: self.calibration_learning,
+            },
+            data: DataSettings {
+                update_interval_hours: self.data_update_hours,
+            },
+            propagation: PropagationSettings {
+                refresh_minutes: self.solar_refresh_minutes,
+            },
+            contest: ContestSettings {
+                name: self
+                    .contest
+                    .map(Contest::as_str)
+                    .unwrap_or_default()
+                    .to_string(),
+            },
+            alerts: AlertSettings {
+                away_idle_minutes: self.away_idle_minutes,
+                most_wanted_rank: self.most_wanted_alert_rank,
+                needed_entities: self.needed_alerts,
+                run_guard_hz: self.run_guard_hz,
+                run_busy_flash: self.run_busy_flash,
+                run_busy_sound: self.run_busy_sound.clone(),
+            },
+            network: NetworkSettings {
+                low_bandwidth: self.low_bandwidth,
+                low_bandwidth_commands: self.low_bandwidth_commands.clone(),
+            },
+            bands: BandSettings {
+                table: self.band_plan.bands().iter().map(BandEntry::from).collect(),
+            },
+            radio: RadioSettings {
+                control: self.radio.to_settings(),
+                band_boost_seconds: self.band_boost_seconds,
+                tune_segments: self.tune_segments.iter().map(BandEntry::from).collect(),
+                locked_bands: self.locked_bands.clone(),
+                confirm_qsy_khz: self.confirm_qsy_khz,
+                dial_frequencies: self.dial_frequencies.clone(),
+                active_profile: self.active_radio_profile.clone(),
+                profiles: self
+                    .radio_profiles
+                    .iter()
+                    .map(|profile| RadioProfileSettings {
+                        name: profile.name.clone(),
+                        control: profile.radio.to_settings(),
+                    })
+                    .collect(),
+            },
+            service: ServiceSettings {
+                watchdog: self.watchdog,
+                feed_stall_minutes: self.feed_stall_minutes,
+            },
+            pages: PagesSettings {
+                station_id_line1: self.station_message[0].clone(),
+                station_id_line2: self.station_message[1].clone(),
+                show_spot_note: self.show_spot_note,
+                carousel: VfdPage::ALL
+                    .iter()
+                    .zip(&self.vfd_pages)
+                    .map(|(page, settings)| {
+                        (
+                            page.as_str().to_string(),
+                            CarouselPageSettings {
+                                enabled: settings.enabled,
+                                dwell_seconds: settings.dwell_seconds,
+                            },
+                        )
+                    })
+                    .collect(),
+            },
+            mqtt: self.mqtt.to_settings(),
+            api: ApiSettings {
+                enabled: self.api_enabled,
+                bind: self.api_bind.clone(),
+            },
+            n1mm: N1mmSettings {
+                enabled: self.n1mm_enabled,
+                port: self.n1mm_port,
+            },
+            history: HistorySettings {
+                enabled: self.history_enabled,
+            },
+            scripting: ScriptingSettings {
+                script: self.script_file.clone(),
+            },
+            profiles: ProfilesSettings {
+                active: self.active_profile.clone(),
+                saved: self.profiles.clone(),
+            },
+        }
     }
 
     /// Reset to defaults
     pub fn reset_to_defaults(&mut self) {
         let defaults = Self::default();
         self.min_snr = defaults.min_snr;
+        self.adaptive_snr = defaults.adaptive_snr;
+        self.adaptive_keep_percent = defaults.adaptive_keep_percent;
         self.max_age_minutes = defaults.max_age_minutes;
         self.scroll_interval_seconds = defaults.scroll_interval_seconds;
+        self.marquee = defaults.marquee;
+        self.marquee_chars_per_second = defaults.marquee_chars_per_second;
         self.random_char_percent = defaults.random_char_percent;
+        self.screensaver = defaults.screensaver;
+        self.idle_content = defaults.idle_content;
+        self.vfd_content = defaults.vfd_content;
+        self.watchlist = defaults.watchlist;
+        self.source_policy = defaults.source_policy;
+        self.blocked_spotters = defaults.blocked_spotters;
+        self.ignored_calls = defaults.ignored_calls;
+        self.show_beacons = defaults.show_beacons;
+        self.hide_dupes = defaults.hide_dupes;
+        self.busted_calls = defaults.busted_calls;
+        self.merge_tolerance_hz = defaults.merge_tolerance_hz;
+        self.frequency_windows = defaults.frequency_windows;
+        self.calibration_offsets = defaults.calibration_offsets;
+        self.calibration_learning = defaults.calibration_learning;
         // Keep callsign and serial_port as-is
     }
+
+    /// Frequency windows that can be saved (a name is required to read them back)
+    fn named_frequency_windows(&self) -> Vec<Band> {
+        self.frequency_windows
+            .iter()
+            .filter(|w| !w.name.trim().is_empty())
+            .cloned()
+            .collect()
+    }
+
+    /// Local time for the clock: the manual offset if set, else the system time zone
+    pub fn local_clock(&self) -> LocalClock {
+        match (self.show_local_time, self.local_offset_minutes) {
+            (false, _) => LocalClock::Off,
+            (true, Some(minutes)) => LocalClock::Offset(minutes),
+            (true, None) => LocalClock::System,
+        }
+    }
+
+    /// Why tuning to a frequency is not allowed, if it is not
+    pub fn tune_refusal(&self, frequency_khz: f64) -> Option<String> {
+        if !self.tune_segments.is_empty()
+            && !self.tune_segments.iter().any(|s| s.contains(frequency_khz))
+        {
+            return Some(format!(
+                "{:.1} kHz is outside the allowed tuning segments",
+                frequency_khz
+            ));
+        }
+        let band = self.band_plan.for_frequency(frequency_khz)?;
+        self.locked_bands
+            .iter()
+            .any(|b| b.eq_ignore_ascii_case(&band.name))
+            .then(|| format!("{} is marked do not QSY", band.name))
+    }
+
+    /// Dial frequency to tune for a digital spot heard at `frequency_khz`, if
+    /// its mode has dial frequencies and one is just below the spot
+    pub fn dial_frequency(&self, mode: &str, frequency_khz: f64) -> Option<f64> {
+        self.dial_frequencies
+            .iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case(mode.trim()))
+            .flat_map(|(_, dials)| dials.iter().copied())
+            .filter(|dial| (0.0..=DIAL_PASSBAND_KHZ).contains(&(frequency_khz - dial)))
+            .max_by(f64::total_cmp)
+    }
+
+    /// Ignore a spotted call for good, or stop ignoring it
+    pub fn set_call_ignored(&mut self, call: &str, ignored: bool) {
+        self.ignored_calls.retain(|c| !c.eq_ignore_ascii_case(call));
+        if ignored {
+            self.ignored_calls.push(call.trim().to_uppercase());
+        }
+    }
+
+    /// Manual frequency corrections in Hz, keyed by skimmer callsign
+    pub fn calibration_table(&self) -> HashMap<String, f64> {
+        self.calibration_offsets.clone().into_iter().collect()
+    }
+
+    /// Whether spots from `spotter` are ignored
+    pub fn is_spotter_blocked(&self, spotter: &str) -> bool {
+        self.blocked_spotters
+            .iter()
+            .any(|c| c.eq_ignore_ascii_case(spotter))
+    }
+
+    /// Block or unblock a skimmer
+    pub fn set_spotter_blocked(&mut self, spotter: &str, blocked: bool) {
+        self.blocked_spotters
+            .retain(|c| !c.eq_ignore_ascii_case(spotter));
+        if blocked {
+            self.blocked_spotters.push(spotter.trim().to_uppercase());
+        }
+    }
+
+    /// How frequencies are written, with the decimal mark of `number_locale`
+    /// or else the system locale
+    pub fn frequency_format(&self) -> FrequencyFormat {
+        let locale = if self.number_locale.trim().is_empty() {
+            system_locale().unwrap_or_default()
+        } else {
+            self.number_locale.clone()
+        };
+        FrequencyFormat {
+            unit: self.frequency_unit,
+            decimal_comma: locale_uses_decimal_comma(&locale),
+        }
+    }
+
+    /// How the on-screen VFD preview is drawn
+    pub fn preview_style(&self) -> PreviewStyle {
+        PreviewStyle {
+            phosphor: self.preview_phosphor,
+            dot_matrix: self.preview_dot_matrix,
+            glow: self.preview_glow,
+        }
+    }
+
+    /// Marquee speed, or None when spots flip a line at a time
+    pub fn marquee_rate(&self) -> Option<u32> {
+        self.marquee.then_some(self.marquee_chars_per_second)
+    }
+
+    /// Serial ports plus the network display, if one is configured
+    pub fn display_ports(&self) -> Vec<String> {
+        let mut ports = VfdDisplay::available_ports();
+        if VfdDisplay::is_network_port(&self.network_display) {
+            ports.push(self.network_display.clone());
+        }
+        ports
+    }
+
+    /// Character mappings from `charset_file` (none if unset)
+    pub fn charset_overrides(&self) -> Result<Vec<(char, u8)>> {
+        if self.charset_file.is_empty() {
+            return Ok(Vec::new());
+        }
+        let text = std::fs::read_to_string(&self.charset_file)
+            .map_err(|e| Error::Config(format!("Failed to read {}: {}", self.charset_file, e)))?;
+        CharMap::parse(&text).map_err(|e| Error::Config(format!("{}: {}", self.charset_file, e)))
+    }
+
+    /// Enabled carousel pages with their dwell times
+    pub fn carousel_pages(&self) -> Vec<(VfdPage, Duration)> {
+        VfdPage::ALL
+            .iter()
+            .zip(&self.vfd_pages)
+            .filter(|(_, settings)| settings.enabled)
+            .map(|(page, settings)| (*page, Duration::from_secs(settings.dwell_seconds as u64)))
+            .collect()
+    }
+
+    /// Make the named radio profile the active radio configuration
+    pub fn select_radio_profile(&mut self, name: &str) -> Result<()> {
+        let profile = self
+            .radio_profiles
+            .iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| Error::Config(format!("No radio profile named {}", name)))?;
+        self.radio = profile.radio.clone();
+        self.active_radio_profile = profile.name.clone();
+        Ok(())
+    }
+
+    /// Save the current radio configuration under a name, replacing any profile with that name
+    pub fn save_radio_profile(&mut self, name: &str) {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        match self.radio_profiles.iter_mut().find(|p| p.name == name) {
+            Some(profile) => profile.radio = self.radio.clone(),
+            None => self.radio_profiles.push(RadioProfile {
+                name: name.clone(),
+                radio: self.radio.clone(),
+            }),
+        }
+        self.active_radio_profile = name;
+    }
+
+    /// Remove a saved radio profile
+    pub fn delete_radio_profile(&mut self, name: &str) {
+        self.radio_profiles.retain(|p| p.name != name);
+        if self.active_radio_profile == name {
+            self.active_radio_profile.clear();
+        }
+    }
+
+    /// Apply the named configuration profile; settings it doesn't mention are
+    /// left alone. A profile holding a value that can't be used isn't applied.
+    pub fn select_profile(&mut self, name: &str) -> Result<()> {
+        let profile = self
+            .profiles
+            .iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| Error::Config(format!("No profile named {}", name)))?;
+        let mut settings = self.to_file();
+        profile.apply(&mut settings);
+        let (mut updated, problems) = Self::from_file(settings);
+        if let Some((key, problem)) = problems.0.first() {
+            return Err(Error::Config(format!(
+                "Profile {}: {}: {}",
+                name, key, problem
+            )));
+        }
+        updated.active_profile = name.to_string();
+        *self = updated;
+        Ok(())
+    }
+
+    /// Save the current settings under a name, replacing any profile with that name
+    pub fn save_profile(&mut self, name: &str) {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let profile = SavedProfile::capture(&name, &self.to_file());
+        match self.profiles.iter_mut().find(|p| p.name == name) {
+            Some(saved) => *saved = profile,
+            None => self.profiles.push(profile),
+        }
+        self.active_profile = name;
+    }
+
+    /// Remove a saved configuration profile
+    pub fn delete_profile(&mut self, name: &str) {
+        self.profiles.retain(|p| p.name != name);
+        if self.active_profile == name {
+            self.active_profile.clear();
+        }
+    }
+}
+
+/// Describes a single persisted setting for the "All Settings" view
+pub struct SettingDescriptor {
+    /// Dotted path in settings.toml, e.g. "filters.min_snr" or "pages.clock.enabled"
+    pub key: String,
+    pub description: String,
+    /// Value in a default config (None for settings a default config leaves out)
+    default: Option<Value>,
+}
+
+impl SettingDescriptor {
+    /// Section of settings.toml the setting is in
+    pub fn section(&self) -> &str {
+        self.key.split('.').next().unwrap_or_default()
+    }
+
+    /// Value of this setting in a default config
+    pub fn default_value(&self) -> String {
+        self.default.as_ref().map(edit_text).unwrap_or_default()
+    }
+
+    /// Read the current value as it is edited
+    pub fn get(&self, config: &Config) -> String {
+        lookup(&config.file_table(), &self.key)
+            .map(edit_text)
+            .unwrap_or_default()
+    }
+
+    /// Parse and store a new value. It goes through the same checks as a
+    /// value read from settings.toml, and is refused where those would drop it.
+    pub fn set(&self, config: &mut Config, text: &str) -> Result<()> {
+        let mut table = config.file_table();
+        match self.parse(text)? {
+            Some(value) => insert(&mut table, &self.key, value),
+            None => remove(&mut table, &self.key),
+        }
+        let settings: SettingsFile = Value::Table(table)
+            .try_into()
+            .map_err(|e: toml::de::Error| Error::Config(e.message().to_string()))?;
+        let (updated, problems) = Config::from_file(settings);
+        if let Some(problem) = problems.about(&self.key) {
+            return Err(Error::Config(problem.to_string()));
+        }
+        *config = updated;
+        Ok(())
+    }
+
+    /// An edit as the TOML value it stands for: strings as typed, anything
+    /// else in TOML syntax (e.g. `true`, `5`, `["K1ABC", "W1AW"]`)
+    fn parse(&self, text: &str) -> Result<Option<Value>> {
+        match &self.default {
+            Some(Value::String(_)) => Ok(Some(Value::String(text.to_string()))),
+            None if text.trim().is_empty() => Ok(None),
+            _ => format!("value = {}", text.trim())
+                .parse::<Table>()
+                .ok()
+                .and_then(|mut table| table.remove("value"))
+                .map(Some)
+                .ok_or_else(|| Error::Config(format!("Invalid value: {}", text.trim()))),
+        }
+    }
+}
+
+/// Parts of settings.toml edited elsewhere than in the settings list
+const NOT_SETTINGS: &[&str] = &["version", "radio.profiles", "profiles"];
+
+/// What each setting does, keyed by its path in settings.toml. The carousel
+/// pages' settings are described in `description`, from each page's label.
+const DESCRIPTIONS: &[(&str, &str)] = &[
+    ("connection.callsign", "Callsign used to log in to the RBN telnet server"),
+    ("connection.locator", "Station Maidenhead locator, used for beam headings (e.g. FN31)"),
+    ("connection.auto_connect", "Connect to RBN on launch (for headless use)"),
+    ("ui.theme", "Window color scheme (dark, light)"),
+    ("ui.accent_color", "Highlight color as #rrggbb (empty = theme default)"),
+    ("ui.scale", "Size of the window contents (0.75-2.0, 1.0 = normal)"),
+    ("ui.preview_color", "Phosphor color of the VFD preview (green, cyan, amber)"),
+    ("ui.preview_dot_matrix", "Draw the VFD preview in a 5x7 dot-matrix font"),
+    ("ui.preview_glow", "Glow and scanline effect on the VFD preview"),
+    ("ui.row_color_age", "Color spot rows by age: bright under a minute old, fading to grey near expiry"),
+    ("ui.row_color_snr", "Dim the rows of weak spots"),
+    ("ui.row_color_mode", "Tint spot rows by mode: CW, phone or digital"),
+    ("ui.sort_by_trend", "List new and rising stations before steady and fading ones"),
+    ("ui.group_by_band", "Split the spot table into collapsible per-band sections"),
+    ("ui.age_indicator", "Time-to-expiry indicator on spot rows (ring, countdown, bar)"),
+    ("ui.frequency_unit", "Show frequencies in kHz (14033.0) or MHz (14.033.0)"),
+    ("ui.number_locale", "Locale for the decimal mark in frequencies, e.g. de_DE (empty = system)"),
+    ("display.serial_port", "Serial port the VFD is connected to"),
+    ("display.random_char_percent", "Idle mode duty cycle: percent of each second a random character is shown (0-100)"),
+    ("display.screensaver", "Idle animation (random_char, rain, bouncing_call, pixel_walk, spot_ticker)"),
+    ("display.flash_new_spots", "Interrupt the VFD rotation to flash each brand-new spot"),
+    ("display.idle_content", "Shown on the VFD while there are no spots (random = screensaver, clock, station_id)"),
+    ("display.content", "Spots the VFD shows (all, watchlist, selected, newest)"),
+    ("display.watchlist", "Calls shown in watchlist mode, e.g. [\"W1AW\", \"DL*\"] (DL* matches a prefix)"),
+    ("display.show_local_time", "Show local time next to UTC on the VFD clock"),
+    ("display.local_offset_minutes", "Local time offset from UTC in minutes for the VFD clock (blank = the system time zone)"),
+    ("display.handoff_file", "Flag file: while it exists the VFD port is released for another program (blank = off)"),
+    ("display.network_display", "Remote display address, tcp://host:port or udp://host:port (blank = none)"),
+    ("display.charset_file", "File of 'character = code' lines overriding the display's font mapping"),
+    ("display.geometry", "Display size in characters (20x2, 16x2, 20x4, 24x2, 40x2)"),
+    ("display.protocol", "Display command set (elo, matrix_orbital, noritake, hd44780, gp9002 or a plugin name)"),
+    ("display.brightness", "Display brightness in percent, for displays that support dimming"),
+    ("filters.min_snr", "Minimum SNR (dB) for a spot to be shown"),
+    ("filters.adaptive_snr", "Set per-band SNR thresholds from recent spots instead of using min_snr"),
+    ("filters.adaptive_keep_percent", "Adaptive SNR: percentage of each band's strongest spots to show (1-100)"),
+    ("filters.max_age_minutes", "Spots not heard for this many minutes are hidden"),
+    ("filters.scroll_interval_seconds", "Seconds between VFD scroll steps"),
+    ("display.marquee", "Scroll spots across line 1 with the newest spot on line 2"),
+    ("display.marquee_chars_per_second", "Marquee scrolling speed in characters per second (1-20)"),
+    ("filters.source_policy", "Which source wins when skimmer and human spots disagree (prefer_skimmer, prefer_human, keep_both)"),
+    ("filters.blocked_spotters", "Skimmers whose spots are ignored, e.g. [\"DL1ABC-#\"]"),
+    ("filters.ignored_calls", "Spotted calls whose spots are dropped, e.g. [\"K1ABC\"]"),
+    ("filters.show_beacons", "List beacon spots (NCDXF/IARU and /B calls) with the other spots"),
+    ("filters.hide_dupes", "Leave out calls already logged on the spot's band this session"),
+    ("filters.busted_calls", "Spots one character off a busier call on the same frequency (off, flag, merge)"),
+    ("filters.merge_tolerance_hz", "Reports of a call within this many Hz are merged into one spot (100-2000)"),
+    ("filters.frequency_windows", "Only show spots inside these kHz ranges, e.g. [{ name = \"40m CW\", low_khz = 7000.0, high_khz = 7040.0 }] ([] = everywhere)"),
+    ("filters.refresh_ms", "Milliseconds between refreshes of the spot list shared by the table, band scope and VFD (0-10000, 0 = on every new spot)"),
+    ("filters.calibration_offsets", "Frequency corrections in Hz for skimmers that read off, e.g. { \"DL1ABC-#\" = -40.0 }"),
+    ("filters.calibration_learning", "Learn each skimmer's frequency error from how its reports compare with others"),
+    ("service.watchdog", "Restart a wedged feed or serial writer and log it (applies on restart)"),
+    ("service.feed_stall_minutes", "Minutes without RBN traffic before the watchdog reconnects"),
+    ("pages.station_id_line1", "First line of the station ID page (e.g. call and grid)"),
+    ("pages.station_id_line2", "Second line of the station ID page"),
+    ("pages.show_spot_note", "Add the selected spot's note to the beam heading page"),
+    ("data.update_interval_hours", "Hours between automatic cty.dat / LoTW / most-wanted updates (0 = manual only)"),
+    ("propagation.refresh_minutes", "Minutes between solar index (SFI, A, K) updates from hamqsl.com (0 = off)"),
+    ("contest.name", "Contest whose new multipliers are highlighted (cq_ww, arrl_dx, cq_wpx, iaru_hf; empty = off)"),
+    ("alerts.away_idle_minutes", "Minutes without input before alerts are held for the away summary (0 = manual only)"),
+    ("alerts.most_wanted_rank", "Alert on spots of entities in the Club Log most-wanted top N (0 = off)"),
+    ("alerts.needed_entities", "Alert on spots of entities still needed on that band and mode (see DXCC Needs)"),
+    ("alerts.run_guard_hz", "Alert when another station is spotted within this many Hz of the run frequency"),
+    ("alerts.run_busy_flash", "Flash the run frequency busy alert on the VFD"),
+    ("alerts.run_busy_sound", "Command run to play a sound for the run frequency busy alert (empty = silent)"),
+    ("network.low_bandwidth", "Low-bandwidth mode: server-side filters, no automatic downloads, slower UI refresh"),
+    ("network.low_bandwidth_commands", "Cluster commands sent after login in low-bandwidth mode, e.g. [\"set/nobeacon\"]"),
+    ("bands.table", "Band labels as kHz ranges, e.g. [{ name = \"40m\", low_khz = 7000.0, high_khz = 7300.0 }] ([] = amateur bands)"),
+    ("radio.band_boost_seconds", "Seconds spots on a newly tuned band are moved to the top after a band change (0 = off)"),
+    ("radio.tune_segments", "Only tune inside these kHz ranges, e.g. license-class segments, as in filters.frequency_windows ([] = anywhere)"),
+    ("radio.locked_bands", "Bands never tuned to, e.g. [\"6m\", \"2m\"] while the amplifier is band-locked"),
+    ("radio.confirm_qsy_khz", "Ask before tuning to a spot more than this many kHz from the radio's frequency (0 = never ask)"),
+    ("radio.dial_frequencies", "Dial frequencies in kHz that FT8/FT4 spots tune to in DATA-USB, e.g. { FT8 = [7074.0, 14074.0] } ({} = tune the spot itself)"),
+    ("radio.active_profile", "Saved radio profile the radio settings were loaded from"),
+    ("radio.enabled", "Enable CAT control of the radio"),
+    ("radio.backend", "Radio control backend (rigctld, omnirig, commander, flex or a plugin name)"),
+    ("radio.rigctld_host", "Host name of the rigctld daemon"),
+    ("radio.rigctld_port", "TCP port of the rigctld daemon"),
+    ("radio.omnirig_rig", "OmniRig rig number (1 or 2)"),
+    ("radio.commander_host", "Host name of the computer running DXLab Commander"),
+    ("radio.commander_port", "Port of DXLab Commander's TCP server"),
+    ("radio.flex_host", "IP address or host name of the FlexRadio (SmartSDR API on port 4992)"),
+    ("radio.flex_slice", "FlexRadio slice tuned from the spot list (0 = A, 1 = B, ...)"),
+    ("radio.tune_steps", "Backend commands run after every tune, with an optional MODE: prefix, e.g. [\"J 0\", \"CW:L AGC 6\"] for rigctld or [\"Rit=0x40000\"] for OmniRig"),
+    ("radio.passband_cw", "Filter width in Hz selected when tuning to a CW spot (0 = radio default; rigctld only)"),
+    ("radio.passband_ssb", "Filter width in Hz selected when tuning in USB or LSB (0 = radio default; rigctld only)"),
+    ("radio.passband_rtty", "Filter width in Hz selected when tuning to an RTTY spot (0 = radio default; rigctld only)"),
+    ("radio.passband_data", "Filter width in Hz selected when tuning in a data mode (0 = radio default; rigctld only)"),
+    ("radio.antenna_output", "How antennas are switched when tuning to another band (none, rigctld, tcp or serial)"),
+    ("radio.antenna_target", "Antenna relay controller address: host:port for tcp, or a serial port"),
+    ("radio.antenna_command", "Line sent to the antenna relay controller; {antenna} is replaced by the antenna number"),
+    ("radio.antennas", "Antenna for each band, e.g. { 40m = 1, 20m = 2 }"),
+    ("mqtt.enabled", "Publish VFD frames and new spots to an MQTT broker"),
+    ("mqtt.host", "MQTT broker host name"),
+    ("mqtt.port", "MQTT broker TCP port"),
+    ("mqtt.username", "MQTT user name (blank for anonymous)"),
+    ("mqtt.password", "MQTT password"),
+    ("mqtt.frame_topic", "Topic the VFD lines are published to as JSON (retained; blank = off)"),
+    ("mqtt.spot_topic", "Topic each new spot is published to as JSON (blank = off)"),
+    ("api.enabled", "Serve the HTTP control API (GET /spots, GET /status, POST /tune, POST /display/message)"),
+    ("api.bind", "Loopback address and port the HTTP API listens on; it has no authentication, so other hosts cannot be allowed"),
+    ("n1mm.enabled", "Follow N1MM Logger+'s frequency from its RadioInfo UDP broadcasts (N1MM keeps CAT control)"),
+    ("n1mm.port", "UDP port N1MM broadcasts RadioInfo to (its Broadcast Data settings)"),
+    ("history.enabled", "Record every spot in history.sqlite in the data directory (needs the sqlite build feature)"),
+    ("scripting.script", "Rhai script whose on_spot, on_tune and on_idle functions run on those events (blank = off)"),
+];
+
+/// Description of the setting at `key`, if it is one
+fn description(key: &str) -> Option<String> {
+    if let Some((page, field)) = key.strip_prefix("pages.").and_then(|k| k.split_once('.')) {
+        let page = VfdPage::ALL.into_iter().find(|p| p.as_str() == page)?;
+        return match field {
+            "enabled" => Some(format!(
+                "Show the {} page in the VFD carousel",
+                page.label()
+            )),
+            "dwell_seconds" => Some(format!(
+                "Seconds the {} page stays on the VFD",
+                page.label()
+            )),
+            _ => None,
+        };
+    }
+    DESCRIPTIONS
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, d)| d.to_string())
+}
+
+/// Every setting persisted in settings.toml, in file order
+pub fn settings() -> &'static [SettingDescriptor] {
+    static SETTINGS: OnceLock<Vec<SettingDescriptor>> = OnceLock::new();
+    SETTINGS.get_or_init(|| {
+        let mut settings = Vec::new();
+        collect_settings("", &Config::default().file_table(), &mut settings);
+        settings
+    })
+}
+
+/// Add a descriptor for each setting under `prefix` in `table`
+fn collect_settings(prefix: &str, table: &Table, settings: &mut Vec<SettingDescriptor>) {
+    let path = |key: &str| {
+        if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        }
+    };
+    for (key, value) in table {
+        let key = path(key);
+        if NOT_SETTINGS.contains(&key.as_str()) {
+            continue;
+        }
+        match (value, description(&key)) {
+            // A section, or a page's table
+            (Value::Table(inner), None) => collect_settings(&key, inner, settings),
+            (value, description) => settings.push(SettingDescriptor {
+                key,
+                description: description.unwrap_or_default(),
+                default: Some(value.clone()),
+            }),
+        }
+    }
+    // Optional settings a default config leaves out of the file
+    for (key, description) in DESCRIPTIONS {
+        if key
+            .rsplit_once('.')
+            .is_some_and(|(parent, leaf)| parent == prefix && !table.contains_key(leaf))
+        {
+            settings.push(SettingDescriptor {
+                key: key.to_string(),
+                description: description.to_string(),
+                default: None,
+            });
+        }
+    }
+}
+
+/// The value at a dotted path
+fn lookup<'a>(table: &'a Table, key: &str) -> Option<&'a Value> {
+    let (parent, leaf) = match key.rsplit_once('.') {
+        Some((parent, leaf)) => (
+            parent
+                .split('.')
+                .try_fold(table, |t, k| t.get(k)?.as_table())?,
+            leaf,
+        ),
+        None => (table, key),
+    };
+    parent.get(leaf)
+}
+
+/// Store a value at a dotted path, creating tables on the way
+fn insert(table: &mut Table, key: &str, value: Value) {
+    match key.split_once('.') {
+        Some((first, rest)) => {
+            let inner = table
+                .entry(first)
+                .or_insert_with(|| Value::Table(Table::new()));
+            if let Value::Table(inner) = inner {
+                insert(inner, rest, value);
+            }
+        }
+        None => {
+            table.insert(key.to_string(), value);
+        }
+    }
+}
+
+/// Remove the value at a dotted path
+fn remove(table: &mut Table, key: &str) {
+    match key.split_once('.') {
+        Some((first, rest)) => {
+            if let Some(Value::Table(inner)) = table.get_mut(first) {
+                remove(inner, rest);
+            }
+        }
+        None => {
+            table.remove(key);
+        }
+    }
+}
+
+/// A value as shown in the editor: strings bare, anything else as TOML
+fn edit_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEGACY_INI: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/settings.ini");
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("rbn-vfd-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn migrates_settings_ini() {
+        let config = Config::load_file(Path::new(LEGACY_INI)).unwrap();
+
+        assert_eq!(config.callsign, "W6JSV");
+        assert!(config.auto_connect);
+        assert_eq!(config.theme, Theme::Light);
+        assert_eq!(config.accent_color, Some([0xff, 0x88, 0x00]));
+        assert_eq!(config.ui_scale, 1.25);
+        assert_eq!(config.preview_phosphor, Phosphor::Amber);
+        assert!(!config.row_color_age);
+        assert_eq!(config.frequency_unit, FrequencyUnit::Mhz);
+        assert_eq!(config.local_offset_minutes, Some(-420));
+        assert_eq!(config.vfd_content, VfdContent::Watchlist);
+        assert_eq!(config.watchlist, ["VP8*", "3Y0J"]);
+        assert_eq!(config.min_snr, 12);
+        assert_eq!(config.blocked_spotters, ["DL1ABC-#", "K9XYZ"]);
+        // Values the INI reader couldn't parse keep their defaults, and
+        // out-of-range ones are clamped as before
+        assert_eq!(config.scroll_interval_seconds, 3);
+        assert_eq!(config.merge_tolerance_hz, 2000);
+        assert!(!config.show_beacons);
+        assert_eq!(config.run_busy_sound, "aplay /usr/share/sounds/busy.wav");
+
+        // Flat <page>_enabled / <page>_dwell_seconds keys become page tables
+        let clock = VfdPage::ALL
+            .iter()
+            .position(|p| *p == VfdPage::Clock)
+            .unwrap();
+        let solar = VfdPage::ALL
+            .iter()
+            .position(|p| *p == VfdPage::Solar)
+            .unwrap();
+        assert_eq!(config.vfd_pages[0].dwell_seconds, 20);
+        assert!(config.vfd_pages[clock].enabled);
+        assert_eq!(config.vfd_pages[clock].dwell_seconds, 8);
+        assert!(config.vfd_pages[solar].enabled);
+        assert_eq!(config.vfd_pages[solar].dwell_seconds, 5);
+        assert_eq!(config.station_message[1], "QRV 40/20M CW");
+
+        assert_eq!(config.radio.rigctld_host, "shack-pi");
+        assert_eq!(config.radio.tune_steps, ["J 0", "CW:L AGC 6"]);
+        assert_eq!(config.radio.antenna_output, AntennaOutput::Tcp);
+        assert_eq!(config.radio.antennas_text(), "20m=2,40m=1");
+        assert_eq!(config.confirm_qsy_khz, 50);
+        assert_eq!(config.mqtt.port, 1884);
+        assert_eq!(config.script_file, "/home/w6jsv/hooks.rhai");
+
+        let names: Vec<_> = config.radio_profiles.iter().map(|p| &p.name).collect();
+        assert_eq!(names, ["IC-7300", "Flex"]);
+        let flex = &config.radio_profiles[1].radio;
+        assert_eq!(flex.backend, "flex");
+        assert_eq!(flex.flex_slice, 1);
+        assert_eq!(flex.rigctld_port, RadioConfig::default().rigctld_port);
+        assert_eq!(config.active_radio_profile, "IC-7300");
+
+        assert_eq!(config.active_profile, "contest");
+        let contest = &config.profiles[0];
+        assert_eq!(contest.name, "contest");
+        assert_eq!(contest.filters.min_snr, Some(5));
+        assert_eq!(contest.pages.carousel["clock"].enabled, Some(false));
+        assert_eq!(contest.display.content.as_deref(), Some("all"));
+        assert_eq!(contest.display.marquee, None);
+
+        // The migrated settings survive a trip through settings.toml
+        let path = temp_path("migrated.toml");
+        config.save_file(&path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let reloaded = Config::load_file(&path);
+        let _ = std::fs::remove_file(&path);
+        assert!(text.starts_with("version = 1\n"), "{}", text);
+        assert!(text.contains("[pages.clock]"), "{}", text);
+        assert!(text.contains("[[radio.profiles]]"), "{}", text);
+        assert!(text.contains("[profiles.saved.filters]"), "{}", text);
+        assert!(!reloaded.unwrap().differs_from(&config));
+    }
+
+    #[test]
+    fn fills_missing_settings_with_defaults() {
+        let path = temp_path("partial.toml");
+        std::fs::write(
+            &path,
+            "schema_version = 1\n\n[connection]\ncallsign = \"K1ABC\"\n\n[pages.clock]\nenabled = true\n\n[[radio.profiles]]\nname = \"IC-705\"\n",
+        )
+        .unwrap();
+        let config = Config::load_file(&path);
+        let _ = std::fs::remove_file(&path);
+        let config = config.unwrap();
+
+        let defaults = Config::default();
+        assert_eq!(config.callsign, "K1ABC");
+        assert_eq!(config.min_snr, defaults.min_snr);
+        assert_eq!(config.vfd_pages, {
+            let mut pages = defaults.vfd_pages;
+            pages[1].enabled = true;
+            pages
+        });
+        assert_eq!(config.radio_profiles[0].name, "IC-705");
+        assert_eq!(config.radio_profiles[0].radio, defaults.radio);
+    }
+
+    #[test]
+    fn applies_partial_profiles() {
+        let path = temp_path("profiles.toml");
+        std::fs::write(
+            &path,
+            concat!(
+                "[display]\nserial_port = \"/dev/ttyUSB0\"\n\n",
+                "[[profiles.saved]]\nname = \"dx\"\n",
+                "[profiles.saved.filters]\nmin_snr = 20\nblocked_spotters = [\"k9xyz\"]\n",
+                "[profiles.saved.pages.clock]\nenabled = true\n\n",
+                "[[profiles.saved]]\nname = \"broken\"\n",
+                "[profiles.saved.display]\ncontent = \"sideways\"\n",
+            ),
+        )
+        .unwrap();
+        let config = Config::load_file(&path);
+        let _ = std::fs::remove_file(&path);
+        let mut config = config.unwrap();
+        let defaults = Config::default();
+
+        config.select_profile("dx").unwrap();
+        assert_eq!(config.active_profile, "dx");
+        assert_eq!(config.min_snr, 20);
+        assert_eq!(config.blocked_spotters, ["K9XYZ"]);
+        assert!(config.vfd_pages[1].enabled);
+        assert_eq!(config.max_age_minutes, defaults.max_age_minutes);
+
+        // A profile with a value that can't be used changes nothing
+        assert!(config.select_profile("broken").is_err());
+        assert_eq!(config.active_profile, "dx");
+        assert_eq!(config.vfd_content, defaults.vfd_content);
+
+        // Saved profiles leave the hardware alone
+        config.min_snr = 3;
+        config.save_profile("quiet");
+        config.min_snr = 30;
+        config.serial_port = "/dev/ttyACM0".to_string();
+        config.select_profile("quiet").unwrap();
+        assert_eq!(config.min_snr, 3);
+        assert_eq!(config.serial_port, "/dev/ttyACM0");
+    }
+
+    #[test]
+    fn refuses_newer_schemas() {
+        let path = temp_path("newer.toml");
+        std::fs::write(&path, format!("version = {}\n", SCHEMA_VERSION + 1)).unwrap();
+        let result = Config::load_file(&path);
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(result, Err(Error::Config(_))));
+    }
+
+    #[test]
+    fn describes_every_setting() {
+        for setting in settings() {
+            assert!(
+                !setting.description.is_empty(),
+                "{} has no description",
+                setting.key
+            );
+        }
+        for (key, _) in DESCRIPTIONS {
+            assert!(
+                settings().iter().any(|s| s.key == *key),
+                "{} is not in settings.toml",
+                key
+            );
+        }
+        for page in VfdPage::ALL {
+            let key = format!("pages.{}.dwell_seconds", page.as_str());
+            assert!(
+                settings().iter().any(|s| s.key == key),
+                "{} is missing",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn edits_settings_as_in_the_file() {
+        let setting = |key: &str| settings().iter().find(|s| s.key == key).unwrap();
+        let mut config = Config::default();
+
+        setting("filters.min_snr").set(&mut config, "5").unwrap();
+        setting("pages.follow.enabled")
+            .set(&mut config, "true")
+            .unwrap();
+        setting("connection.callsign")
+            .set(&mut config, " w6jsv")
+            .unwrap();
+        assert_eq!(config.min_snr, 5);
+        assert!(config.vfd_pages[8].enabled);
+        assert_eq!(setting("connection.callsign").get(&config), "W6JSV");
+
+        assert!(setting("ui.theme").set(&mut config, "purple").is_err());
+        assert!(setting("filters.min_snr").set(&mut config, "ten").is_err());
+        assert_eq!(config.theme, Theme::Dark);
+        assert_eq!(config.min_snr, 5);
+
+        let offset = setting("display.local_offset_minutes");
+        offset.set(&mut config, "-420").unwrap();
+        assert_eq!(config.local_offset_minutes, Some(-420));
+        assert_eq!(config.local_clock(), LocalClock::Offset(-420));
+        offset.set(&mut config, "").unwrap();
+        assert_eq!(config.local_offset_minutes, None);
+        assert_eq!(config.local_clock(), LocalClock::System);
+        setting("display.show_local_time")
+            .set(&mut config, "false")
+            .unwrap();
+        assert_eq!(config.local_clock(), LocalClock::Off);
+    }
+
+    #[test]
+    fn backs_up_unreadable_settings() {
+        let path = temp_path("broken.toml");
+        let backup = path.with_extension("toml.bad");
+        std::fs::write(&path, "[filters\nmin_snr = 5\n").unwrap();
+        let result = Config::load_existing(&path);
+        let copied = std::fs::read_to_string(&backup);
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&backup);
+        assert!(matches!(result, Err(Error::Config(e)) if e.contains("toml.bad")));
+        assert_eq!(copied.unwrap(), "[filters\nmin_snr = 5\n");
+    }
 }
@@ -0,0 +1,71 @@
+//! Crate-wide error type
+
+use crate::services::radio::RadioError;
+
+/// Errors from each subsystem, so the UI can surface and categorize them uniformly
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    /// Invalid settings or settings that could not be saved
+    #[error("{0}")]
+    Config(String),
+    /// VFD serial port problems
+    #[error("{0}")]
+    Display(String),
+    /// RBN telnet feed problems
+    #[error("{0}")]
+    Feed(String),
+    /// MQTT broker problems
+    #[error("{0}")]
+    Mqtt(String),
+    /// HTTP control API problems
+    #[error("{0}")]
+    Api(String),
+    /// Spot list export problems
+    #[error("{0}")]
+    Export(String),
+    /// Spot history database problems
+    #[error("{0}")]
+    History(String),
+    /// User hook script problems
+    #[error("{0}")]
+    Script(String),
+    /// Radio control problems
+    #[error(transparent)]
+    Radio(#[from] RadioError),
+}
+
+impl Error {
+    /// Subsystem name shown with the message
+    pub fn category(&self) -> &'static str {
+        match self {
+            Error::Config(_) => "Settings",
+            Error::Display(_) => "VFD",
+            Error::Feed(_) => "RBN",
+            Error::Mqtt(_) => "MQTT",
+            Error::Api(_) => "API",
+            Error::Export(_) => "Export",
+            Error::History(_) => "History",
+            Error::Script(_) => "Script",
+            Error::Radio(_) => "Radio",
+        }
+    }
+
+    /// Whether the same operation may succeed if retried later
+    #[allow(dead_code)]
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Error::Config(_) | Error::Export(_) | Error::Script(_) => false,
+            Error::Display(_)
+            | Error::Feed(_)
+            | Error::Mqtt(_)
+            | Error::Api(_)
+            | Error::History(_) => true,
+            Error::Radio(e) => matches!(
+                e,
+                RadioError::NotConnected | RadioError::ConnectionFailed(_) | RadioError::Timeout
+            ),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,462 @@
+//! Running without the GUI, e.g. on a Raspberry Pi attached only to the display
+//!
+//! Uses the saved settings as-is: connects to RBN, drives the VFD and, if radio
+//! control is enabled, keeps spots on the radio's current band at the top.
+//! Status is printed to stdout with a UTC timestamp. The HTTP control API
+//! (including `/metrics`) runs here too when enabled. Spots are filtered, the
+//! radio tuned and API requests answered by [`crate::pipeline`], as in the GUI.
+//!
+//! Not supported in headless mode, whatever the settings say: MQTT
+//! publishing, script hooks, the display handoff file, the subsystem
+//! watchdog, alerts, N1MM frequency following, contest tracking and session
+//! recording or replay.
+
+use crate::config::Config;
+use crate::error::Error;
+use crate::models::{AggregatedSpot, UtcTime};
+use crate::pipeline::{self, ApiHost};
+use crate::services::radio::{self, RadioController, RadioError, RadioMode};
+use crate::services::{
+    beacon_lines, HttpApi, PropagationFetcher, PropagationMessage, RbnClient, RbnMessage,
+    SelfSpots, SolarData, SpotDb, SpotStore, VfdDisplay,
+};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Main loop period, matching the GUI's repaint interval
+const TICK: Duration = Duration::from_millis(100);
+
+/// Wait before reconnecting after the feed drops
+const RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// How often the radio's VFO is read
+const VFO_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long the startup health page stays on the VFD
+const STARTUP_PAGE_TIME: Duration = Duration::from_secs(5);
+
+/// Window for the spot count shown by the ticker screensaver
+const SPOT_TICKER_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+
+struct Headless {
+    config: Config,
+    spot_store: SpotStore,
+    vfd_display: VfdDisplay,
+    rbn_client: Option<RbnClient>,
+    rbn_traffic: Arc<AtomicU64>,
+    /// When the feed dropped, to reconnect after a delay
+    disconnected_at: Option<Instant>,
+    radio_controller: Box<dyn RadioController>,
+    /// Band the radio is on, whose spots are listed first
+    radio_band: Option<String>,
+    /// Antenna last selected for the radio's band
+    antenna: Option<u8>,
+    last_vfo_poll: Instant,
+    last_purge: Instant,
+    http_api: Option<HttpApi>,
+    spot_db: Option<SpotDb>,
+    /// Skimmers hearing our own callsign, for the "My signal" page
+    self_spots: SelfSpots,
+    propagation: Option<PropagationFetcher>,
+    solar: Option<SolarData>,
+}
+
+/// Run until Ctrl-C; returns the process exit code
+pub fn run() -> i32 {
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 2;
+        }
+    };
+    if config.callsign.trim().is_empty() {
+        eprintln!("No callsign configured; set connection.callsign in settings.toml");
+        return 2;
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    watch_for_ctrl_c(stop.clone());
+
+    let mut headless = Headless::new(config);
+    headless.startup();
+    while !stop.load(Ordering::Relaxed) {
+        headless.tick();
+        std::thread::sleep(TICK);
+    }
+    headless.shutdown();
+    0
+}
+
+/// Set `stop` when the process is interrupted
+fn watch_for_ctrl_c(stop: Arc<AtomicBool>) {
+    std::thread::spawn(move || {
+        let Ok(rt) = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        else {
+            return;
+        };
+        if rt.block_on(tokio::signal::ctrl_c()).is_ok() {
+            stop.store(true, Ordering::Relaxed);
+        }
+    });
+}
+
+/// Print a status line with a UTC timestamp
+fn log(message: &str) {
+    println!("{} {}", UtcTime::from_system(SystemTime::now()), message);
+}
+
+impl Headless {
+    fn new(config: Config) -> Self {
+        let mut vfd_display = VfdDisplay::new();
+        vfd_display.set_scroll_interval(config.scroll_interval_seconds);
+        vfd_display.set_marquee(config.marquee_rate());
+        vfd_display.set_frequency_format(config.frequency_format());
+        vfd_display.set_random_char_percent(config.random_char_percent);
+        vfd_display.set_screensaver(config.screensaver);
+        vfd_display.set_callsign(&config.callsign);
+        vfd_display.set_flash_new_spots(config.flash_new_spots);
+        vfd_display.set_idle_content(config.idle_content);
+        vfd_display.set_content(config.vfd_content);
+        vfd_display.set_watchlist(&config.watchlist);
+        vfd_display.set_station_message(&config.station_message);
+        vfd_display.set_local_clock(config.local_clock());
+        vfd_display.set_geometry(config.display_geometry);
+        vfd_display.set_protocol(config.display_protocol);
+        vfd_display.set_brightness(config.display_brightness as u8);
+        vfd_display.set_pages(config.carousel_pages());
+        vfd_display.set_band_plan(config.band_plan.clone());
+
+        Self {
+            radio_controller: radio::create_controller(&config.radio),
+            config,
+            spot_store: SpotStore::new(),
+            vfd_display,
+            rbn_client: None,
+            rbn_traffic: Arc::new(AtomicU64::new(0)),
+            disconnected_at: None,
+            radio_band: None,
+            antenna: None,
+            last_vfo_poll: Instant::now(),
+            last_purge: Instant::now(),
+            http_api: None,
+            spot_db: None,
+            self_spots: SelfSpots::new(),
+            propagation: None,
+            solar: None,
+        }
+    }
+
+    fn report_error(&self, error: &Error) {
+        log(&format!("{} error: {}", error.category(), error));
+    }
+
+    /// Open the display, radio and feed, then show how that went on the VFD
+    fn startup(&mut self) {
+        match self.config.charset_overrides() {
+            Ok(overrides) => self.vfd_display.set_charset_overrides(overrides),
+            Err(e) => self.report_error(&e),
+        }
+        pipeline::configure_spot_store(&self.spot_store, &self.config);
+
+        let port = self.config.serial_port.clone();
+        if port.is_empty() {
+            log("No VFD port configured; running without a display");
+        } else {
+            match self.vfd_display.open(&port) {
+                Ok(()) => log(&format!("VFD opened on {}", port)),
+                Err(e) => self.report_error(&e),
+            }
+        }
+
+        let radio = if !self.config.radio.enabled {
+            "Radio off"
+        } else {
+            match self.radio_controller.connect() {
+                Ok(()) => {
+                    log(&format!(
+                        "{} connected",
+                        self.radio_controller.backend_name()
+                    ));
+                    "Radio OK"
+                }
+                Err(e) => {
+                    self.report_error(&e.into());
+                    "Radio FAIL"
+                }
+            }
+        };
+
+        if self.config.api_enabled {
+            match HttpApi::start(&self.config.api_bind) {
+                Ok(api) => {
+                    log(&format!("HTTP API listening on {}", api.bind_address()));
+                    self.http_api = Some(api);
+                }
+                Err(e) => self.report_error(&e),
+            }
+        }
+
+        if self.config.history_enabled {
+            if let Some(dir) = Config::data_dir() {
+                match SpotDb::open(&dir.join("history.sqlite")) {
+                    Ok(db) => self.spot_db = Some(db),
+                    Err(e) => self.report_error(&e),
+                }
+            }
+        }
+
+        let solar_minutes = self.config.effective_solar_refresh_minutes();
+        if solar_minutes > 0 {
+            self.propagation = Some(PropagationFetcher::new(
+                Duration::from_secs(solar_minutes as u64 * 60),
+                Config::data_dir().map(|dir| dir.join("solar.xml")),
+            ));
+        }
+
+        self.connect_rbn();
+        self.vfd_display
+            .show_status_page(&["Headless", "RBN connecting", radio], STARTUP_PAGE_TIME);
+    }
+
+    fn connect_rbn(&mut self) {
+        let client = RbnClient::new(self.rbn_traffic.clone());
+        client.connect(
+            self.config.callsign.trim().to_uppercase(),
+            self.config.login_commands(),
+        );
+        self.rbn_client = Some(client);
+        self.disconnected_at = None;
+    }
+
+    fn tick(&mut self) {
+        self.process_rbn_messages();
+        self.process_api_requests();
+
+        if self
+            .disconnected_at
+            .is_some_and(|at| at.elapsed() >= RECONNECT_DELAY)
+        {
+            log("Reconnecting to RBN");
+            self.connect_rbn();
+        }
+
+        if self.last_purge.elapsed() >= Duration::from_secs(5) {
+            self.spot_store.purge_old_spots();
+            self.self_spots
+                .purge(Duration::from_secs(self.config.max_age_minutes as u64 * 60));
+            self.vfd_display.set_my_signal_lines(
+                self.self_spots
+                    .vfd_lines(&self.config.band_plan, self.config.display_geometry.width),
+            );
+            self.process_propagation_messages();
+            self.vfd_display.set_solar_lines(
+                self.solar
+                    .as_ref()
+                    .filter(|s| !s.is_stale())
+                    .map(|s| s.vfd_lines(self.config.display_geometry.width)),
+            );
+            let max_age = Duration::from_secs(self.config.max_age_minutes as u64 * 60);
+            self.vfd_display.set_beacon_lines(beacon_lines(
+                &self.spot_store.get_filtered_spots(i32::MIN, max_age),
+                &self.config.band_plan,
+                self.config.display_geometry.width,
+            ));
+            self.last_purge = Instant::now();
+        }
+
+        if self.last_vfo_poll.elapsed() >= VFO_POLL_INTERVAL {
+            self.last_vfo_poll = Instant::now();
+            self.poll_vfo();
+        }
+
+        if let Some(error) = self.vfd_display.take_error() {
+            self.report_error(&error);
+        }
+        if let Some(error) = self.spot_db.as_ref().and_then(|db| db.take_error()) {
+            self.report_error(&error);
+        }
+
+        let mut spots = self.filtered_spots();
+        self.follow_radio_band(&mut spots);
+        self.vfd_display
+            .set_spots_24h(self.spot_store.count_heard_since(SPOT_TICKER_WINDOW));
+        self.vfd_display.update(&spots);
+    }
+
+    /// Show new solar data on the solar page
+    fn process_propagation_messages(&mut self) {
+        let Some(ref fetcher) = self.propagation else {
+            return;
+        };
+        let messages: Vec<PropagationMessage> = std::iter::from_fn(|| fetcher.try_recv()).collect();
+
+        for msg in messages {
+            match msg {
+                PropagationMessage::Updated(data) => {
+                    log(&format!("Solar data: {}", data.summary()));
+                    self.solar = Some(data);
+                }
+                PropagationMessage::Failed(e) => log(&e),
+            }
+        }
+    }
+
+    fn process_rbn_messages(&mut self) {
+        let messages: Vec<RbnMessage> = match self.rbn_client {
+            Some(ref mut client) => std::iter::from_fn(|| client.try_recv()).collect(),
+            None => Vec::new(),
+        };
+
+        let mut spots = Vec::new();
+        for msg in messages {
+            match msg {
+                RbnMessage::Status(s) => log(&s),
+                RbnMessage::Error(e) => self.report_error(&e),
+                RbnMessage::Spot(raw) => {
+                    if let Some(ref db) = self.spot_db {
+                        db.record(&raw);
+                    }
+                    if pipeline::keep_report(
+                        &self.config,
+                        &self.spot_store,
+                        &mut self.self_spots,
+                        &raw,
+                    ) {
+                        spots.push(raw);
+                    }
+                }
+                RbnMessage::Disconnected => {
+                    log(&format!(
+                        "Disconnected; retrying in {}s",
+                        RECONNECT_DELAY.as_secs()
+                    ));
+                    self.rbn_client = None;
+                    self.disconnected_at = Some(Instant::now());
+                }
+                RbnMessage::RawData { .. } => {}
+            }
+        }
+
+        // Stored in one batch so bursts don't take the store's locks per spot
+        for spot in self.spot_store.add_spots(spots, self.config.source_policy) {
+            if let Some(ref api) = self.http_api {
+                api.publish_spot(&spot);
+            }
+        }
+    }
+
+    /// Answer requests waiting on the HTTP control API
+    fn process_api_requests(&mut self) {
+        let Some(ref api) = self.http_api else {
+            return;
+        };
+        let calls: Vec<_> = std::iter::from_fn(|| api.try_recv()).collect();
+
+        for call in calls {
+            let response = pipeline::answer_api(self, &call.request);
+            call.respond(response);
+        }
+    }
+
+    /// Spots passing the SNR (fixed or adaptive) and age filters, sorted by frequency
+    fn filtered_spots(&self) -> Vec<AggregatedSpot> {
+        pipeline::filtered_spots(&self.spot_store, &self.config)
+    }
+
+    /// Track the band the radio is on
+    fn poll_vfo(&mut self) {
+        if !self.radio_controller.is_connected() {
+            return;
+        }
+        let Ok(frequency_khz) = self.radio_controller.get_frequency() else {
+            return;
+        };
+        let band = self
+            .config
+            .band_plan
+            .for_frequency(frequency_khz)
+            .map(|b| b.name.clone());
+        if band.is_some() && band != self.radio_band {
+            log(&format!("Radio on {}", band.as_deref().unwrap_or_default()));
+            if let Err(e) = pipeline::select_antenna(
+                self.radio_controller.as_mut(),
+                &self.config,
+                &mut self.antenna,
+                frequency_khz,
+            ) {
+                log(&e);
+            }
+            self.radio_band = band;
+        }
+    }
+
+    /// Move spots on the radio's band to the top, keeping their order otherwise
+    fn follow_radio_band(&self, spots: &mut [AggregatedSpot]) {
+        let Some(band) = self.radio_band.as_ref().and_then(|name| {
+            self.config
+                .band_plan
+                .bands()
+                .iter()
+                .find(|b| &b.name == name)
+        }) else {
+            return;
+        };
+        spots.sort_by_key(|s| !band.contains(s.frequency_khz));
+    }
+
+    fn shutdown(&mut self) {
+        log("Shutting down");
+        if let Some(ref client) = self.rbn_client {
+            client.disconnect();
+        }
+        self.radio_controller.disconnect();
+        self.vfd_display.close();
+    }
+}
+
+impl ApiHost for Headless {
+    fn config(&self) -> &Config {
+        &self.config
+    }
+
+    fn shown_spots(&self) -> Vec<AggregatedSpot> {
+        self.filtered_spots()
+    }
+
+    /// Feed, VFD and radio state for the HTTP API
+    fn status_json(&self) -> String {
+        serde_json::json!({
+            "rbn_connected": self.rbn_client.is_some(),
+            "spot_count": self.spot_store.count(),
+            "vfd_open": self.vfd_display.is_open(),
+            "vfd_port": self.vfd_display.port_name(),
+            "radio_connected": self.radio_controller.is_connected(),
+            "radio_band": self.radio_band.as_deref().unwrap_or_default(),
+            "display": self.vfd_display.get_preview(),
+        })
+        .to_string()
+    }
+
+    fn tune(&mut self, frequency_khz: f64, mode: RadioMode) -> Result<(), RadioError> {
+        let antenna_error = pipeline::tune(
+            self.radio_controller.as_mut(),
+            &self.config,
+            &mut self.antenna,
+            frequency_khz,
+            mode,
+        )?;
+        if let Some(error) = antenna_error {
+            log(&error);
+        }
+        log(&format!("Tuned to {:.1} kHz", frequency_khz));
+        Ok(())
+    }
+
+    fn vfd_display(&mut self) -> &mut VfdDisplay {
+        &mut self.vfd_display
+    }
+}
@@ -1,19 +1,47 @@
 mod app;
 mod config;
+mod error;
+mod headless;
 mod models;
+mod pipeline;
+mod plugins;
 mod services;
+mod settings_file;
+mod ui;
 
 fn main() -> eframe::Result<()> {
+    let launch = match app::LaunchOptions::from_args(std::env::args().skip(1)) {
+        Ok(launch) => launch,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!(
+                "Usage: rbn-vfd [--headless] [--portable] [--record-session FILE] [--replay-session FILE]"
+            );
+            std::process::exit(2);
+        }
+    };
+
+    if launch.portable {
+        if let Err(e) = config::Config::enable_portable_mode() {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    }
+
+    if launch.headless {
+        std::process::exit(headless::run());
+    }
+
     let options = eframe::NativeOptions {
         viewport: eframe::egui::ViewportBuilder::default()
-            .with_inner_size([500.0, 600.0])
-            .with_min_inner_size([400.0, 400.0]),
+            .with_inner_size(app::WINDOW_SIZE)
+            .with_min_inner_size(app::MIN_WINDOW_SIZE),
         ..Default::default()
     };
 
     eframe::run_native(
         "RBN VFD Display",
         options,
-        Box::new(|cc| Ok(Box::new(app::RbnVfdApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(app::RbnVfdApp::new(cc, launch)))),
     )
 }
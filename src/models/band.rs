@@ -0,0 +1,101 @@
+/// A named frequency range (an amateur band by default)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Band {
+    pub name: String,
+    pub low_khz: f64,
+    pub high_khz: f64,
+}
+
+impl Band {
+    /// Whether a frequency falls inside this band
+    pub fn contains(&self, frequency_khz: f64) -> bool {
+        frequency_khz >= self.low_khz && frequency_khz <= self.high_khz
+    }
+
+    /// Parse `NAME:LOW-HIGH;...` ranges (kHz)
+    pub fn parse_list(text: &str) -> Result<Vec<Band>, String> {
+        let mut bands = Vec::new();
+        for entry in text.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let invalid = || format!("Invalid band entry: {}", entry);
+            let (name, range) = entry.split_once(':').ok_or_else(invalid)?;
+            let (low, high) = range.split_once('-').ok_or_else(invalid)?;
+            let low_khz: f64 = low.trim().parse().map_err(|_| invalid())?;
+            let high_khz: f64 = high.trim().parse().map_err(|_| invalid())?;
+            if name.trim().is_empty() || low_khz >= high_khz {
+                return Err(invalid());
+            }
+            bands.push(Band {
+                name: name.trim().to_string(),
+                low_khz,
+                high_khz,
+            });
+        }
+        Ok(bands)
+    }
+}
+
+/// Amateur bands covered by RBN skimmers (IARU region-wide edges)
+const AMATEUR_BANDS: &[(&str, f64, f64)] = &[
+    ("160m", 1800.0, 2000.0),
+    ("80m", 3500.0, 4000.0),
+    ("60m", 5330.0, 5410.0),
+    ("40m", 7000.0, 7300.0),
+    ("30m", 10100.0, 10150.0),
+    ("20m", 14000.0, 14350.0),
+    ("17m", 18068.0, 18168.0),
+    ("15m", 21000.0, 21450.0),
+    ("12m", 24890.0, 24990.0),
+    ("10m", 28000.0, 29700.0),
+    ("6m", 50000.0, 54000.0),
+    ("2m", 144000.0, 148000.0),
+];
+
+/// The table used to label frequencies, in display order
+///
+/// Stored in settings.toml as `[[bands.table]]` entries, so feeds outside the
+/// amateur allocations (marine, aviation) can be labeled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandPlan {
+    bands: Vec<Band>,
+}
+
+impl BandPlan {
+    /// The built-in amateur band plan
+    pub fn amateur() -> Self {
+        Self {
+            bands: AMATEUR_BANDS
+                .iter()
+                .map(|&(name, low_khz, high_khz)| Band {
+                    name: name.to_string(),
+                    low_khz,
+                    high_khz,
+                })
+                .collect(),
+        }
+    }
+
+    /// A plan of `bands`; an empty list means the amateur plan
+    pub fn from_bands(bands: Vec<Band>) -> Self {
+        if bands.is_empty() {
+            Self::amateur()
+        } else {
+            Self { bands }
+        }
+    }
+
+    /// Find the band containing a frequency
+    pub fn for_frequency(&self, frequency_khz: f64) -> Option<&Band> {
+        self.bands.iter().find(|band| band.contains(frequency_khz))
+    }
+
+    /// All bands in display order
+    pub fn bands(&self) -> &[Band] {
+        &self.bands
+    }
+}
+
+impl Default for BandPlan {
+    fn default() -> Self {
+        Self::amateur()
+    }
+}
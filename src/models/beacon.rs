@@ -0,0 +1,11 @@
+/// The NCDXF/IARU International Beacon Project transmitters, in time-slot order
+const NCDXF_BEACONS: &[&str] = &[
+    "4U1UN", "VE8AT", "W6WX", "KH6RS", "ZL6B", "VK6RBP", "JA2IGY", "RR9O", "VR2B", "4S7B", "ZS6DN",
+    "5Z4B", "4X6TU", "OH2B", "CS3B", "LU4AA", "OA4B", "YV5B",
+];
+
+/// Whether a spotted call is a beacon: an NCDXF/IARU beacon or a `/B` call
+pub fn is_beacon_call(callsign: &str) -> bool {
+    let call = callsign.to_uppercase();
+    call.ends_with("/B") || NCDXF_BEACONS.contains(&call.as_str())
+}
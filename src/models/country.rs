@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+/// A DXCC entity from cty.dat
+#[derive(Debug, Clone)]
+pub struct Entity {
+    pub name: String,
+    pub cq_zone: u8,
+    pub itu_zone: u8,
+    #[allow(dead_code)]
+    pub continent: String,
+    /// Degrees north
+    pub latitude: f64,
+    /// Degrees east (cty.dat stores west-positive, converted on load)
+    pub longitude: f64,
+    pub primary_prefix: String,
+}
+
+/// Callsign to DXCC entity lookup built from cty.dat
+#[derive(Debug, Default)]
+pub struct CountryDb {
+    entities: Vec<Entity>,
+    prefixes: HashMap<String, usize>,
+    exact_calls: HashMap<String, usize>,
+}
+
+impl CountryDb {
+    /// Parse the contents of a cty.dat file
+    pub fn parse(data: &str) -> Self {
+        let mut db = Self::default();
+        let mut current: Option<usize> = None;
+
+        for line in data.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // Entity header lines start in column 0, alias lines are indented
+            if !line.starts_with(char::is_whitespace) {
+                let fields: Vec<&str> = line.split(':').map(str::trim).collect();
+                if fields.len() < 8 {
+                    current = None;
+                    continue;
+                }
+                let entity = Entity {
+                    name: fields[0].to_string(),
+                    cq_zone: fields[1].parse().unwrap_or(0),
+                    itu_zone: fields[2].parse().unwrap_or(0),
+                    continent: fields[3].to_string(),
+                    latitude: fields[4].parse().unwrap_or(0.0),
+                    longitude: -fields[5].parse::<f64>().unwrap_or(0.0),
+                    primary_prefix: fields[7].trim_start_matches('*').to_string(),
+                };
+                db.entities.push(entity);
+                current = Some(db.entities.len() - 1);
+                continue;
+            }
+
+            let Some(index) = current else {
+                continue;
+            };
+
+            for alias in line.split([',', ';']) {
+                // Strip zone/location overrides: (cq) [itu] <lat/lon> {cont} ~offset~
+                let alias = alias
+                    .trim()
+                    .split(['(', '[', '<', '{', '~'])
+                    .next()
+                    .unwrap_or_default();
+                if alias.is_empty() {
+                    continue;
+                }
+                if let Some(call) = alias.strip_prefix('=') {
+                    db.exact_calls.insert(call.to_uppercase(), index);
+                } else {
+                    db.prefixes.insert(alias.to_uppercase(), index);
+                }
+            }
+        }
+
+        db
+    }
+
+    /// Number of entities loaded
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Look up the entity for a callsign using exact matches, then the longest prefix
+    pub fn lookup(&self, callsign: &str) -> Option<&Entity> {
+        let call = callsign.trim().to_uppercase();
+        if let Some(&index) = self.exact_calls.get(&call) {
+            return Some(&self.entities[index]);
+        }
+
+        let base = Self::prefix_part(&call);
+        (1..=base.len())
+            .rev()
+            .find_map(|len| base.get(..len).and_then(|p| self.prefixes.get(p)))
+            .map(|&index| &self.entities[index])
+    }
+
+    /// Pick the part of a portable callsign that determines the entity
+    fn prefix_part(call: &str) -> &str {
+        let parts: Vec<&str> = call.split('/').collect();
+        match parts.as_slice() {
+            [single] => single,
+            // "W6JSV/P" uses the base call; otherwise the shorter part is the prefix
+            [first, second, ..] => {
+                let is_suffix = |s: &str| {
+                    matches!(s, "P" | "M" | "MM" | "AM" | "QRP" | "A")
+                        || s.chars().all(|c| c.is_ascii_digit())
+                };
+                if is_suffix(second) || first.len() <= second.len() {
+                    first
+                } else {
+                    second
+                }
+            }
+            [] => call,
+        }
+    }
+}
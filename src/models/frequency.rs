@@ -0,0 +1,167 @@
+/// Unit frequencies are shown in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrequencyUnit {
+    /// "14033.0"
+    #[default]
+    Khz,
+    /// "14.033.0", MHz and kHz split the way a radio's dial shows them
+    Mhz,
+}
+
+impl FrequencyUnit {
+    pub const ALL: [FrequencyUnit; 2] = [FrequencyUnit::Khz, FrequencyUnit::Mhz];
+
+    /// Name used in settings.toml
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FrequencyUnit::Khz => "khz",
+            FrequencyUnit::Mhz => "mhz",
+        }
+    }
+
+    /// Parse the settings.toml name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|u| u.as_str() == name.trim().to_ascii_lowercase())
+    }
+
+    /// Unit symbol written after a frequency
+    pub fn symbol(self) -> &'static str {
+        match self {
+            FrequencyUnit::Khz => "kHz",
+            FrequencyUnit::Mhz => "MHz",
+        }
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            FrequencyUnit::Khz => "kHz (14033.0)",
+            FrequencyUnit::Mhz => "MHz (14.033.0)",
+        }
+    }
+}
+
+/// How frequencies are written: the unit, and the decimal mark of the
+/// operator's locale
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrequencyFormat {
+    pub unit: FrequencyUnit,
+    /// Write "14033,0" rather than "14033.0"
+    pub decimal_comma: bool,
+}
+
+impl FrequencyFormat {
+    /// A frequency to 100 Hz, e.g. "14033.0", "14033,0", "14.033.0" or "14.033,0"
+    pub fn format(&self, frequency_khz: f64) -> String {
+        let tenths = (frequency_khz * 10.0).round() as i64;
+        let decimal = if self.decimal_comma { ',' } else { '.' };
+        let (whole, tenth) = (tenths / 10, tenths.rem_euclid(10));
+        match self.unit {
+            FrequencyUnit::Khz => format!("{}{}{}", whole, decimal, tenth),
+            FrequencyUnit::Mhz => format!(
+                "{}.{:03}{}{}",
+                whole / 1000,
+                whole.rem_euclid(1000),
+                decimal,
+                tenth
+            ),
+        }
+    }
+
+    /// Width of a formatted HF frequency, for lining up columns
+    pub fn width(&self) -> usize {
+        match self.unit {
+            FrequencyUnit::Khz => 7,
+            FrequencyUnit::Mhz => 8,
+        }
+    }
+}
+
+/// Whether a locale name such as "de_DE.UTF-8" or "fr-CA" writes decimals
+/// with a comma
+pub fn locale_uses_decimal_comma(locale: &str) -> bool {
+    // Languages written with a decimal comma; anything else uses a point
+    const DECIMAL_COMMA: &[&str] = &[
+        "af", "az", "be", "bg", "bs", "ca", "cs", "da", "de", "el", "es", "et", "eu", "fi", "fo",
+        "fr", "gl", "hr", "hu", "id", "is", "it", "ka", "kk", "lt", "lv", "mk", "nb", "nl", "nn",
+        "no", "pl", "pt", "ro", "ru", "sk", "sl", "sq", "sr", "sv", "tr", "uk", "vi",
+    ];
+    // Regions where those languages use a decimal point after all
+    const DECIMAL_POINT_REGIONS: &[(&str, &str)] = &[
+        ("de", "CH"),
+        ("de", "LI"),
+        ("it", "CH"),
+        ("es", "DO"),
+        ("es", "GT"),
+        ("es", "HN"),
+        ("es", "MX"),
+        ("es", "NI"),
+        ("es", "PA"),
+        ("es", "PE"),
+        ("es", "PR"),
+        ("es", "SV"),
+        ("es", "US"),
+    ];
+    let name = locale.split(['.', '@']).next().unwrap_or_default();
+    let mut parts = name.split(['_', '-']);
+    let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+    let region = parts.next().unwrap_or_default().to_ascii_uppercase();
+    DECIMAL_COMMA.contains(&language.as_str())
+        && !DECIMAL_POINT_REGIONS.contains(&(language.as_str(), region.as_str()))
+}
+
+/// The locale the environment asks numbers to be written in, if any
+pub fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_frequencies() {
+        let cases = [
+            (FrequencyUnit::Khz, false, 14033.0, "14033.0"),
+            (FrequencyUnit::Khz, true, 14033.0, "14033,0"),
+            (FrequencyUnit::Khz, false, 1830.46, "1830.5"),
+            (FrequencyUnit::Mhz, false, 14033.0, "14.033.0"),
+            (FrequencyUnit::Mhz, true, 14033.0, "14.033,0"),
+            (FrequencyUnit::Mhz, false, 7001.2, "7.001.2"),
+            (FrequencyUnit::Mhz, true, 144050.0, "144.050,0"),
+        ];
+        for (unit, decimal_comma, khz, expected) in cases {
+            let format = FrequencyFormat {
+                unit,
+                decimal_comma,
+            };
+            assert_eq!(format.format(khz), expected, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn picks_decimal_mark_by_locale() {
+        let cases = [
+            ("de_DE.UTF-8", true),
+            ("de_CH.UTF-8", false),
+            ("fr-CA", true),
+            ("es_ES", true),
+            ("es_MX.UTF-8", false),
+            ("pt_BR", true),
+            ("en_US.UTF-8", false),
+            ("en_GB", false),
+            ("ja_JP", false),
+            ("C.UTF-8", false),
+            ("POSIX", false),
+            ("", false),
+        ];
+        for (locale, comma) in cases {
+            assert_eq!(locale_uses_decimal_comma(locale), comma, "{}", locale);
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! Maidenhead locators
+
+/// Locator of a position, 2 (field), 4 (square) or 6 (subsquare) characters long
+pub fn to_locator(latitude: f64, longitude: f64, chars: usize) -> String {
+    let lon = (longitude + 180.0).clamp(0.0, 359.999_999);
+    let lat = (latitude + 90.0).clamp(0.0, 179.999_999);
+    let letter = |base: u8, n: f64| (base + n as u8) as char;
+    let mut locator = String::with_capacity(6);
+    locator.push(letter(b'A', lon / 20.0));
+    locator.push(letter(b'A', lat / 10.0));
+    if chars >= 4 {
+        locator.push(letter(b'0', (lon % 20.0) / 2.0));
+        locator.push(letter(b'0', lat % 10.0));
+    }
+    if chars >= 6 {
+        locator.push(letter(b'a', (lon % 2.0) * 12.0));
+        locator.push(letter(b'a', (lat % 1.0) * 24.0));
+    }
+    locator
+}
+
+/// Great-circle short-path bearing in degrees (0-360) between two (lat, lon) positions
+pub fn bearing(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+    let dlon = lon2 - lon1;
+    let y = dlon.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// Center (degrees north, degrees east) of a 2, 4 or 6 character locator
+pub fn locator_center(locator: &str) -> Option<(f64, f64)> {
+    let chars: Vec<u8> = locator.trim().to_ascii_uppercase().bytes().collect();
+    if !matches!(chars.len(), 2 | 4 | 6) {
+        return None;
+    }
+    let index =
+        |c: u8, base: u8, count: u8| (c >= base && c < base + count).then(|| (c - base) as f64);
+
+    let mut lon = index(chars[0], b'A', 18)? * 20.0 - 180.0;
+    let mut lat = index(chars[1], b'A', 18)? * 10.0 - 90.0;
+    let (mut width, mut height) = (20.0, 10.0);
+    if chars.len() >= 4 {
+        lon += index(chars[2], b'0', 10)? * 2.0;
+        lat += index(chars[3], b'0', 10)?;
+        (width, height) = (2.0, 1.0);
+    }
+    if chars.len() == 6 {
+        lon += index(chars[4], b'A', 24)? * 2.0 / 24.0;
+        lat += index(chars[5], b'A', 24)? / 24.0;
+        (width, height) = (2.0 / 24.0, 1.0 / 24.0);
+    }
+    Some((lat + height / 2.0, lon + width / 2.0))
+}
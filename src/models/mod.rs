@@ -1,3 +1,15 @@
+mod band;
+mod beacon;
+mod country;
+mod frequency;
+mod grid;
 mod spot;
+mod utc_time;
 
-pub use spot::{AggregatedSpot, RawSpot};
+pub use band::{Band, BandPlan};
+pub use beacon::is_beacon_call;
+pub use country::CountryDb;
+pub use frequency::{locale_uses_decimal_comma, system_locale, FrequencyFormat, FrequencyUnit};
+pub use grid::{bearing, locator_center, to_locator};
+pub use spot::{AggregatedSpot, BustedCallPolicy, RawSpot, SourcePolicy, SpotSource, Trend};
+pub use utc_time::UtcTime;
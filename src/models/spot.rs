@@ -1,18 +1,156 @@
-use std::time::Instant;
+use super::{is_beacon_call, FrequencyFormat};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long reports are kept for the short-term pile-up and trend estimates
+const RECENT_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Where a spot originated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpotSource {
+    /// Automated skimmer (spotter callsign ends in "-#")
+    Skimmer,
+    /// Spot entered by a human operator
+    Human,
+    /// Entered locally (a sked arranged off-air)
+    Manual,
+}
+
+/// How to resolve disagreements between skimmer and human spots of the same signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourcePolicy {
+    /// Skimmer data wins over human spots
+    PreferSkimmer,
+    /// Human spots win over skimmer data
+    PreferHuman,
+    /// Keep both modes, e.g. "CW/SSB"
+    KeepBoth,
+}
+
+impl SourcePolicy {
+    pub const ALL: [SourcePolicy; 3] = [
+        SourcePolicy::PreferSkimmer,
+        SourcePolicy::PreferHuman,
+        SourcePolicy::KeepBoth,
+    ];
+
+    /// Name used in settings.toml
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SourcePolicy::PreferSkimmer => "prefer_skimmer",
+            SourcePolicy::PreferHuman => "prefer_human",
+            SourcePolicy::KeepBoth => "keep_both",
+        }
+    }
+
+    /// Parse the settings.toml name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == name.trim())
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            SourcePolicy::PreferSkimmer => "Prefer skimmer",
+            SourcePolicy::PreferHuman => "Prefer human",
+            SourcePolicy::KeepBoth => "Keep both",
+        }
+    }
+}
+
+/// What to do with spots that look like a miscopy of a busier station nearby
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BustedCallPolicy {
+    /// Show them like any other spot
+    Off,
+    /// Show them marked with the call they probably are
+    Flag,
+    /// Fold their reports into the real station's spot
+    Merge,
+}
+
+impl BustedCallPolicy {
+    pub const ALL: [BustedCallPolicy; 3] = [
+        BustedCallPolicy::Off,
+        BustedCallPolicy::Flag,
+        BustedCallPolicy::Merge,
+    ];
+
+    /// Name used in settings.toml
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BustedCallPolicy::Off => "off",
+            BustedCallPolicy::Flag => "flag",
+            BustedCallPolicy::Merge => "merge",
+        }
+    }
+
+    /// Parse the settings.toml name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == name.trim())
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            BustedCallPolicy::Off => "Show",
+            BustedCallPolicy::Flag => "Flag",
+            BustedCallPolicy::Merge => "Merge",
+        }
+    }
+}
+
+/// Short-term direction of a station's signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Trend {
+    /// First heard on the band this session in the last few minutes
+    New,
+    Rising,
+    Steady,
+    Fading,
+    /// No reports in the last few minutes
+    Gone,
+}
+
+impl Trend {
+    /// Glyph shown in the spot table
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Trend::New => "★",
+            Trend::Rising => "▲",
+            Trend::Steady => "●",
+            Trend::Fading => "▼",
+            Trend::Gone => "○",
+        }
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            Trend::New => "New",
+            Trend::Rising => "Rising",
+            Trend::Steady => "Steady",
+            Trend::Fading => "Fading",
+            Trend::Gone => "Gone",
+        }
+    }
+}
 
 /// Raw spot data as received from RBN telnet
 #[derive(Debug, Clone)]
 pub struct RawSpot {
-    #[allow(dead_code)]
     pub spotter_callsign: String,
     pub spotted_callsign: String,
     pub frequency_khz: f64,
     pub snr: i32,
     pub speed_wpm: i32,
-    #[allow(dead_code)]
     pub mode: String,
     #[allow(dead_code)]
     pub timestamp: Instant,
+    /// Wall-clock time the spot was received, for display and export
+    pub received_at: SystemTime,
+    pub source: SpotSource,
+    /// Flagged as a beacon by the feed or a known beacon call
+    pub beacon: bool,
 }
 
 impl RawSpot {
@@ -23,7 +161,9 @@ impl RawSpot {
         snr: i32,
         speed_wpm: i32,
         mode: String,
+        source: SpotSource,
     ) -> Self {
+        let beacon = is_beacon_call(&spotted_callsign);
         Self {
             spotter_callsign,
             spotted_callsign,
@@ -32,6 +172,9 @@ impl RawSpot {
             speed_wpm,
             mode,
             timestamp: Instant::now(),
+            received_at: SystemTime::now(),
+            source,
+            beacon,
         }
     }
 }
@@ -39,6 +182,8 @@ impl RawSpot {
 /// Aggregated spot data for display
 #[derive(Debug, Clone)]
 pub struct AggregatedSpot {
+    /// Serial number given by the spot store
+    pub id: u64,
     pub callsign: String,
     pub frequency_khz: f64,
     #[allow(dead_code)]
@@ -46,27 +191,63 @@ pub struct AggregatedSpot {
     pub highest_snr: i32,
     pub average_speed: f64,
     pub spot_count: u32,
+    /// Monotonic time of the latest report, for ages and sorting
     pub last_spotted: Instant,
+    /// Wall-clock time of the first report
+    pub first_spotted_at: SystemTime,
+    /// First report of the call on this band this session, before this spot
+    /// if an earlier one was purged
+    pub first_heard_at: SystemTime,
+    /// Wall-clock time of the latest report
+    pub last_spotted_at: SystemTime,
     pub mode: String,
+    /// Source that supplied the current mode
+    pub mode_source: SpotSource,
+    /// Latest mode reported by each source, which `KeepBoth` lists together
+    source_modes: Vec<(SpotSource, String)>,
+    /// Entered locally; shown regardless of the SNR filter
+    pub manual: bool,
+    /// Call this spot is probably a miscopy of, when busted calls are flagged
+    pub busted_of: Option<String>,
+    /// A beacon rather than an operator
+    pub beacon: bool,
+    /// Pinned by the operator: kept past expiry and listed first
+    pub pinned: bool,
+    /// Operator's note, e.g. "QSX 1.5 up" (empty = none); goes with the spot
+    pub note: String,
+    /// Spotter and SNR of each report in the last few minutes, oldest first
+    pub recent: Vec<(Instant, String, i32)>,
 }
 
 impl AggregatedSpot {
     /// Create a new aggregated spot from a raw spot
     pub fn from_raw(raw: &RawSpot) -> Self {
         Self {
+            id: 0,
             callsign: raw.spotted_callsign.clone(),
             frequency_khz: raw.frequency_khz,
-            center_frequency_khz: raw.frequency_khz.round(),
+            center_frequency_khz: raw.frequency_khz,
             highest_snr: raw.snr,
             average_speed: raw.speed_wpm as f64,
             spot_count: 1,
             last_spotted: Instant::now(),
+            first_spotted_at: raw.received_at,
+            first_heard_at: raw.received_at,
+            last_spotted_at: raw.received_at,
             mode: raw.mode.clone(),
+            mode_source: raw.source,
+            source_modes: vec![(raw.source, raw.mode.clone())],
+            manual: raw.source == SpotSource::Manual,
+            busted_of: None,
+            beacon: raw.beacon,
+            pinned: false,
+            note: String::new(),
+            recent: vec![(Instant::now(), raw.spotter_callsign.clone(), raw.snr)],
         }
     }
 
     /// Update this spot with new data using incremental averaging
-    pub fn update(&mut self, raw: &RawSpot) {
+    pub fn update(&mut self, raw: &RawSpot, policy: SourcePolicy) {
         self.spot_count += 1;
         self.average_speed += (raw.speed_wpm as f64 - self.average_speed) / self.spot_count as f64;
         self.frequency_khz += (raw.frequency_khz - self.frequency_khz) / self.spot_count as f64;
@@ -74,13 +255,134 @@ impl AggregatedSpot {
             self.highest_snr = raw.snr;
         }
         self.last_spotted = Instant::now();
-        self.mode = raw.mode.clone();
+        self.last_spotted_at = raw.received_at;
+        self.manual |= raw.source == SpotSource::Manual;
+        self.beacon |= raw.beacon;
+        self.merge_mode(raw, policy);
+        self.recent
+            .retain(|(at, _, _)| at.elapsed() < RECENT_WINDOW);
+        self.recent
+            .push((self.last_spotted, raw.spotter_callsign.clone(), raw.snr));
     }
 
-    /// Generate the unique key for this spot (callsign + center frequency)
-    #[allow(dead_code)]
+    /// Signal trend over the last few minutes. Skimmers disagree on absolute
+    /// SNR, so only skimmers with several reports count, each compared with
+    /// its own first report.
+    pub fn trend(&self) -> Trend {
+        let recent: Vec<_> = self
+            .recent
+            .iter()
+            .filter(|(at, _, _)| at.elapsed() < RECENT_WINDOW)
+            .collect();
+        if recent.is_empty() {
+            return Trend::Gone;
+        }
+        if self
+            .first_heard_at
+            .elapsed()
+            .is_ok_and(|age| age < RECENT_WINDOW)
+        {
+            return Trend::New;
+        }
+
+        let mut changes = Vec::new();
+        for (i, (_, spotter, first)) in recent.iter().enumerate() {
+            if recent[..i].iter().any(|(_, s, _)| s == spotter) {
+                continue;
+            }
+            if let Some((_, _, last)) = recent[i + 1..].iter().rev().find(|(_, s, _)| s == spotter)
+            {
+                changes.push(last - first);
+            }
+        }
+        if changes.is_empty() {
+            return Trend::Steady;
+        }
+        let average = changes.iter().sum::<i32>() as f64 / changes.len() as f64;
+        if average >= 3.0 {
+            Trend::Rising
+        } else if average <= -3.0 {
+            Trend::Fading
+        } else {
+            Trend::Steady
+        }
+    }
+
+    /// Rough pile-up size from 1 to 5: how many skimmers heard the station in
+    /// the last few minutes, one higher when their SNRs spread widely (heard
+    /// well from many directions)
+    pub fn pileup_level(&self) -> u8 {
+        let recent: Vec<_> = self
+            .recent
+            .iter()
+            .filter(|(at, _, _)| at.elapsed() < RECENT_WINDOW)
+            .collect();
+        let mut spotters: Vec<&str> = recent.iter().map(|(_, s, _)| s.as_str()).collect();
+        spotters.sort_unstable();
+        spotters.dedup();
+        let level: u8 = match spotters.len() {
+            0..=1 => 1,
+            2..=3 => 2,
+            4..=6 => 3,
+            7..=10 => 4,
+            _ => 5,
+        };
+        let snrs = recent.iter().map(|(_, _, snr)| *snr);
+        let spread = snrs.clone().max().unwrap_or(0) - snrs.min().unwrap_or(0);
+        if spread >= 20 {
+            (level + 1).min(5)
+        } else {
+            level
+        }
+    }
+
+    /// Resolve the mode when a new spot disagrees with what we have
+    fn merge_mode(&mut self, raw: &RawSpot, policy: SourcePolicy) {
+        // A newer report replaces only its own source's mode
+        match self.source_modes.iter_mut().find(|(s, _)| *s == raw.source) {
+            Some((_, mode)) => *mode = raw.mode.clone(),
+            None => self.source_modes.push((raw.source, raw.mode.clone())),
+        }
+        if policy == SourcePolicy::KeepBoth {
+            let mut modes: Vec<&str> = Vec::new();
+            for (_, mode) in &self.source_modes {
+                if !modes.contains(&mode.as_str()) {
+                    modes.push(mode);
+                }
+            }
+            self.mode = modes.join("/");
+            return;
+        }
+
+        if raw.source == self.mode_source || self.mode.split('/').any(|m| m == raw.mode) {
+            // Same source (or already known): newest report wins
+            if raw.source == self.mode_source {
+                self.mode = raw.mode.clone();
+            }
+            return;
+        }
+
+        match policy {
+            SourcePolicy::PreferSkimmer if raw.source == SpotSource::Skimmer => {
+                self.mode = raw.mode.clone();
+                self.mode_source = raw.source;
+            }
+            SourcePolicy::PreferHuman if raw.source != SpotSource::Skimmer => {
+                self.mode = raw.mode.clone();
+                self.mode_source = raw.source;
+            }
+            _ => {}
+        }
+    }
+
+    /// Generate the unique key for this spot (callsign + first reported
+    /// frequency + serial number, since two spots of a call can round to the
+    /// same frequency)
     pub fn key(&self) -> String {
-        format!("{}|{:.0}", self.callsign, self.center_frequency_khz)
+        format!(
+            "{}|{:.1}|{}",
+            self.callsign, self.center_frequency_khz, self.id
+        )
     }
 
     /// Get age in seconds since last spotted
@@ -94,24 +396,51 @@ impl AggregatedSpot {
         (age.as_secs_f32() / max_age.as_secs_f32()).min(1.0)
     }
 
-    /// Format for VFD display (max 20 characters)
-    /// Format: "FFFFF.F WW CCCCCCCCC" (freq aligned at decimal, WPM right-aligned, call left-aligned)
-    /// Example: "14033.0 22 WO6W     "
-    pub fn to_display_string(&self) -> String {
-        // Fixed widths: 7 freq + 1 space + 2 wpm + 1 space + 9 call = 20 chars
-        // Frequency: right-aligned with decimal at position 5
+    /// Format for a VFD line of the given width
+    /// Format at 20 chars: "FFFFF.F WW CCCCCCCCC" (freq aligned at decimal, WPM right-aligned, call left-aligned)
+    /// Example: "14033.0 22 WO6W     " ("14.033.0 22 WO6W    " in MHz)
+    /// Wider displays get a longer callsign field and, from 30 chars, the SNR;
+    /// narrower ones drop the WPM.
+    pub fn to_display_string(&self, width: usize, format: FrequencyFormat) -> String {
+        // Frequency: right-aligned so the decimal mark lines up
         // WPM: right-aligned in 2 chars
-        // Callsign: left-aligned, truncated to 9 chars
-        let call = if self.callsign.len() > 9 {
-            &self.callsign[..9]
+        // Callsign: left-aligned, truncated to fit
+        let wpm = self.average_speed.round() as i32;
+        let freq = format.format(self.frequency_khz);
+        let freq_width = format.width();
+        let line = if width >= 30 {
+            format!(
+                "{:>freq_width$} {:2} {:<12} {:>3}dB",
+                freq,
+                wpm,
+                truncate(&self.callsign, 12),
+                self.highest_snr
+            )
+        } else if width >= 20 {
+            // freq + 1 space + 2 wpm + 1 space + call
+            let call_width = width - freq_width - 4;
+            format!(
+                "{:>freq_width$} {:2} {:<call_width$}",
+                freq,
+                wpm,
+                truncate(&self.callsign, call_width),
+            )
         } else {
-            &self.callsign
+            let call_width = width.saturating_sub(freq_width + 1);
+            format!(
+                "{:>freq_width$} {:<call_width$}",
+                freq,
+                truncate(&self.callsign, call_width),
+            )
         };
-        format!(
-            "{:7.1} {:2} {:<9}",
-            self.frequency_khz,
-            self.average_speed.round() as i32,
-            call
-        )
+        line.chars().take(width).collect()
+    }
+}
+
+/// Truncate a string to at most `max` characters
+fn truncate(text: &str, max: usize) -> &str {
+    match text.char_indices().nth(max) {
+        Some((idx, _)) => &text[..idx],
+        None => text,
     }
 }
@@ -0,0 +1,88 @@
+//! UTC calendar time, for timestamps in logs, exports and on the display
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A UTC calendar time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UtcTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    /// Day of the week, 0 = Sunday
+    pub weekday: u32,
+}
+
+impl UtcTime {
+    pub fn from_system(time: SystemTime) -> Self {
+        let secs = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        let days = secs.div_euclid(86400);
+        let of_day = secs.rem_euclid(86400) as u32;
+
+        // Civil date from days since the epoch (Howard Hinnant's algorithm)
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: of_day / 3600,
+            minute: of_day / 60 % 60,
+            second: of_day % 60,
+            // 1970-01-01 was a Thursday
+            weekday: (days + 4).rem_euclid(7) as u32,
+        }
+    }
+
+    /// RFC 3339 form, e.g. "2026-10-17T14:05:09Z"
+    pub fn to_rfc3339(self) -> String {
+        format!(
+            "{}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+
+    pub fn weekday_name(&self) -> &'static str {
+        ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"][self.weekday as usize]
+    }
+}
+
+impl std::fmt::Display for UtcTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}-{:02}-{:02} {:02}:{:02}:{:02}Z",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn converts_system_time() {
+        let t = UtcTime::from_system(UNIX_EPOCH + Duration::from_secs(1_792_245_909));
+        assert_eq!(t.to_rfc3339(), "2026-10-17T14:05:09Z");
+        assert_eq!(t.weekday_name(), "Sat");
+        assert_eq!(t.to_string(), "2026-10-17 14:05:09Z");
+
+        let leap_day = UtcTime::from_system(UNIX_EPOCH + Duration::from_secs(951_782_400));
+        assert_eq!((leap_day.year, leap_day.month, leap_day.day), (2000, 2, 29));
+    }
+}
@@ -0,0 +1,229 @@
+//! Spot, radio and HTTP API handling shared by the GUI and headless mode
+//!
+//! Each front end owns its services and decides when to call these; the rules
+//! for which reports are stored, which spots are shown, how the radio is tuned
+//! and how API requests are answered live here so both apply them the same way.
+
+use crate::config::Config;
+use crate::models::{AggregatedSpot, RawSpot, SpotSource};
+use crate::services::radio::{
+    self, switch_antenna, AntennaOutput, RadioController, RadioError, RadioMode,
+};
+use crate::services::{
+    is_own_call, spots_json, to_json, ApiRequest, ApiResponse, LinesJson, SelfSpots, SpotStore,
+    VfdDisplay,
+};
+use std::time::Duration;
+
+/// Hand the aggregation settings to the spot store
+pub fn configure_spot_store(store: &SpotStore, config: &Config) {
+    store.set_merge_tolerance(config.merge_tolerance_hz);
+    store.set_busted_policy(config.busted_calls);
+    store.set_frequency_windows(config.frequency_windows.clone());
+    store.set_band_plan(config.band_plan.clone());
+    store.set_refresh_interval(Duration::from_millis(config.spot_refresh_ms as u64));
+    store.set_ignored_calls(config.ignored_calls.clone());
+    store.set_calibration(config.calibration_table(), config.calibration_learning);
+}
+
+/// Whether a report from the feed goes to the spot store. Reports from
+/// blocked spotters and of ignored calls are dropped; reports of our own call
+/// go to `self_spots` instead.
+pub fn keep_report(
+    config: &Config,
+    store: &SpotStore,
+    self_spots: &mut SelfSpots,
+    raw: &RawSpot,
+) -> bool {
+    if config.is_spotter_blocked(&raw.spotter_callsign) {
+        return false;
+    }
+    if raw.source != SpotSource::Manual && store.is_ignored(&raw.spotted_callsign) {
+        return false;
+    }
+    if is_own_call(&config.callsign, &raw.spotted_callsign) {
+        self_spots.record(raw, &config.band_plan);
+        return false;
+    }
+    true
+}
+
+/// Spots passing the SNR (fixed or adaptive) and age filters, sorted by
+/// frequency, without beacons unless they are shown
+pub fn filtered_spots(store: &SpotStore, config: &Config) -> Vec<AggregatedSpot> {
+    let max_age = Duration::from_secs(config.max_age_minutes as u64 * 60);
+    let mut spots = if config.adaptive_snr {
+        store.get_adaptive_spots(
+            &config.band_plan,
+            config.adaptive_keep_percent,
+            config.min_snr,
+            max_age,
+        )
+    } else {
+        store.get_filtered_spots(config.min_snr, max_age)
+    };
+    if !config.show_beacons {
+        spots.retain(|s| !s.beacon);
+    }
+    spots
+}
+
+/// Tune the radio unless the settings forbid it: switch to the band's
+/// antenna, set frequency and mode, then run the mode's tune steps
+///
+/// `antenna` is the antenna currently selected. A failed antenna switch
+/// doesn't stop the tune; its message is returned.
+pub fn tune(
+    controller: &mut dyn RadioController,
+    config: &Config,
+    antenna: &mut Option<u8>,
+    frequency_khz: f64,
+    mode: RadioMode,
+) -> Result<Option<String>, RadioError> {
+    if let Some(reason) = config.tune_refusal(frequency_khz) {
+        return Err(RadioError::Refused(reason));
+    }
+    let antenna_error = select_antenna(controller, config, antenna, frequency_khz).err();
+    controller.tune(frequency_khz, mode)?;
+    radio::run_tune_steps(controller, &config.radio.tune_steps_for(mode))?;
+    Ok(antenna_error)
+}
+
+/// Switch to the antenna configured for the frequency's band, if it is not
+/// already selected. `antenna` is the antenna currently selected, updated
+/// when it changes.
+pub fn select_antenna(
+    controller: &mut dyn RadioController,
+    config: &Config,
+    antenna: &mut Option<u8>,
+    frequency_khz: f64,
+) -> Result<(), String> {
+    let radio = &config.radio;
+    if radio.antenna_output == AntennaOutput::None {
+        return Ok(());
+    }
+    let Some(band) = config.band_plan.for_frequency(frequency_khz) else {
+        return Ok(());
+    };
+    let wanted = radio.antennas.get(&band.name.to_lowercase()).copied();
+    let Some(wanted) = wanted.filter(|a| Some(*a) != *antenna) else {
+        return Ok(());
+    };
+    switch_antenna(
+        radio.antenna_output,
+        &radio.antenna_target,
+        &radio.antenna_command,
+        wanted,
+        controller,
+    )
+    .map_err(|e| format!("Antenna switch: {}", e))?;
+    *antenna = Some(wanted);
+    Ok(())
+}
+
+/// What answering the HTTP API needs from a front end
+pub trait ApiHost {
+    fn config(&self) -> &Config;
+
+    /// The spots currently shown
+    fn shown_spots(&self) -> Vec<AggregatedSpot>;
+
+    /// Feed, VFD and radio state as JSON
+    fn status_json(&self) -> String;
+
+    /// Tune as the front end does when the user picks a spot
+    fn tune(&mut self, frequency_khz: f64, mode: RadioMode) -> Result<(), RadioError>;
+
+    fn vfd_display(&mut self) -> &mut VfdDisplay;
+}
+
+/// The response to an API request
+pub fn answer_api(host: &mut impl ApiHost, request: &ApiRequest) -> ApiResponse {
+    match request {
+        ApiRequest::Spots => ApiResponse::ok(spots_json(&host.shown_spots())),
+        ApiRequest::Status => ApiResponse::ok(host.status_json()),
+        ApiRequest::Tune {
+            frequency_khz,
+            mode,
+        } => {
+            if host
+                .config()
+                .band_plan
+                .for_frequency(*frequency_khz)
+                .is_none()
+            {
+                return ApiResponse::error(400, "Frequency is not in a band");
+            }
+            match host.tune(*frequency_khz, *mode) {
+                Ok(()) => ApiResponse::ok(host.status_json()),
+                Err(e) => ApiResponse::error(409, &e.to_string()),
+            }
+        }
+        ApiRequest::DisplayMessage { lines, duration } => {
+            let display = host.vfd_display();
+            display.interrupt(lines.clone(), *duration);
+            ApiResponse::ok(to_json(&LinesJson {
+                lines: &display.get_preview(),
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeHost {
+        config: Config,
+        display: VfdDisplay,
+        tuned: Vec<f64>,
+    }
+
+    impl ApiHost for FakeHost {
+        fn config(&self) -> &Config {
+            &self.config
+        }
+
+        fn shown_spots(&self) -> Vec<AggregatedSpot> {
+            Vec::new()
+        }
+
+        fn status_json(&self) -> String {
+            "{}".to_string()
+        }
+
+        fn tune(&mut self, frequency_khz: f64, _mode: RadioMode) -> Result<(), RadioError> {
+            self.tuned.push(frequency_khz);
+            Ok(())
+        }
+
+        fn vfd_display(&mut self) -> &mut VfdDisplay {
+            &mut self.display
+        }
+    }
+
+    #[test]
+    fn answers_api_requests() {
+        let mut host = FakeHost {
+            config: Config::default(),
+            display: VfdDisplay::new(),
+            tuned: Vec::new(),
+        };
+        let tune = |frequency_khz| ApiRequest::Tune {
+            frequency_khz,
+            mode: RadioMode::Cw,
+        };
+        assert_eq!(answer_api(&mut host, &tune(14025.0)).status, 200);
+        assert_eq!(answer_api(&mut host, &tune(12000.0)).status, 400);
+        assert_eq!(host.tuned, [14025.0]);
+
+        let message = ApiRequest::DisplayMessage {
+            lines: vec!["QRT".to_string()],
+            duration: Duration::from_secs(5),
+        };
+        let response = answer_api(&mut host, &message);
+        assert_eq!(response.status, 200);
+        assert!(response.body.contains("QRT"));
+        assert_eq!(answer_api(&mut host, &ApiRequest::Spots).body, "[]");
+    }
+}
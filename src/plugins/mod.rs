@@ -0,0 +1,60 @@
+//! Compile-time registry of extra display and radio backends
+//!
+//! A backend lives in its own module in this directory, behind a cargo
+//! feature, and is listed in `DISPLAY_PLUGINS` or `RADIO_PLUGINS` under the
+//! same `cfg`. Its `name` is what settings.toml stores (`display.protocol` or
+//! `radio.backend`), so nothing in `VfdDisplay` or `create_controller` needs
+//! to change. `plain_text` is a small example:
+//!
+//! ```text
+//! cargo build --release --features plugin-plain-text
+//! ```
+
+#[cfg(feature = "plugin-plain-text")]
+mod plain_text;
+
+use crate::config::RadioConfig;
+use crate::services::radio::RadioController;
+use crate::services::{DisplayGeometry, DisplayProtocol};
+
+/// A display command set provided by a plugin
+pub struct DisplayPlugin {
+    /// Name used in settings.toml; must not clash with a built-in protocol
+    pub name: &'static str,
+    /// Human-readable label for the UI
+    pub label: &'static str,
+    pub create: fn(DisplayGeometry) -> Box<dyn DisplayProtocol>,
+}
+
+/// A radio control backend provided by a plugin
+pub struct RadioPlugin {
+    /// Name used in settings.toml; must not clash with a built-in backend
+    pub name: &'static str,
+    /// Human-readable label for the UI
+    pub label: &'static str,
+    /// Called with the radio settings (host and port are free for the plugin to use)
+    pub create: fn(&RadioConfig) -> Box<dyn RadioController>,
+}
+
+/// Display plugins compiled into this build
+pub static DISPLAY_PLUGINS: &[DisplayPlugin] = &[
+    #[cfg(feature = "plugin-plain-text")]
+    DisplayPlugin {
+        name: "plain_text",
+        label: "Plain text (CRLF)",
+        create: |_| Box::new(plain_text::PlainTextProtocol),
+    },
+];
+
+/// Radio plugins compiled into this build
+pub static RADIO_PLUGINS: &[RadioPlugin] = &[];
+
+/// Look up a display plugin by its settings name
+pub fn display_plugin(name: &str) -> Option<&'static DisplayPlugin> {
+    DISPLAY_PLUGINS.iter().find(|p| p.name == name.trim())
+}
+
+/// Look up a radio plugin by its settings name
+pub fn radio_plugin(name: &str) -> Option<&'static RadioPlugin> {
+    RADIO_PLUGINS.iter().find(|p| p.name == name.trim())
+}
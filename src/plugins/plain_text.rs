@@ -0,0 +1,25 @@
+//! Example display plugin: each frame as plain lines ending in CRLF, for a
+//! serial terminal or anything else that just prints what it receives
+
+use crate::services::{CharMap, DisplayProtocol};
+
+pub struct PlainTextProtocol;
+
+impl DisplayProtocol for PlainTextProtocol {
+    fn clear(&self) -> Vec<u8> {
+        b"\r\n".to_vec()
+    }
+
+    fn set_cursor(&self, _col: usize, _row: usize) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn write_frame(&self, lines: &[String], charset: &CharMap) -> Vec<u8> {
+        let mut bytes = self.clear();
+        for line in lines {
+            bytes.extend(charset.encode(line.trim_end()));
+            bytes.extend(b"\r\n");
+        }
+        bytes
+    }
+}
@@ -0,0 +1,38 @@
+//! Minimal ADIF reading: just enough to pull fields out of log exports
+
+use crate::models::BandPlan;
+
+/// The records of an ADIF file, after its header
+pub fn records(text: &str) -> Vec<&str> {
+    // Tags are case-insensitive; ASCII upper-casing keeps byte offsets intact
+    let upper = text.to_ascii_uppercase();
+    let mut pos = upper.find("<EOH>").map(|i| i + 5).unwrap_or(0);
+    let mut records = Vec::new();
+    while let Some(len) = upper[pos..].find("<EOR>") {
+        records.push(&text[pos..pos + len]);
+        pos += len + 5;
+    }
+    records
+}
+
+/// Value of `<NAME:len>value` in one record (tag names are case-insensitive)
+pub fn field(record: &str, name: &str) -> Option<String> {
+    let upper = record.to_ascii_uppercase();
+    let start = upper.find(&format!("<{}:", name))?;
+    let spec_end = start + upper[start..].find('>')?;
+    let len: usize = upper[start + name.len() + 2..spec_end]
+        .split(':')
+        .next()?
+        .parse()
+        .ok()?;
+    let value = record.get(spec_end + 1..spec_end + 1 + len)?.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Band of a record, from its BAND or else its FREQ (MHz)
+pub fn band(record: &str, plan: &BandPlan) -> Option<String> {
+    field(record, "BAND").map(|b| b.to_lowercase()).or_else(|| {
+        let mhz: f64 = field(record, "FREQ")?.parse().ok()?;
+        Some(plan.for_frequency(mhz * 1000.0)?.name.clone())
+    })
+}
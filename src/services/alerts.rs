@@ -0,0 +1,140 @@
+//! Operator alerts with presence tracking
+//!
+//! While the operator is present, alerts are shown immediately. While away
+//! (toggled manually or after an idle timeout) they are collected into a
+//! summary shown on return.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How long the same alert key stays quiet after firing
+const ALERT_COOLDOWN: Duration = Duration::from_secs(30 * 60);
+
+/// A single alert
+#[derive(Debug, Clone)]
+pub struct Alert {
+    pub message: String,
+    pub raised: Instant,
+}
+
+/// Outcome of raising an alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertDelivery {
+    /// Show it now
+    Immediate,
+    /// Queued for the away summary
+    Deferred,
+    /// Suppressed by the cooldown
+    Suppressed,
+}
+
+/// Tracks operator presence and routes alerts accordingly
+pub struct AlertCenter {
+    away: bool,
+    manual_away: bool,
+    idle_timeout: Option<Duration>,
+    last_activity: Instant,
+    last_fired: HashMap<String, Instant>,
+    away_alerts: Vec<Alert>,
+    /// Summary ready to show after returning
+    summary: Option<Vec<Alert>>,
+}
+
+impl AlertCenter {
+    pub fn new() -> Self {
+        Self {
+            away: false,
+            manual_away: false,
+            idle_timeout: None,
+            last_activity: Instant::now(),
+            last_fired: HashMap::new(),
+            away_alerts: Vec::new(),
+            summary: None,
+        }
+    }
+
+    /// Set the idle time after which the operator counts as away (None disables)
+    pub fn set_idle_timeout(&mut self, timeout: Option<Duration>) {
+        self.idle_timeout = timeout;
+    }
+
+    /// Record user input; returns from away unless it was set manually
+    pub fn note_activity(&mut self) {
+        self.last_activity = Instant::now();
+        if self.away && !self.manual_away {
+            self.set_present();
+        }
+    }
+
+    /// Check the idle timer
+    pub fn update(&mut self) {
+        if let Some(timeout) = self.idle_timeout {
+            if !self.away && self.last_activity.elapsed() >= timeout {
+                self.away = true;
+            }
+        }
+    }
+
+    /// Manually toggle away
+    pub fn set_away(&mut self, away: bool) {
+        if away {
+            self.away = true;
+            self.manual_away = true;
+        } else {
+            self.last_activity = Instant::now();
+            self.set_present();
+        }
+    }
+
+    pub fn is_away(&self) -> bool {
+        self.away
+    }
+
+    /// Raise an alert; `key` identifies repeats for the cooldown
+    pub fn raise(&mut self, key: &str, message: String) -> AlertDelivery {
+        let now = Instant::now();
+        if let Some(fired) = self.last_fired.get(key) {
+            if now.duration_since(*fired) < ALERT_COOLDOWN {
+                return AlertDelivery::Suppressed;
+            }
+        }
+        self.last_fired.insert(key.to_string(), now);
+        self.last_fired
+            .retain(|_, fired| now.duration_since(*fired) < ALERT_COOLDOWN);
+
+        if self.away {
+            self.away_alerts.push(Alert {
+                message,
+                raised: now,
+            });
+            AlertDelivery::Deferred
+        } else {
+            AlertDelivery::Immediate
+        }
+    }
+
+    /// Number of alerts collected while away
+    pub fn pending_count(&self) -> usize {
+        self.away_alerts.len()
+    }
+
+    /// The "while you were away" summary, if one is waiting
+    pub fn summary(&self) -> Option<&[Alert]> {
+        self.summary.as_deref()
+    }
+
+    /// Dismiss the summary
+    pub fn dismiss_summary(&mut self) {
+        self.summary = None;
+    }
+
+    fn set_present(&mut self) {
+        self.away = false;
+        self.manual_away = false;
+        if !self.away_alerts.is_empty() {
+            let mut summary = self.summary.take().unwrap_or_default();
+            summary.append(&mut self.away_alerts);
+            self.summary = Some(summary);
+        }
+    }
+}
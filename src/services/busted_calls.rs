@@ -0,0 +1,89 @@
+//! Busted-call detection
+//!
+//! Skimmers regularly decode a call one character off (K1ABC as K1ABD or
+//! K1AB). Such a spot sits on the real station's frequency with only a
+//! handful of reports while the real call has many.
+
+use crate::models::{AggregatedSpot, BustedCallPolicy};
+
+/// Spots further apart than this (in kHz) are different signals
+const FREQUENCY_WINDOW_KHZ: f64 = 0.2;
+
+/// The real station needs at least this many times the busted spot's reports
+const MIN_REPORT_RATIO: u32 = 4;
+
+/// Flag or merge the busted spots in `spots` according to `policy`
+pub fn resolve(spots: &mut Vec<AggregatedSpot>, policy: BustedCallPolicy) {
+    if policy == BustedCallPolicy::Off {
+        return;
+    }
+    spots.sort_by(|a, b| a.frequency_khz.total_cmp(&b.frequency_khz));
+
+    // Index of the real spot for each busted one
+    let mut busted: Vec<(usize, usize)> = Vec::new();
+    for (i, spot) in spots.iter().enumerate() {
+        if spot.manual || spot.pinned {
+            continue;
+        }
+        let low = spot.frequency_khz - FREQUENCY_WINDOW_KHZ;
+        let high = spot.frequency_khz + FREQUENCY_WINDOW_KHZ;
+        let start = spots[..i].partition_point(|s| s.frequency_khz < low);
+        let nearby = spots
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take_while(|(_, s)| s.frequency_khz <= high);
+        let real = nearby
+            .filter(|(j, other)| {
+                *j != i
+                    && other.spot_count >= spot.spot_count.saturating_mul(MIN_REPORT_RATIO)
+                    && one_edit_apart(&spot.callsign, &other.callsign)
+            })
+            .max_by_key(|(_, other)| other.spot_count);
+        if let Some((j, _)) = real {
+            busted.push((i, j));
+        }
+    }
+
+    match policy {
+        BustedCallPolicy::Off => {}
+        BustedCallPolicy::Flag => {
+            for (i, j) in busted {
+                spots[i].busted_of = Some(spots[j].callsign.clone());
+            }
+        }
+        BustedCallPolicy::Merge => {
+            for &(i, j) in &busted {
+                let (count, snr, last) = (
+                    spots[i].spot_count,
+                    spots[i].highest_snr,
+                    spots[i].last_spotted,
+                );
+                let real = &mut spots[j];
+                real.spot_count += count;
+                real.highest_snr = real.highest_snr.max(snr);
+                real.last_spotted = real.last_spotted.max(last);
+            }
+            let mut index = 0;
+            spots.retain(|_| {
+                let keep = !busted.iter().any(|(i, _)| *i == index);
+                index += 1;
+                keep
+            });
+        }
+    }
+}
+
+/// Whether two calls differ by exactly one substituted, added or dropped character
+fn one_edit_apart(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    match long.len() - short.len() {
+        0 => short.iter().zip(long).filter(|(x, y)| x != y).count() == 1,
+        1 => {
+            let prefix = short.iter().zip(long).take_while(|(x, y)| x == y).count();
+            short[prefix..] == long[prefix + 1..]
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,74 @@
+//! Per-skimmer frequency corrections
+//!
+//! A skimmer whose receiver is a few tens of Hz off can split a signal across
+//! two 1 kHz buckets. Corrections come from a manual table or, when learning
+//! is on, from how far each skimmer's reports sit from the signal's average.
+
+use std::collections::HashMap;
+
+/// Weight of each new observation in a learned offset
+const LEARNING_RATE: f64 = 0.1;
+
+/// Reports needed before a learned offset is applied
+const MIN_SAMPLES: u32 = 20;
+
+/// Deviations larger than this (in kHz) are a different signal, not an error
+const MAX_DEVIATION_KHZ: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Learned {
+    offset_khz: f64,
+    samples: u32,
+}
+
+#[derive(Debug, Default)]
+pub struct Calibration {
+    /// Manual offsets in kHz, keyed by upper-case spotter callsign
+    manual: HashMap<String, f64>,
+    learned: HashMap<String, Learned>,
+    learning: bool,
+}
+
+impl Calibration {
+    /// Replace the manual table (offsets in Hz) and turn learning on or off
+    pub fn configure(&mut self, manual_hz: HashMap<String, f64>, learning: bool) {
+        self.manual = manual_hz
+            .into_iter()
+            .map(|(call, hz)| (call.to_uppercase(), hz / 1000.0))
+            .collect();
+        self.learning = learning;
+    }
+
+    /// Offset in kHz currently applied to `spotter`'s reports
+    pub fn offset_khz(&self, spotter: &str) -> Option<f64> {
+        if let Some(offset) = self.manual.get(&spotter.to_uppercase()) {
+            return Some(*offset);
+        }
+        if !self.learning {
+            return None;
+        }
+        self.learned
+            .get(spotter)
+            .filter(|l| l.samples >= MIN_SAMPLES)
+            .map(|l| l.offset_khz)
+    }
+
+    /// `frequency_khz` reported by `spotter` with its offset removed
+    pub fn correct(&self, spotter: &str, frequency_khz: f64) -> f64 {
+        frequency_khz - self.offset_khz(spotter).unwrap_or(0.0)
+    }
+
+    /// Learn from a report of a signal others put at `consensus_khz`
+    pub fn observe(&mut self, spotter: &str, reported_khz: f64, consensus_khz: f64) {
+        if !self.learning || self.manual.contains_key(&spotter.to_uppercase()) {
+            return;
+        }
+        let learned = self.learned.entry(spotter.to_string()).or_default();
+        let residual = reported_khz - learned.offset_khz - consensus_khz;
+        if residual.abs() > MAX_DEVIATION_KHZ {
+            return;
+        }
+        learned.offset_khz += residual * LEARNING_RATE;
+        learned.samples = learned.samples.saturating_add(1);
+    }
+}
@@ -0,0 +1,253 @@
+//! Contest mode: which spots would be new multipliers
+//!
+//! QSOs come from the "Log QSO" button or an imported ADIF/Cabrillo log.
+//! Multipliers are derived from the callsign alone (cty.dat zones and
+//! entities, WPX prefixes), so exchange-based ones like ARRL sections aren't
+//! covered.
+
+use crate::models::{BandPlan, CountryDb};
+use crate::services::adif;
+use std::collections::HashSet;
+
+/// A supported contest and its multiplier rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Contest {
+    /// CQ zones and DXCC entities, per band
+    CqWw,
+    /// DXCC entities outside W/VE, per band (as worked from W/VE)
+    ArrlDx,
+    /// WPX prefixes, once per contest
+    CqWpx,
+    /// ITU zones, per band
+    IaruHf,
+}
+
+impl Contest {
+    pub const ALL: [Contest; 4] = [
+        Contest::CqWw,
+        Contest::ArrlDx,
+        Contest::CqWpx,
+        Contest::IaruHf,
+    ];
+
+    /// Name used in settings.toml
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Contest::CqWw => "cq_ww",
+            Contest::ArrlDx => "arrl_dx",
+            Contest::CqWpx => "cq_wpx",
+            Contest::IaruHf => "iaru_hf",
+        }
+    }
+
+    /// Parse the settings.toml name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == name.trim())
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            Contest::CqWw => "CQ WW",
+            Contest::ArrlDx => "ARRL DX",
+            Contest::CqWpx => "CQ WPX",
+            Contest::IaruHf => "IARU HF",
+        }
+    }
+
+    /// Whether multipliers count again on each band
+    fn per_band(self) -> bool {
+        self != Contest::CqWpx
+    }
+
+    /// Multipliers a QSO with `callsign` would count for
+    fn multipliers(self, callsign: &str, db: &CountryDb) -> Vec<Multiplier> {
+        let entity = db.lookup(callsign);
+        match self {
+            Contest::CqWw => entity
+                .map(|e| {
+                    vec![
+                        Multiplier::CqZone(e.cq_zone),
+                        Multiplier::Country(e.name.clone()),
+                    ]
+                })
+                .unwrap_or_default(),
+            Contest::ArrlDx => entity
+                .filter(|e| e.primary_prefix != "K" && e.primary_prefix != "VE")
+                .map(|e| vec![Multiplier::Country(e.name.clone())])
+                .unwrap_or_default(),
+            Contest::CqWpx => vec![Multiplier::Prefix(wpx_prefix(callsign))],
+            Contest::IaruHf => entity
+                .map(|e| vec![Multiplier::ItuZone(e.itu_zone)])
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Something that counts as a multiplier
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Multiplier {
+    CqZone(u8),
+    ItuZone(u8),
+    Country(String),
+    Prefix(String),
+}
+
+impl Multiplier {
+    pub fn label(&self) -> String {
+        match self {
+            Multiplier::CqZone(zone) => format!("CQ zone {}", zone),
+            Multiplier::ItuZone(zone) => format!("ITU zone {}", zone),
+            Multiplier::Country(name) => name.clone(),
+            Multiplier::Prefix(prefix) => format!("Prefix {}", prefix),
+        }
+    }
+}
+
+/// QSOs made this session and the multipliers they count for
+#[derive(Debug)]
+pub struct ContestLog {
+    contest: Contest,
+    /// (callsign, band) of each QSO
+    qsos: Vec<(String, String)>,
+    /// Worked multipliers with their band (None when not per band)
+    worked: HashSet<(Option<String>, Multiplier)>,
+}
+
+impl ContestLog {
+    pub fn new(contest: Contest) -> Self {
+        Self {
+            contest,
+            qsos: Vec::new(),
+            worked: HashSet::new(),
+        }
+    }
+
+    pub fn contest(&self) -> Contest {
+        self.contest
+    }
+
+    /// Switch contest, recounting the multipliers of the QSOs so far
+    pub fn set_contest(&mut self, contest: Contest, db: &CountryDb) {
+        self.contest = contest;
+        self.recount(db);
+    }
+
+    /// Recount the worked multipliers (e.g. after cty.dat was updated)
+    pub fn recount(&mut self, db: &CountryDb) {
+        self.worked.clear();
+        for (call, band) in std::mem::take(&mut self.qsos) {
+            self.log_qso(&call, &band, db);
+        }
+    }
+
+    fn mult_band(&self, band: &str) -> Option<String> {
+        self.contest.per_band().then(|| band.to_string())
+    }
+
+    pub fn log_qso(&mut self, callsign: &str, band: &str, db: &CountryDb) {
+        let callsign = callsign.trim().to_uppercase();
+        for mult in self.contest.multipliers(&callsign, db) {
+            self.worked.insert((self.mult_band(band), mult));
+        }
+        self.qsos.push((callsign, band.to_string()));
+    }
+
+    /// Multipliers `callsign` on `band` would add
+    pub fn new_multipliers(&self, callsign: &str, band: &str, db: &CountryDb) -> Vec<Multiplier> {
+        let mult_band = self.mult_band(band);
+        self.contest
+            .multipliers(callsign, db)
+            .into_iter()
+            .filter(|mult| !self.worked.contains(&(mult_band.clone(), mult.clone())))
+            .collect()
+    }
+
+    /// Whether `callsign` was already worked on `band`
+    pub fn is_dupe(&self, callsign: &str, band: &str) -> bool {
+        self.qsos
+            .iter()
+            .any(|(call, b)| b == band && call.eq_ignore_ascii_case(callsign))
+    }
+
+    pub fn qso_count(&self) -> usize {
+        self.qsos.len()
+    }
+
+    pub fn multiplier_count(&self) -> usize {
+        self.worked.len()
+    }
+
+    /// Add the QSOs of an ADIF or Cabrillo log; returns how many were read
+    pub fn import(&mut self, text: &str, plan: &BandPlan, db: &CountryDb) -> Result<usize, String> {
+        let qsos = if text.contains("QSO:") {
+            parse_cabrillo(text, plan)
+        } else {
+            parse_adif(text, plan)
+        };
+        if qsos.is_empty() {
+            return Err("No QSOs found (expected an ADIF or Cabrillo log)".to_string());
+        }
+        let count = qsos.len();
+        for (call, band) in qsos {
+            self.log_qso(&call, &band, db);
+        }
+        Ok(count)
+    }
+}
+
+/// WPX prefix: the call's prefix through its last digit (K1ABC -> K1, 4U1UN -> 4U1)
+fn wpx_prefix(callsign: &str) -> String {
+    let call = callsign.trim().to_uppercase();
+    let mut parts = call.split('/');
+    let base = parts.next().unwrap_or_default();
+    let prefix = match base.rfind(|c: char| c.is_ascii_digit()) {
+        Some(i) => base[..=i].to_string(),
+        None => format!("{}0", base),
+    };
+    // A portable digit replaces the call area: K1ABC/4 -> K4
+    match parts.next() {
+        Some(area) if area.len() == 1 && area.chars().all(|c| c.is_ascii_digit()) => {
+            format!(
+                "{}{}",
+                prefix.trim_end_matches(|c: char| c.is_ascii_digit()),
+                area
+            )
+        }
+        _ => prefix,
+    }
+}
+
+/// (callsign, band) of each ADIF record with a CALL and a BAND or FREQ
+fn parse_adif(text: &str, plan: &BandPlan) -> Vec<(String, String)> {
+    adif::records(text)
+        .into_iter()
+        .filter_map(|record| {
+            let call = adif::field(record, "CALL")?;
+            let band = adif::band(record, plan)?;
+            Some((call.to_uppercase(), band))
+        })
+        .collect()
+}
+
+/// (callsign, band) of each Cabrillo `QSO:` line; the worked call is the first
+/// callsign-shaped token after our own
+fn parse_cabrillo(text: &str, plan: &BandPlan) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| {
+            let tokens: Vec<&str> = line.strip_prefix("QSO:")?.split_whitespace().collect();
+            let khz: f64 = tokens.first()?.parse().ok()?;
+            let band = plan.for_frequency(khz)?.name.clone();
+            let call = tokens.iter().skip(5).find(|t| looks_like_call(t))?;
+            Some((call.to_uppercase(), band))
+        })
+        .collect()
+}
+
+/// A digit after the first character followed by a letter (K1ABC, 4U1UN; not 599 or 5NN)
+fn looks_like_call(token: &str) -> bool {
+    let bytes = token.as_bytes();
+    bytes.len() >= 3
+        && (1..bytes.len() - 1)
+            .any(|i| bytes[i].is_ascii_digit() && bytes[i + 1].is_ascii_alphabetic())
+}
@@ -0,0 +1,244 @@
+//! Background downloader for reference data files (cty.dat, LoTW users, most wanted)
+
+use crate::models::CountryDb;
+use regex::Regex;
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+
+/// How often the updater wakes up to check whether files are stale
+const CHECK_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// Largest file we are willing to download
+const MAX_DOWNLOAD_BYTES: u64 = 64 * 1024 * 1024;
+
+/// A reference data file kept in the data directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFileKind {
+    CtyDat,
+    LotwUsers,
+    MostWanted,
+}
+
+impl DataFileKind {
+    pub const ALL: [DataFileKind; 3] = [
+        DataFileKind::CtyDat,
+        DataFileKind::LotwUsers,
+        DataFileKind::MostWanted,
+    ];
+
+    /// File name inside the data directory
+    pub fn file_name(self) -> &'static str {
+        match self {
+            DataFileKind::CtyDat => "cty.dat",
+            DataFileKind::LotwUsers => "lotw-user-activity.csv",
+            DataFileKind::MostWanted => "mostwanted.json",
+        }
+    }
+
+    /// Download URL
+    pub fn url(self) -> &'static str {
+        match self {
+            DataFileKind::CtyDat => "https://www.country-files.com/cty/cty.dat",
+            DataFileKind::LotwUsers => "https://lotw.arrl.org/lotw-user-activity.csv",
+            DataFileKind::MostWanted => "https://clublog.org/mostwanted.php?api=1",
+        }
+    }
+
+    /// Human-readable name for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            DataFileKind::CtyDat => "Country file (cty.dat)",
+            DataFileKind::LotwUsers => "LoTW users",
+            DataFileKind::MostWanted => "Club Log most wanted",
+        }
+    }
+
+    /// Full path of this file in the data directory
+    pub fn path(self, data_dir: &Path) -> PathBuf {
+        data_dir.join(self.file_name())
+    }
+
+    /// Modification time of the local copy, if any
+    pub fn last_updated(self, data_dir: &Path) -> Option<SystemTime> {
+        std::fs::metadata(self.path(data_dir))
+            .and_then(|m| m.modified())
+            .ok()
+    }
+}
+
+/// Reference data loaded from the data directory
+#[derive(Default)]
+pub struct DataFiles {
+    pub country_db: CountryDb,
+    pub lotw_users: HashSet<String>,
+    /// Club Log most-wanted prefixes, most wanted first
+    pub most_wanted: Vec<String>,
+}
+
+impl DataFiles {
+    /// Load every data file that exists in the data directory
+    pub fn load(data_dir: &Path) -> Self {
+        let mut files = Self::default();
+        for kind in DataFileKind::ALL {
+            files.reload(kind, data_dir);
+        }
+        files
+    }
+
+    /// Re-read a single file after it has been updated
+    pub fn reload(&mut self, kind: DataFileKind, data_dir: &Path) {
+        let Ok(contents) = std::fs::read_to_string(kind.path(data_dir)) else {
+            return;
+        };
+        match kind {
+            DataFileKind::CtyDat => self.country_db = CountryDb::parse(&contents),
+            DataFileKind::LotwUsers => self.lotw_users = parse_lotw_users(&contents),
+            DataFileKind::MostWanted => self.most_wanted = parse_most_wanted(&contents),
+        }
+    }
+
+    /// Number of entries loaded from a file, for display
+    pub fn entry_count(&self, kind: DataFileKind) -> usize {
+        match kind {
+            DataFileKind::CtyDat => self.country_db.len(),
+            DataFileKind::LotwUsers => self.lotw_users.len(),
+            DataFileKind::MostWanted => self.most_wanted.len(),
+        }
+    }
+}
+
+/// Parse the LoTW user activity CSV ("CALL,date,time" per line)
+fn parse_lotw_users(contents: &str) -> HashSet<String> {
+    contents
+        .lines()
+        .filter_map(|line| line.split(',').next())
+        .map(|call| call.trim().to_uppercase())
+        .filter(|call| !call.is_empty())
+        .collect()
+}
+
+/// Parse the Club Log most-wanted JSON ({"1":"P5","2":"3Y/B",...})
+fn parse_most_wanted(contents: &str) -> Vec<String> {
+    let entry = Regex::new(r#""(\d+)"\s*:\s*"([^"]+)""#).expect("Invalid regex");
+    let mut ranked: Vec<(u32, String)> = entry
+        .captures_iter(contents)
+        .filter_map(|caps| Some((caps[1].parse().ok()?, caps[2].to_uppercase())))
+        .collect();
+    ranked.sort();
+    ranked.into_iter().map(|(_, prefix)| prefix).collect()
+}
+
+/// Messages sent from the updater to the main app
+#[derive(Debug, Clone)]
+pub enum DataFileMessage {
+    Updated(DataFileKind),
+    Failed(DataFileKind, String),
+}
+
+/// Commands sent to the updater
+#[derive(Debug)]
+enum DataFileCommand {
+    UpdateNow,
+    SetInterval(Duration),
+}
+
+/// Handle to the background data file updater thread
+pub struct DataFileUpdater {
+    cmd_tx: mpsc::Sender<DataFileCommand>,
+    msg_rx: mpsc::Receiver<DataFileMessage>,
+}
+
+impl DataFileUpdater {
+    /// Spawn the updater; `interval` of zero disables automatic updates
+    pub fn new(data_dir: PathBuf, interval: Duration) -> Self {
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let (msg_tx, msg_rx) = mpsc::channel();
+
+        std::thread::spawn(move || updater_task(data_dir, interval, cmd_rx, msg_tx));
+
+        Self { cmd_tx, msg_rx }
+    }
+
+    /// Download every file now, regardless of age
+    pub fn update_now(&self) {
+        let _ = self.cmd_tx.send(DataFileCommand::UpdateNow);
+    }
+
+    /// Change how old a file may get before it is refreshed
+    pub fn set_interval(&self, interval: Duration) {
+        let _ = self.cmd_tx.send(DataFileCommand::SetInterval(interval));
+    }
+
+    /// Try to receive a message (non-blocking)
+    pub fn try_recv(&self) -> Option<DataFileMessage> {
+        self.msg_rx.try_recv().ok()
+    }
+}
+
+fn updater_task(
+    data_dir: PathBuf,
+    mut interval: Duration,
+    cmd_rx: mpsc::Receiver<DataFileCommand>,
+    msg_tx: mpsc::Sender<DataFileMessage>,
+) {
+    let mut force = false;
+
+    loop {
+        for kind in DataFileKind::ALL {
+            let stale = match kind.last_updated(&data_dir) {
+                Some(modified) => {
+                    !interval.is_zero() && modified.elapsed().unwrap_or_default() >= interval
+                }
+                None => !interval.is_zero(),
+            };
+            if !force && !stale {
+                continue;
+            }
+
+            let msg = match download(kind, &data_dir) {
+                Ok(()) => DataFileMessage::Updated(kind),
+                Err(e) => DataFileMessage::Failed(kind, e),
+            };
+            if msg_tx.send(msg).is_err() {
+                return;
+            }
+        }
+        force = false;
+
+        match cmd_rx.recv_timeout(CHECK_INTERVAL) {
+            Ok(DataFileCommand::UpdateNow) => force = true,
+            Ok(DataFileCommand::SetInterval(new_interval)) => interval = new_interval,
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+/// Download a file to a temporary name and rename it into place
+fn download(kind: DataFileKind, data_dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(data_dir)
+        .map_err(|e| format!("Failed to create data directory: {}", e))?;
+
+    let response = ureq::get(kind.url())
+        .timeout(Duration::from_secs(60))
+        .call()
+        .map_err(|e| format!("Download failed: {}", e))?;
+
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_DOWNLOAD_BYTES)
+        .read_to_end(&mut body)
+        .map_err(|e| format!("Download failed: {}", e))?;
+    if body.is_empty() {
+        return Err("Downloaded file is empty".to_string());
+    }
+
+    let path = kind.path(data_dir);
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, &body).map_err(|e| format!("Failed to write file: {}", e))?;
+    std::fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to replace file: {}", e))
+}
@@ -0,0 +1,93 @@
+//! Writing spot lists to CSV or JSON for analysis in other tools
+
+use crate::error::{Error, Result};
+use crate::models::UtcTime;
+use crate::models::{AggregatedSpot, SpotSource};
+use crate::services::json::spot_json;
+use std::path::Path;
+
+/// File format for an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 2] = [ExportFormat::Csv, ExportFormat::Json];
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Json => "JSON",
+        }
+    }
+
+    /// File name extension, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// Column names of the CSV export
+const CSV_HEADER: &str =
+    "callsign,frequency_khz,mode,mode_source,snr,wpm,spots,first_spotted,last_spotted,manual";
+
+/// Write `spots` to `path`, creating its directory if needed
+pub fn write_spots(path: &Path, spots: &[AggregatedSpot], format: ExportFormat) -> Result<()> {
+    let text = match format {
+        ExportFormat::Csv => to_csv(spots),
+        ExportFormat::Json => {
+            let spots: Vec<String> = spots.iter().map(spot_json).collect();
+            format!("[\n{}\n]\n", spots.join(",\n"))
+        }
+    };
+
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| Error::Export(format!("Failed to create {}: {}", dir.display(), e)))?;
+    }
+    std::fs::write(path, text)
+        .map_err(|e| Error::Export(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+fn to_csv(spots: &[AggregatedSpot]) -> String {
+    let mut out = format!("{}\n", CSV_HEADER);
+    for spot in spots {
+        out.push_str(&format!(
+            "{},{:.1},{},{},{},{},{},{},{},{}\n",
+            csv_field(&spot.callsign),
+            spot.frequency_khz,
+            csv_field(&spot.mode),
+            source_name(spot.mode_source),
+            spot.highest_snr,
+            spot.average_speed.round() as i32,
+            spot.spot_count,
+            UtcTime::from_system(spot.first_spotted_at).to_rfc3339(),
+            UtcTime::from_system(spot.last_spotted_at).to_rfc3339(),
+            spot.manual
+        ));
+    }
+    out
+}
+
+/// Quote a field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn source_name(source: SpotSource) -> &'static str {
+    match source {
+        SpotSource::Skimmer => "skimmer",
+        SpotSource::Human => "human",
+        SpotSource::Manual => "manual",
+    }
+}
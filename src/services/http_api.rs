@@ -0,0 +1,460 @@
+//! Embedded HTTP control API for scripts and other shack software
+//!
+//! Endpoints (JSON in and out):
+//!
+//! - `GET /spots` — the spots currently shown
+//! - `GET /status` — feed, VFD and radio state
+//! - `POST /tune` — `{"frequency_khz": 14025.0, "mode": "cw"}` (mode optional)
+//! - `POST /display/message` — `{"text": "line 1\nline 2", "seconds": 10}`
+//! - `GET /metrics` — counters and gauges in the Prometheus text format
+//! - `GET /stream` — WebSocket; each newly heard spot is pushed as a JSON text
+//!   message, in the same format as `/spots` entries
+//!
+//! There is no authentication, so the API only listens on loopback addresses;
+//! anything on the machine can use it, nothing else can.
+//!
+//! Connections are handled on worker threads (at most `MAX_CONNECTIONS` at a
+//! time); each request is passed to the app as an [`ApiCall`] and the worker
+//! waits for the app's reply.
+
+use crate::error::{Error, Result};
+use crate::models::AggregatedSpot;
+use crate::services::json::spot_json;
+use crate::services::metrics::METRICS;
+use crate::services::radio::RadioMode;
+use crate::services::websocket::{self, Subscribers};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::Duration;
+
+/// How long a connection waits for the app to answer
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often the listener checks for connections and for shutdown
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+
+/// Largest request body accepted
+const MAX_BODY: usize = 16 * 1024;
+
+/// Longest request or header line accepted, in bytes
+const MAX_LINE: usize = 8 * 1024;
+
+/// Most header lines accepted in one request
+const MAX_HEADERS: usize = 64;
+
+/// Connections served at once, WebSocket streams included; more are refused
+const MAX_CONNECTIONS: usize = 32;
+
+/// Default time a posted message stays on the VFD
+const DEFAULT_MESSAGE_SECONDS: u64 = 10;
+
+/// A request the app has to answer
+#[derive(Debug, Clone, PartialEq)]
+pub enum ApiRequest {
+    Spots,
+    Status,
+    Tune {
+        frequency_khz: f64,
+        mode: RadioMode,
+    },
+    DisplayMessage {
+        lines: Vec<String>,
+        duration: Duration,
+    },
+}
+
+/// The app's answer: an HTTP status and a body, JSON unless noted
+#[derive(Debug, Clone)]
+pub struct ApiResponse {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl ApiResponse {
+    pub fn ok(body: String) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body,
+        }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+/// A request waiting for the app's reply
+pub struct ApiCall {
+    pub request: ApiRequest,
+    reply_tx: mpsc::Sender<ApiResponse>,
+}
+
+impl ApiCall {
+    pub fn respond(self, response: ApiResponse) {
+        let _ = self.reply_tx.send(response);
+    }
+}
+
+/// Handle to the listening server
+pub struct HttpApi {
+    bind_address: String,
+    call_rx: mpsc::Receiver<ApiCall>,
+    /// Clients of the WebSocket spot stream
+    subscribers: Subscribers,
+    stop: Arc<AtomicBool>,
+}
+
+impl HttpApi {
+    /// Start listening on `bind_address` (e.g. "127.0.0.1:8073")
+    pub fn start(bind_address: &str) -> Result<Self> {
+        let listener = TcpListener::bind(bind_address)
+            .map_err(|e| Error::Api(format!("Failed to listen on {}: {}", bind_address, e)))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| Error::Api(e.to_string()))?;
+        let (call_tx, call_rx) = mpsc::channel();
+        let subscribers: Subscribers = Arc::new(Mutex::new(Vec::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let listener_subscribers = subscribers.clone();
+        let listener_stop = stop.clone();
+        let active = Arc::new(AtomicUsize::new(0));
+        std::thread::spawn(move || {
+            // Polled so the address is released soon after the handle is dropped
+            while !listener_stop.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        let _ = stream.set_nonblocking(false);
+                        let Some(slot) = ConnectionSlot::take(&active) else {
+                            let _ = stream.set_write_timeout(Some(ACCEPT_POLL));
+                            write_response(
+                                stream,
+                                &ApiResponse::error(503, "Too many connections"),
+                            );
+                            continue;
+                        };
+                        let call_tx = call_tx.clone();
+                        let subscribers = listener_subscribers.clone();
+                        let stop = listener_stop.clone();
+                        std::thread::spawn(move || {
+                            handle_connection(stream, call_tx, &subscribers, &stop);
+                            drop(slot);
+                        });
+                    }
+                    Err(_) => std::thread::sleep(ACCEPT_POLL),
+                }
+            }
+        });
+
+        Ok(Self {
+            bind_address: bind_address.to_string(),
+            call_rx,
+            subscribers,
+            stop,
+        })
+    }
+
+    /// Address the server was started on
+    pub fn bind_address(&self) -> &str {
+        &self.bind_address
+    }
+
+    /// Next request waiting for an answer (non-blocking)
+    pub fn try_recv(&self) -> Option<ApiCall> {
+        self.call_rx.try_recv().ok()
+    }
+
+    /// Push a newly heard spot to the stream's clients
+    pub fn publish_spot(&self, spot: &AggregatedSpot) {
+        websocket::broadcast(&self.subscribers, &spot_json(spot));
+    }
+}
+
+impl Drop for HttpApi {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// One of the `MAX_CONNECTIONS` connections, given back when dropped
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl ConnectionSlot {
+    fn take(active: &Arc<AtomicUsize>) -> Option<Self> {
+        active
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| {
+                (n < MAX_CONNECTIONS).then_some(n + 1)
+            })
+            .ok()
+            .map(|_| Self(active.clone()))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    call_tx: mpsc::Sender<ApiCall>,
+    subscribers: &Subscribers,
+    stop: &AtomicBool,
+) {
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let response = match read_request(&stream) {
+        Ok(request) if request.path == "/stream" => {
+            match request.headers.get("sec-websocket-key") {
+                Some(key) if request.method == "GET" => {
+                    let _ = stream.set_read_timeout(None);
+                    websocket::serve(stream, key, subscribers, stop);
+                    return;
+                }
+                Some(_) => ApiResponse::error(405, "Method not allowed"),
+                None => ApiResponse::error(400, "Expected a WebSocket upgrade"),
+            }
+        }
+        Ok(request) if request.path == "/metrics" && request.method == "GET" => ApiResponse {
+            status: 200,
+            content_type: "text/plain; version=0.0.4",
+            body: METRICS.render(),
+        },
+        Ok(request) => match route(&request.method, &request.path, &request.body) {
+            Ok(request) => ask_app(request, &call_tx),
+            Err(response) => response,
+        },
+        Err(response) => response,
+    };
+    write_response(stream, &response);
+}
+
+/// Pass a request to the app and wait for its answer
+fn ask_app(request: ApiRequest, call_tx: &mpsc::Sender<ApiCall>) -> ApiResponse {
+    let (reply_tx, reply_rx) = mpsc::channel();
+    if call_tx.send(ApiCall { request, reply_tx }).is_err() {
+        return ApiResponse::error(503, "Shutting down");
+    }
+    reply_rx
+        .recv_timeout(REPLY_TIMEOUT)
+        .unwrap_or_else(|_| ApiResponse::error(503, "No reply from the application"))
+}
+
+/// An HTTP request as read off the wire
+struct HttpRequest {
+    method: String,
+    path: String,
+    /// Header values by lowercase name
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+/// Read the request line, headers and body, or the error response to send
+fn read_request(stream: &TcpStream) -> std::result::Result<HttpRequest, ApiResponse> {
+    let mut reader = BufReader::new(stream);
+    let request_line = read_line(&mut reader)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts
+        .next()
+        .ok_or_else(|| ApiResponse::error(400, "Empty request"))?
+        .to_string();
+    let path = parts
+        .next()
+        .ok_or_else(|| ApiResponse::error(400, "Missing path"))?;
+    let path = path
+        .split('?')
+        .next()
+        .unwrap_or(path)
+        .trim_end_matches('/')
+        .to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let header = read_line(&mut reader)?;
+        if header.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err(ApiResponse::error(431, "Too many headers"));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let content_length = match headers.get("content-length") {
+        Some(value) => value
+            .parse()
+            .map_err(|_| ApiResponse::error(400, "Bad Content-Length"))?,
+        None => 0,
+    };
+    if content_length > MAX_BODY {
+        return Err(ApiResponse::error(413, "Request body too large"));
+    }
+
+    let mut body = vec![0u8; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|e| ApiResponse::error(400, &e.to_string()))?;
+    let body = String::from_utf8(body).map_err(|_| ApiResponse::error(400, "Body is not UTF-8"))?;
+    Ok(HttpRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+/// One line without its line ending, refusing lines over `MAX_LINE` bytes
+fn read_line(reader: &mut impl BufRead) -> std::result::Result<String, ApiResponse> {
+    let mut line = Vec::new();
+    reader
+        .take(MAX_LINE as u64 + 1)
+        .read_until(b'\n', &mut line)
+        .map_err(|e| ApiResponse::error(400, &e.to_string()))?;
+    if line.len() > MAX_LINE {
+        return Err(ApiResponse::error(431, "Request line too long"));
+    }
+    let line =
+        String::from_utf8(line).map_err(|_| ApiResponse::error(400, "Request is not UTF-8"))?;
+    Ok(line.trim_end().to_string())
+}
+
+/// Map a method and path to a request, or an error response
+fn route(method: &str, path: &str, body: &str) -> std::result::Result<ApiRequest, ApiResponse> {
+    match (method, path) {
+        ("GET", "/spots") => Ok(ApiRequest::Spots),
+        ("GET", "/status") => Ok(ApiRequest::Status),
+        ("POST", "/tune") => {
+            let TuneBody {
+                frequency_khz,
+                mode,
+            } = parse_body(body)?;
+            if !frequency_khz.is_finite() || frequency_khz <= 0.0 {
+                return Err(ApiResponse::error(400, "Invalid frequency"));
+            }
+            let mode = match mode {
+                Some(name) => RadioMode::from_name(&name)
+                    .ok_or_else(|| ApiResponse::error(400, "Unknown mode"))?,
+                None => RadioMode::Cw,
+            };
+            Ok(ApiRequest::Tune {
+                frequency_khz,
+                mode,
+            })
+        }
+        ("POST", "/display/message") => {
+            let MessageBody { text, seconds } = parse_body(body)?;
+            let seconds = seconds.unwrap_or(DEFAULT_MESSAGE_SECONDS);
+            Ok(ApiRequest::DisplayMessage {
+                lines: text.lines().map(str::to_string).collect(),
+                duration: Duration::from_secs(seconds.clamp(1, 3600)),
+            })
+        }
+        (_, "/spots" | "/status" | "/tune" | "/display/message" | "/metrics") => {
+            Err(ApiResponse::error(405, "Method not allowed"))
+        }
+        _ => Err(ApiResponse::error(404, "Not found")),
+    }
+}
+
+/// Body of `POST /tune`
+#[derive(Deserialize)]
+struct TuneBody {
+    frequency_khz: f64,
+    mode: Option<String>,
+}
+
+/// Body of `POST /display/message`
+#[derive(Deserialize)]
+struct MessageBody {
+    text: String,
+    seconds: Option<u64>,
+}
+
+/// A JSON request body, or a 400 response saying what is wrong with it
+fn parse_body<T: DeserializeOwned>(body: &str) -> std::result::Result<T, ApiResponse> {
+    serde_json::from_str(body)
+        .map_err(|e| ApiResponse::error(400, &format!("Invalid request body: {}", e)))
+}
+
+/// Reason phrase for the statuses the API sends
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        // The reason phrase may be empty; clients go by the code
+        _ => "",
+    }
+}
+
+fn write_response(mut stream: TcpStream, response: &ApiResponse) {
+    let _ = write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason_phrase(response.status),
+        response.content_type,
+        response.body.len(),
+        response.body
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_request_bodies() {
+        let request = route(
+            "POST",
+            "/display/message",
+            r#"{"text": "CQ\bTEST 📡\nQRV", "seconds": 5}"#,
+        );
+        assert_eq!(
+            request.unwrap(),
+            ApiRequest::DisplayMessage {
+                lines: vec!["CQ\u{8}TEST \u{1f4e1}".to_string(), "QRV".to_string()],
+                duration: Duration::from_secs(5),
+            }
+        );
+        let tune = route("POST", "/tune", r#"{"frequency_khz": 14025.5}"#);
+        assert_eq!(
+            tune.unwrap(),
+            ApiRequest::Tune {
+                frequency_khz: 14025.5,
+                mode: RadioMode::Cw,
+            }
+        );
+        assert!(route("POST", "/tune", r#"{"frequency_khz": "fast"}"#).is_err());
+        assert!(route("POST", "/tune", r#"{"frequency_khz": -7025}"#).is_err());
+    }
+
+    #[test]
+    fn refuses_oversized_header_lines() {
+        let mut reader =
+            std::io::Cursor::new(format!("GET /{} HTTP/1.1\r\n", "a".repeat(MAX_LINE)));
+        assert_eq!(read_line(&mut reader).unwrap_err().status, 431);
+        let mut reader = std::io::Cursor::new("Host: localhost\r\n\r\n");
+        assert_eq!(read_line(&mut reader).unwrap(), "Host: localhost");
+        assert_eq!(read_line(&mut reader).unwrap(), "");
+    }
+}
@@ -0,0 +1,61 @@
+//! JSON shapes shared by the MQTT payloads, the HTTP API and the spot export
+
+use crate::models::AggregatedSpot;
+use crate::models::UtcTime;
+use serde::Serialize;
+
+/// A spot as published and exported
+#[derive(Debug, Serialize)]
+pub struct SpotJson<'a> {
+    pub callsign: &'a str,
+    /// Rounded to 100 Hz
+    pub frequency_khz: f64,
+    pub mode: &'a str,
+    pub snr: i32,
+    pub wpm: i32,
+    pub spots: u32,
+    pub age_seconds: u64,
+    /// RFC 3339, UTC
+    pub first_spotted: String,
+    pub last_spotted: String,
+    pub manual: bool,
+}
+
+impl<'a> From<&'a AggregatedSpot> for SpotJson<'a> {
+    fn from(spot: &'a AggregatedSpot) -> Self {
+        Self {
+            callsign: &spot.callsign,
+            frequency_khz: (spot.frequency_khz * 10.0).round() / 10.0,
+            mode: &spot.mode,
+            snr: spot.highest_snr,
+            wpm: spot.average_speed.round() as i32,
+            spots: spot.spot_count,
+            age_seconds: spot.age_seconds(),
+            first_spotted: UtcTime::from_system(spot.first_spotted_at).to_rfc3339(),
+            last_spotted: UtcTime::from_system(spot.last_spotted_at).to_rfc3339(),
+            manual: spot.manual,
+        }
+    }
+}
+
+/// The lines on the VFD, as published and returned by the API
+#[derive(Debug, Serialize)]
+pub struct LinesJson<'a> {
+    pub lines: &'a [String],
+}
+
+/// A value as compact JSON
+pub fn to_json(value: &impl Serialize) -> String {
+    // Only maps with non-string keys fail to serialize, and none are used
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// A spot as a JSON object
+pub fn spot_json(spot: &AggregatedSpot) -> String {
+    to_json(&SpotJson::from(spot))
+}
+
+/// Spots as a JSON array
+pub fn spots_json(spots: &[AggregatedSpot]) -> String {
+    to_json(&spots.iter().map(SpotJson::from).collect::<Vec<_>>())
+}
@@ -0,0 +1,99 @@
+//! Parsing of manually entered spots (skeds arranged by phone or chat)
+//!
+//! Accepts one spot per line, either a pasted cluster line
+//! ("DX de W1AW: 14025.0 K1ABC ...") or "frequency, callsign[, mode]" in either
+//! order, separated by commas or whitespace. Frequencies below 1000 are
+//! taken as MHz.
+
+use crate::models::{RawSpot, SpotSource};
+use regex::Regex;
+
+/// Modes recognized in comments and CSV fields
+const MODES: &[&str] = &[
+    "CW", "SSB", "USB", "LSB", "RTTY", "FT8", "FT4", "PSK31", "PSK63", "AM", "FM",
+];
+
+/// Parse pasted text; returns the spots and the lines that could not be parsed
+pub fn parse_manual_spots(text: &str, spotter: &str) -> (Vec<RawSpot>, Vec<String>) {
+    let cluster =
+        Regex::new(r"(?i)^DX de (\S+?):?\s+(\d+\.?\d*)\s+(\S+)(.*)$").expect("Invalid regex");
+    let rbn_details = Regex::new(r"(\w+)\s+(-?\d+)\s+dB\s+(\d+)\s+WPM").expect("Invalid regex");
+
+    let mut spots = Vec::new();
+    let mut rejected = Vec::new();
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let spot = match cluster.captures(line) {
+            Some(caps) => {
+                let rest = &caps[4];
+                let (mode, snr, wpm) = match rbn_details.captures(rest) {
+                    Some(d) => (
+                        d[1].to_uppercase(),
+                        d[2].parse().unwrap_or(0),
+                        d[3].parse().unwrap_or(0),
+                    ),
+                    None => (
+                        find_mode(rest.split_whitespace())
+                            .unwrap_or("CW")
+                            .to_string(),
+                        0,
+                        0,
+                    ),
+                };
+                spot(&caps[3], &caps[2], mode, snr, wpm, spotter)
+            }
+            None => parse_fields(line, spotter),
+        };
+        match spot {
+            Some(spot) => spots.push(spot),
+            None => rejected.push(line.to_string()),
+        }
+    }
+    (spots, rejected)
+}
+
+/// Parse "frequency, callsign[, mode]" in either order
+fn parse_fields(line: &str, spotter: &str) -> Option<RawSpot> {
+    let fields: Vec<&str> = line
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|f| !f.is_empty())
+        .collect();
+    let frequency = fields.iter().find(|f| f.parse::<f64>().is_ok())?;
+    let callsign = fields
+        .iter()
+        .find(|f| f.parse::<f64>().is_err() && find_mode([**f]).is_none())?;
+    let mode = find_mode(fields.iter().copied()).unwrap_or("CW");
+    spot(callsign, frequency, mode.to_string(), 0, 0, spotter)
+}
+
+fn find_mode<'a>(words: impl IntoIterator<Item = &'a str>) -> Option<&'static str> {
+    words
+        .into_iter()
+        .find_map(|w| MODES.iter().find(|m| m.eq_ignore_ascii_case(w)).copied())
+}
+
+fn spot(
+    callsign: &str,
+    frequency: &str,
+    mode: String,
+    snr: i32,
+    wpm: i32,
+    spotter: &str,
+) -> Option<RawSpot> {
+    let mut frequency_khz: f64 = frequency.parse().ok()?;
+    if frequency_khz < 1000.0 {
+        frequency_khz *= 1000.0;
+    }
+    let callsign = callsign.trim().to_uppercase();
+    if frequency_khz <= 0.0 || !callsign.chars().any(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some(RawSpot::new(
+        spotter.to_string(),
+        callsign,
+        frequency_khz,
+        snr,
+        wpm,
+        mode,
+        SpotSource::Manual,
+    ))
+}
@@ -0,0 +1,121 @@
+//! Process-wide counters and gauges, exposed in the Prometheus text format
+//! on the control API's `/metrics`
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// The metrics shared by every part of the app
+pub static METRICS: Metrics = Metrics::new();
+
+pub struct Metrics {
+    spots_received: AtomicU64,
+    parse_failures: AtomicU64,
+    feed_connects: AtomicU64,
+    active_spots: AtomicU64,
+    vfd_write_errors: AtomicU64,
+    radio_commands: AtomicU64,
+    radio_command_micros: AtomicU64,
+}
+
+impl Metrics {
+    const fn new() -> Self {
+        Self {
+            spots_received: AtomicU64::new(0),
+            parse_failures: AtomicU64::new(0),
+            feed_connects: AtomicU64::new(0),
+            active_spots: AtomicU64::new(0),
+            vfd_write_errors: AtomicU64::new(0),
+            radio_commands: AtomicU64::new(0),
+            radio_command_micros: AtomicU64::new(0),
+        }
+    }
+
+    /// A spot line from the feed was parsed
+    pub fn spot_received(&self) {
+        self.spots_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A "DX de" line from the feed could not be parsed
+    pub fn parse_failed(&self) {
+        self.parse_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A connection to the feed was attempted
+    pub fn feed_connecting(&self) {
+        self.feed_connects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of signals currently held in the spot store
+    pub fn set_active_spots(&self, count: usize) {
+        self.active_spots.store(count as u64, Ordering::Relaxed);
+    }
+
+    /// A write to the VFD port failed
+    pub fn vfd_write_failed(&self) {
+        self.vfd_write_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A command to the radio completed (successfully or not) after `elapsed`
+    pub fn radio_command(&self, elapsed: Duration) {
+        self.radio_commands.fetch_add(1, Ordering::Relaxed);
+        self.radio_command_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    /// Everything in the Prometheus text exposition format
+    pub fn render(&self) -> String {
+        let get = |value: &AtomicU64| value.load(Ordering::Relaxed);
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: String| {
+            let _ = write!(
+                out,
+                "# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n"
+            );
+        };
+
+        metric(
+            "rbn_vfd_spots_received_total",
+            "counter",
+            "Spot lines received from the RBN feed",
+            get(&self.spots_received).to_string(),
+        );
+        metric(
+            "rbn_vfd_parse_failures_total",
+            "counter",
+            "Spot lines from the RBN feed that could not be parsed",
+            get(&self.parse_failures).to_string(),
+        );
+        metric(
+            "rbn_vfd_reconnects_total",
+            "counter",
+            "Connection attempts to the RBN feed after the first",
+            get(&self.feed_connects).saturating_sub(1).to_string(),
+        );
+        metric(
+            "rbn_vfd_active_spots",
+            "gauge",
+            "Signals currently held in the spot store",
+            get(&self.active_spots).to_string(),
+        );
+        metric(
+            "rbn_vfd_vfd_write_errors_total",
+            "counter",
+            "Failed writes to the VFD port",
+            get(&self.vfd_write_errors).to_string(),
+        );
+        metric(
+            "rbn_vfd_radio_commands_total",
+            "counter",
+            "Commands sent to the radio",
+            get(&self.radio_commands).to_string(),
+        );
+        metric(
+            "rbn_vfd_radio_command_seconds_total",
+            "counter",
+            "Total time spent waiting on radio commands",
+            format!("{:.6}", get(&self.radio_command_micros) as f64 / 1e6),
+        );
+        out
+    }
+}
@@ -1,8 +1,52 @@
+mod adif;
+mod alerts;
+mod busted_calls;
+mod calibration;
+mod contest;
+mod data_files;
+mod export;
+mod http_api;
+mod json;
+mod manual_spots;
+mod metrics;
+mod mqtt;
+mod n1mm;
+mod needs;
+mod notifications;
+mod propagation;
 pub mod radio;
 mod rbn_client;
+mod script_hooks;
+mod self_spots;
+mod session;
+mod spot_db;
+mod spot_stats;
 mod spot_store;
 mod vfd_display;
+mod watchdog;
+mod websocket;
 
+pub use alerts::{Alert, AlertCenter, AlertDelivery};
+pub use contest::{Contest, ContestLog, Multiplier};
+pub use data_files::{DataFileKind, DataFileMessage, DataFileUpdater, DataFiles};
+pub use export::{write_spots, ExportFormat};
+pub use http_api::{ApiRequest, ApiResponse, HttpApi};
+pub use json::{spots_json, to_json, LinesJson};
+pub use manual_spots::parse_manual_spots;
+pub use mqtt::MqttPublisher;
+pub use n1mm::{RadioInfo, RadioInfoListener};
+pub use needs::{ModeClass, NeedsMatrix};
+pub use notifications::{Notification, Notifications, Severity};
+pub use propagation::{PropagationFetcher, PropagationMessage, SolarData};
 pub use rbn_client::{RbnClient, RbnMessage};
+pub use script_hooks::{run_detached, ScriptAction, ScriptHost};
+pub use self_spots::{is_own_call, SelfSpots};
+pub use session::{SessionEvent, SessionPlayer, SessionRecorder};
+pub use spot_db::{HistoryRow, SpotDb};
 pub use spot_store::SpotStore;
-pub use vfd_display::VfdDisplay;
+pub use vfd_display::{
+    beacon_lines, follow_lines, glyph, heading_lines, heading_text, Bitmap, CharMap,
+    DisplayGeometry, DisplayProtocol, IdleContent, LocalClock, ProtocolKind, ScreensaverStyle,
+    VfdContent, VfdDisplay, VfdPage,
+};
+pub use watchdog::Watchdog;
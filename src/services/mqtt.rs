@@ -0,0 +1,187 @@
+//! Publishes VFD frames and new spots as JSON to an MQTT broker
+//!
+//! rumqttc keeps the connection: it pings the broker, notices when the
+//! replies stop and reconnects, holding publishes made in the meantime (up to
+//! `MAX_QUEUED`). Publishes are QoS 0. Frames are retained so a subscriber
+//! that connects later gets the current display straight away.
+
+use crate::config::MqttConfig;
+use crate::error::Error;
+use crate::models::AggregatedSpot;
+use crate::services::json::{spot_json, to_json, LinesJson};
+use rumqttc::{
+    Client, ConnectReturnCode, Connection, ConnectionError, Event, MqttOptions, Packet, QoS,
+};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Keep-alive interval announced to the broker
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+
+/// Wait before reconnecting after the broker is unreachable
+const RETRY_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Publishes allowed to queue up before new ones are dropped
+const MAX_QUEUED: usize = 256;
+
+/// Handle to the publisher thread
+pub struct MqttPublisher {
+    config: MqttConfig,
+    client: Client,
+    error_rx: mpsc::Receiver<String>,
+}
+
+impl MqttPublisher {
+    pub fn start(config: MqttConfig) -> Self {
+        let mut options = MqttOptions::new(
+            format!("rbn-vfd-{}", std::process::id()),
+            &config.host,
+            config.port,
+        );
+        options.set_keep_alive(KEEP_ALIVE);
+        if !config.username.is_empty() {
+            options.set_credentials(&config.username, &config.password);
+        }
+        let (client, connection) = Client::new(options, MAX_QUEUED);
+        let (error_tx, error_rx) = mpsc::channel();
+
+        let address = format!("{}:{}", config.host, config.port);
+        std::thread::spawn(move || connection_task(connection, &address, error_tx));
+
+        Self {
+            config,
+            client,
+            error_rx,
+        }
+    }
+
+    /// Settings the publisher was started with
+    pub fn config(&self) -> &MqttConfig {
+        &self.config
+    }
+
+    /// Publish the lines currently on the VFD
+    pub fn publish_frame(&self, lines: &[String]) {
+        self.publish(
+            &self.config.frame_topic,
+            to_json(&LinesJson { lines }),
+            true,
+        );
+    }
+
+    /// Publish a newly heard spot
+    pub fn publish_spot(&self, spot: &AggregatedSpot) {
+        self.publish(&self.config.spot_topic, spot_json(spot), false);
+    }
+
+    fn publish(&self, topic: &str, payload: String, retain: bool) {
+        if topic.is_empty() {
+            return;
+        }
+        // Dropped if the broker is too slow; frames are superseded anyway
+        let _ = self
+            .client
+            .try_publish(topic, QoS::AtMostOnce, retain, payload);
+    }
+
+    /// Next connection error, if any (non-blocking)
+    pub fn take_error(&self) -> Option<Error> {
+        self.error_rx.try_recv().ok().map(Error::Mqtt)
+    }
+}
+
+impl Drop for MqttPublisher {
+    fn drop(&mut self) {
+        let _ = self.client.try_disconnect();
+    }
+}
+
+/// Drive the connection until the publisher is dropped
+fn connection_task(mut connection: Connection, address: &str, error_tx: mpsc::Sender<String>) {
+    // Report a failing broker once, not on every retry
+    let mut failing = false;
+    for event in connection.iter() {
+        match event {
+            Ok(Event::Incoming(Packet::ConnAck(_))) => failing = false,
+            Ok(Event::Outgoing(rumqttc::Outgoing::Disconnect)) => return,
+            Ok(_) => {}
+            Err(e) => {
+                if !failing {
+                    failing = true;
+                    let _ = error_tx.send(describe(&e, address));
+                }
+                std::thread::sleep(RETRY_INTERVAL);
+            }
+        }
+    }
+}
+
+fn describe(error: &ConnectionError, address: &str) -> String {
+    match error {
+        ConnectionError::ConnectionRefused(
+            ConnectReturnCode::BadUserNamePassword | ConnectReturnCode::NotAuthorized,
+        ) => "MQTT broker rejected the username or password".to_string(),
+        ConnectionError::ConnectionRefused(code) => {
+            format!("MQTT broker refused the connection ({:?})", code)
+        }
+        e => format!("MQTT broker {}: {}", address, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+
+    /// Read one packet: its first byte and its body
+    fn read_packet(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let mut byte = [0u8; 1];
+        stream.read_exact(&mut byte).unwrap();
+        let header = byte[0];
+        let (mut length, mut shift) = (0usize, 0);
+        loop {
+            stream.read_exact(&mut byte).unwrap();
+            length |= ((byte[0] & 0x7F) as usize) << shift;
+            shift += 7;
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+        }
+        let mut body = vec![0u8; length];
+        stream.read_exact(&mut body).unwrap();
+        (header, body)
+    }
+
+    #[test]
+    fn delivers_frames_published_before_the_broker_answers() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let publisher = MqttPublisher::start(MqttConfig {
+            enabled: true,
+            host: "127.0.0.1".to_string(),
+            port: listener.local_addr().unwrap().port(),
+            username: "w6jsv".to_string(),
+            password: "secret".to_string(),
+            ..MqttConfig::default()
+        });
+        publisher.publish_frame(&["CQ TEST".to_string()]);
+
+        let (mut broker, _) = listener.accept().unwrap();
+        broker
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let (header, connect) = read_packet(&mut broker);
+        assert_eq!(header, 0x10);
+        assert_eq!(&connect[..7], b"\x00\x04MQTT\x04");
+        // Clean session with a username and password
+        assert_eq!(connect[7], 0xC2);
+        assert_eq!(&connect[8..10], &60u16.to_be_bytes());
+        broker.write_all(&[0x20, 2, 0, 0]).unwrap();
+
+        let (header, publish) = read_packet(&mut broker);
+        assert_eq!(header, 0x31, "QoS 0 publish, retained");
+        assert_eq!(&publish[..2], &15u16.to_be_bytes());
+        assert_eq!(&publish[2..17], b"rbn-vfd/display");
+        assert_eq!(&publish[17..], br#"{"lines":["CQ TEST"]}"#);
+    }
+}
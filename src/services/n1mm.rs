@@ -0,0 +1,111 @@
+//! Listener for N1MM Logger+ RadioInfo UDP broadcasts
+//!
+//! N1MM sends a `<RadioInfo>` XML packet whenever a radio's frequency or mode
+//! changes. Listening lets the app follow the logger's frequency while the
+//! logger keeps sole control of the radio.
+
+use crate::error::{Error, Result};
+use crate::services::radio::RadioError;
+use std::net::UdpSocket;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::Duration;
+
+/// How often the listener checks for shutdown while no packets arrive
+const RECEIVE_POLL: Duration = Duration::from_millis(250);
+
+/// One radio's state as broadcast by N1MM
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadioInfo {
+    pub radio_number: u8,
+    pub frequency_khz: f64,
+    pub mode: String,
+    /// Whether this is the radio N1MM is transmitting on (always true for SO1R)
+    pub active: bool,
+}
+
+/// Handle to the background UDP listener
+pub struct RadioInfoListener {
+    port: u16,
+    msg_rx: mpsc::Receiver<RadioInfo>,
+    stop: Arc<AtomicBool>,
+}
+
+impl RadioInfoListener {
+    /// Listen for broadcasts on `port` (N1MM's default is 12060)
+    pub fn start(port: u16) -> Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", port)).map_err(|e| {
+            Error::Radio(RadioError::ConnectionFailed(format!(
+                "Failed to listen for N1MM RadioInfo on port {}: {}",
+                port, e
+            )))
+        })?;
+        socket
+            .set_read_timeout(Some(RECEIVE_POLL))
+            .map_err(|e| Error::Radio(RadioError::ConnectionFailed(e.to_string())))?;
+        let (msg_tx, msg_rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let listener_stop = stop.clone();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            while !listener_stop.load(Ordering::Relaxed) {
+                let Ok(n) = socket.recv(&mut buf) else {
+                    continue;
+                };
+                if let Some(info) = parse_radio_info(&String::from_utf8_lossy(&buf[..n])) {
+                    if msg_tx.send(info).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Self { port, msg_rx, stop })
+    }
+
+    /// Port the listener was started on
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Try to receive a radio update (non-blocking)
+    pub fn try_recv(&self) -> Option<RadioInfo> {
+        self.msg_rx.try_recv().ok()
+    }
+}
+
+impl Drop for RadioInfoListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Text of the first `<name>` element
+fn element<'a>(xml: &'a str, name: &str) -> Option<&'a str> {
+    let open = format!("<{}>", name);
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&format!("</{}>", name))?;
+    Some(xml[start..end].trim())
+}
+
+/// Parse a `<RadioInfo>` packet; other N1MM broadcasts give `None`
+fn parse_radio_info(xml: &str) -> Option<RadioInfo> {
+    if !xml.contains("<RadioInfo>") {
+        return None;
+    }
+    let radio_number = element(xml, "RadioNr")
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(1);
+    // Frequencies are in tens of Hz
+    let frequency_khz = element(xml, "Freq")?.parse::<f64>().ok()? / 100.0;
+    let active = element(xml, "ActiveRadioNr")
+        .and_then(|n| n.parse::<u8>().ok())
+        .is_none_or(|n| n == radio_number);
+    Some(RadioInfo {
+        radio_number,
+        frequency_khz,
+        mode: element(xml, "Mode").unwrap_or_default().to_string(),
+        active,
+    })
+}
@@ -0,0 +1,175 @@
+//! DXCC needs: which entity/band/mode slots are already confirmed
+//!
+//! Filled from a LoTW QSL report or a Club Log ADIF export and kept in
+//! needs.txt in the data directory, one "PREFIX band mode" slot per line, so
+//! it can also be edited by hand or in the app.
+
+use crate::models::{BandPlan, CountryDb};
+use crate::services::adif;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Mode groups as DXCC awards count them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ModeClass {
+    Cw,
+    Phone,
+    Digital,
+}
+
+impl ModeClass {
+    pub const ALL: [ModeClass; 3] = [ModeClass::Cw, ModeClass::Phone, ModeClass::Digital];
+
+    /// Name used in needs.txt
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ModeClass::Cw => "cw",
+            ModeClass::Phone => "phone",
+            ModeClass::Digital => "digital",
+        }
+    }
+
+    /// Parse the needs.txt name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|m| m.as_str().eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            ModeClass::Cw => "CW",
+            ModeClass::Phone => "Phone",
+            ModeClass::Digital => "Digital",
+        }
+    }
+
+    /// Group of an RBN or ADIF mode name
+    pub fn from_mode(mode: &str) -> Self {
+        match mode.trim().to_uppercase().as_str() {
+            "CW" => ModeClass::Cw,
+            "SSB" | "USB" | "LSB" | "AM" | "FM" | "PHONE" => ModeClass::Phone,
+            _ => ModeClass::Digital,
+        }
+    }
+}
+
+/// A confirmed slot: entity primary prefix, band and mode group
+pub type NeedsSlot = (String, String, ModeClass);
+
+/// Confirmed entity/band/mode slots; anything else is needed
+#[derive(Debug, Default)]
+pub struct NeedsMatrix {
+    confirmed: BTreeSet<NeedsSlot>,
+}
+
+impl NeedsMatrix {
+    /// Read needs.txt, starting empty if it doesn't exist
+    pub fn load(path: &Path) -> Self {
+        let Ok(text) = std::fs::read_to_string(path) else {
+            return Self::default();
+        };
+        let confirmed = text
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let prefix = parts.next()?.to_uppercase();
+                let band = parts.next()?.to_lowercase();
+                let mode = ModeClass::from_name(parts.next()?)?;
+                Some((prefix, band, mode))
+            })
+            .collect();
+        Self { confirmed }
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let mut text = String::from("# Confirmed DXCC slots: entity prefix, band, mode\n");
+        for (prefix, band, mode) in &self.confirmed {
+            text.push_str(&format!("{} {} {}\n", prefix, band, mode.as_str()));
+        }
+        std::fs::write(path, text)
+    }
+
+    /// Whether nothing has been imported or entered yet
+    pub fn is_empty(&self) -> bool {
+        self.confirmed.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.confirmed.len()
+    }
+
+    /// Confirmed slots, sorted by prefix, band and mode
+    pub fn slots(&self) -> impl Iterator<Item = &NeedsSlot> {
+        self.confirmed.iter()
+    }
+
+    /// Whether a QSO with entity `prefix` on `band` in `mode` would be a new one
+    pub fn is_needed(&self, prefix: &str, band: &str, mode: ModeClass) -> bool {
+        !self.is_empty()
+            && !self
+                .confirmed
+                .contains(&(prefix.to_uppercase(), band.to_lowercase(), mode))
+    }
+
+    /// Mark a slot confirmed or needed
+    pub fn set_confirmed(&mut self, prefix: &str, band: &str, mode: ModeClass, confirmed: bool) {
+        let slot = (
+            prefix.trim().to_uppercase(),
+            band.trim().to_lowercase(),
+            mode,
+        );
+        if confirmed {
+            self.confirmed.insert(slot);
+        } else {
+            self.confirmed.remove(&slot);
+        }
+    }
+
+    /// Add the confirmed QSOs of a LoTW or Club Log ADIF export; returns how
+    /// many slots were new
+    pub fn import_adif(
+        &mut self,
+        text: &str,
+        plan: &BandPlan,
+        db: &CountryDb,
+    ) -> Result<usize, String> {
+        let records = adif::records(text);
+        if records.is_empty() {
+            return Err("No QSOs found (expected an ADIF file)".to_string());
+        }
+        let before = self.confirmed.len();
+        for record in records {
+            if !is_confirmed(record) {
+                continue;
+            }
+            let Some(entity) = adif::field(record, "CALL").and_then(|call| db.lookup(&call)) else {
+                continue;
+            };
+            let (Some(band), Some(mode)) = (adif::band(record, plan), adif::field(record, "MODE"))
+            else {
+                continue;
+            };
+            self.set_confirmed(
+                &entity.primary_prefix,
+                &band,
+                ModeClass::from_mode(&mode),
+                true,
+            );
+        }
+        Ok(self.confirmed.len() - before)
+    }
+}
+
+/// Records without QSL fields count as worked; otherwise a paper or LoTW
+/// confirmation is required
+fn is_confirmed(record: &str) -> bool {
+    let qsl = ["QSL_RCVD", "LOTW_QSL_RCVD"].map(|name| adif::field(record, name));
+    qsl.iter().all(Option::is_none)
+        || qsl
+            .iter()
+            .flatten()
+            .any(|v| v.eq_ignore_ascii_case("Y") || v.eq_ignore_ascii_case("V"))
+}
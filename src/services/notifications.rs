@@ -0,0 +1,130 @@
+//! Status notifications with severities and a history
+//!
+//! Info messages replace each other in the status line. Warnings and errors
+//! are also shown as toasts (warnings for a while, errors until dismissed) so
+//! a later message can't hide them, and everything is kept in a history.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How many notifications the history keeps
+const HISTORY_LIMIT: usize = 200;
+
+/// How long a warning toast stays up
+const WARNING_TOAST_TIME: Duration = Duration::from_secs(10);
+
+/// How serious a notification is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Info => "Info",
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        }
+    }
+}
+
+/// A single notification
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: u64,
+    pub severity: Severity,
+    pub message: String,
+    pub raised: Instant,
+    /// Wall-clock time, for the history
+    pub time: SystemTime,
+    pub dismissed: bool,
+}
+
+/// The app's notifications
+pub struct Notifications {
+    history: VecDeque<Notification>,
+    next_id: u64,
+    /// Latest info message, shown in the status line
+    status: String,
+}
+
+impl Notifications {
+    pub fn new(status: String) -> Self {
+        Self {
+            history: VecDeque::new(),
+            next_id: 0,
+            status,
+        }
+    }
+
+    pub fn info(&mut self, message: impl Into<String>) {
+        self.push(Severity::Info, message.into());
+    }
+
+    pub fn warning(&mut self, message: impl Into<String>) {
+        self.push(Severity::Warning, message.into());
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(Severity::Error, message.into());
+    }
+
+    fn push(&mut self, severity: Severity, message: String) {
+        if severity == Severity::Info {
+            self.status = message.clone();
+        }
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(Notification {
+            id: self.next_id,
+            severity,
+            message,
+            raised: Instant::now(),
+            time: SystemTime::now(),
+            dismissed: false,
+        });
+        self.next_id += 1;
+    }
+
+    /// The status line text
+    pub fn status(&self) -> &str {
+        &self.status
+    }
+
+    /// Warnings and errors to show as toasts, oldest first
+    pub fn toasts(&self) -> impl Iterator<Item = &Notification> {
+        self.history.iter().filter(|n| match n.severity {
+            Severity::Info => false,
+            Severity::Warning => !n.dismissed && n.raised.elapsed() < WARNING_TOAST_TIME,
+            Severity::Error => !n.dismissed,
+        })
+    }
+
+    /// Hide a toast; it stays in the history
+    pub fn dismiss(&mut self, id: u64) {
+        if let Some(n) = self.history.iter_mut().find(|n| n.id == id) {
+            n.dismissed = true;
+        }
+    }
+
+    /// Every notification kept, oldest first
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = &Notification> {
+        self.history.iter()
+    }
+
+    /// Number of errors not yet dismissed
+    pub fn unread_errors(&self) -> usize {
+        self.history
+            .iter()
+            .filter(|n| n.severity == Severity::Error && !n.dismissed)
+            .count()
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+}
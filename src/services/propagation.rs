@@ -0,0 +1,202 @@
+//! Background fetcher for solar and geomagnetic indices (hamqsl.com XML feed)
+
+use crate::services::vfd_display::wrap_words;
+use regex::Regex;
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Source of the solar data
+const SOLAR_URL: &str = "https://www.hamqsl.com/solarxml.php";
+
+/// Wait before retrying after a failed fetch
+const RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+
+/// Largest response we are willing to read
+const MAX_RESPONSE_BYTES: u64 = 256 * 1024;
+
+/// Data older than this is no longer shown
+const SOLAR_STALE_AFTER: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Solar flux and geomagnetic indices
+#[derive(Debug, Clone, PartialEq)]
+pub struct SolarData {
+    /// 10.7 cm solar flux index
+    pub sfi: u32,
+    pub a_index: u32,
+    pub k_index: u32,
+    pub sunspots: Option<u32>,
+    /// X-ray flare class, e.g. "B5.1"
+    pub xray: Option<String>,
+    /// Geomagnetic field description, e.g. "QUIET"
+    pub geomag_field: Option<String>,
+    /// Time the source last updated, as it reports it
+    pub updated: String,
+    /// When we fetched (or cached) this data
+    pub fetched_at: SystemTime,
+}
+
+impl SolarData {
+    /// Parse the hamqsl.com solar XML
+    pub fn parse(xml: &str, fetched_at: SystemTime) -> Option<Self> {
+        let field = |tag: &str| -> Option<String> {
+            let re = Regex::new(&format!(r"<{tag}>\s*([^<]*?)\s*</{tag}>")).ok()?;
+            let value = re.captures(xml)?.get(1)?.as_str().to_string();
+            (!value.is_empty()).then_some(value)
+        };
+        let number = |tag: &str| field(tag).and_then(|v| v.parse::<u32>().ok());
+
+        Some(Self {
+            sfi: number("solarflux")?,
+            a_index: number("aindex")?,
+            k_index: number("kindex")?,
+            sunspots: number("sunspots"),
+            xray: field("xray"),
+            geomag_field: field("geomagfield"),
+            updated: field("updated").unwrap_or_default(),
+            fetched_at,
+        })
+    }
+
+    /// Whether the data is too old to show
+    pub fn is_stale(&self) -> bool {
+        self.fetched_at.elapsed().unwrap_or_default() >= SOLAR_STALE_AFTER
+    }
+
+    /// One-line summary for the window header
+    pub fn summary(&self) -> String {
+        format!("SFI {} A {} K {}", self.sfi, self.a_index, self.k_index)
+    }
+
+    /// Content of the VFD solar page
+    pub fn vfd_lines(&self, width: usize) -> Vec<String> {
+        let mut words = vec![
+            format!("SFI:{}", self.sfi),
+            format!("A:{}", self.a_index),
+            format!("K:{}", self.k_index),
+        ];
+        if let Some(sunspots) = self.sunspots {
+            words.push(format!("SSN:{}", sunspots));
+        }
+        if let Some(ref xray) = self.xray {
+            words.push(format!("X:{}", xray));
+        }
+        wrap_words(&words, width)
+    }
+}
+
+/// Messages sent from the fetcher to the main app
+#[derive(Debug, Clone)]
+pub enum PropagationMessage {
+    Updated(SolarData),
+    Failed(String),
+}
+
+/// Commands sent to the fetcher
+#[derive(Debug)]
+enum PropagationCommand {
+    SetInterval(Duration),
+}
+
+/// Handle to the background solar data fetcher thread
+pub struct PropagationFetcher {
+    cmd_tx: mpsc::Sender<PropagationCommand>,
+    msg_rx: mpsc::Receiver<PropagationMessage>,
+}
+
+impl PropagationFetcher {
+    /// Spawn the fetcher, refreshing every `interval`; the last good response
+    /// is kept in `cache` (if given) so the data survives being offline
+    pub fn new(interval: Duration, cache: Option<PathBuf>) -> Self {
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let (msg_tx, msg_rx) = mpsc::channel();
+
+        std::thread::spawn(move || fetcher_task(interval, cache, cmd_rx, msg_tx));
+
+        Self { cmd_tx, msg_rx }
+    }
+
+    /// Change how often the data is refreshed
+    pub fn set_interval(&self, interval: Duration) {
+        let _ = self.cmd_tx.send(PropagationCommand::SetInterval(interval));
+    }
+
+    /// Try to receive a message (non-blocking)
+    pub fn try_recv(&self) -> Option<PropagationMessage> {
+        self.msg_rx.try_recv().ok()
+    }
+}
+
+fn fetcher_task(
+    mut interval: Duration,
+    cache: Option<PathBuf>,
+    cmd_rx: mpsc::Receiver<PropagationCommand>,
+    msg_tx: mpsc::Sender<PropagationMessage>,
+) {
+    let mut fetched_at = Instant::now();
+    let mut next_fetch = fetched_at;
+
+    // Start from the cached response; only fetch now if it is due
+    if let Some(data) = cache.as_deref().and_then(|path| {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+        SolarData::parse(&std::fs::read_to_string(path).ok()?, modified)
+    }) {
+        let age = data.fetched_at.elapsed().unwrap_or_default();
+        next_fetch += interval.saturating_sub(age);
+        if msg_tx.send(PropagationMessage::Updated(data)).is_err() {
+            return;
+        }
+    }
+
+    loop {
+        if Instant::now() >= next_fetch {
+            let msg = match fetch() {
+                Ok(xml) => match SolarData::parse(&xml, SystemTime::now()) {
+                    Some(data) => {
+                        if let Some(ref path) = cache {
+                            let _ = std::fs::write(path, &xml);
+                        }
+                        PropagationMessage::Updated(data)
+                    }
+                    None => PropagationMessage::Failed("Solar data not understood".to_string()),
+                },
+                Err(e) => PropagationMessage::Failed(e),
+            };
+            fetched_at = Instant::now();
+            next_fetch = fetched_at
+                + match msg {
+                    PropagationMessage::Updated(_) => interval,
+                    PropagationMessage::Failed(_) => RETRY_DELAY.min(interval),
+                };
+            if msg_tx.send(msg).is_err() {
+                return;
+            }
+        }
+
+        match cmd_rx.recv_timeout(next_fetch.saturating_duration_since(Instant::now())) {
+            Ok(PropagationCommand::SetInterval(new_interval)) => {
+                if new_interval != interval {
+                    interval = new_interval;
+                    next_fetch = fetched_at + interval;
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        }
+    }
+}
+
+fn fetch() -> Result<String, String> {
+    let response = ureq::get(SOLAR_URL)
+        .timeout(Duration::from_secs(30))
+        .call()
+        .map_err(|e| format!("Solar data fetch failed: {}", e))?;
+    let mut body = String::new();
+    response
+        .into_reader()
+        .take(MAX_RESPONSE_BYTES)
+        .read_to_string(&mut body)
+        .map_err(|e| format!("Solar data fetch failed: {}", e))?;
+    Ok(body)
+}
@@ -0,0 +1,101 @@
+//! Antenna switching when tuning to another band
+
+use super::{RadioController, RadioError, RadioResult};
+use std::io::Write;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// How the antenna switch is driven
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AntennaOutput {
+    /// Antennas are never switched
+    #[default]
+    None,
+    /// The radio's own antenna selection, through the rigctld `Y` command
+    Rigctld,
+    /// A command line sent to a relay controller over TCP (`host:port`)
+    Tcp,
+    /// A command line written to a relay controller's serial port
+    Serial,
+}
+
+impl AntennaOutput {
+    pub const ALL: [AntennaOutput; 4] = [
+        AntennaOutput::None,
+        AntennaOutput::Rigctld,
+        AntennaOutput::Tcp,
+        AntennaOutput::Serial,
+    ];
+
+    /// Name used in settings.toml
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AntennaOutput::None => "none",
+            AntennaOutput::Rigctld => "rigctld",
+            AntennaOutput::Tcp => "tcp",
+            AntennaOutput::Serial => "serial",
+        }
+    }
+
+    /// Parse the settings.toml name
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Self::ALL.into_iter().find(|o| o.as_str() == name)
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            AntennaOutput::None => "Off",
+            AntennaOutput::Rigctld => "Radio (rigctld)",
+            AntennaOutput::Tcp => "Relay controller (TCP)",
+            AntennaOutput::Serial => "Relay controller (serial)",
+        }
+    }
+}
+
+/// Select `antenna` through `output`. `target` is the relay controller's
+/// `host:port` or serial port, and `command` the line sent to it, with
+/// `{antenna}` replaced by the antenna number.
+pub fn switch_antenna(
+    output: AntennaOutput,
+    target: &str,
+    command: &str,
+    antenna: u8,
+    radio: &mut dyn RadioController,
+) -> RadioResult<()> {
+    let line = format!("{}\r\n", command.replace("{antenna}", &antenna.to_string()));
+    match output {
+        AntennaOutput::None => Ok(()),
+        AntennaOutput::Rigctld => radio.set_antenna(antenna),
+        AntennaOutput::Tcp => {
+            let addr = target
+                .to_socket_addrs()
+                .ok()
+                .and_then(|mut addrs| addrs.next())
+                .ok_or_else(|| {
+                    RadioError::ConnectionFailed(format!("Invalid address: {}", target))
+                })?;
+            let mut stream =
+                TcpStream::connect_timeout(&addr, Duration::from_secs(3)).map_err(|e| {
+                    RadioError::ConnectionFailed(format!(
+                        "Cannot connect to antenna switch at {} ({})",
+                        target, e
+                    ))
+                })?;
+            stream
+                .write_all(line.as_bytes())
+                .map_err(|e| RadioError::CommandFailed(e.to_string()))
+        }
+        AntennaOutput::Serial => {
+            let mut port = serialport::new(target, 9600)
+                .timeout(Duration::from_millis(1000))
+                .open()
+                .map_err(|e| {
+                    RadioError::ConnectionFailed(format!("Failed to open {}: {}", target, e))
+                })?;
+            port.write_all(line.as_bytes())
+                .map_err(|e| RadioError::CommandFailed(e.to_string()))
+        }
+    }
+}
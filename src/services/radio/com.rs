@@ -0,0 +1,47 @@
+//! Late-bound COM automation access, abstracted so rig logic can be tested
+//! against a mock on every platform
+
+#![cfg_attr(not(target_os = "windows"), allow(dead_code))]
+
+/// A value read from or written to an automation property
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComValue {
+    I4(i32),
+    Bstr(String),
+    /// Any other variant type (never written)
+    Other,
+}
+
+/// Property access on an IDispatch object
+pub trait Dispatch {
+    /// Read a property
+    fn get(&self, property: &str) -> Result<ComValue, String>;
+
+    /// Write a property
+    fn put(&self, property: &str, value: ComValue) -> Result<(), String>;
+}
+
+#[cfg(target_os = "windows")]
+impl Dispatch for winsafe::IDispatch {
+    fn get(&self, property: &str) -> Result<ComValue, String> {
+        use winsafe::prelude::*;
+
+        match self.invoke_get(property, &[]).map_err(|e| e.to_string())? {
+            winsafe::Variant::I4(value) => Ok(ComValue::I4(value)),
+            winsafe::Variant::Bstr(value) => Ok(ComValue::Bstr(value)),
+            _ => Ok(ComValue::Other),
+        }
+    }
+
+    fn put(&self, property: &str, value: ComValue) -> Result<(), String> {
+        use winsafe::prelude::*;
+
+        let variant = match value {
+            ComValue::I4(value) => winsafe::Variant::I4(value),
+            ComValue::Bstr(value) => winsafe::Variant::Bstr(value),
+            ComValue::Other => return Err("Unsupported value type".to_string()),
+        };
+        self.invoke_put(property, &variant)
+            .map_err(|e| e.to_string())
+    }
+}
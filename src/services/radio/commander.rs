@@ -0,0 +1,220 @@
+//! DXLab Commander radio controller via its TCP command interface
+//!
+//! Messages are sequences of `<name:length>value` elements with no line
+//! terminator, e.g. `<command:10>CmdGetFreq<parameters:0>`.
+
+use super::{RadioController, RadioError, RadioMode, RadioResult};
+use crate::services::metrics::METRICS;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+/// Controller for DXLab Commander
+pub struct CommanderController {
+    host: String,
+    port: u16,
+    stream: Option<TcpStream>,
+}
+
+impl CommanderController {
+    pub fn new(host: String, port: u16) -> Self {
+        Self {
+            host,
+            port,
+            stream: None,
+        }
+    }
+
+    /// Send a command with its parameters
+    fn send_command(&mut self, command: &str, parameters: &[(&str, &str)]) -> RadioResult<()> {
+        let stream = self.stream.as_mut().ok_or(RadioError::NotConnected)?;
+        stream
+            .write_all(command_message(command, parameters).as_bytes())
+            .map_err(|e| RadioError::CommandFailed(e.to_string()))
+    }
+
+    /// Read until the named element of a reply arrives and return its value
+    fn read_element(&mut self, name: &str) -> RadioResult<String> {
+        let stream = self.stream.as_mut().ok_or(RadioError::NotConnected)?;
+        let mut received = String::new();
+        let mut buf = [0u8; 256];
+        loop {
+            if let Some(value) = find_element(&received, name) {
+                return Ok(value);
+            }
+            let n = stream.read(&mut buf).map_err(|e| match e.kind() {
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => {
+                    RadioError::Timeout
+                }
+                _ => RadioError::CommandFailed(e.to_string()),
+            })?;
+            if n == 0 {
+                self.stream = None;
+                return Err(RadioError::NotConnected);
+            }
+            received.push_str(&String::from_utf8_lossy(&buf[..n]));
+        }
+    }
+}
+
+/// Commander's name for a mode
+fn commander_mode(mode: RadioMode) -> &'static str {
+    match mode {
+        RadioMode::Cw => "CW",
+        RadioMode::CwReverse => "CW-R",
+        RadioMode::Usb => "USB",
+        RadioMode::Lsb => "LSB",
+        RadioMode::Rtty => "RTTY",
+        RadioMode::RttyReverse => "RTTY-R",
+        RadioMode::Am => "AM",
+        RadioMode::Fm => "FM",
+        RadioMode::Data => "DATA-U",
+    }
+}
+
+/// One `<name:length>value` element
+fn element(name: &str, value: &str) -> String {
+    format!("<{}:{}>{}", name, value.len(), value)
+}
+
+/// A complete command message
+fn command_message(command: &str, parameters: &[(&str, &str)]) -> String {
+    let parameters: String = parameters
+        .iter()
+        .map(|(name, value)| element(name, value))
+        .collect();
+    format!(
+        "{}{}",
+        element("command", command),
+        element("parameters", &parameters)
+    )
+}
+
+/// Value of the named element, once all of it has been received
+fn find_element(message: &str, name: &str) -> Option<String> {
+    let lower = message.to_ascii_lowercase();
+    let start = lower.find(&format!("<{}:", name.to_ascii_lowercase()))?;
+    let header_end = start + message[start..].find('>')?;
+    let length: usize = message[start + name.len() + 2..header_end]
+        .split(':')
+        .next()?
+        .trim()
+        .parse()
+        .ok()?;
+    message
+        .get(header_end + 1..header_end + 1 + length)
+        .map(str::to_string)
+}
+
+impl RadioController for CommanderController {
+    fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    fn connect(&mut self) -> RadioResult<()> {
+        let addr = format!("{}:{}", self.host, self.port);
+        let socket_addr = addr
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+            .ok_or_else(|| RadioError::ConnectionFailed(format!("Invalid address: {}", addr)))?;
+        let stream =
+            TcpStream::connect_timeout(&socket_addr, Duration::from_secs(3)).map_err(|e| {
+                RadioError::ConnectionFailed(format!(
+                    "Cannot connect to Commander at {}. Is its TCP server enabled? ({})",
+                    addr, e
+                ))
+            })?;
+
+        stream
+            .set_read_timeout(Some(Duration::from_secs(3)))
+            .map_err(|e| RadioError::ConnectionFailed(e.to_string()))?;
+        stream
+            .set_write_timeout(Some(Duration::from_secs(3)))
+            .map_err(|e| RadioError::ConnectionFailed(e.to_string()))?;
+
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    fn disconnect(&mut self) {
+        self.stream = None;
+    }
+
+    fn tune(&mut self, frequency_khz: f64, mode: RadioMode) -> RadioResult<()> {
+        let started = Instant::now();
+        let frequency = format!("{:.3}", frequency_khz);
+        let result = self.send_command(
+            "CmdSetFreqMode",
+            &[
+                ("xcvrfreq", &frequency),
+                ("xcvrmode", commander_mode(mode)),
+                ("preservesplitanddual", "Y"),
+            ],
+        );
+        METRICS.radio_command(started.elapsed());
+        result
+    }
+
+    fn get_frequency(&mut self) -> RadioResult<f64> {
+        let started = Instant::now();
+        self.send_command("CmdGetFreq", &[])?;
+        let result = self.read_element("CmdFreq");
+        METRICS.radio_command(started.elapsed());
+
+        // Answers in kHz, possibly with thousands separators
+        let response = result?;
+        response
+            .trim()
+            .replace(',', "")
+            .parse::<f64>()
+            .map_err(|_| RadioError::CommandFailed(format!("Unexpected frequency: {}", response)))
+    }
+
+    fn send_raw(&mut self, command: &str) -> RadioResult<()> {
+        // A complete message, e.g. <command:11>CmdSetRitOn<parameters:0>
+        let stream = self.stream.as_mut().ok_or(RadioError::NotConnected)?;
+        stream
+            .write_all(command.as_bytes())
+            .map_err(|e| RadioError::CommandFailed(e.to_string()))
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "Commander"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_commands() {
+        assert_eq!(
+            command_message("CmdGetFreq", &[]),
+            "<command:10>CmdGetFreq<parameters:0>"
+        );
+        assert_eq!(
+            command_message(
+                "CmdSetFreqMode",
+                &[("xcvrfreq", "14025.000"), ("xcvrmode", "CW")]
+            ),
+            "<command:14>CmdSetFreqMode<parameters:35><xcvrfreq:9>14025.000<xcvrmode:2>CW"
+        );
+    }
+
+    #[test]
+    fn finds_reply_elements() {
+        assert_eq!(
+            find_element("<CmdFreq:10>14,025.000", "CmdFreq").as_deref(),
+            Some("14,025.000")
+        );
+        assert_eq!(
+            find_element("<cmdfreq:8>7025.000", "CmdFreq").as_deref(),
+            Some("7025.000")
+        );
+        // Not all of the value has arrived yet
+        assert_eq!(find_element("<CmdFreq:10>14,02", "CmdFreq"), None);
+        assert_eq!(find_element("<CmdMode:2>CW", "CmdFreq"), None);
+    }
+}
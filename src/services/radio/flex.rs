@@ -0,0 +1,277 @@
+//! FlexRadio controller via the SmartSDR TCP API
+//!
+//! Commands are lines of the form `C<seq>|<command>`, answered by
+//! `R<seq>|<hex code>|<message>`. After `sub slice all` the radio also sends
+//! `S<handle>|slice <n> key=value ...` status lines whenever a slice changes,
+//! which a reader thread keeps track of so frequency reads need no round trip.
+
+use super::{RadioController, RadioError, RadioMode, RadioResult};
+use crate::services::metrics::METRICS;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// TCP port of the SmartSDR API
+const FLEX_API_PORT: u16 = 4992;
+
+/// How long to wait for the radio to answer a command
+const REPLY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Controller for a FlexRadio signature series radio
+pub struct FlexController {
+    host: String,
+    /// Slice to tune, 0 = A
+    slice: u8,
+    stream: Option<TcpStream>,
+    sequence: u32,
+    reply_rx: Option<mpsc::Receiver<(u32, u32, String)>>,
+    /// Slice frequencies in kHz, kept current by the reader thread
+    frequencies: Arc<Mutex<HashMap<u8, f64>>>,
+    /// Cleared when the reader thread ends because the radio went away
+    alive: Arc<AtomicBool>,
+}
+
+impl FlexController {
+    pub fn new(host: String, slice: u8) -> Self {
+        Self {
+            host,
+            slice,
+            stream: None,
+            sequence: 0,
+            reply_rx: None,
+            frequencies: Arc::new(Mutex::new(HashMap::new())),
+            alive: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Send a command and wait for its reply
+    fn send_command(&mut self, command: &str) -> RadioResult<()> {
+        let stream = self.stream.as_mut().ok_or(RadioError::NotConnected)?;
+        let reply_rx = self.reply_rx.as_ref().ok_or(RadioError::NotConnected)?;
+        self.sequence += 1;
+        let sequence = self.sequence;
+
+        let started = Instant::now();
+        writeln!(stream, "C{}|{}", sequence, command)
+            .map_err(|e| RadioError::CommandFailed(e.to_string()))?;
+        let result = loop {
+            let remaining = REPLY_TIMEOUT.saturating_sub(started.elapsed());
+            match reply_rx.recv_timeout(remaining) {
+                Ok((seq, 0, _)) if seq == sequence => break Ok(()),
+                Ok((seq, code, message)) if seq == sequence => {
+                    break Err(RadioError::CommandFailed(format!(
+                        "SmartSDR error {:08X} {}",
+                        code, message
+                    )))
+                }
+                Ok(_) => continue,
+                Err(mpsc::RecvTimeoutError::Timeout) => break Err(RadioError::Timeout),
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    self.stream = None;
+                    break Err(RadioError::NotConnected);
+                }
+            }
+        };
+        METRICS.radio_command(started.elapsed());
+        result
+    }
+}
+
+/// Pass replies on and track slice frequencies until the connection ends or
+/// the controller goes away, then clear `alive`
+fn read_status(
+    reader: impl Read,
+    reply_tx: mpsc::Sender<(u32, u32, String)>,
+    frequencies: &Mutex<HashMap<u8, f64>>,
+    alive: &AtomicBool,
+) {
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if let Some(reply) = parse_reply(&line) {
+            if reply_tx.send(reply).is_err() {
+                break;
+            }
+        } else if let Some((slice, khz)) = parse_slice_frequency(&line) {
+            if let Ok(mut frequencies) = frequencies.lock() {
+                frequencies.insert(slice, khz);
+            }
+        }
+    }
+    alive.store(false, Ordering::Relaxed);
+}
+
+/// SmartSDR's name for a mode
+fn flex_mode(mode: RadioMode) -> &'static str {
+    match mode {
+        RadioMode::Cw | RadioMode::CwReverse => "CW",
+        RadioMode::Usb => "USB",
+        RadioMode::Lsb => "LSB",
+        RadioMode::Rtty | RadioMode::RttyReverse => "RTTY",
+        RadioMode::Am => "AM",
+        RadioMode::Fm => "FM",
+        RadioMode::Data => "DIGU",
+    }
+}
+
+/// Sequence number, error code and message of an `R` reply line
+fn parse_reply(line: &str) -> Option<(u32, u32, String)> {
+    let mut parts = line.strip_prefix('R')?.splitn(3, '|');
+    let sequence = parts.next()?.parse().ok()?;
+    let code = u32::from_str_radix(parts.next()?.trim(), 16).ok()?;
+    Some((sequence, code, parts.next().unwrap_or_default().to_string()))
+}
+
+/// Slice number and frequency in kHz from a slice status line, if it has one
+fn parse_slice_frequency(line: &str) -> Option<(u8, f64)> {
+    let status = line.strip_prefix('S')?.split_once('|')?.1;
+    let mut words = status.split_whitespace();
+    if words.next()? != "slice" {
+        return None;
+    }
+    let slice = words.next()?.parse().ok()?;
+    let mhz: f64 = words
+        .find_map(|word| word.strip_prefix("RF_frequency="))?
+        .parse()
+        .ok()?;
+    Some((slice, mhz * 1000.0))
+}
+
+impl RadioController for FlexController {
+    fn is_connected(&self) -> bool {
+        self.stream.is_some() && self.alive.load(Ordering::Relaxed)
+    }
+
+    fn connect(&mut self) -> RadioResult<()> {
+        let addr = format!("{}:{}", self.host, FLEX_API_PORT);
+        let socket_addr = addr
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+            .ok_or_else(|| RadioError::ConnectionFailed(format!("Invalid address: {}", addr)))?;
+        let stream =
+            TcpStream::connect_timeout(&socket_addr, Duration::from_secs(3)).map_err(|e| {
+                RadioError::ConnectionFailed(format!(
+                    "Cannot connect to the radio at {}. Is it on and reachable? ({})",
+                    addr, e
+                ))
+            })?;
+        stream
+            .set_write_timeout(Some(Duration::from_secs(3)))
+            .map_err(|e| RadioError::ConnectionFailed(e.to_string()))?;
+        let reader = stream
+            .try_clone()
+            .map_err(|e| RadioError::ConnectionFailed(e.to_string()))?;
+
+        let (reply_tx, reply_rx) = mpsc::channel();
+        let frequencies = Arc::new(Mutex::new(HashMap::new()));
+        let alive = Arc::new(AtomicBool::new(true));
+        let reader_frequencies = frequencies.clone();
+        let reader_alive = alive.clone();
+        std::thread::spawn(move || {
+            read_status(reader, reply_tx, &reader_frequencies, &reader_alive)
+        });
+
+        self.stream = Some(stream);
+        self.reply_rx = Some(reply_rx);
+        self.frequencies = frequencies;
+        self.alive = alive;
+        if let Err(e) = self.send_command("sub slice all") {
+            self.disconnect();
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn disconnect(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+        self.reply_rx = None;
+    }
+
+    fn tune(&mut self, frequency_khz: f64, mode: RadioMode) -> RadioResult<()> {
+        self.send_command(&format!(
+            "slice tune {} {:.6}",
+            self.slice,
+            frequency_khz / 1000.0
+        ))?;
+        self.send_command(&format!(
+            "slice set {} mode={}",
+            self.slice,
+            flex_mode(mode)
+        ))
+    }
+
+    fn get_frequency(&mut self) -> RadioResult<f64> {
+        if !self.is_connected() {
+            return Err(RadioError::NotConnected);
+        }
+        self.frequencies
+            .lock()
+            .map_err(|_| RadioError::NotConnected)?
+            .get(&self.slice)
+            .copied()
+            .ok_or_else(|| {
+                RadioError::CommandFailed(format!("Slice {} is not open", slice_letter(self.slice)))
+            })
+    }
+
+    fn send_raw(&mut self, command: &str) -> RadioResult<()> {
+        self.send_command(command)
+    }
+
+    fn backend_name(&self) -> &'static str {
+        "SmartSDR"
+    }
+}
+
+/// Letter SmartSDR shows for a slice (0 = A)
+pub fn slice_letter(slice: u8) -> char {
+    (b'A' + slice.min(7)) as char
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_replies() {
+        assert_eq!(parse_reply("R12|0|"), Some((12, 0, String::new())));
+        assert_eq!(
+            parse_reply("R3|50000016|Invalid slice"),
+            Some((3, 0x5000_0016, "Invalid slice".to_string()))
+        );
+        assert_eq!(parse_reply("S1A2B|slice 0 RF_frequency=14.025000"), None);
+    }
+
+    #[test]
+    fn reader_marks_the_connection_dead_when_it_ends() {
+        let (reply_tx, reply_rx) = mpsc::channel();
+        let frequencies = Mutex::new(HashMap::new());
+        let alive = AtomicBool::new(true);
+        let stream = "R1|0|\nS4A3F|slice 0 RF_frequency=14.025000\n".as_bytes();
+        read_status(stream, reply_tx, &frequencies, &alive);
+
+        assert_eq!(reply_rx.try_recv(), Ok((1, 0, String::new())));
+        assert_eq!(frequencies.lock().unwrap().get(&0), Some(&14025.0));
+        assert!(!alive.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn parses_slice_frequencies() {
+        assert_eq!(
+            parse_slice_frequency("S4A3F|slice 1 in_use=1 RF_frequency=7.025500 mode=CW"),
+            Some((1, 7025.5))
+        );
+        assert_eq!(parse_slice_frequency("S4A3F|slice 1 mode=USB"), None);
+        assert_eq!(
+            parse_slice_frequency("S4A3F|radio slices=4 panadapters=2"),
+            None
+        );
+    }
+}
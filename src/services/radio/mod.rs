@@ -1,11 +1,21 @@
 //! Radio controller abstraction for CAT control
 
+mod antenna;
+mod com;
+mod commander;
+mod flex;
 mod noop;
+mod omnirig_rig;
 mod rigctld;
 
 #[cfg(target_os = "windows")]
 mod omnirig;
+#[cfg(target_os = "windows")]
+mod omnirig_sink;
 
+pub use antenna::{switch_antenna, AntennaOutput};
+pub use commander::CommanderController;
+pub use flex::{slice_letter, FlexController};
 pub use noop::NoOpController;
 pub use rigctld::RigctldController;
 
@@ -28,14 +38,43 @@ pub enum RadioMode {
 }
 
 impl RadioMode {
-    /// Convert RBN mode string to RadioMode
-    pub fn from_rbn_mode(mode: &str) -> Self {
+    /// Convert an RBN mode string to the RadioMode for a spot at `frequency_khz`
+    pub fn from_rbn_mode(mode: &str, frequency_khz: f64) -> Self {
         match mode.to_uppercase().as_str() {
             "CW" => RadioMode::Cw,
             "RTTY" => RadioMode::Rtty,
             "FT8" | "FT4" | "PSK31" | "PSK63" | "JT65" | "JT9" | "WSPR" => RadioMode::Usb,
-            "SSB" => RadioMode::Usb, // Default to USB for SSB
-            _ => RadioMode::Cw,      // Default to CW for unknown modes
+            "SSB" => RadioMode::sideband(frequency_khz),
+            "USB" => RadioMode::Usb,
+            "LSB" => RadioMode::Lsb,
+            "AM" => RadioMode::Am,
+            "FM" => RadioMode::Fm,
+            _ => RadioMode::Cw, // Default to CW for unknown modes
+        }
+    }
+
+    /// Conventional SSB sideband: LSB below 10 MHz, USB above and on 60m
+    pub fn sideband(frequency_khz: f64) -> Self {
+        if frequency_khz < 10_000.0 && !(5_250.0..5_450.0).contains(&frequency_khz) {
+            RadioMode::Lsb
+        } else {
+            RadioMode::Usb
+        }
+    }
+
+    /// Parse a mode name as typed by the user (e.g. "usb", "CW", "pktusb")
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_uppercase().as_str() {
+            "CW" => Some(RadioMode::Cw),
+            "CWR" => Some(RadioMode::CwReverse),
+            "USB" => Some(RadioMode::Usb),
+            "LSB" => Some(RadioMode::Lsb),
+            "RTTY" => Some(RadioMode::Rtty),
+            "RTTYR" => Some(RadioMode::RttyReverse),
+            "AM" => Some(RadioMode::Am),
+            "FM" => Some(RadioMode::Fm),
+            "DATA" | "PKTUSB" => Some(RadioMode::Data),
+            _ => None,
         }
     }
 
@@ -55,6 +94,39 @@ impl RadioMode {
     }
 }
 
+/// Receive filter width in Hz to select when tuning, per mode (0 = leave it to the radio)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Passbands {
+    pub cw: u32,
+    pub ssb: u32,
+    pub rtty: u32,
+    pub data: u32,
+}
+
+impl Default for Passbands {
+    fn default() -> Self {
+        Self {
+            cw: 400,
+            ssb: 2400,
+            rtty: 300,
+            data: 3000,
+        }
+    }
+}
+
+impl Passbands {
+    /// Width for a mode (AM and FM always use the radio's default)
+    pub fn for_mode(&self, mode: RadioMode) -> u32 {
+        match mode {
+            RadioMode::Cw | RadioMode::CwReverse => self.cw,
+            RadioMode::Usb | RadioMode::Lsb => self.ssb,
+            RadioMode::Rtty | RadioMode::RttyReverse => self.rtty,
+            RadioMode::Data => self.data,
+            RadioMode::Am | RadioMode::Fm => 0,
+        }
+    }
+}
+
 /// Result type for radio operations
 pub type RadioResult<T> = Result<T, RadioError>;
 
@@ -67,6 +139,8 @@ pub enum RadioError {
     CommandFailed(String),
     Timeout,
     NotConfigured,
+    /// The tune was refused by the band-change guard
+    Refused(String),
 }
 
 impl std::fmt::Display for RadioError {
@@ -77,6 +151,7 @@ impl std::fmt::Display for RadioError {
             RadioError::CommandFailed(msg) => write!(f, "Command failed: {}", msg),
             RadioError::Timeout => write!(f, "Radio not responding"),
             RadioError::NotConfigured => write!(f, "Radio not configured"),
+            RadioError::Refused(reason) => write!(f, "Not tuned: {}", reason),
         }
     }
 }
@@ -98,8 +173,44 @@ pub trait RadioController: Send {
     /// Tune to a frequency (in kHz) and mode
     fn tune(&mut self, frequency_khz: f64, mode: RadioMode) -> RadioResult<()>;
 
+    /// Read the current VFO frequency (in kHz)
+    fn get_frequency(&mut self) -> RadioResult<f64> {
+        Err(RadioError::NotConnected)
+    }
+
+    /// Send a command in the backend's own syntax (see `RadioConfig::tune_steps`)
+    fn send_raw(&mut self, _command: &str) -> RadioResult<()> {
+        Err(RadioError::CommandFailed(format!(
+            "{} cannot send raw commands",
+            self.backend_name()
+        )))
+    }
+
+    /// Select one of the radio's antenna ports
+    fn set_antenna(&mut self, _antenna: u8) -> RadioResult<()> {
+        Err(RadioError::CommandFailed(format!(
+            "{} cannot select antennas",
+            self.backend_name()
+        )))
+    }
+
     /// Get a description of the backend
     fn backend_name(&self) -> &'static str;
+
+    /// Model name reported by the radio, if the backend knows it
+    fn rig_name(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Run extra commands after a tune (e.g. clear RIT), stopping at the first failure
+pub fn run_tune_steps(controller: &mut dyn RadioController, steps: &[String]) -> RadioResult<()> {
+    for step in steps {
+        controller.send_raw(step).map_err(|e| {
+            RadioError::CommandFailed(format!("Tune step \"{}\" failed: {}", step, e))
+        })?;
+    }
+    Ok(())
 }
 
 /// Factory function to create the appropriate controller
@@ -108,11 +219,23 @@ pub fn create_controller(config: &crate::config::RadioConfig) -> Box<dyn RadioCo
     if !config.enabled {
         return Box::new(NoOpController::new());
     }
+    if let Some(plugin) = crate::plugins::radio_plugin(&config.backend) {
+        return (plugin.create)(config);
+    }
     match config.backend.as_str() {
         "omnirig" => Box::new(OmniRigController::new(config.omnirig_rig)),
         "rigctld" => Box::new(RigctldController::new(
             config.rigctld_host.clone(),
             config.rigctld_port,
+            config.passbands,
+        )),
+        "commander" => Box::new(CommanderController::new(
+            config.commander_host.clone(),
+            config.commander_port,
+        )),
+        "flex" => Box::new(FlexController::new(
+            config.flex_host.clone(),
+            config.flex_slice,
         )),
         _ => Box::new(NoOpController::new()),
     }
@@ -123,8 +246,22 @@ pub fn create_controller(config: &crate::config::RadioConfig) -> Box<dyn RadioCo
     if !config.enabled {
         return Box::new(NoOpController::new());
     }
-    Box::new(RigctldController::new(
-        config.rigctld_host.clone(),
-        config.rigctld_port,
-    ))
+    if let Some(plugin) = crate::plugins::radio_plugin(&config.backend) {
+        return (plugin.create)(config);
+    }
+    match config.backend.as_str() {
+        "commander" => Box::new(CommanderController::new(
+            config.commander_host.clone(),
+            config.commander_port,
+        )),
+        "flex" => Box::new(FlexController::new(
+            config.flex_host.clone(),
+            config.flex_slice,
+        )),
+        _ => Box::new(RigctldController::new(
+            config.rigctld_host.clone(),
+            config.rigctld_port,
+            config.passbands,
+        )),
+    }
 }
@@ -1,145 +1,299 @@
 //! OmniRig radio controller for Windows (COM interop)
+//!
+//! COM objects belong to the apartment that created them, so OmniRig is driven
+//! from a dedicated thread that initializes COM once and keeps it initialized
+//! until the controller disconnects. That thread subscribes to OmniRig's
+//! change events and re-reads the rig's status and frequency when one
+//! arrives, so reads never wait on the COM server.
 
 #![cfg(target_os = "windows")]
 
+use super::omnirig_rig::{parse_property_write, rig_property_name, OmniRigRig};
+use super::omnirig_sink::{self as sink, EventSubscription};
 use super::{RadioController, RadioError, RadioMode, RadioResult};
+use crate::services::metrics::METRICS;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
 use winsafe::{self as w, co, prelude::*};
 
-/// OmniRig mode constants (from OmniRig type library)
-#[allow(dead_code)]
-mod omnirig_modes {
-    pub const PM_CW_U: i32 = 0x00800000;
-    pub const PM_CW_L: i32 = 0x01000000;
-    pub const PM_SSB_U: i32 = 0x02000000;
-    pub const PM_SSB_L: i32 = 0x04000000;
-    pub const PM_DIG_U: i32 = 0x08000000;
-    pub const PM_DIG_L: i32 = 0x10000000;
-    pub const PM_AM: i32 = 0x20000000;
-    pub const PM_FM: i32 = 0x40000000;
+/// How often status and frequency are re-read without an event, so a COM
+/// server that went away is noticed
+const STATUS_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How long to wait for the rig thread to connect or answer a command
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Commands sent to the rig thread
+enum RigCommand {
+    Tune {
+        frequency_khz: f64,
+        mode: RadioMode,
+        reply: mpsc::Sender<RadioResult<()>>,
+    },
+    Put {
+        property: String,
+        value: i32,
+        reply: mpsc::Sender<RadioResult<()>>,
+    },
+}
+
+/// Latest state reported by the rig thread
+struct RigState {
+    status: RadioResult<()>,
+    frequency_khz: Option<f64>,
+}
+
+/// Handle to a running rig thread; dropping it ends the thread
+struct RigThread {
+    cmd_tx: mpsc::Sender<RigCommand>,
+    state: Arc<Mutex<RigState>>,
+    thread_id: u32,
+}
+
+impl Drop for RigThread {
+    fn drop(&mut self) {
+        sink::quit(self.thread_id);
+    }
 }
 
 /// Controller for OmniRig (Windows COM server)
 pub struct OmniRigController {
     rig_number: u8,
-    omnirig: Option<w::IDispatch>,
-    rig: Option<w::IDispatch>,
+    thread: Option<RigThread>,
+    rig_type: Option<String>,
 }
 
 impl OmniRigController {
     pub fn new(rig_number: u8) -> Self {
         Self {
             rig_number: rig_number.clamp(1, 2),
-            omnirig: None,
-            rig: None,
-        }
-    }
-
-    /// Convert RadioMode to OmniRig mode constant
-    fn mode_to_omnirig(mode: RadioMode) -> i32 {
-        match mode {
-            RadioMode::Cw => omnirig_modes::PM_CW_U,
-            RadioMode::CwReverse => omnirig_modes::PM_CW_L,
-            RadioMode::Usb => omnirig_modes::PM_SSB_U,
-            RadioMode::Lsb => omnirig_modes::PM_SSB_L,
-            RadioMode::Am => omnirig_modes::PM_AM,
-            RadioMode::Fm => omnirig_modes::PM_FM,
-            RadioMode::Rtty => omnirig_modes::PM_DIG_U,
-            RadioMode::RttyReverse => omnirig_modes::PM_DIG_L,
-            RadioMode::Data => omnirig_modes::PM_DIG_U,
+            thread: None,
+            rig_type: None,
         }
     }
+}
 
-    /// Get the rig property name based on rig number
-    fn rig_property_name(&self) -> &'static str {
-        if self.rig_number == 2 {
-            "Rig2"
-        } else {
-            "Rig1"
-        }
+impl OmniRigController {
+    /// Hand a command to the rig thread and wait for its result
+    fn request(
+        &self,
+        command: impl FnOnce(mpsc::Sender<RadioResult<()>>) -> RigCommand,
+    ) -> RadioResult<()> {
+        let thread = self.thread.as_ref().ok_or(RadioError::NotConnected)?;
+        let started = Instant::now();
+        let (reply, reply_rx) = mpsc::channel();
+        let result = thread
+            .cmd_tx
+            .send(command(reply))
+            .map_err(|_| RadioError::NotConnected)
+            .and_then(|()| {
+                sink::wake(thread.thread_id);
+                reply_rx
+                    .recv_timeout(REPLY_TIMEOUT)
+                    .map_err(|_| RadioError::Timeout)?
+            });
+        METRICS.radio_command(started.elapsed());
+        result
     }
 }
 
 impl RadioController for OmniRigController {
     fn is_connected(&self) -> bool {
-        self.rig.is_some()
+        self.thread.is_some()
     }
 
     fn connect(&mut self) -> RadioResult<()> {
-        // Initialize COM if not already done
-        let _com_guard =
-            w::CoInitializeEx(co::COINIT::APARTMENTTHREADED | co::COINIT::DISABLE_OLE1DDE)
-                .map_err(|e| {
-                    RadioError::ConnectionFailed(format!("Failed to initialize COM: {}", e))
-                })?;
-
-        // Get CLSID for OmniRig
-        let clsid = w::CLSIDFromProgID("Omnirig.OmnirigX").map_err(|e| {
-            RadioError::ConnectionFailed(format!(
-                "OmniRig not found. Is it installed? Error: {}",
-                e
-            ))
-        })?;
-
-        // Create OmniRig instance
-        let omnirig: w::IDispatch =
-            w::CoCreateInstance(&clsid, None::<&w::IUnknown>, co::CLSCTX::LOCAL_SERVER).map_err(
-                |e| {
-                    RadioError::ConnectionFailed(format!(
-                        "Failed to create OmniRig instance. Is OmniRig running? Error: {}",
-                        e
-                    ))
-                },
-            )?;
-
-        // Get the rig object (Rig1 or Rig2)
-        let rig_name = self.rig_property_name();
-        let rig_variant = omnirig.invoke_get(rig_name, &[]).map_err(|e| {
-            RadioError::ConnectionFailed(format!("Failed to get {}: {}", rig_name, e))
-        })?;
-
-        // Extract IDispatch from Variant
-        let rig = match rig_variant {
-            w::Variant::Dispatch(disp) => disp,
-            _ => {
-                return Err(RadioError::ConnectionFailed(format!(
-                    "Failed to get {} interface: not an IDispatch",
-                    rig_name
-                )))
-            }
-        };
+        self.disconnect();
 
-        self.omnirig = Some(omnirig);
-        self.rig = Some(rig);
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let state = Arc::new(Mutex::new(RigState {
+            status: Err(RadioError::NotConnected),
+            frequency_khz: None,
+        }));
+        let rig_number = self.rig_number;
+        let task_state = Arc::clone(&state);
+        std::thread::spawn(move || rig_task(rig_number, cmd_rx, ready_tx, task_state));
 
+        let (rig_type, thread_id) = ready_rx
+            .recv_timeout(REPLY_TIMEOUT)
+            .map_err(|_| RadioError::Timeout)??;
+        self.rig_type = rig_type;
+        self.thread = Some(RigThread {
+            cmd_tx,
+            state,
+            thread_id,
+        });
         Ok(())
     }
 
     fn disconnect(&mut self) {
-        self.rig = None;
-        self.omnirig = None;
+        self.thread = None;
+        self.rig_type = None;
     }
 
     fn tune(&mut self, frequency_khz: f64, mode: RadioMode) -> RadioResult<()> {
-        let rig = self.rig.as_ref().ok_or(RadioError::NotConnected)?;
-
-        // Convert frequency from kHz to Hz
-        let freq_hz = (frequency_khz * 1000.0) as i32;
-
-        // Set frequency (FreqA property)
-        let freq_variant = w::Variant::I4(freq_hz);
-        rig.invoke_put("FreqA", &freq_variant)
-            .map_err(|e| RadioError::CommandFailed(format!("Failed to set frequency: {}", e)))?;
+        self.request(|reply| RigCommand::Tune {
+            frequency_khz,
+            mode,
+            reply,
+        })
+    }
 
-        // Set mode
-        let mode_value = Self::mode_to_omnirig(mode);
-        let mode_variant = w::Variant::I4(mode_value);
-        rig.invoke_put("Mode", &mode_variant)
-            .map_err(|e| RadioError::CommandFailed(format!("Failed to set mode: {}", e)))?;
+    fn send_raw(&mut self, command: &str) -> RadioResult<()> {
+        let (property, value) = parse_property_write(command).ok_or_else(|| {
+            RadioError::CommandFailed(format!("Expected Property=value, got {}", command))
+        })?;
+        let property = property.to_string();
+        self.request(|reply| RigCommand::Put {
+            property,
+            value,
+            reply,
+        })
+    }
 
-        Ok(())
+    fn get_frequency(&mut self) -> RadioResult<f64> {
+        let thread = self.thread.as_ref().ok_or(RadioError::NotConnected)?;
+        let state = thread.state.lock().map_err(|_| RadioError::NotConnected)?;
+        state.status.clone()?;
+        state.frequency_khz.ok_or(RadioError::Timeout)
     }
 
     fn backend_name(&self) -> &'static str {
         "OmniRig"
     }
+
+    fn rig_name(&self) -> Option<String> {
+        self.rig_type.clone()
+    }
+}
+
+/// Own the COM apartment and OmniRig objects until the controller goes away
+fn rig_task(
+    rig_number: u8,
+    cmd_rx: mpsc::Receiver<RigCommand>,
+    ready_tx: mpsc::Sender<RadioResult<(Option<String>, u32)>>,
+    state: Arc<Mutex<RigState>>,
+) {
+    // Declared first so COM is torn down only after the objects below are released
+    let _com_guard =
+        match w::CoInitializeEx(co::COINIT::APARTMENTTHREADED | co::COINIT::DISABLE_OLE1DDE) {
+            Ok(guard) => guard,
+            Err(e) => {
+                let _ = ready_tx.send(Err(RadioError::ConnectionFailed(format!(
+                    "Failed to initialize COM: {}",
+                    e
+                ))));
+                return;
+            }
+        };
+
+    let (omnirig, rig) = match open_rig(rig_number) {
+        Ok(objects) => objects,
+        Err(e) => {
+            let _ = ready_tx.send(Err(e));
+            return;
+        }
+    };
+    // Declared after the objects so it unsubscribes before they are released
+    let changed = Arc::new(AtomicBool::new(false));
+    let _events = match unsafe {
+        EventSubscription::advise(omnirig.ptr() as *mut _, rig_number, changed.clone())
+    } {
+        Ok(events) => events,
+        Err(e) => {
+            let _ = ready_tx.send(Err(e));
+            return;
+        }
+    };
+    refresh_state(&rig, &state);
+    let mut last_refresh = Instant::now();
+    if ready_tx
+        .send(Ok((rig.rig_type(), sink::current_thread_id())))
+        .is_err()
+    {
+        return;
+    }
+
+    // Events and the wake-ups for commands both arrive as thread messages
+    while sink::wait_for_messages(STATUS_CHECK_INTERVAL) {
+        let mut refresh = changed.swap(false, Ordering::SeqCst)
+            || last_refresh.elapsed() >= STATUS_CHECK_INTERVAL;
+        loop {
+            match cmd_rx.try_recv() {
+                Ok(RigCommand::Tune {
+                    frequency_khz,
+                    mode,
+                    reply,
+                }) => {
+                    let _ = reply.send(rig.tune(frequency_khz, mode));
+                }
+                Ok(RigCommand::Put {
+                    property,
+                    value,
+                    reply,
+                }) => {
+                    let _ = reply.send(rig.put_int(&property, value));
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => return,
+            }
+            refresh = true;
+        }
+        if refresh {
+            refresh_state(&rig, &state);
+            last_refresh = Instant::now();
+        }
+    }
+}
+
+/// Create the OmniRig server object and get the rig from it
+fn open_rig(rig_number: u8) -> RadioResult<(w::IDispatch, OmniRigRig<w::IDispatch>)> {
+    // Get CLSID for OmniRig
+    let clsid = w::CLSIDFromProgID("Omnirig.OmnirigX").map_err(|e| {
+        RadioError::ConnectionFailed(format!("OmniRig not found. Is it installed? Error: {}", e))
+    })?;
+
+    // Create OmniRig instance
+    let omnirig: w::IDispatch =
+        w::CoCreateInstance(&clsid, None::<&w::IUnknown>, co::CLSCTX::LOCAL_SERVER).map_err(
+            |e| {
+                RadioError::ConnectionFailed(format!(
+                    "Failed to create OmniRig instance. Is OmniRig running? Error: {}",
+                    e
+                ))
+            },
+        )?;
+
+    // Get the rig object (Rig1 or Rig2)
+    let rig_name = rig_property_name(rig_number);
+    let rig_variant = omnirig
+        .invoke_get(rig_name, &[])
+        .map_err(|e| RadioError::ConnectionFailed(format!("Failed to get {}: {}", rig_name, e)))?;
+
+    // Extract IDispatch from Variant
+    let rig = match rig_variant {
+        w::Variant::Dispatch(disp) => disp,
+        _ => {
+            return Err(RadioError::ConnectionFailed(format!(
+                "Failed to get {} interface: not an IDispatch",
+                rig_name
+            )))
+        }
+    };
+
+    let rig = OmniRigRig::new(rig);
+    rig.check_status()?;
+    Ok((omnirig, rig))
+}
+
+/// Publish the rig's current status and frequency
+fn refresh_state(rig: &OmniRigRig<w::IDispatch>, state: &Mutex<RigState>) {
+    let status = rig.check_status();
+    let frequency_khz = status.is_ok().then(|| rig.frequency().ok()).flatten();
+    if let Ok(mut state) = state.lock() {
+        state.status = status;
+        state.frequency_khz = frequency_khz;
+    }
 }
@@ -0,0 +1,394 @@
+//! OmniRig rig object logic (status, tuning, mode mapping), independent of COM setup
+
+#![cfg_attr(not(target_os = "windows"), allow(dead_code))]
+
+use super::com::{ComValue, Dispatch};
+use super::{RadioError, RadioMode, RadioResult};
+
+/// OmniRig mode constants (from OmniRig type library)
+#[allow(dead_code)]
+mod omnirig_modes {
+    pub const PM_CW_U: i32 = 0x00800000;
+    pub const PM_CW_L: i32 = 0x01000000;
+    pub const PM_SSB_U: i32 = 0x02000000;
+    pub const PM_SSB_L: i32 = 0x04000000;
+    pub const PM_DIG_U: i32 = 0x08000000;
+    pub const PM_DIG_L: i32 = 0x10000000;
+    pub const PM_AM: i32 = 0x20000000;
+    pub const PM_FM: i32 = 0x40000000;
+}
+
+/// OmniRig rig status constants (from OmniRig type library)
+#[allow(dead_code)]
+mod omnirig_status {
+    pub const ST_NOTCONFIGURED: i32 = 0;
+    pub const ST_DISABLED: i32 = 1;
+    pub const ST_PORTBUSY: i32 = 2;
+    pub const ST_NOTRESPONDING: i32 = 3;
+    pub const ST_ONLINE: i32 = 4;
+}
+
+/// Dispatch IDs of the IOmniRigXEvents methods (from OmniRig type library)
+#[allow(dead_code)]
+pub mod omnirig_events {
+    pub const VISIBLE_CHANGE: i32 = 1;
+    pub const RIG_TYPE_CHANGE: i32 = 2;
+    pub const STATUS_CHANGE: i32 = 3;
+    pub const PARAMS_CHANGE: i32 = 4;
+    pub const CUSTOM_REPLY: i32 = 5;
+}
+
+/// Whether an OmniRig event means the given rig's status or frequency may
+/// have changed. `event_rig` is the event's RigNumber argument, if it had one.
+pub fn event_changes_rig(dispid: i32, event_rig: Option<i32>, rig_number: u8) -> bool {
+    matches!(
+        dispid,
+        omnirig_events::RIG_TYPE_CHANGE
+            | omnirig_events::STATUS_CHANGE
+            | omnirig_events::PARAMS_CHANGE
+    ) && event_rig.is_none_or(|rig| rig == i32::from(rig_number.clamp(1, 2)))
+}
+
+/// Convert RadioMode to OmniRig mode constant
+pub fn mode_to_omnirig(mode: RadioMode) -> i32 {
+    match mode {
+        RadioMode::Cw => omnirig_modes::PM_CW_U,
+        RadioMode::CwReverse => omnirig_modes::PM_CW_L,
+        RadioMode::Usb => omnirig_modes::PM_SSB_U,
+        RadioMode::Lsb => omnirig_modes::PM_SSB_L,
+        RadioMode::Am => omnirig_modes::PM_AM,
+        RadioMode::Fm => omnirig_modes::PM_FM,
+        RadioMode::Rtty => omnirig_modes::PM_DIG_U,
+        RadioMode::RttyReverse => omnirig_modes::PM_DIG_L,
+        RadioMode::Data => omnirig_modes::PM_DIG_U,
+    }
+}
+
+/// Property of the OmniRig object holding the given rig (1 or 2)
+pub fn rig_property_name(rig_number: u8) -> &'static str {
+    if rig_number.clamp(1, 2) == 2 {
+        "Rig2"
+    } else {
+        "Rig1"
+    }
+}
+
+/// Parse a `Property=value` tune step (decimal or 0x hex, e.g. `Rit=0x40000`)
+pub fn parse_property_write(command: &str) -> Option<(&str, i32)> {
+    let (property, value) = command.split_once('=')?;
+    let value = value.trim();
+    let value = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16).ok()? as i32,
+        None => value.parse().ok()?,
+    };
+    Some((property.trim(), value))
+}
+
+/// An OmniRig rig object (Rig1 or Rig2)
+pub struct OmniRigRig<D: Dispatch> {
+    rig: D,
+}
+
+impl<D: Dispatch> OmniRigRig<D> {
+    pub fn new(rig: D) -> Self {
+        Self { rig }
+    }
+
+    /// Read a string property
+    pub fn get_string(&self, property: &str) -> Option<String> {
+        match self.rig.get(property).ok()? {
+            ComValue::Bstr(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Model name of the configured rig, if set
+    pub fn rig_type(&self) -> Option<String> {
+        self.get_string("RigType").filter(|s| !s.is_empty())
+    }
+
+    /// Check that the rig reports itself online, mapping other states to an error
+    pub fn check_status(&self) -> RadioResult<()> {
+        let status = match self.rig.get("Status") {
+            Ok(ComValue::I4(status)) => status,
+            Ok(_) => {
+                return Err(RadioError::CommandFailed(
+                    "Unexpected rig status".to_string(),
+                ))
+            }
+            Err(e) => {
+                return Err(RadioError::CommandFailed(format!(
+                    "Failed to read rig status: {}",
+                    e
+                )))
+            }
+        };
+
+        match status {
+            omnirig_status::ST_ONLINE => Ok(()),
+            omnirig_status::ST_NOTRESPONDING => Err(RadioError::Timeout),
+            omnirig_status::ST_NOTCONFIGURED => Err(RadioError::NotConfigured),
+            _ => {
+                let detail = self
+                    .get_string("StatusStr")
+                    .unwrap_or_else(|| format!("status {}", status));
+                Err(RadioError::ConnectionFailed(format!("OmniRig: {}", detail)))
+            }
+        }
+    }
+
+    /// Set frequency and mode
+    pub fn tune(&self, frequency_khz: f64, mode: RadioMode) -> RadioResult<()> {
+        // The COM server accepts writes even when the rig is offline, so check first
+        self.check_status()?;
+
+        // Convert frequency from kHz to Hz
+        let freq_hz = (frequency_khz * 1000.0) as i32;
+
+        // Set frequency (FreqA property)
+        self.rig
+            .put("FreqA", ComValue::I4(freq_hz))
+            .map_err(|e| RadioError::CommandFailed(format!("Failed to set frequency: {}", e)))?;
+
+        // Set mode
+        self.rig
+            .put("Mode", ComValue::I4(mode_to_omnirig(mode)))
+            .map_err(|e| RadioError::CommandFailed(format!("Failed to set mode: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Write an integer property of the rig
+    pub fn put_int(&self, property: &str, value: i32) -> RadioResult<()> {
+        self.check_status()?;
+        self.rig
+            .put(property, ComValue::I4(value))
+            .map_err(|e| RadioError::CommandFailed(format!("Failed to set {}: {}", property, e)))
+    }
+
+    /// Read the current frequency in kHz
+    pub fn frequency(&self) -> RadioResult<f64> {
+        // Freq follows whichever VFO is active
+        match self.rig.get("Freq") {
+            Ok(ComValue::I4(freq_hz)) => Ok(freq_hz as f64 / 1000.0),
+            Ok(_) => Err(RadioError::CommandFailed(
+                "Unexpected frequency value".to_string(),
+            )),
+            Err(e) => Err(RadioError::CommandFailed(format!(
+                "Failed to read frequency: {}",
+                e
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    /// In-memory rig object recording every property write
+    #[derive(Default)]
+    struct MockRig {
+        properties: RefCell<HashMap<String, ComValue>>,
+        writes: RefCell<Vec<(String, ComValue)>>,
+        fail_puts: bool,
+    }
+
+    impl MockRig {
+        fn with(properties: &[(&str, ComValue)]) -> Self {
+            let rig = Self::default();
+            for (name, value) in properties {
+                rig.properties
+                    .borrow_mut()
+                    .insert(name.to_string(), value.clone());
+            }
+            rig
+        }
+    }
+
+    impl Dispatch for MockRig {
+        fn get(&self, property: &str) -> Result<ComValue, String> {
+            self.properties
+                .borrow()
+                .get(property)
+                .cloned()
+                .ok_or_else(|| format!("Unknown name: {}", property))
+        }
+
+        fn put(&self, property: &str, value: ComValue) -> Result<(), String> {
+            if self.fail_puts {
+                return Err("Access denied".to_string());
+            }
+            self.writes
+                .borrow_mut()
+                .push((property.to_string(), value.clone()));
+            self.properties
+                .borrow_mut()
+                .insert(property.to_string(), value);
+            Ok(())
+        }
+    }
+
+    fn online() -> MockRig {
+        MockRig::with(&[("Status", ComValue::I4(omnirig_status::ST_ONLINE))])
+    }
+
+    #[test]
+    fn maps_modes_to_omnirig_constants() {
+        assert_eq!(mode_to_omnirig(RadioMode::Cw), omnirig_modes::PM_CW_U);
+        assert_eq!(
+            mode_to_omnirig(RadioMode::CwReverse),
+            omnirig_modes::PM_CW_L
+        );
+        assert_eq!(mode_to_omnirig(RadioMode::Usb), omnirig_modes::PM_SSB_U);
+        assert_eq!(mode_to_omnirig(RadioMode::Lsb), omnirig_modes::PM_SSB_L);
+        assert_eq!(mode_to_omnirig(RadioMode::Am), omnirig_modes::PM_AM);
+        assert_eq!(mode_to_omnirig(RadioMode::Fm), omnirig_modes::PM_FM);
+        assert_eq!(mode_to_omnirig(RadioMode::Rtty), omnirig_modes::PM_DIG_U);
+        assert_eq!(
+            mode_to_omnirig(RadioMode::RttyReverse),
+            omnirig_modes::PM_DIG_L
+        );
+        assert_eq!(mode_to_omnirig(RadioMode::Data), omnirig_modes::PM_DIG_U);
+    }
+
+    #[test]
+    fn selects_rig_property() {
+        assert_eq!(rig_property_name(1), "Rig1");
+        assert_eq!(rig_property_name(2), "Rig2");
+        assert_eq!(rig_property_name(0), "Rig1");
+        assert_eq!(rig_property_name(9), "Rig2");
+    }
+
+    #[test]
+    fn picks_events_for_the_rig() {
+        assert!(event_changes_rig(omnirig_events::PARAMS_CHANGE, Some(1), 1));
+        assert!(event_changes_rig(omnirig_events::STATUS_CHANGE, Some(2), 2));
+        assert!(event_changes_rig(omnirig_events::STATUS_CHANGE, None, 2));
+        assert!(!event_changes_rig(
+            omnirig_events::PARAMS_CHANGE,
+            Some(2),
+            1
+        ));
+        assert!(!event_changes_rig(omnirig_events::VISIBLE_CHANGE, None, 1));
+        assert!(!event_changes_rig(omnirig_events::CUSTOM_REPLY, Some(1), 1));
+    }
+
+    #[test]
+    fn maps_status_to_errors() {
+        assert!(OmniRigRig::new(online()).check_status().is_ok());
+
+        let rig = MockRig::with(&[("Status", ComValue::I4(omnirig_status::ST_NOTRESPONDING))]);
+        assert!(matches!(
+            OmniRigRig::new(rig).check_status(),
+            Err(RadioError::Timeout)
+        ));
+
+        let rig = MockRig::with(&[("Status", ComValue::I4(omnirig_status::ST_NOTCONFIGURED))]);
+        assert!(matches!(
+            OmniRigRig::new(rig).check_status(),
+            Err(RadioError::NotConfigured)
+        ));
+
+        let rig = MockRig::with(&[
+            ("Status", ComValue::I4(omnirig_status::ST_PORTBUSY)),
+            ("StatusStr", ComValue::Bstr("Port busy".to_string())),
+        ]);
+        match OmniRigRig::new(rig).check_status() {
+            Err(RadioError::ConnectionFailed(msg)) => assert_eq!(msg, "OmniRig: Port busy"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let rig = MockRig::with(&[("Status", ComValue::I4(omnirig_status::ST_DISABLED))]);
+        match OmniRigRig::new(rig).check_status() {
+            Err(RadioError::ConnectionFailed(msg)) => assert_eq!(msg, "OmniRig: status 1"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn reports_unreadable_status() {
+        assert!(matches!(
+            OmniRigRig::new(MockRig::default()).check_status(),
+            Err(RadioError::CommandFailed(_))
+        ));
+
+        let rig = MockRig::with(&[("Status", ComValue::Bstr("online".to_string()))]);
+        assert!(matches!(
+            OmniRigRig::new(rig).check_status(),
+            Err(RadioError::CommandFailed(_))
+        ));
+    }
+
+    #[test]
+    fn tunes_frequency_then_mode() {
+        let rig = OmniRigRig::new(online());
+        rig.tune(14025.5, RadioMode::Cw).unwrap();
+        assert_eq!(
+            *rig.rig.writes.borrow(),
+            vec![
+                ("FreqA".to_string(), ComValue::I4(14_025_500)),
+                ("Mode".to_string(), ComValue::I4(omnirig_modes::PM_CW_U)),
+            ]
+        );
+    }
+
+    #[test]
+    fn does_not_write_to_offline_rig() {
+        let rig = OmniRigRig::new(MockRig::with(&[(
+            "Status",
+            ComValue::I4(omnirig_status::ST_NOTRESPONDING),
+        )]));
+        assert!(rig.tune(7030.0, RadioMode::Cw).is_err());
+        assert!(rig.rig.writes.borrow().is_empty());
+    }
+
+    #[test]
+    fn reports_failed_writes() {
+        let mut mock = online();
+        mock.fail_puts = true;
+        match OmniRigRig::new(mock).tune(7030.0, RadioMode::Cw) {
+            Err(RadioError::CommandFailed(msg)) => {
+                assert_eq!(msg, "Failed to set frequency: Access denied")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_property_writes() {
+        assert_eq!(parse_property_write("Rit=0x40000"), Some(("Rit", 0x40000)));
+        assert_eq!(
+            parse_property_write(" RitOffset = -50 "),
+            Some(("RitOffset", -50))
+        );
+        assert_eq!(parse_property_write("Rit"), None);
+        assert_eq!(parse_property_write("Rit=off"), None);
+
+        let rig = OmniRigRig::new(online());
+        rig.put_int("Rit", 0x40000).unwrap();
+        assert_eq!(
+            *rig.rig.writes.borrow(),
+            vec![("Rit".to_string(), ComValue::I4(0x40000))]
+        );
+    }
+
+    #[test]
+    fn reads_frequency_and_rig_type() {
+        let rig = OmniRigRig::new(MockRig::with(&[
+            ("Freq", ComValue::I4(3_525_000)),
+            ("RigType", ComValue::Bstr("IC-7300".to_string())),
+        ]));
+        assert_eq!(rig.frequency().unwrap(), 3525.0);
+        assert_eq!(rig.rig_type().as_deref(), Some("IC-7300"));
+
+        let rig = OmniRigRig::new(MockRig::with(&[("RigType", ComValue::Bstr(String::new()))]));
+        assert!(rig.frequency().is_err());
+        assert_eq!(rig.rig_type(), None);
+    }
+}
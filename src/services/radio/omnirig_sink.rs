@@ -0,0 +1,373 @@
+//! OmniRig event sink and the rig thread's message pump (Windows only)
+//!
+//! OmniRig raises `StatusChange` and `ParamsChange` through a COM connection
+//! point. The sink is a minimal IDispatch that only flags that the rig changed;
+//! the rig thread re-reads status and frequency when it sees the flag. Events
+//! arrive as window messages, so the thread waits in [`wait_for_messages`]
+//! instead of polling.
+
+#![cfg(target_os = "windows")]
+
+use super::omnirig_rig::event_changes_rig;
+use super::{RadioError, RadioResult};
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+type HResult = i32;
+
+const S_OK: HResult = 0;
+const E_NOINTERFACE: HResult = 0x8000_4002_u32 as i32;
+const E_NOTIMPL: HResult = 0x8000_4001_u32 as i32;
+const VT_I2: u16 = 2;
+const VT_I4: u16 = 3;
+const QS_ALLINPUT: u32 = 0x04FF;
+const PM_REMOVE: u32 = 0x0001;
+const WM_QUIT: u32 = 0x0012;
+/// Posted to wake the rig thread when a command is queued
+const WM_APP: u32 = 0x8000;
+
+#[repr(C)]
+#[derive(PartialEq, Eq)]
+struct Guid(u32, u16, u16, [u8; 8]);
+
+const IID_IUNKNOWN: Guid = Guid(0, 0, 0, [0xC0, 0, 0, 0, 0, 0, 0, 0x46]);
+const IID_IDISPATCH: Guid = Guid(0x0002_0400, 0, 0, [0xC0, 0, 0, 0, 0, 0, 0, 0x46]);
+const IID_ICONNECTION_POINT_CONTAINER: Guid = Guid(
+    0xB196_B284,
+    0xBAB4,
+    0x101A,
+    [0xB6, 0x9C, 0x00, 0xAA, 0x00, 0x34, 0x1D, 0x07],
+);
+/// IOmniRigXEvents dispinterface (from OmniRig type library)
+const DIID_IOMNIRIGX_EVENTS: Guid = Guid(
+    0x2219_175F,
+    0xE561,
+    0x47E7,
+    [0xAD, 0x17, 0x73, 0xC4, 0xD8, 0x89, 0x1A, 0xA1],
+);
+
+#[repr(C)]
+struct Variant {
+    vt: u16,
+    reserved: [u16; 3],
+    value: [usize; 2],
+}
+
+#[repr(C)]
+struct DispParams {
+    args: *const Variant,
+    named_args: *const i32,
+    arg_count: u32,
+    named_arg_count: u32,
+}
+
+#[repr(C)]
+struct Msg {
+    hwnd: isize,
+    message: u32,
+    wparam: usize,
+    lparam: isize,
+    time: u32,
+    pt: [i32; 2],
+    private: u32,
+}
+
+#[link(name = "user32")]
+extern "system" {
+    fn MsgWaitForMultipleObjects(
+        count: u32,
+        handles: *const isize,
+        wait_all: i32,
+        milliseconds: u32,
+        wake_mask: u32,
+    ) -> u32;
+    fn PeekMessageW(msg: *mut Msg, hwnd: isize, min: u32, max: u32, remove: u32) -> i32;
+    fn TranslateMessage(msg: *const Msg) -> i32;
+    fn DispatchMessageW(msg: *const Msg) -> isize;
+    fn PostThreadMessageW(thread_id: u32, message: u32, wparam: usize, lparam: isize) -> i32;
+}
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn GetCurrentThreadId() -> u32;
+}
+
+#[repr(C)]
+struct UnknownVtbl {
+    query_interface:
+        unsafe extern "system" fn(*mut c_void, *const Guid, *mut *mut c_void) -> HResult,
+    add_ref: unsafe extern "system" fn(*mut c_void) -> u32,
+    release: unsafe extern "system" fn(*mut c_void) -> u32,
+}
+
+#[repr(C)]
+struct ConnectionPointContainerVtbl {
+    unknown: UnknownVtbl,
+    enum_connection_points: *const c_void,
+    find_connection_point:
+        unsafe extern "system" fn(*mut c_void, *const Guid, *mut *mut c_void) -> HResult,
+}
+
+#[repr(C)]
+struct ConnectionPointVtbl {
+    unknown: UnknownVtbl,
+    get_connection_interface: *const c_void,
+    get_connection_point_container: *const c_void,
+    advise: unsafe extern "system" fn(*mut c_void, *mut c_void, *mut u32) -> HResult,
+    unadvise: unsafe extern "system" fn(*mut c_void, u32) -> HResult,
+}
+
+#[repr(C)]
+struct DispatchVtbl {
+    unknown: UnknownVtbl,
+    get_type_info_count: unsafe extern "system" fn(*mut c_void, *mut u32) -> HResult,
+    get_type_info: unsafe extern "system" fn(*mut c_void, u32, u32, *mut *mut c_void) -> HResult,
+    get_ids_of_names: unsafe extern "system" fn(
+        *mut c_void,
+        *const Guid,
+        *const *const u16,
+        u32,
+        u32,
+        *mut i32,
+    ) -> HResult,
+    invoke: unsafe extern "system" fn(
+        *mut c_void,
+        i32,
+        *const Guid,
+        u32,
+        u16,
+        *const DispParams,
+        *mut Variant,
+        *mut c_void,
+        *mut u32,
+    ) -> HResult,
+}
+
+/// The vtable of a COM object, which is the first field of the object
+unsafe fn vtbl<T>(object: *mut c_void) -> &'static T {
+    &**(object as *mut *const T)
+}
+
+/// IDispatch object handed to OmniRig's connection point
+#[repr(C)]
+struct Sink {
+    vtbl: *const DispatchVtbl,
+    refs: AtomicU32,
+    rig_number: u8,
+    changed: Arc<AtomicBool>,
+}
+
+static SINK_VTBL: DispatchVtbl = DispatchVtbl {
+    unknown: UnknownVtbl {
+        query_interface: sink_query_interface,
+        add_ref: sink_add_ref,
+        release: sink_release,
+    },
+    get_type_info_count: sink_get_type_info_count,
+    get_type_info: sink_get_type_info,
+    get_ids_of_names: sink_get_ids_of_names,
+    invoke: sink_invoke,
+};
+
+unsafe extern "system" fn sink_query_interface(
+    this: *mut c_void,
+    iid: *const Guid,
+    out: *mut *mut c_void,
+) -> HResult {
+    let iid = &*iid;
+    if *iid == IID_IUNKNOWN || *iid == IID_IDISPATCH || *iid == DIID_IOMNIRIGX_EVENTS {
+        sink_add_ref(this);
+        *out = this;
+        S_OK
+    } else {
+        *out = std::ptr::null_mut();
+        E_NOINTERFACE
+    }
+}
+
+unsafe extern "system" fn sink_add_ref(this: *mut c_void) -> u32 {
+    (*(this as *const Sink)).refs.fetch_add(1, Ordering::SeqCst) + 1
+}
+
+unsafe extern "system" fn sink_release(this: *mut c_void) -> u32 {
+    let refs = (*(this as *const Sink)).refs.fetch_sub(1, Ordering::SeqCst) - 1;
+    if refs == 0 {
+        drop(Box::from_raw(this as *mut Sink));
+    }
+    refs
+}
+
+unsafe extern "system" fn sink_get_type_info_count(_: *mut c_void, count: *mut u32) -> HResult {
+    *count = 0;
+    S_OK
+}
+
+unsafe extern "system" fn sink_get_type_info(
+    _: *mut c_void,
+    _: u32,
+    _: u32,
+    _: *mut *mut c_void,
+) -> HResult {
+    E_NOTIMPL
+}
+
+unsafe extern "system" fn sink_get_ids_of_names(
+    _: *mut c_void,
+    _: *const Guid,
+    _: *const *const u16,
+    _: u32,
+    _: u32,
+    _: *mut i32,
+) -> HResult {
+    E_NOTIMPL
+}
+
+unsafe extern "system" fn sink_invoke(
+    this: *mut c_void,
+    dispid: i32,
+    _: *const Guid,
+    _: u32,
+    _: u16,
+    params: *const DispParams,
+    _: *mut Variant,
+    _: *mut c_void,
+    _: *mut u32,
+) -> HResult {
+    let sink = &*(this as *const Sink);
+    // Arguments arrive last first, so RigNumber is the last one
+    let event_rig = params
+        .as_ref()
+        .filter(|params| params.arg_count > 0 && !params.args.is_null())
+        .map(|params| &*params.args.add(params.arg_count as usize - 1))
+        .and_then(|arg| match arg.vt {
+            VT_I4 => Some(arg.value[0] as u32 as i32),
+            VT_I2 => Some(arg.value[0] as u16 as i16 as i32),
+            _ => None,
+        });
+    if event_changes_rig(dispid, event_rig, sink.rig_number) {
+        sink.changed.store(true, Ordering::SeqCst);
+    }
+    S_OK
+}
+
+/// A sink subscribed to OmniRig's events; unsubscribes when dropped
+pub struct EventSubscription {
+    connection_point: *mut c_void,
+    cookie: u32,
+}
+
+impl EventSubscription {
+    /// Subscribe to the events of the OmniRig object at `omnirig`, flagging
+    /// `changed` whenever rig `rig_number` changes
+    ///
+    /// # Safety
+    /// `omnirig` must be a live OmniRigX interface pointer, used on this thread
+    pub unsafe fn advise(
+        omnirig: *mut c_void,
+        rig_number: u8,
+        changed: Arc<AtomicBool>,
+    ) -> RadioResult<Self> {
+        let failed = |step: &str, hr: HResult| {
+            RadioError::ConnectionFailed(format!("OmniRig events: {} failed (0x{:08X})", step, hr))
+        };
+
+        let mut container = std::ptr::null_mut();
+        let hr = (vtbl::<UnknownVtbl>(omnirig).query_interface)(
+            omnirig,
+            &IID_ICONNECTION_POINT_CONTAINER,
+            &mut container,
+        );
+        if hr != S_OK {
+            return Err(failed("QueryInterface", hr));
+        }
+        let mut connection_point = std::ptr::null_mut();
+        let hr = (vtbl::<ConnectionPointContainerVtbl>(container).find_connection_point)(
+            container,
+            &DIID_IOMNIRIGX_EVENTS,
+            &mut connection_point,
+        );
+        (vtbl::<UnknownVtbl>(container).release)(container);
+        if hr != S_OK {
+            return Err(failed("FindConnectionPoint", hr));
+        }
+
+        let sink = Box::into_raw(Box::new(Sink {
+            vtbl: &SINK_VTBL,
+            refs: AtomicU32::new(1),
+            rig_number,
+            changed,
+        })) as *mut c_void;
+        let mut cookie = 0;
+        let hr = (vtbl::<ConnectionPointVtbl>(connection_point).advise)(
+            connection_point,
+            sink,
+            &mut cookie,
+        );
+        // The connection point holds its own reference while subscribed
+        sink_release(sink);
+        if hr != S_OK {
+            (vtbl::<UnknownVtbl>(connection_point).release)(connection_point);
+            return Err(failed("Advise", hr));
+        }
+        Ok(Self {
+            connection_point,
+            cookie,
+        })
+    }
+}
+
+impl Drop for EventSubscription {
+    fn drop(&mut self) {
+        unsafe {
+            (vtbl::<ConnectionPointVtbl>(self.connection_point).unadvise)(
+                self.connection_point,
+                self.cookie,
+            );
+            (vtbl::<UnknownVtbl>(self.connection_point).release)(self.connection_point);
+        }
+    }
+}
+
+/// Id of the calling thread, for [`wake`] and [`quit`]
+pub fn current_thread_id() -> u32 {
+    unsafe { GetCurrentThreadId() }
+}
+
+/// Wake a thread blocked in [`wait_for_messages`]
+pub fn wake(thread_id: u32) {
+    unsafe {
+        PostThreadMessageW(thread_id, WM_APP, 0, 0);
+    }
+}
+
+/// Make a thread's next [`wait_for_messages`] return false
+pub fn quit(thread_id: u32) {
+    unsafe {
+        PostThreadMessageW(thread_id, WM_QUIT, 0, 0);
+    }
+}
+
+/// Wait up to `timeout` for a message, then dispatch every queued message
+/// (which delivers COM events). Returns false once [`quit`] was called.
+pub fn wait_for_messages(timeout: Duration) -> bool {
+    unsafe {
+        MsgWaitForMultipleObjects(
+            0,
+            std::ptr::null(),
+            0,
+            timeout.as_millis().min(u32::MAX as u128 - 1) as u32,
+            QS_ALLINPUT,
+        );
+        let mut msg = std::mem::zeroed::<Msg>();
+        while PeekMessageW(&mut msg, 0, 0, 0, PM_REMOVE) != 0 {
+            if msg.message == WM_QUIT {
+                return false;
+            }
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+        }
+    }
+    true
+}
@@ -1,27 +1,38 @@
 //! rigctld (Hamlib) radio controller via TCP
 
-use super::{RadioController, RadioError, RadioMode, RadioResult};
+use super::{Passbands, RadioController, RadioError, RadioMode, RadioResult};
+use crate::services::metrics::METRICS;
 use std::io::{BufRead, BufReader, Write};
 use std::net::TcpStream;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Controller for rigctld (Hamlib network daemon)
 pub struct RigctldController {
     host: String,
     port: u16,
+    passbands: Passbands,
     stream: Option<TcpStream>,
 }
 
 impl RigctldController {
-    pub fn new(host: String, port: u16) -> Self {
+    pub fn new(host: String, port: u16, passbands: Passbands) -> Self {
         Self {
             host,
             port,
+            passbands,
             stream: None,
         }
     }
 
     fn send_command(&mut self, command: &str) -> RadioResult<String> {
+        let started = Instant::now();
+        let result = self.exchange(command);
+        METRICS.radio_command(started.elapsed());
+        result
+    }
+
+    /// Send one command and read its one-line response
+    fn exchange(&mut self, command: &str) -> RadioResult<String> {
         let stream = self.stream.as_mut().ok_or(RadioError::NotConnected)?;
 
         // Send command
@@ -108,9 +119,33 @@ impl RadioController for RigctldController {
         self.send_command(&format!("F {}", frequency_hz))?;
 
         // Set mode: M <mode> <passband>
-        // Using 0 for passband lets rigctld use the radio's default
-        self.send_command(&format!("M {} 0", mode.to_rigctld_mode()))?;
+        // A passband of 0 lets rigctld use the radio's default
+        self.send_command(&format!(
+            "M {} {}",
+            mode.to_rigctld_mode(),
+            self.passbands.for_mode(mode)
+        ))?;
+
+        Ok(())
+    }
+
+    fn get_frequency(&mut self) -> RadioResult<f64> {
+        // Get frequency: f (answers in Hz)
+        let response = self.send_command("f")?;
+        response
+            .parse::<f64>()
+            .map(|hz| hz / 1000.0)
+            .map_err(|_| RadioError::CommandFailed(format!("Unexpected frequency: {}", response)))
+    }
+
+    fn send_raw(&mut self, command: &str) -> RadioResult<()> {
+        self.send_command(command)?;
+        Ok(())
+    }
 
+    fn set_antenna(&mut self, antenna: u8) -> RadioResult<()> {
+        // Set antenna: Y <antenna> <option>
+        self.send_command(&format!("Y {} 0", antenna))?;
         Ok(())
     }
 
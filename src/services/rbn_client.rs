@@ -1,6 +1,10 @@
-use crate::models::RawSpot;
+use crate::error::Error;
+use crate::models::{RawSpot, SpotSource};
+use crate::services::metrics::METRICS;
 use regex::Regex;
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufReader};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::net::TcpStream;
 use tokio::sync::mpsc;
 
@@ -11,6 +15,8 @@ const RBN_PORT: u16 = 7000;
 #[derive(Debug, Clone)]
 pub enum RbnMessage {
     Status(String),
+    /// The connection failed or was lost
+    Error(Error),
     Spot(RawSpot),
     Disconnected,
     /// Raw data for debugging (direction: true = received, false = sent)
@@ -23,10 +29,20 @@ pub enum RbnMessage {
 /// Commands sent to the RBN client
 #[derive(Debug)]
 pub enum RbnCommand {
-    Connect(String),
+    /// Log in with a callsign, then send the given server commands (e.g. filters)
+    Connect {
+        callsign: String,
+        commands: Vec<String>,
+    },
     Disconnect,
 }
 
+/// A connection the feed is read from and logged into: TCP in normal use, an
+/// in-memory pipe replaying a recorded session in tests
+pub trait FeedStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> FeedStream for T {}
+
 /// Handle to communicate with the RBN client task
 pub struct RbnClient {
     cmd_tx: mpsc::Sender<RbnCommand>,
@@ -35,7 +51,9 @@ pub struct RbnClient {
 
 impl RbnClient {
     /// Create a new RBN client and spawn the background task
-    pub fn new() -> Self {
+    ///
+    /// Bytes sent and received are added to `traffic`.
+    pub fn new(traffic: Arc<AtomicU64>) -> Self {
         let (cmd_tx, cmd_rx) = mpsc::channel(16);
         let (msg_tx, msg_rx) = mpsc::channel(256);
 
@@ -44,16 +62,18 @@ impl RbnClient {
                 .enable_all()
                 .build()
                 .expect("Failed to create tokio runtime");
-            rt.block_on(rbn_task(cmd_rx, msg_tx));
+            rt.block_on(rbn_task(cmd_rx, msg_tx, traffic));
         });
 
         Self { cmd_tx, msg_rx }
     }
 
     /// Send a connect command (non-blocking from UI)
-    pub fn connect(&self, callsign: String) {
+    ///
+    /// `commands` are sent to the server after login.
+    pub fn connect(&self, callsign: String, commands: Vec<String>) {
         let tx = self.cmd_tx.clone();
-        let _ = tx.blocking_send(RbnCommand::Connect(callsign));
+        let _ = tx.blocking_send(RbnCommand::Connect { callsign, commands });
     }
 
     /// Send a disconnect command (non-blocking from UI)
@@ -62,22 +82,29 @@ impl RbnClient {
         let _ = tx.blocking_send(RbnCommand::Disconnect);
     }
 
+    /// Whether the background task is still running (false if it died)
+    pub fn is_alive(&self) -> bool {
+        !self.msg_rx.is_closed()
+    }
+
     /// Try to receive a message (non-blocking)
     pub fn try_recv(&mut self) -> Option<RbnMessage> {
         self.msg_rx.try_recv().ok()
     }
 }
 
-async fn rbn_task(mut cmd_rx: mpsc::Receiver<RbnCommand>, msg_tx: mpsc::Sender<RbnMessage>) {
-    let spot_regex =
-        Regex::new(r"DX de (\S+):\s+(\d+\.?\d*)\s+(\S+)\s+(\w+)\s+(\d+)\s+dB\s+(\d+)\s+WPM")
-            .expect("Invalid regex");
+async fn rbn_task(
+    mut cmd_rx: mpsc::Receiver<RbnCommand>,
+    msg_tx: mpsc::Sender<RbnMessage>,
+    traffic: Arc<AtomicU64>,
+) {
+    let spot_regex = spot_regex();
 
     loop {
         // Wait for a connect command
-        let callsign = loop {
+        let (callsign, commands) = loop {
             match cmd_rx.recv().await {
-                Some(RbnCommand::Connect(cs)) => break cs,
+                Some(RbnCommand::Connect { callsign, commands }) => break (callsign, commands),
                 Some(RbnCommand::Disconnect) => continue,
                 None => return, // Channel closed
             }
@@ -91,11 +118,15 @@ async fn rbn_task(mut cmd_rx: mpsc::Receiver<RbnCommand>, msg_tx: mpsc::Sender<R
             .await;
 
         // Try to connect
+        METRICS.feed_connecting();
         let stream = match TcpStream::connect((RBN_HOST, RBN_PORT)).await {
             Ok(s) => s,
             Err(e) => {
                 let _ = msg_tx
-                    .send(RbnMessage::Status(format!("Connection failed: {}", e)))
+                    .send(RbnMessage::Error(Error::Feed(format!(
+                        "Connection failed: {}",
+                        e
+                    ))))
                     .await;
                 let _ = msg_tx.send(RbnMessage::Disconnected).await;
                 continue;
@@ -109,20 +140,31 @@ async fn rbn_task(mut cmd_rx: mpsc::Receiver<RbnCommand>, msg_tx: mpsc::Sender<R
             .await;
 
         // Handle the connection
-        handle_connection(stream, &callsign, &mut cmd_rx, &msg_tx, &spot_regex).await;
+        handle_connection(
+            stream,
+            &callsign,
+            &commands,
+            &mut cmd_rx,
+            &msg_tx,
+            &spot_regex,
+            &traffic,
+        )
+        .await;
 
         let _ = msg_tx.send(RbnMessage::Disconnected).await;
     }
 }
 
 async fn handle_connection(
-    stream: TcpStream,
+    stream: impl FeedStream,
     callsign: &str,
+    commands: &[String],
     cmd_rx: &mut mpsc::Receiver<RbnCommand>,
     msg_tx: &mpsc::Sender<RbnMessage>,
     spot_regex: &Regex,
+    traffic: &AtomicU64,
 ) {
-    let (reader, mut writer) = stream.into_split();
+    let (reader, mut writer) = tokio::io::split(stream);
     let mut reader = BufReader::new(reader);
     let mut buffer = String::new();
     let mut logged_in = false;
@@ -137,7 +179,7 @@ async fn handle_connection(
                         let _ = msg_tx.send(RbnMessage::Status("Disconnected".to_string())).await;
                         return;
                     }
-                    Some(RbnCommand::Connect(_)) => {
+                    Some(RbnCommand::Connect { .. }) => {
                         // Already connected, ignore
                     }
                 }
@@ -147,10 +189,12 @@ async fn handle_connection(
             result = reader.read(&mut byte_buf) => {
                 match result {
                     Ok(0) => {
-                        let _ = msg_tx.send(RbnMessage::Status("Connection closed by server".to_string())).await;
+                        let _ = msg_tx.send(RbnMessage::Error(Error::Feed("Connection closed by server".to_string()))).await;
                         return;
                     }
                     Ok(n) => {
+                        traffic.fetch_add(n as u64, Ordering::Relaxed);
+
                         // Convert bytes to string and append to buffer
                         if let Ok(chunk) = std::str::from_utf8(&byte_buf[..n]) {
                             buffer.push_str(chunk);
@@ -171,7 +215,10 @@ async fn handle_connection(
                             // Parse spots from complete lines
                             if line.starts_with("DX de") {
                                 if let Some(spot) = parse_spot_line(&line, spot_regex) {
+                                    METRICS.spot_received();
                                     let _ = msg_tx.send(RbnMessage::Spot(spot)).await;
+                                } else {
+                                    METRICS.parse_failed();
                                 }
                             }
                         }
@@ -191,6 +238,7 @@ async fn handle_connection(
 
                             let send_data = format!("{}\r\n", callsign);
                             if writer.write_all(send_data.as_bytes()).await.is_ok() {
+                                traffic.fetch_add(send_data.len() as u64, Ordering::Relaxed);
                                 // Send raw sent data for debugging
                                 let _ = msg_tx
                                     .send(RbnMessage::RawData {
@@ -202,11 +250,26 @@ async fn handle_connection(
                                     .send(RbnMessage::Status(format!("Logged in as {}", callsign)))
                                     .await;
                                 logged_in = true;
+
+                                // Server-side filters and other post-login commands
+                                for command in commands {
+                                    let send_data = format!("{}\r\n", command);
+                                    if writer.write_all(send_data.as_bytes()).await.is_err() {
+                                        break;
+                                    }
+                                    traffic.fetch_add(send_data.len() as u64, Ordering::Relaxed);
+                                    let _ = msg_tx
+                                        .send(RbnMessage::RawData {
+                                            data: send_data,
+                                            received: false,
+                                        })
+                                        .await;
+                                }
                             }
                         }
                     }
                     Err(e) => {
-                        let _ = msg_tx.send(RbnMessage::Status(format!("Read error: {}", e))).await;
+                        let _ = msg_tx.send(RbnMessage::Error(Error::Feed(format!("Read error: {}", e)))).await;
                         return;
                     }
                 }
@@ -215,18 +278,214 @@ async fn handle_connection(
     }
 }
 
+/// Matches spot lines. SNR can be negative (FT8/FT4), and digital modes report
+/// BPS or nothing at all in place of WPM.
+fn spot_regex() -> Regex {
+    Regex::new(
+        r"DX de (\S+):\s+(\d+\.?\d*)\s+(\S+)\s+(\w+)\s+(-?\d+)\s+dB(?:\s+(\d+)\s+(?:WPM|BPS))?",
+    )
+    .expect("Invalid regex")
+}
+
 fn parse_spot_line(line: &str, regex: &Regex) -> Option<RawSpot> {
     let caps = regex.captures(line)?;
+    let spotter = caps.get(1)?.as_str().trim_end_matches(':');
+    let source = if spotter.ends_with("-#") {
+        SpotSource::Skimmer
+    } else {
+        SpotSource::Human
+    };
 
-    Some(RawSpot::new(
-        caps.get(1)?
-            .as_str()
-            .trim_end_matches(['-', '#', ':'])
-            .to_string(),
+    let mut spot = RawSpot::new(
+        spotter.trim_end_matches(['-', '#']).to_string(),
         caps.get(3)?.as_str().to_string(),
         caps.get(2)?.as_str().parse().ok()?,
         caps.get(5)?.as_str().parse().ok()?,
-        caps.get(6)?.as_str().parse().ok()?,
+        match caps.get(6) {
+            Some(speed) => speed.as_str().parse().ok()?,
+            None => 0,
+        },
         caps.get(4)?.as_str().to_string(),
-    ))
+        source,
+    );
+    // The spot type follows the speed: CQ, DX, BEACON or "NCDXF B"
+    let rest = &line[caps.get(0)?.end()..];
+    spot.beacon |= rest.contains("BEACON") || rest.contains("NCDXF B");
+    Some(spot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncBufReadExt;
+
+    /// Spot lines recorded from the feed, one per line
+    const FIXTURE: &str = include_str!("../../tests/fixtures/rbn_spots.txt");
+
+    fn parse(line: &str) -> Option<RawSpot> {
+        parse_spot_line(line, &spot_regex())
+    }
+
+    #[test]
+    fn every_fixture_line_parses() {
+        for line in FIXTURE.lines() {
+            assert!(parse(line).is_some(), "failed to parse {:?}", line);
+        }
+    }
+
+    #[test]
+    fn fixture_fields() {
+        // (spotter, callsign, kHz, SNR, WPM, mode, source) in fixture order
+        let expected = [
+            ("EA5WU", "RA3DNC", 7018.3, 13, 24, "CW", SpotSource::Skimmer),
+            ("W3LPL", "K1ABC", 14025.0, 31, 28, "CW", SpotSource::Skimmer),
+            (
+                "DL8LAS-2",
+                "OH2BH",
+                3525.1,
+                8,
+                22,
+                "CW",
+                SpotSource::Skimmer,
+            ),
+            (
+                "KM3T",
+                "JA1XYZ",
+                14080.0,
+                12,
+                45,
+                "RTTY",
+                SpotSource::Skimmer,
+            ),
+            (
+                "VE6WZ",
+                "VE6WZ/B",
+                28200.0,
+                18,
+                14,
+                "CW",
+                SpotSource::Skimmer,
+            ),
+            ("K9LC", "4U1UN", 14100.0, 10, 22, "CW", SpotSource::Skimmer),
+            (
+                "N2QT-3",
+                "JH1ABC",
+                14074.0,
+                -12,
+                0,
+                "FT8",
+                SpotSource::Skimmer,
+            ),
+            ("KO7SS", "W1AW/4", 7047.5, -4, 0, "FT4", SpotSource::Skimmer),
+            ("K1TTT", "DL1ABC", 7025.0, 20, 25, "CW", SpotSource::Human),
+            ("SM6FMB", "LY2AAA", 1822.9, 3, 18, "CW", SpotSource::Skimmer),
+            ("OH6BG", "ZS6WAB", 50092.0, 0, 16, "CW", SpotSource::Skimmer),
+            (
+                "F5MYK",
+                "KP4/N2ABC",
+                10112.5,
+                27,
+                35,
+                "CW",
+                SpotSource::Skimmer,
+            ),
+        ];
+        let lines: Vec<&str> = FIXTURE.lines().collect();
+        assert_eq!(lines.len(), expected.len());
+
+        for (line, (spotter, call, khz, snr, wpm, mode, source)) in lines.iter().zip(expected) {
+            let spot = parse(line).unwrap();
+            assert_eq!(spot.spotter_callsign, spotter, "{}", line);
+            assert_eq!(spot.spotted_callsign, call, "{}", line);
+            assert_eq!(spot.frequency_khz, khz, "{}", line);
+            assert_eq!(spot.snr, snr, "{}", line);
+            assert_eq!(spot.speed_wpm, wpm, "{}", line);
+            assert_eq!(spot.mode, mode, "{}", line);
+            assert_eq!(spot.source, source, "{}", line);
+        }
+    }
+
+    #[test]
+    fn beacons_are_flagged() {
+        let beacons: Vec<String> = FIXTURE
+            .lines()
+            .filter_map(parse)
+            .filter(|spot| spot.beacon)
+            .map(|spot| spot.spotted_callsign)
+            .collect();
+        assert_eq!(beacons, ["VE6WZ/B", "4U1UN", "ZS6WAB"]);
+    }
+
+    #[test]
+    fn non_spot_lines_are_ignored() {
+        assert!(parse("Welcome to the Reverse Beacon Network").is_none());
+        assert!(parse("DX de W3LPL-#: garbled").is_none());
+        assert!(parse("").is_none());
+    }
+
+    #[tokio::test]
+    async fn logs_in_and_forwards_spots_from_a_recorded_session() {
+        let (server, client) = tokio::io::duplex(64 * 1024);
+        let (cmd_tx, mut cmd_rx) = mpsc::channel(1);
+        let (msg_tx, mut msg_rx) = mpsc::channel(256);
+        let traffic = AtomicU64::new(0);
+        let commands = vec!["set/skimmer".to_string()];
+
+        let feed = async {
+            let (client_read, mut client_write) = tokio::io::split(client);
+            let mut client_read = tokio::io::BufReader::new(client_read);
+            client_write
+                .write_all(b"Welcome to the Reverse Beacon Network\r\nPlease enter your callsign: ")
+                .await
+                .unwrap();
+
+            // The login and the post-login command come back before any spots are sent
+            let mut sent = Vec::new();
+            for _ in 0..2 {
+                let mut line = String::new();
+                client_read.read_line(&mut line).await.unwrap();
+                sent.push(line);
+            }
+
+            for line in FIXTURE.lines() {
+                client_write
+                    .write_all(format!("{}\r\n", line).as_bytes())
+                    .await
+                    .unwrap();
+            }
+            client_write.shutdown().await.unwrap();
+            sent
+        };
+        let regex = spot_regex();
+        let connection = handle_connection(
+            server,
+            "N0CALL",
+            &commands,
+            &mut cmd_rx,
+            &msg_tx,
+            &regex,
+            &traffic,
+        );
+        let (sent, ()) = tokio::join!(feed, connection);
+        drop(cmd_tx);
+
+        assert_eq!(sent, ["N0CALL\r\n", "set/skimmer\r\n"]);
+
+        let mut spots = Vec::new();
+        let mut closed = false;
+        while let Ok(message) = msg_rx.try_recv() {
+            match message {
+                RbnMessage::Spot(spot) => spots.push(spot.spotted_callsign),
+                RbnMessage::Error(Error::Feed(_)) => closed = true,
+                _ => {}
+            }
+        }
+        let expected: Vec<String> = FIXTURE
+            .lines()
+            .map(|line| parse(line).unwrap().spotted_callsign)
+            .collect();
+        assert_eq!(spots, expected);
+        assert!(closed, "end of stream should be reported");
+        assert!(traffic.load(Ordering::Relaxed) > FIXTURE.len() as u64);
+    }
 }
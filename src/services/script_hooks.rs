@@ -0,0 +1,358 @@
+//! User script hooks
+//!
+//! The configured Rhai script is compiled once and run on its own thread. Its
+//! top level runs at start; after that the app calls whichever of these
+//! functions the script defines:
+//!
+//! - `on_spot(spot)` for each newly heard signal. `spot` is a map with the
+//!   same fields as the HTTP API's `/spots` (`spot.callsign`,
+//!   `spot.frequency_khz`, `spot.mode`, `spot.snr`, ...)
+//! - `on_tune(frequency_khz, mode)` when the radio is tuned
+//! - `on_idle()` when the spot list empties
+//!
+//! The script acts through these functions:
+//!
+//! - `alert(text)` raises an operator alert
+//! - `display(text)` / `display(text, seconds)` shows text on the VFD, one
+//!   line per `\n`
+//! - `hide(callsign)` / `show(callsign)` filter a callsign out of (or back
+//!   into) the spot list
+//!
+//! `print` output goes to stdout. A script that runs away is stopped after
+//! `MAX_OPERATIONS` steps per call.
+
+use crate::error::{Error, Result};
+use crate::models::AggregatedSpot;
+use crate::models::UtcTime;
+use crate::services::radio::RadioMode;
+use rhai::{CallFnOptions, Dynamic, Engine, FuncArgs, Map, Scope, AST};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Default time text from the script stays on the VFD
+const DEFAULT_DISPLAY_SECONDS: i64 = 10;
+
+/// Steps a single hook call may take before it is stopped
+const MAX_OPERATIONS: u64 = 1_000_000;
+
+/// Something the script asked for
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScriptAction {
+    Alert(String),
+    Display {
+        lines: Vec<String>,
+        duration: Duration,
+    },
+    Hide(String),
+    Show(String),
+}
+
+/// An event passed to the script
+enum ScriptEvent {
+    Spot(Box<AggregatedSpot>),
+    Tune(f64, RadioMode),
+    Idle,
+}
+
+/// A running hook script
+pub struct ScriptHost {
+    path: String,
+    event_tx: mpsc::Sender<ScriptEvent>,
+    action_rx: mpsc::Receiver<ScriptAction>,
+    error_rx: mpsc::Receiver<String>,
+}
+
+impl ScriptHost {
+    /// Compile the Rhai script at `path` and run its top level
+    pub fn start(path: &str) -> Result<Self> {
+        let (event_tx, event_rx) = mpsc::channel();
+        let (action_tx, action_rx) = mpsc::channel();
+        let (error_tx, error_rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::channel();
+
+        let script = PathBuf::from(path);
+        std::thread::spawn(move || {
+            // The engine isn't Send, so it lives on this thread for good
+            let engine = engine(action_tx);
+            let mut scope = Scope::new();
+            let ast = match engine
+                .compile_file(script)
+                .and_then(|ast| engine.run_ast_with_scope(&mut scope, &ast).map(|()| ast))
+            {
+                Ok(ast) => {
+                    let _ = ready_tx.send(Ok(()));
+                    ast
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e.to_string()));
+                    return;
+                }
+            };
+
+            for event in event_rx {
+                let result = match event {
+                    ScriptEvent::Spot(spot) => {
+                        call_hook(&engine, &mut scope, &ast, "on_spot", (spot_map(&spot),))
+                    }
+                    ScriptEvent::Tune(frequency_khz, mode) => call_hook(
+                        &engine,
+                        &mut scope,
+                        &ast,
+                        "on_tune",
+                        (frequency_khz, mode.to_rigctld_mode().to_string()),
+                    ),
+                    ScriptEvent::Idle => call_hook(&engine, &mut scope, &ast, "on_idle", ()),
+                };
+                if let Err(e) = result {
+                    if error_tx.send(e).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        match ready_rx.recv() {
+            Ok(Ok(())) => Ok(Self {
+                path: path.to_string(),
+                event_tx,
+                action_rx,
+                error_rx,
+            }),
+            Ok(Err(e)) => Err(Error::Script(format!("{}: {}", path, e))),
+            Err(_) => Err(Error::Script(format!("{}: script host stopped", path))),
+        }
+    }
+
+    /// Path of the running script
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// A newly heard signal
+    pub fn on_spot(&self, spot: &AggregatedSpot) {
+        let _ = self
+            .event_tx
+            .send(ScriptEvent::Spot(Box::new(spot.clone())));
+    }
+
+    /// The radio was tuned
+    pub fn on_tune(&self, frequency_khz: f64, mode: RadioMode) {
+        let _ = self.event_tx.send(ScriptEvent::Tune(frequency_khz, mode));
+    }
+
+    /// The spot list became empty
+    pub fn on_idle(&self) {
+        let _ = self.event_tx.send(ScriptEvent::Idle);
+    }
+
+    /// Next action requested by the script (non-blocking)
+    pub fn try_recv(&self) -> Option<ScriptAction> {
+        self.action_rx.try_recv().ok()
+    }
+
+    /// Next problem with the script, if any
+    pub fn take_error(&self) -> Option<Error> {
+        self.error_rx.try_recv().ok().map(Error::Script)
+    }
+}
+
+/// A Rhai engine with the script's action functions registered
+fn engine(action_tx: mpsc::Sender<ScriptAction>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+
+    let tx = action_tx.clone();
+    engine.register_fn("alert", move |text: &str| {
+        let _ = tx.send(ScriptAction::Alert(text.to_string()));
+    });
+    let tx = action_tx.clone();
+    engine.register_fn("display", move |text: &str| {
+        let _ = tx.send(display_action(text, DEFAULT_DISPLAY_SECONDS));
+    });
+    let tx = action_tx.clone();
+    engine.register_fn("display", move |text: &str, seconds: i64| {
+        let _ = tx.send(display_action(text, seconds));
+    });
+    let tx = action_tx.clone();
+    engine.register_fn("hide", move |callsign: &str| {
+        let _ = tx.send(ScriptAction::Hide(callsign.trim().to_uppercase()));
+    });
+    let tx = action_tx;
+    engine.register_fn("show", move |callsign: &str| {
+        let _ = tx.send(ScriptAction::Show(callsign.trim().to_uppercase()));
+    });
+    engine
+}
+
+fn display_action(text: &str, seconds: i64) -> ScriptAction {
+    ScriptAction::Display {
+        lines: text.lines().map(str::to_string).collect(),
+        duration: Duration::from_secs(seconds.clamp(1, 3600) as u64),
+    }
+}
+
+/// Call `name` if the script defines it with a matching number of parameters
+fn call_hook(
+    engine: &Engine,
+    scope: &mut Scope,
+    ast: &AST,
+    name: &str,
+    args: impl FuncArgs,
+) -> std::result::Result<(), String> {
+    let mut values = Vec::new();
+    args.parse(&mut values);
+    if !ast
+        .iter_functions()
+        .any(|f| f.name == name && f.params.len() == values.len())
+    {
+        return Ok(());
+    }
+    // The top level already ran at start; keep its variables between calls
+    let options = CallFnOptions::new().eval_ast(false).rewind_scope(true);
+    engine
+        .call_fn_with_options::<Dynamic>(options, scope, ast, name, values)
+        .map(|_| ())
+        .map_err(|e| format!("{}: {}", name, e))
+}
+
+/// The spot fields given to `on_spot`, named as in the HTTP API
+fn spot_map(spot: &AggregatedSpot) -> Map {
+    let mut map = Map::new();
+    map.insert("callsign".into(), spot.callsign.clone().into());
+    map.insert("frequency_khz".into(), spot.frequency_khz.into());
+    map.insert("mode".into(), spot.mode.clone().into());
+    map.insert("snr".into(), (spot.highest_snr as i64).into());
+    map.insert("wpm".into(), (spot.average_speed.round() as i64).into());
+    map.insert("spots".into(), (spot.spot_count as i64).into());
+    map.insert("age_seconds".into(), (spot.age_seconds() as i64).into());
+    map.insert(
+        "first_spotted".into(),
+        UtcTime::from_system(spot.first_spotted_at)
+            .to_rfc3339()
+            .into(),
+    );
+    map.insert(
+        "last_spotted".into(),
+        UtcTime::from_system(spot.last_spotted_at)
+            .to_rfc3339()
+            .into(),
+    );
+    map.insert("manual".into(), spot.manual.into());
+    map
+}
+
+/// Run a one-off shell command (e.g. to play a sound) without waiting for it
+pub fn run_detached(command: &str) -> std::io::Result<()> {
+    let mut child = shell(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    // Reap it in the background so it doesn't linger as a zombie
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.args(["/C", command]);
+    shell
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.args(["-c", command]);
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{RawSpot, SpotSource};
+    use std::time::Instant;
+
+    fn start(name: &str, script: &str) -> ScriptHost {
+        let path =
+            std::env::temp_dir().join(format!("rbn-vfd-{}-{}.rhai", name, std::process::id()));
+        std::fs::write(&path, script).unwrap();
+        let host = ScriptHost::start(path.to_str().unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        host
+    }
+
+    fn next_action(host: &ScriptHost) -> ScriptAction {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(action) = host.try_recv() {
+                return action;
+            }
+            if let Some(error) = host.take_error() {
+                panic!("{}", error);
+            }
+            assert!(Instant::now() < deadline, "no action from the script");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn hooks_see_spot_fields_and_send_actions() {
+        let host = start(
+            "hooks",
+            r#"
+            fn on_spot(spot) {
+                if spot.snr < 10 {
+                    hide(spot.callsign);
+                } else {
+                    alert(`${spot.callsign} ${spot.mode} ${spot.snr}`);
+                }
+            }
+            fn on_tune(frequency_khz, mode) {
+                display(mode + "\n" + frequency_khz, 5);
+            }
+            "#,
+        );
+
+        let mut spot = AggregatedSpot::from_raw(&RawSpot::new(
+            "DL8LAS-#".to_string(),
+            "K3LR".to_string(),
+            14025.0,
+            20,
+            25,
+            "CW".to_string(),
+            SpotSource::Skimmer,
+        ));
+        host.on_spot(&spot);
+        assert_eq!(
+            next_action(&host),
+            ScriptAction::Alert("K3LR CW 20".to_string())
+        );
+
+        spot.highest_snr = 3;
+        host.on_spot(&spot);
+        assert_eq!(next_action(&host), ScriptAction::Hide("K3LR".to_string()));
+
+        // on_idle isn't defined, so it is skipped quietly
+        host.on_idle();
+        host.on_tune(7025.0, RadioMode::Cw);
+        assert_eq!(
+            next_action(&host),
+            ScriptAction::Display {
+                lines: vec!["CW".to_string(), "7025.0".to_string()],
+                duration: Duration::from_secs(5),
+            }
+        );
+    }
+
+    #[test]
+    fn reports_compile_errors() {
+        let path = std::env::temp_dir().join(format!("rbn-vfd-broken-{}.rhai", std::process::id()));
+        std::fs::write(&path, "fn on_spot(spot) {").unwrap();
+        let result = ScriptHost::start(path.to_str().unwrap());
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(result, Err(Error::Script(_))));
+    }
+}
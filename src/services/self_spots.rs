@@ -0,0 +1,128 @@
+//! Where RBN hears the operator's own signal
+//!
+//! Spots of the configured callsign are kept apart from the main list, one
+//! report per skimmer and band, for comparing antennas and conditions.
+
+use crate::models::{BandPlan, RawSpot};
+use crate::services::vfd_display::wrap_words;
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime};
+
+/// A skimmer's latest report of our signal on one band
+#[derive(Debug, Clone)]
+pub struct SelfSpotReport {
+    pub spotter: String,
+    pub band: String,
+    pub frequency_khz: f64,
+    pub snr: i32,
+    pub speed_wpm: i32,
+    pub heard_at: SystemTime,
+}
+
+#[derive(Debug, Default)]
+pub struct SelfSpots {
+    reports: Vec<SelfSpotReport>,
+    /// Spots of us per skimmer this session
+    per_spotter: HashMap<String, u32>,
+}
+
+/// Whether a spotted callsign is ours, ignoring portable prefixes and
+/// suffixes such as `/P` or `EA8/`
+pub fn is_own_call(own_callsign: &str, spotted: &str) -> bool {
+    let own = base_call(own_callsign.trim());
+    !own.is_empty() && own.eq_ignore_ascii_case(base_call(spotted))
+}
+
+/// Longest `/`-separated part of a callsign
+fn base_call(callsign: &str) -> &str {
+    callsign
+        .split('/')
+        .max_by_key(|part| part.len())
+        .unwrap_or(callsign)
+}
+
+impl SelfSpots {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep `raw` as its skimmer's latest report on the band
+    pub fn record(&mut self, raw: &RawSpot, plan: &BandPlan) {
+        let band = plan
+            .for_frequency(raw.frequency_khz)
+            .map(|b| b.name.clone())
+            .unwrap_or_else(|| "Other".to_string());
+        *self
+            .per_spotter
+            .entry(raw.spotter_callsign.clone())
+            .or_default() += 1;
+        let report = SelfSpotReport {
+            spotter: raw.spotter_callsign.clone(),
+            band,
+            frequency_khz: raw.frequency_khz,
+            snr: raw.snr,
+            speed_wpm: raw.speed_wpm,
+            heard_at: raw.received_at,
+        };
+        match self
+            .reports
+            .iter_mut()
+            .find(|r| r.spotter == report.spotter && r.band == report.band)
+        {
+            Some(existing) => *existing = report,
+            None => self.reports.push(report),
+        }
+    }
+
+    /// Drop reports older than `max_age`
+    pub fn purge(&mut self, max_age: Duration) {
+        self.reports
+            .retain(|r| r.heard_at.elapsed().unwrap_or_default() < max_age);
+    }
+
+    /// Spots of us from `spotter` this session
+    pub fn count_from(&self, spotter: &str) -> u32 {
+        self.per_spotter.get(spotter).copied().unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reports.is_empty()
+    }
+
+    /// Reports grouped by band in band plan order, loudest first within a band
+    pub fn by_band(&self, plan: &BandPlan) -> Vec<(String, Vec<&SelfSpotReport>)> {
+        let mut groups: Vec<(String, Vec<&SelfSpotReport>)> = Vec::new();
+        for report in &self.reports {
+            match groups.iter_mut().find(|(band, _)| *band == report.band) {
+                Some((_, reports)) => reports.push(report),
+                None => groups.push((report.band.clone(), vec![report])),
+            }
+        }
+        let order = |band: &str| {
+            plan.bands()
+                .iter()
+                .position(|b| b.name == band)
+                .unwrap_or(usize::MAX)
+        };
+        groups.sort_by_key(|(band, _)| order(band));
+        for (_, reports) in &mut groups {
+            reports.sort_by(|a, b| b.snr.cmp(&a.snr).then_with(|| a.spotter.cmp(&b.spotter)));
+        }
+        groups
+    }
+
+    /// "My signal" VFD page: skimmer count and best SNR per band, or `None`
+    /// while nobody hears us
+    pub fn vfd_lines(&self, plan: &BandPlan, width: usize) -> Option<Vec<String>> {
+        if self.reports.is_empty() {
+            return None;
+        }
+        let mut words = vec!["Heard".to_string()];
+        words.extend(
+            self.by_band(plan)
+                .into_iter()
+                .map(|(band, reports)| format!("{}:{}/{}dB", band, reports.len(), reports[0].snr)),
+        );
+        Some(wrap_words(&words, width))
+    }
+}
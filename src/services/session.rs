@@ -0,0 +1,187 @@
+//! Recording and deterministic replay of pipeline events
+//!
+//! A session file has one event per line: milliseconds since the start of the
+//! recording, an event tag, then tab-separated fields.
+
+use crate::models::{RawSpot, SpotSource};
+use crate::services::radio::RadioMode;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// An event in the spot pipeline
+#[derive(Debug, Clone)]
+pub enum SessionEvent {
+    /// A spot accepted from the RBN feed
+    Spot(RawSpot),
+    /// Filter settings in effect from this point on
+    Filters { min_snr: i32, max_age_minutes: u32 },
+    /// The radio was tuned
+    Tune { frequency_khz: f64, mode: RadioMode },
+    /// Lines shown on the VFD
+    Frame(Vec<String>),
+}
+
+impl SessionEvent {
+    /// Serialize to a tab-separated record (without the timestamp)
+    fn to_record(&self) -> String {
+        match self {
+            SessionEvent::Spot(spot) => format!(
+                "SPOT\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+                spot.spotter_callsign,
+                spot.spotted_callsign,
+                spot.frequency_khz,
+                spot.snr,
+                spot.speed_wpm,
+                spot.mode,
+                match spot.source {
+                    SpotSource::Skimmer => "S",
+                    SpotSource::Human => "H",
+                    SpotSource::Manual => "M",
+                }
+            ),
+            SessionEvent::Filters {
+                min_snr,
+                max_age_minutes,
+            } => format!("FILTERS\t{}\t{}", min_snr, max_age_minutes),
+            SessionEvent::Tune {
+                frequency_khz,
+                mode,
+            } => format!("TUNE\t{}\t{}", frequency_khz, mode.to_rigctld_mode()),
+            SessionEvent::Frame(lines) => format!("FRAME\t{}", lines.join("\t")),
+        }
+    }
+
+    /// Parse a record produced by `to_record`
+    fn from_fields(fields: &[&str]) -> Option<Self> {
+        match fields {
+            ["SPOT", spotter, spotted, freq, snr, wpm, mode, source] => {
+                Some(SessionEvent::Spot(RawSpot::new(
+                    spotter.to_string(),
+                    spotted.to_string(),
+                    freq.parse().ok()?,
+                    snr.parse().ok()?,
+                    wpm.parse().ok()?,
+                    mode.to_string(),
+                    match *source {
+                        "H" => SpotSource::Human,
+                        "M" => SpotSource::Manual,
+                        _ => SpotSource::Skimmer,
+                    },
+                )))
+            }
+            ["FILTERS", min_snr, max_age] => Some(SessionEvent::Filters {
+                min_snr: min_snr.parse().ok()?,
+                max_age_minutes: max_age.parse().ok()?,
+            }),
+            ["TUNE", freq, mode] => Some(SessionEvent::Tune {
+                frequency_khz: freq.parse().ok()?,
+                mode: RadioMode::from_name(mode)?,
+            }),
+            ["FRAME", lines @ ..] => Some(SessionEvent::Frame(
+                lines.iter().map(|l| l.to_string()).collect(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Writes timestamped events to a session file
+pub struct SessionRecorder {
+    writer: BufWriter<File>,
+    start: Instant,
+}
+
+impl SessionRecorder {
+    /// Create (or truncate) a session file
+    pub fn create(path: &Path) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create session directory: {}", e))?;
+        }
+        let file = File::create(path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+            start: Instant::now(),
+        })
+    }
+
+    /// Append an event
+    pub fn record(&mut self, event: &SessionEvent) {
+        let millis = self.start.elapsed().as_millis();
+        let _ = writeln!(self.writer, "{}\t{}", millis, event.to_record());
+    }
+
+    /// Flush buffered events to disk
+    pub fn flush(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+impl Drop for SessionRecorder {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+/// Plays back a session file with its original timing
+pub struct SessionPlayer {
+    events: Vec<(Duration, SessionEvent)>,
+    next: usize,
+    start: Instant,
+}
+
+impl SessionPlayer {
+    /// Load a session file; malformed lines are skipped
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let file =
+            File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+
+        let mut events = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| format!("Failed to read session: {}", e))?;
+            let fields: Vec<&str> = line.split('\t').collect();
+            let Some((millis, rest)) = fields.split_first() else {
+                continue;
+            };
+            let (Ok(millis), Some(event)) =
+                (millis.parse::<u64>(), SessionEvent::from_fields(rest))
+            else {
+                continue;
+            };
+            events.push((Duration::from_millis(millis), event));
+        }
+
+        Ok(Self {
+            events,
+            next: 0,
+            start: Instant::now(),
+        })
+    }
+
+    /// Return every event whose time has come since the last poll
+    pub fn poll(&mut self) -> Vec<SessionEvent> {
+        let elapsed = self.start.elapsed();
+        let mut due = Vec::new();
+        while let Some((at, event)) = self.events.get(self.next) {
+            if *at > elapsed {
+                break;
+            }
+            due.push(event.clone());
+            self.next += 1;
+        }
+        due
+    }
+
+    /// Whether every event has been played
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.events.len()
+    }
+
+    /// Playback progress as (events played, total events)
+    pub fn progress(&self) -> (usize, usize) {
+        (self.next, self.events.len())
+    }
+}
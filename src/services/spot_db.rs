@@ -0,0 +1,198 @@
+//! Long-term spot history in SQLite (built with the `sqlite` feature)
+//!
+//! Every raw spot is appended by a writer thread in batched transactions, so
+//! the UI never waits on the disk. Queries use a second connection.
+
+use crate::error::{Error, Result};
+use crate::models::RawSpot;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+/// One recorded spot
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
+pub struct HistoryRow {
+    pub received_at: SystemTime,
+    pub spotter: String,
+    pub callsign: String,
+    pub frequency_khz: f64,
+    pub snr: i32,
+    pub wpm: i32,
+    pub mode: String,
+}
+
+#[cfg(feature = "sqlite")]
+pub use imp::SpotDb;
+
+#[cfg(not(feature = "sqlite"))]
+pub use stub::SpotDb;
+
+#[cfg(feature = "sqlite")]
+mod imp {
+    use super::*;
+    use rusqlite::{params, Connection};
+    use std::sync::mpsc;
+    use std::time::UNIX_EPOCH;
+
+    /// Most spots written in one transaction
+    const MAX_BATCH: usize = 500;
+
+    const SCHEMA: &str = "
+        PRAGMA journal_mode = WAL;
+        CREATE TABLE IF NOT EXISTS spots (
+            id INTEGER PRIMARY KEY,
+            received_at INTEGER NOT NULL,
+            spotter TEXT NOT NULL,
+            callsign TEXT NOT NULL,
+            frequency_khz REAL NOT NULL,
+            snr INTEGER NOT NULL,
+            wpm INTEGER NOT NULL,
+            mode TEXT NOT NULL,
+            source TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS spots_by_call ON spots (callsign, received_at);
+    ";
+
+    /// Handle to the history database
+    pub struct SpotDb {
+        spot_tx: mpsc::Sender<RawSpot>,
+        error_rx: mpsc::Receiver<String>,
+        reader: Connection,
+    }
+
+    fn db_error(e: rusqlite::Error) -> Error {
+        Error::History(e.to_string())
+    }
+
+    fn unix_seconds(time: SystemTime) -> i64 {
+        time.duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64
+    }
+
+    impl SpotDb {
+        /// Open (creating if needed) the database at `path`
+        pub fn open(path: &Path) -> Result<Self> {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| {
+                    Error::History(format!("Failed to create {}: {}", dir.display(), e))
+                })?;
+            }
+            let mut writer = Connection::open(path).map_err(db_error)?;
+            writer.execute_batch(SCHEMA).map_err(db_error)?;
+            let reader = Connection::open(path).map_err(db_error)?;
+
+            let (spot_tx, spot_rx) = mpsc::channel::<RawSpot>();
+            let (error_tx, error_rx) = mpsc::channel();
+            std::thread::spawn(move || {
+                while let Ok(first) = spot_rx.recv() {
+                    let mut batch = vec![first];
+                    batch.extend(spot_rx.try_iter().take(MAX_BATCH - 1));
+                    if let Err(e) = insert(&mut writer, &batch) {
+                        if error_tx
+                            .send(format!("Failed to record spots: {}", e))
+                            .is_err()
+                        {
+                            return;
+                        }
+                    }
+                }
+            });
+
+            Ok(Self {
+                spot_tx,
+                error_rx,
+                reader,
+            })
+        }
+
+        /// Queue a spot to be recorded
+        pub fn record(&self, spot: &RawSpot) {
+            let _ = self.spot_tx.send(spot.clone());
+        }
+
+        /// Spots of `callsign` received within `window`, newest first
+        pub fn spots_for(&self, callsign: &str, window: Duration) -> Result<Vec<HistoryRow>> {
+            let since = unix_seconds(SystemTime::now() - window);
+            let mut stmt = self
+                .reader
+                .prepare_cached(
+                    "SELECT received_at, spotter, callsign, frequency_khz, snr, wpm, mode
+                     FROM spots WHERE callsign = ?1 AND received_at >= ?2
+                     ORDER BY received_at DESC",
+                )
+                .map_err(db_error)?;
+            let rows = stmt
+                .query_map(params![callsign.trim().to_uppercase(), since], |row| {
+                    Ok(HistoryRow {
+                        received_at: UNIX_EPOCH + Duration::from_secs(row.get::<_, i64>(0)? as u64),
+                        spotter: row.get(1)?,
+                        callsign: row.get(2)?,
+                        frequency_khz: row.get(3)?,
+                        snr: row.get(4)?,
+                        wpm: row.get(5)?,
+                        mode: row.get(6)?,
+                    })
+                })
+                .map_err(db_error)?;
+            rows.collect::<rusqlite::Result<Vec<_>>>().map_err(db_error)
+        }
+
+        /// Next problem recording spots, if any
+        pub fn take_error(&self) -> Option<Error> {
+            self.error_rx.try_recv().ok().map(Error::History)
+        }
+    }
+
+    fn insert(conn: &mut Connection, spots: &[RawSpot]) -> rusqlite::Result<()> {
+        let tx = conn.transaction()?;
+        {
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO spots
+                 (received_at, spotter, callsign, frequency_khz, snr, wpm, mode, source)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            )?;
+            for spot in spots {
+                stmt.execute(params![
+                    unix_seconds(spot.received_at),
+                    spot.spotter_callsign,
+                    spot.spotted_callsign,
+                    spot.frequency_khz,
+                    spot.snr,
+                    spot.speed_wpm,
+                    spot.mode,
+                    format!("{:?}", spot.source).to_lowercase(),
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+}
+
+#[cfg(not(feature = "sqlite"))]
+mod stub {
+    use super::*;
+
+    /// Stand-in when built without the `sqlite` feature; never opens
+    #[allow(dead_code)]
+    pub struct SpotDb;
+
+    #[allow(dead_code)]
+    impl SpotDb {
+        pub fn open(_path: &Path) -> Result<Self> {
+            Err(Error::History(
+                "This build has no SQLite support (enable the sqlite feature)".to_string(),
+            ))
+        }
+
+        pub fn record(&self, _spot: &RawSpot) {}
+
+        pub fn spots_for(&self, _callsign: &str, _window: Duration) -> Result<Vec<HistoryRow>> {
+            Ok(Vec::new())
+        }
+
+        pub fn take_error(&self) -> Option<Error> {
+            None
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Session statistics, updated as each raw spot arrives
+
+use crate::models::UtcTime;
+use crate::models::{BandPlan, RawSpot};
+use std::collections::{HashMap, HashSet};
+
+/// A station's best report
+#[derive(Debug, Clone, PartialEq)]
+pub struct Standout {
+    pub callsign: String,
+    pub frequency_khz: f64,
+    /// SNR in dB for the loudest station, WPM for the fastest
+    pub value: i32,
+}
+
+/// Counts since the app started; each spot costs a few map updates
+#[derive(Debug, Clone, Default)]
+pub struct SessionStats {
+    pub total_spots: u64,
+    /// Spots per whole kHz, grouped into bands on demand by `per_band`
+    per_khz: HashMap<u32, u32>,
+    pub per_mode: HashMap<String, u32>,
+    /// Spots per UTC hour of the day
+    pub per_hour: [u32; 24],
+    pub unique_calls: HashSet<String>,
+    /// Spots per skimmer
+    per_spotter: HashMap<String, u32>,
+    pub loudest: Option<Standout>,
+    pub fastest: Option<Standout>,
+}
+
+impl SessionStats {
+    pub fn record(&mut self, raw: &RawSpot) {
+        self.total_spots += 1;
+        *self.per_khz.entry(raw.frequency_khz as u32).or_default() += 1;
+        *self.per_mode.entry(raw.mode.clone()).or_default() += 1;
+        *self
+            .per_spotter
+            .entry(raw.spotter_callsign.clone())
+            .or_default() += 1;
+        self.per_hour[UtcTime::from_system(raw.received_at).hour as usize] += 1;
+        if !self.unique_calls.contains(&raw.spotted_callsign) {
+            self.unique_calls.insert(raw.spotted_callsign.clone());
+        }
+
+        let standout = |value| Standout {
+            callsign: raw.spotted_callsign.clone(),
+            frequency_khz: raw.frequency_khz,
+            value,
+        };
+        if self.loudest.as_ref().is_none_or(|s| raw.snr > s.value) {
+            self.loudest = Some(standout(raw.snr));
+        }
+        // Only CW reports a speed in WPM (RTTY's is in baud)
+        if raw.mode == "CW"
+            && self
+                .fastest
+                .as_ref()
+                .is_none_or(|s| raw.speed_wpm > s.value)
+        {
+            self.fastest = Some(standout(raw.speed_wpm));
+        }
+    }
+
+    /// Spot counts per band in band plan order, skipping bands with none;
+    /// spots outside the plan are counted as "Other"
+    pub fn per_band(&self, plan: &BandPlan) -> Vec<(String, u32)> {
+        let mut counts = vec![0u32; plan.bands().len()];
+        let mut other = 0;
+        for (&khz, &count) in &self.per_khz {
+            match plan
+                .bands()
+                .iter()
+                .position(|b| b.contains(khz as f64 + 0.5))
+            {
+                Some(i) => counts[i] += count,
+                None => other += count,
+            }
+        }
+
+        let mut result: Vec<(String, u32)> = plan
+            .bands()
+            .iter()
+            .zip(counts)
+            .filter(|(_, count)| *count > 0)
+            .map(|(band, count)| (band.name.clone(), count))
+            .collect();
+        if other > 0 {
+            result.push(("Other".to_string(), other));
+        }
+        result
+    }
+
+    /// Skimmers by descending spot count
+    pub fn spotters_by_count(&self) -> Vec<(String, u32)> {
+        let mut spotters: Vec<(String, u32)> = self
+            .per_spotter
+            .iter()
+            .map(|(spotter, count)| (spotter.clone(), *count))
+            .collect();
+        spotters.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        spotters
+    }
+
+    /// Modes by descending spot count
+    pub fn modes_by_count(&self) -> Vec<(String, u32)> {
+        let mut modes: Vec<(String, u32)> = self
+            .per_mode
+            .iter()
+            .map(|(mode, count)| (mode.clone(), *count))
+            .collect();
+        modes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        modes
+    }
+}
@@ -1,62 +1,650 @@
-use crate::models::{AggregatedSpot, RawSpot};
-use std::collections::HashMap;
+use crate::models::{
+    AggregatedSpot, Band, BandPlan, BustedCallPolicy, RawSpot, SourcePolicy, SpotSource,
+};
+use crate::services::busted_calls;
+use crate::services::calibration::Calibration;
+use crate::services::metrics::METRICS;
+use crate::services::spot_stats::SessionStats;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 
 /// Thread-safe store for aggregated spots
 #[derive(Clone)]
 pub struct SpotStore {
-    spots: Arc<Mutex<HashMap<String, AggregatedSpot>>>,
+    spots: Arc<Mutex<SpotTable>>,
+    /// When each new signal was first reported over the last day, oldest first
+    heard: Arc<Mutex<VecDeque<Instant>>>,
+    /// Spots purged from the store this session, oldest first
+    history: Arc<Mutex<VecDeque<AggregatedSpot>>>,
+    /// First report of each call on each band this session; never purged
+    first_heard: Arc<Mutex<HashMap<(String, String), SystemTime>>>,
+    /// Bands `first_heard` is kept per
+    band_plan: Arc<Mutex<BandPlan>>,
+    stats: Arc<Mutex<SessionStats>>,
+    /// Per-skimmer frequency corrections applied before bucketing
+    calibration: Arc<Mutex<Calibration>>,
+    /// Reports of a call within this many Hz belong to the same spot
+    merge_tolerance_hz: Arc<AtomicU32>,
+    /// Serial number given to the next new spot, so keys never collide
+    next_spot_id: Arc<AtomicU64>,
+    /// Handling of spots that look like miscopies of a nearby call
+    busted_policy: Arc<Mutex<BustedCallPolicy>>,
+    /// Frequency ranges spots must fall in to be shown (empty = everywhere)
+    windows: Arc<Mutex<Vec<Band>>>,
+    /// Spotted calls whose reports are dropped: until when, or None for good
+    ignored: Arc<Mutex<HashMap<String, Option<Instant>>>>,
+    /// Bumped whenever spots are added or removed
+    version: Arc<AtomicU64>,
+    /// Bumped whenever the settings shaping the filtered view change (or on clear)
+    settings_version: Arc<AtomicU64>,
+    /// How often the filtered view is rebuilt, in ms (0 = whenever spots change)
+    refresh_interval_ms: Arc<AtomicU64>,
+    /// Recent results of `get_filtered_spots`, shared until they are due a refresh
+    view_cache: Arc<Mutex<Vec<FilteredView>>>,
 }
 
+/// The current spots by key, indexed by callsign so a report is only compared
+/// with the spots of its own call
+#[derive(Default)]
+struct SpotTable {
+    spots: HashMap<String, AggregatedSpot>,
+    /// Keys of each call's spots
+    by_call: HashMap<String, Vec<String>>,
+}
+
+impl SpotTable {
+    fn len(&self) -> usize {
+        self.spots.len()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (&String, &AggregatedSpot)> {
+        self.spots.iter()
+    }
+
+    fn values(&self) -> impl Iterator<Item = &AggregatedSpot> {
+        self.spots.values()
+    }
+
+    fn get_mut(&mut self, key: &str) -> Option<&mut AggregatedSpot> {
+        self.spots.get_mut(key)
+    }
+
+    fn insert(&mut self, spot: AggregatedSpot) {
+        let key = spot.key();
+        self.by_call
+            .entry(spot.callsign.clone())
+            .or_default()
+            .push(key.clone());
+        self.spots.insert(key, spot);
+    }
+
+    fn remove(&mut self, key: &str) -> Option<AggregatedSpot> {
+        let spot = self.spots.remove(key)?;
+        if let Some(keys) = self.by_call.get_mut(&spot.callsign) {
+            keys.retain(|k| k != key);
+            if keys.is_empty() {
+                self.by_call.remove(&spot.callsign);
+            }
+        }
+        Some(spot)
+    }
+
+    /// Keep the spots `keep` accepts; returns the others
+    fn retain(&mut self, mut keep: impl FnMut(&AggregatedSpot) -> bool) -> Vec<AggregatedSpot> {
+        let dropped: Vec<String> = self
+            .spots
+            .iter()
+            .filter(|(_, spot)| !keep(spot))
+            .map(|(key, _)| key.clone())
+            .collect();
+        dropped.iter().filter_map(|key| self.remove(key)).collect()
+    }
+
+    fn clear(&mut self) {
+        self.spots.clear();
+        self.by_call.clear();
+    }
+
+    /// The spot of `callsign` nearest `frequency_khz`, if one is within
+    /// `tolerance_khz`
+    fn nearest_mut(
+        &mut self,
+        callsign: &str,
+        frequency_khz: f64,
+        tolerance_khz: f64,
+    ) -> Option<&mut AggregatedSpot> {
+        let distance = |spot: &AggregatedSpot| (spot.frequency_khz - frequency_khz).abs();
+        let key = self
+            .by_call
+            .get(callsign)?
+            .iter()
+            .filter_map(|key| self.spots.get(key).map(|spot| (key, distance(spot))))
+            .filter(|(_, distance)| *distance <= tolerance_khz)
+            .min_by(|a, b| a.1.total_cmp(&b.1))?
+            .0
+            .clone();
+        self.spots.get_mut(&key)
+    }
+}
+
+/// A cached `get_filtered_spots` result and what it was built from
+struct FilteredView {
+    version: u64,
+    settings_version: u64,
+    min_snr: i32,
+    max_age: Duration,
+    built: Instant,
+    spots: Vec<AggregatedSpot>,
+}
+
+/// Band name of a frequency for first-heard tracking; outside the plan, the MHz
+fn band_key(band_plan: &BandPlan, frequency_khz: f64) -> String {
+    band_plan
+        .for_frequency(frequency_khz)
+        .map(|band| band.name.clone())
+        .unwrap_or_else(|| format!("{:.0} MHz", (frequency_khz / 1000.0).floor()))
+}
+
+/// How long first-report times are kept for `count_heard_since`
+const HEARD_HISTORY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Merge distance until `set_merge_tolerance` is called
+const DEFAULT_MERGE_TOLERANCE_HZ: u32 = 300;
+
+/// Most purged spots kept for `session_history`
+const MAX_HISTORY: usize = 100_000;
+
+/// Most spots held at once; the least recently reported are evicted beyond this
+const MAX_SPOTS: usize = 5_000;
+
+/// Refresh interval until `set_refresh_interval` is called
+const DEFAULT_REFRESH_INTERVAL_MS: u64 = 1000;
+
+/// With no refresh interval, longest an unchanged view is reused so spots
+/// still age out of it
+const UNCHANGED_VIEW_TTL: Duration = Duration::from_millis(500);
+
 impl SpotStore {
     pub fn new() -> Self {
         Self {
-            spots: Arc::new(Mutex::new(HashMap::new())),
+            spots: Arc::new(Mutex::new(SpotTable::default())),
+            heard: Arc::new(Mutex::new(VecDeque::new())),
+            history: Arc::new(Mutex::new(VecDeque::new())),
+            first_heard: Arc::new(Mutex::new(HashMap::new())),
+            band_plan: Arc::new(Mutex::new(BandPlan::default())),
+            stats: Arc::new(Mutex::new(SessionStats::default())),
+            calibration: Arc::new(Mutex::new(Calibration::default())),
+            merge_tolerance_hz: Arc::new(AtomicU32::new(DEFAULT_MERGE_TOLERANCE_HZ)),
+            next_spot_id: Arc::new(AtomicU64::new(0)),
+            busted_policy: Arc::new(Mutex::new(BustedCallPolicy::Off)),
+            windows: Arc::new(Mutex::new(Vec::new())),
+            ignored: Arc::new(Mutex::new(HashMap::new())),
+            version: Arc::new(AtomicU64::new(0)),
+            settings_version: Arc::new(AtomicU64::new(0)),
+            refresh_interval_ms: Arc::new(AtomicU64::new(DEFAULT_REFRESH_INTERVAL_MS)),
+            view_cache: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
     /// Add or update a spot (stores all spots, filtering happens at retrieval)
-    pub fn add_spot(&self, raw: RawSpot) {
-        let center_freq = raw.frequency_khz.round();
-        let key = format!("{}|{:.0}", raw.spotted_callsign, center_freq);
+    ///
+    /// The report joins the nearest spot of the same call within the merge
+    /// tolerance, so a station sitting on a kHz boundary isn't split in two.
+    /// `policy` decides which source wins when skimmer and human spots of the
+    /// same signal disagree. Returns the new aggregated spot if this is the first
+    /// report of the signal. The spotter's frequency correction is applied first.
+    /// Reports of ignored calls are dropped, unless entered manually.
+    pub fn add_spot(&self, raw: RawSpot, policy: SourcePolicy) -> Option<AggregatedSpot> {
+        self.add_spots(vec![raw], policy).pop()
+    }
 
-        if let Ok(mut spots) = self.spots.lock() {
-            if let Some(existing) = spots.get_mut(&key) {
-                existing.update(&raw);
+    /// Add a batch of reports as `add_spot` does, taking each lock once
+    ///
+    /// Returns the spots that were new. If the store grows past its cap the
+    /// least recently reported spots are moved to the session history.
+    pub fn add_spots(&self, raws: Vec<RawSpot>, policy: SourcePolicy) -> Vec<AggregatedSpot> {
+        let raws: Vec<RawSpot> = raws
+            .into_iter()
+            .filter(|raw| {
+                raw.source == SpotSource::Manual || !self.is_ignored(&raw.spotted_callsign)
+            })
+            .collect();
+        if raws.is_empty() {
+            return Vec::new();
+        }
+        let tolerance_khz = self.merge_tolerance_hz.load(Ordering::Relaxed) as f64 / 1000.0;
+        let (Ok(mut calibration), Ok(mut stats), Ok(band_plan), Ok(mut first_heard), Ok(mut spots)) = (
+            self.calibration.lock(),
+            self.stats.lock(),
+            self.band_plan.lock(),
+            self.first_heard.lock(),
+            self.spots.lock(),
+        ) else {
+            return Vec::new();
+        };
+
+        let mut new_spots = Vec::new();
+        for mut raw in raws {
+            let reported_khz = raw.frequency_khz;
+            raw.frequency_khz = calibration.correct(&raw.spotter_callsign, reported_khz);
+            stats.record(&raw);
+            let first_heard_at = *first_heard
+                .entry((
+                    raw.spotted_callsign.clone(),
+                    band_key(&band_plan, raw.frequency_khz),
+                ))
+                .or_insert(raw.received_at);
+
+            let nearest =
+                spots.nearest_mut(&raw.spotted_callsign, raw.frequency_khz, tolerance_khz);
+            if let Some(existing) = nearest {
+                if raw.source == SpotSource::Skimmer {
+                    calibration.observe(
+                        &raw.spotter_callsign,
+                        reported_khz,
+                        existing.frequency_khz,
+                    );
+                }
+                existing.update(&raw, policy);
             } else {
-                let spot = AggregatedSpot::from_raw(&raw);
-                spots.insert(key, spot);
+                let mut spot = AggregatedSpot::from_raw(&raw);
+                spot.id = self.next_spot_id.fetch_add(1, Ordering::Relaxed);
+                spot.first_heard_at = first_heard_at;
+                spots.insert(spot.clone());
+                new_spots.push(spot);
+            }
+        }
+        drop((calibration, stats, band_plan, first_heard));
+
+        if spots.len() > MAX_SPOTS {
+            let mut by_age: Vec<(Instant, String)> = spots
+                .iter()
+                .filter(|(_, spot)| !spot.pinned)
+                .map(|(key, spot)| (spot.last_spotted, key.clone()))
+                .collect();
+            by_age.sort();
+            let excess = (spots.len() - MAX_SPOTS).min(by_age.len());
+            let evicted: Vec<AggregatedSpot> = by_age[..excess]
+                .iter()
+                .filter_map(|(_, key)| spots.remove(key))
+                .collect();
+            self.add_to_history(evicted);
+        }
+        METRICS.set_active_spots(spots.len());
+        drop(spots);
+
+        if let Ok(mut heard) = self.heard.lock() {
+            heard.extend(new_spots.iter().map(|s| s.last_spotted));
+        }
+        self.touch();
+        new_spots
+    }
+
+    /// Note that spots changed (the view catches up at its next refresh)
+    fn touch(&self) {
+        self.version.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Note that the view's settings changed, so it is rebuilt right away
+    fn touch_settings(&self) {
+        self.settings_version.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Set how often the filtered view is rebuilt (zero = whenever spots change)
+    pub fn set_refresh_interval(&self, interval: Duration) {
+        self.refresh_interval_ms
+            .store(interval.as_millis() as u64, Ordering::Relaxed);
+        self.touch_settings();
+    }
+
+    /// Append spots leaving the store to the session history
+    fn add_to_history(&self, mut purged: Vec<AggregatedSpot>) {
+        if let Ok(mut history) = self.history.lock() {
+            purged.sort_by_key(|s| s.first_spotted_at);
+            history.extend(purged);
+            let excess = history.len().saturating_sub(MAX_HISTORY);
+            history.drain(..excess);
+        }
+    }
+
+    /// Set how far apart (in Hz) reports of a call can be and still merge
+    pub fn set_merge_tolerance(&self, hz: u32) {
+        self.merge_tolerance_hz.store(hz, Ordering::Relaxed);
+    }
+
+    /// Set how spots of busted calls are handled
+    pub fn set_busted_policy(&self, policy: BustedCallPolicy) {
+        if let Ok(mut current) = self.busted_policy.lock() {
+            *current = policy;
+        }
+        self.touch_settings();
+    }
+
+    /// Set the frequency ranges spots must fall in (empty = no restriction)
+    pub fn set_frequency_windows(&self, windows: Vec<Band>) {
+        if let Ok(mut current) = self.windows.lock() {
+            *current = windows;
+        }
+        self.touch_settings();
+    }
+
+    /// Set the bands first-heard times are kept per
+    pub fn set_band_plan(&self, band_plan: BandPlan) {
+        if let Ok(mut current) = self.band_plan.lock() {
+            *current = band_plan;
+        }
+    }
+
+    /// Band first-heard times are kept per for `frequency_khz`
+    pub fn band_of(&self, frequency_khz: f64) -> Option<String> {
+        let plan = self.band_plan.lock().ok()?;
+        Some(band_key(&plan, frequency_khz))
+    }
+
+    /// When `call` was first reported this session on the band of `frequency_khz`,
+    /// even if its spot has since been purged
+    pub fn first_heard(&self, call: &str, frequency_khz: f64) -> Option<SystemTime> {
+        let band = self.band_of(frequency_khz)?;
+        self.first_heard
+            .lock()
+            .ok()?
+            .get(&(call.to_string(), band))
+            .copied()
+    }
+
+    /// Set the calls ignored for good, keeping any snoozes
+    pub fn set_ignored_calls(&self, calls: Vec<String>) {
+        if let Ok(mut ignored) = self.ignored.lock() {
+            ignored.retain(|_, until| until.is_some());
+            for call in calls {
+                ignored.insert(call.to_uppercase(), None);
+            }
+        }
+        self.drop_ignored_spots();
+    }
+
+    /// Ignore a call for `duration`, removing its current spots
+    pub fn snooze_call(&self, call: &str, duration: Duration) {
+        if let Ok(mut ignored) = self.ignored.lock() {
+            let call = call.trim().to_uppercase();
+            // A call ignored for good stays that way
+            if ignored.get(&call) != Some(&None) {
+                ignored.insert(call, Some(Instant::now() + duration));
+            }
+        }
+        self.drop_ignored_spots();
+    }
+
+    /// End a call's snooze early
+    pub fn unsnooze_call(&self, call: &str) {
+        if let Ok(mut ignored) = self.ignored.lock() {
+            let call = call.trim().to_uppercase();
+            if ignored.get(&call).is_some_and(Option::is_some) {
+                ignored.remove(&call);
             }
         }
     }
 
-    /// Remove spots older than 30 minutes (hard limit for memory management)
+    /// Snoozed calls and when each snooze ends, soonest first
+    pub fn snoozed_calls(&self) -> Vec<(String, Instant)> {
+        let mut snoozed: Vec<(String, Instant)> = self
+            .ignored
+            .lock()
+            .map(|ignored| {
+                ignored
+                    .iter()
+                    .filter_map(|(call, until)| Some((call.clone(), (*until)?)))
+                    .filter(|(_, until)| *until > Instant::now())
+                    .collect()
+            })
+            .unwrap_or_default();
+        snoozed.sort_by_key(|(_, until)| *until);
+        snoozed
+    }
+
+    /// Whether reports of `call` are currently dropped
+    pub fn is_ignored(&self, call: &str) -> bool {
+        let Ok(mut ignored) = self.ignored.lock() else {
+            return false;
+        };
+        ignored.retain(|_, until| until.is_none_or(|t| t > Instant::now()));
+        ignored.contains_key(&call.to_uppercase())
+    }
+
+    /// Move the spots of ignored calls (other than manual ones) to the history
+    fn drop_ignored_spots(&self) {
+        let Ok(mut spots) = self.spots.lock() else {
+            return;
+        };
+        let dropped = spots.retain(|spot| spot.manual || !self.is_ignored(&spot.callsign));
+        METRICS.set_active_spots(spots.len());
+        drop(spots);
+        if !dropped.is_empty() {
+            self.add_to_history(dropped);
+            self.touch_settings();
+        }
+    }
+
+    /// Set the manual frequency corrections (Hz per spotter) and whether
+    /// corrections are learned from the spots themselves
+    pub fn set_calibration(&self, manual_hz: HashMap<String, f64>, learning: bool) {
+        if let Ok(mut calibration) = self.calibration.lock() {
+            calibration.configure(manual_hz, learning);
+        }
+    }
+
+    /// Frequency correction in Hz applied to a spotter's reports, if any
+    pub fn spotter_offset_hz(&self, spotter: &str) -> Option<f64> {
+        self.calibration
+            .lock()
+            .ok()?
+            .offset_khz(spotter)
+            .map(|khz| khz * 1000.0)
+    }
+
+    /// Pin or unpin the spot with `key`
+    pub fn set_pinned(&self, key: &str, pinned: bool) {
+        if let Ok(mut spots) = self.spots.lock() {
+            if let Some(spot) = spots.get_mut(key) {
+                spot.pinned = pinned;
+            }
+        }
+        self.touch_settings();
+    }
+
+    /// Attach a note to the spot with `key` (empty clears it)
+    pub fn set_note(&self, key: &str, note: &str) {
+        if let Ok(mut spots) = self.spots.lock() {
+            if let Some(spot) = spots.get_mut(key) {
+                spot.note = note.trim().to_string();
+            }
+        }
+        self.touch_settings();
+    }
+
+    /// Remove spots older than 30 minutes (hard limit for memory management),
+    /// except pinned ones
     pub fn purge_old_spots(&self) {
         let cutoff = Instant::now() - Duration::from_secs(30 * 60);
 
         if let Ok(mut spots) = self.spots.lock() {
-            spots.retain(|_, spot| spot.last_spotted >= cutoff);
+            let purged = spots.retain(|spot| spot.pinned || spot.last_spotted >= cutoff);
+            METRICS.set_active_spots(spots.len());
+            if !purged.is_empty() {
+                self.add_to_history(purged);
+                self.touch();
+            }
+        }
+
+        if let Ok(mut heard) = self.heard.lock() {
+            while heard.front().is_some_and(|t| t.elapsed() >= HEARD_HISTORY) {
+                heard.pop_front();
+            }
         }
     }
 
-    /// Get spots filtered by min_snr and max_age, sorted by frequency
+    /// Number of new signals first reported within `window` (up to 24 hours), purged or not
+    pub fn count_heard_since(&self, window: Duration) -> usize {
+        self.heard
+            .lock()
+            .map(|heard| heard.iter().filter(|t| t.elapsed() < window).count())
+            .unwrap_or(0)
+    }
+
+    /// Get spots filtered by min_snr and the frequency windows (manual spots
+    /// exempt from both) and max_age, sorted by frequency; pinned spots pass
+    /// every filter
+    ///
+    /// Busted calls are flagged or merged first, so a weak miscopy can't
+    /// outlive the filter by borrowing the real station's reports. The result
+    /// is a snapshot rebuilt at the refresh interval and shared by every
+    /// caller, so the table, band scope and VFD stay cheap while spots pour in.
     pub fn get_filtered_spots(&self, min_snr: i32, max_age: Duration) -> Vec<AggregatedSpot> {
+        let version = self.version.load(Ordering::Relaxed);
+        let settings_version = self.settings_version.load(Ordering::Relaxed);
+        let interval = Duration::from_millis(self.refresh_interval_ms.load(Ordering::Relaxed));
+        if let Ok(cache) = self.view_cache.lock() {
+            let fresh = |v: &&FilteredView| {
+                let current = if interval.is_zero() {
+                    v.version == version && v.built.elapsed() < UNCHANGED_VIEW_TTL
+                } else {
+                    v.built.elapsed() < interval
+                };
+                current
+                    && v.settings_version == settings_version
+                    && v.min_snr == min_snr
+                    && v.max_age == max_age
+            };
+            if let Some(view) = cache.iter().find(fresh) {
+                return view.spots.clone();
+            }
+        }
+
         let cutoff = Instant::now() - max_age;
+        let policy = self
+            .busted_policy
+            .lock()
+            .map(|p| *p)
+            .unwrap_or(BustedCallPolicy::Off);
+        let windows = self.windows.lock().map(|w| w.clone()).unwrap_or_default();
+        let in_window = |spot: &AggregatedSpot| {
+            spot.manual
+                || spot.pinned
+                || windows.is_empty()
+                || windows.iter().any(|w| w.contains(spot.frequency_khz))
+        };
 
         if let Ok(spots) = self.spots.lock() {
             let mut result: Vec<_> = spots
                 .values()
-                .filter(|spot| spot.highest_snr >= min_snr && spot.last_spotted >= cutoff)
+                .filter(|spot| (spot.pinned || spot.last_spotted >= cutoff) && in_window(spot))
                 .cloned()
                 .collect();
+            drop(spots);
+            busted_calls::resolve(&mut result, policy);
+            result.retain(|spot| spot.manual || spot.pinned || spot.highest_snr >= min_snr);
             result.sort_by(|a, b| a.frequency_khz.partial_cmp(&b.frequency_khz).unwrap());
+            if let Ok(mut cache) = self.view_cache.lock() {
+                cache.retain(|v| {
+                    v.settings_version == settings_version
+                        && (v.min_snr, v.max_age) != (min_snr, max_age)
+                });
+                cache.push(FilteredView {
+                    version,
+                    settings_version,
+                    min_snr,
+                    max_age,
+                    built: Instant::now(),
+                    spots: result.clone(),
+                });
+            }
             result
         } else {
             Vec::new()
         }
     }
 
+    /// Spots reported within `max_age` and `within_khz` of `frequency_khz`,
+    /// unfiltered otherwise, nearest first
+    pub fn get_spots_near(
+        &self,
+        frequency_khz: f64,
+        within_khz: f64,
+        max_age: Duration,
+    ) -> Vec<AggregatedSpot> {
+        let cutoff = Instant::now() - max_age;
+        let distance = |spot: &AggregatedSpot| (spot.frequency_khz - frequency_khz).abs();
+        let mut result: Vec<AggregatedSpot> = self
+            .spots
+            .lock()
+            .map(|spots| {
+                spots
+                    .values()
+                    .filter(|spot| spot.last_spotted >= cutoff && distance(spot) <= within_khz)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default();
+        result.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+        result
+    }
+
+    /// Get spots filtered per band: within each band of `band_plan` only the strongest
+    /// `keep_percent` of recent spots are shown, so quiet bands aren't emptied by a
+    /// threshold tuned for a busy one. Spots outside the plan fall back to `min_snr`;
+    /// manual and pinned spots are exempt.
+    pub fn get_adaptive_spots(
+        &self,
+        band_plan: &BandPlan,
+        keep_percent: u32,
+        min_snr: i32,
+        max_age: Duration,
+    ) -> Vec<AggregatedSpot> {
+        let mut spots = self.get_filtered_spots(i32::MIN, max_age);
+        let thresholds = Self::adaptive_thresholds(&spots, band_plan, keep_percent);
+        spots.retain(|spot| {
+            let threshold = band_plan
+                .for_frequency(spot.frequency_khz)
+                .and_then(|band| thresholds.get(&band.name))
+                .copied()
+                .unwrap_or(min_snr);
+            spot.manual || spot.pinned || spot.highest_snr >= threshold
+        });
+        spots
+    }
+
+    /// Per-band SNR thresholds keeping the strongest `keep_percent` of each band's spots
+    pub fn adaptive_thresholds(
+        spots: &[AggregatedSpot],
+        band_plan: &BandPlan,
+        keep_percent: u32,
+    ) -> HashMap<String, i32> {
+        let mut snrs_by_band: HashMap<String, Vec<i32>> = HashMap::new();
+        for spot in spots.iter().filter(|s| !s.manual) {
+            if let Some(band) = band_plan.for_frequency(spot.frequency_khz) {
+                snrs_by_band
+                    .entry(band.name.clone())
+                    .or_default()
+                    .push(spot.highest_snr);
+            }
+        }
+
+        snrs_by_band
+            .into_iter()
+            .map(|(band, mut snrs)| {
+                snrs.sort_unstable_by(|a, b| b.cmp(a));
+                let keep = (snrs.len() * keep_percent.min(100) as usize).div_ceil(100);
+                let threshold = match keep {
+                    0 => i32::MAX,
+                    n => snrs[n - 1],
+                };
+                (band, threshold)
+            })
+            .collect()
+    }
+
     /// Get all spots sorted by frequency (no filtering, utility method)
     #[allow(dead_code)]
     pub fn get_spots_by_frequency(&self) -> Vec<AggregatedSpot> {
@@ -74,13 +662,32 @@ impl SpotStore {
     pub fn get_spots_by_recency(&self) -> Vec<AggregatedSpot> {
         if let Ok(spots) = self.spots.lock() {
             let mut result: Vec<_> = spots.values().cloned().collect();
-            result.sort_by(|a, b| b.last_spotted.cmp(&a.last_spotted));
+            result.sort_by_key(|spot| std::cmp::Reverse(spot.last_spotted));
             result
         } else {
             Vec::new()
         }
     }
 
+    /// Every spot heard this session, purged or current, in order of first report
+    pub fn session_history(&self) -> Vec<AggregatedSpot> {
+        let mut spots: Vec<AggregatedSpot> = self
+            .history
+            .lock()
+            .map(|h| h.iter().cloned().collect())
+            .unwrap_or_default();
+        if let Ok(current) = self.spots.lock() {
+            spots.extend(current.values().cloned());
+        }
+        spots.sort_by_key(|s| s.first_spotted_at);
+        spots
+    }
+
+    /// Run `f` on the statistics gathered this session
+    pub fn with_stats<T>(&self, f: impl FnOnce(&SessionStats) -> T) -> Option<T> {
+        self.stats.lock().ok().map(|stats| f(&stats))
+    }
+
     /// Get spot count
     pub fn count(&self) -> usize {
         self.spots.lock().map(|s| s.len()).unwrap_or(0)
@@ -91,6 +698,106 @@ impl SpotStore {
     pub fn clear(&self) {
         if let Ok(mut spots) = self.spots.lock() {
             spots.clear();
+            METRICS.set_active_spots(0);
         }
+        self.touch_settings();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(source: SpotSource, call: &str, frequency_khz: f64, mode: &str) -> RawSpot {
+        let spotter = if source == SpotSource::Skimmer {
+            "DL8LAS-#"
+        } else {
+            "W6JSV"
+        };
+        RawSpot::new(
+            spotter.to_string(),
+            call.to_string(),
+            frequency_khz,
+            20,
+            25,
+            mode.to_string(),
+            source,
+        )
+    }
+
+    fn modes(store: &SpotStore) -> Vec<String> {
+        store
+            .get_filtered_spots(i32::MIN, Duration::from_secs(600))
+            .into_iter()
+            .map(|s| s.mode)
+            .collect()
+    }
+
+    #[test]
+    fn keep_both_merges_per_source() {
+        let store = SpotStore::new();
+        store.set_refresh_interval(Duration::ZERO);
+        let keep = SourcePolicy::KeepBoth;
+
+        // Same source: the newer report replaces that source's mode
+        store.add_spot(report(SpotSource::Skimmer, "K3LR", 14025.0, "CW"), keep);
+        store.add_spot(report(SpotSource::Skimmer, "K3LR", 14025.1, "RTTY"), keep);
+        assert_eq!(modes(&store), ["RTTY"]);
+
+        // Another source is kept alongside, and survives later skimmer reports
+        store.add_spot(report(SpotSource::Human, "K3LR", 14025.0, "SSB"), keep);
+        store.add_spot(report(SpotSource::Skimmer, "K3LR", 14025.1, "CW"), keep);
+        assert_eq!(modes(&store), ["CW/SSB"]);
+        store.add_spot(report(SpotSource::Human, "K3LR", 14025.0, "FT8"), keep);
+        assert_eq!(modes(&store), ["CW/FT8"]);
+    }
+
+    #[test]
+    fn reports_just_outside_tolerance_keep_their_own_spot() {
+        let store = SpotStore::new();
+        store.set_refresh_interval(Duration::ZERO);
+        store.set_merge_tolerance(50);
+        let policy = SourcePolicy::PreferSkimmer;
+
+        store.add_spot(report(SpotSource::Skimmer, "K3LR", 14025.00, "CW"), policy);
+        store.add_spot(report(SpotSource::Skimmer, "K3LR", 14025.04, "CW"), policy);
+        // 60 Hz from the averaged 14025.02, but also "14025.0" to one decimal
+        let new = store.add_spot(report(SpotSource::Skimmer, "K3LR", 14024.96, "CW"), policy);
+        assert!(new.is_some());
+
+        let mut counts: Vec<u32> = store
+            .get_filtered_spots(i32::MIN, Duration::from_secs(600))
+            .iter()
+            .map(|s| s.spot_count)
+            .collect();
+        counts.sort();
+        assert_eq!(counts, [1, 2]);
+    }
+
+    #[test]
+    fn table_matches_reports_only_against_their_call() {
+        let spot = |call: &str, frequency_khz: f64, id: u64| {
+            let mut spot =
+                AggregatedSpot::from_raw(&report(SpotSource::Skimmer, call, frequency_khz, "CW"));
+            spot.id = id;
+            spot
+        };
+        let mut table = SpotTable::default();
+        table.insert(spot("K3LR", 14025.0, 1));
+        table.insert(spot("K3LR", 14026.0, 2));
+        table.insert(spot("W1AW", 14025.2, 3));
+
+        let nearest = table.nearest_mut("K3LR", 14025.3, 0.5).map(|s| s.id);
+        assert_eq!(nearest, Some(1));
+        assert!(table.nearest_mut("K3LR", 14025.5, 0.3).is_none());
+        assert!(table.nearest_mut("N1MM", 14025.2, 0.5).is_none());
+
+        let dropped = table.retain(|spot| spot.id != 1);
+        assert_eq!(dropped.len(), 1);
+        assert!(table.nearest_mut("K3LR", 14025.3, 0.5).is_none());
+        let key = spot("W1AW", 14025.2, 3).key();
+        assert_eq!(table.remove(&key).map(|s| s.id), Some(3));
+        assert!(!table.by_call.contains_key("W1AW"));
+        assert_eq!(table.len(), 1);
     }
 }
@@ -0,0 +1,108 @@
+//! Translation from text to the character codes in a display's font ROM
+//!
+//! Each driver supplies the codes its ROM uses for characters outside plain
+//! ASCII (or in place of ASCII codes it draws as something else). Users can add
+//! or override entries with a mapping file, one entry per line:
+//!
+//! ```text
+//! # character = code
+//! ° = 0xDF
+//! \ = /
+//! U+00D8 = 0
+//! ```
+//!
+//! The character may be written literally or as `U+XXXX`; the code may be hex,
+//! decimal, or a single ASCII character to substitute.
+
+use std::collections::HashMap;
+
+/// Characters with no mapping and no ASCII code are shown as this
+const UNKNOWN: u8 = b'?';
+
+/// HD44780 A00 (Japanese) font, also used by Matrix Orbital and Noritake CU modules:
+/// 0x7E is an arrow and 0x5C a yen sign, so '~' is approximated
+pub const HD44780_ROM: &[(char, u8)] = &[
+    ('°', 0xDF),
+    ('¥', 0x5C),
+    ('~', b'-'),
+    ('µ', 0xE4),
+    ('█', 0xFF),
+];
+
+/// Code page 437, used by ELO-style customer displays
+pub const CP437: &[(char, u8)] = &[('°', 0xF8), ('±', 0xF1), ('µ', 0xE6), ('█', 0xDB)];
+
+/// Maps characters to display character codes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CharMap {
+    codes: HashMap<char, u8>,
+}
+
+impl CharMap {
+    pub fn new(entries: &[(char, u8)]) -> Self {
+        let mut map = Self::default();
+        map.extend(entries.iter().copied());
+        map
+    }
+
+    /// Add entries, replacing existing ones for the same character
+    pub fn extend(&mut self, entries: impl IntoIterator<Item = (char, u8)>) {
+        self.codes.extend(entries);
+    }
+
+    /// Character codes for `text`
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        text.chars()
+            .map(|c| match self.codes.get(&c) {
+                Some(code) => *code,
+                None if c.is_ascii() => c as u8,
+                None => UNKNOWN,
+            })
+            .collect()
+    }
+
+    /// Parse a mapping file into entries
+    pub fn parse(text: &str) -> Result<Vec<(char, u8)>, String> {
+        text.lines()
+            .enumerate()
+            .map(|(n, line)| (n + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(n, line)| {
+                // Split on the last '=' so '=' itself can be mapped
+                let (key, value) = line
+                    .rsplit_once('=')
+                    .ok_or_else(|| format!("line {}: expected 'character = code'", n))?;
+                let character = parse_char(key.trim())
+                    .ok_or_else(|| format!("line {}: bad character '{}'", n, key.trim()))?;
+                let code = parse_code(value.trim())
+                    .ok_or_else(|| format!("line {}: bad code '{}'", n, value.trim()))?;
+                Ok((character, code))
+            })
+            .collect()
+    }
+}
+
+fn parse_char(text: &str) -> Option<char> {
+    if let Some(hex) = text.strip_prefix("U+").or_else(|| text.strip_prefix("u+")) {
+        return u32::from_str_radix(hex, 16).ok().and_then(char::from_u32);
+    }
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+fn parse_code(text: &str) -> Option<u8> {
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        return u8::from_str_radix(hex, 16).ok();
+    }
+    if let Ok(code) = text.parse() {
+        return Some(code);
+    }
+    let mut chars = text.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii() => Some(c as u8),
+        _ => None,
+    }
+}
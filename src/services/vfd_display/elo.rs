@@ -0,0 +1,25 @@
+//! ELO customer display command set
+
+use super::charset::{CharMap, CP437};
+use super::protocol::DisplayProtocol;
+
+/// Form feed - clear and home cursor
+const CLEAR_DISPLAY: u8 = 0x0C;
+
+/// Simple protocol without ANSI escape sequences: clear, then write every
+/// cell in order and let the display wrap lines
+pub struct EloProtocol;
+
+impl DisplayProtocol for EloProtocol {
+    fn charset(&self) -> CharMap {
+        CharMap::new(CP437)
+    }
+
+    fn clear(&self) -> Vec<u8> {
+        vec![CLEAR_DISPLAY]
+    }
+
+    fn set_cursor(&self, _col: usize, _row: usize) -> Option<Vec<u8>> {
+        None
+    }
+}
@@ -0,0 +1,68 @@
+//! Futaba/Noritake GP9002-class 128x64 graphic VFD
+//!
+//! Driven through a Noritake GU-7000 compatible serial interface: whole
+//! frames are sent as a real-time bit image, column-major with eight
+//! vertical pixels per byte (MSB at the top).
+
+use super::graphic::Bitmap;
+use super::protocol::DisplayProtocol;
+
+/// Panel size in pixels
+pub const WIDTH: usize = 128;
+pub const HEIGHT: usize = 64;
+
+pub struct Gp9002Protocol;
+
+impl DisplayProtocol for Gp9002Protocol {
+    fn init(&self) -> Vec<u8> {
+        // ESC @: initialize display
+        vec![0x1B, 0x40]
+    }
+
+    fn clear(&self) -> Vec<u8> {
+        vec![0x0C]
+    }
+
+    fn set_cursor(&self, _col: usize, _row: usize) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn brightness(&self, percent: u8) -> Option<Vec<u8>> {
+        // US X n: brightness level 1-8
+        let level = 1 + (percent.min(100) as u32 * 7 / 100) as u8;
+        Some(vec![0x1F, 0x58, level])
+    }
+
+    fn graphic_size(&self) -> Option<(usize, usize)> {
+        Some((WIDTH, HEIGHT))
+    }
+
+    fn write_bitmap(&self, bitmap: &Bitmap) -> Vec<u8> {
+        let rows = bitmap.height.div_ceil(8);
+        // Home the cursor, then US ( f 0x11: real-time bit image write
+        let mut bytes = vec![
+            0x0B,
+            0x1F,
+            0x28,
+            0x66,
+            0x11,
+            (bitmap.width & 0xFF) as u8,
+            (bitmap.width >> 8) as u8,
+            (rows & 0xFF) as u8,
+            (rows >> 8) as u8,
+            0x01,
+        ];
+        for x in 0..bitmap.width {
+            for row in 0..rows {
+                let mut byte = 0u8;
+                for bit in 0..8 {
+                    if bitmap.get(x, row * 8 + bit) {
+                        byte |= 0x80 >> bit;
+                    }
+                }
+                bytes.push(byte);
+            }
+        }
+        bytes
+    }
+}
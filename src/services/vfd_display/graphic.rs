@@ -0,0 +1,233 @@
+//! Bitmap rendering for graphic displays
+//!
+//! Spots are drawn as a large seven-segment frequency with the callsign in a
+//! smaller font beneath it; anything else falls back to text lines.
+
+/// A monochrome framebuffer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bitmap {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<bool>,
+}
+
+impl Bitmap {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![false; width * height],
+        }
+    }
+
+    /// Whether a pixel is lit (out-of-range pixels are dark)
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.pixels[y * self.width + x]
+    }
+
+    fn set(&mut self, x: usize, y: usize) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = true;
+        }
+    }
+
+    fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize) {
+        for yy in y..y + h {
+            for xx in x..x + w {
+                self.set(xx, yy);
+            }
+        }
+    }
+}
+
+/// What a graphic frame should show
+pub enum GraphicContent<'a> {
+    /// Large frequency with the callsign beneath it
+    Spot {
+        frequency_khz: f64,
+        callsign: &'a str,
+    },
+    /// Plain text lines in the small font
+    Text(&'a [String]),
+}
+
+/// Large digit size
+const DIGIT_WIDTH: usize = 14;
+const DIGIT_HEIGHT: usize = 30;
+const SEGMENT: usize = 3;
+const DIGIT_GAP: usize = 3;
+
+/// Small font cell (5x7 glyph plus one pixel of spacing)
+const GLYPH_WIDTH: usize = 6;
+const GLYPH_HEIGHT: usize = 8;
+
+/// Render content into a bitmap of the given size
+pub fn render(content: &GraphicContent, width: usize, height: usize) -> Bitmap {
+    let mut bitmap = Bitmap::new(width, height);
+    match content {
+        GraphicContent::Spot {
+            frequency_khz,
+            callsign,
+        } => {
+            let freq = format!("{:.1}", frequency_khz);
+            let freq_width = large_text_width(&freq);
+            let x = width.saturating_sub(freq_width) / 2;
+            draw_large_text(&mut bitmap, &freq, x, 2);
+
+            // Callsign at double size when it fits, otherwise single size
+            let y = 2 + DIGIT_HEIGHT + 6;
+            let scale =
+                if callsign.len() * GLYPH_WIDTH * 2 <= width && y + GLYPH_HEIGHT * 2 <= height {
+                    2
+                } else {
+                    1
+                };
+            let call_width = callsign.len() * GLYPH_WIDTH * scale;
+            draw_text(
+                &mut bitmap,
+                callsign,
+                width.saturating_sub(call_width) / 2,
+                y,
+                scale,
+            );
+        }
+        GraphicContent::Text(lines) => {
+            for (row, line) in lines.iter().enumerate() {
+                draw_text(&mut bitmap, line, 0, row * (GLYPH_HEIGHT + 2), 1);
+            }
+        }
+    }
+    bitmap
+}
+
+/// Width in pixels of text drawn with the large digits
+fn large_text_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| {
+            if c == '.' {
+                SEGMENT + DIGIT_GAP
+            } else {
+                DIGIT_WIDTH + DIGIT_GAP
+            }
+        })
+        .sum::<usize>()
+        .saturating_sub(DIGIT_GAP)
+}
+
+/// Draw digits and decimal points as seven-segment characters
+fn draw_large_text(bitmap: &mut Bitmap, text: &str, mut x: usize, y: usize) {
+    for c in text.chars() {
+        if c == '.' {
+            bitmap.fill_rect(x, y + DIGIT_HEIGHT - SEGMENT, SEGMENT, SEGMENT);
+            x += SEGMENT + DIGIT_GAP;
+            continue;
+        }
+        if let Some(digit) = c.to_digit(10) {
+            draw_segments(bitmap, SEGMENTS[digit as usize], x, y);
+        }
+        x += DIGIT_WIDTH + DIGIT_GAP;
+    }
+}
+
+/// Segments lit per digit, bits a-g from LSB (a = top, then clockwise, g = middle)
+const SEGMENTS: [u8; 10] = [
+    0b0111111, 0b0000110, 0b1011011, 0b1001111, 0b1100110, 0b1101101, 0b1111101, 0b0000111,
+    0b1111111, 0b1101111,
+];
+
+fn draw_segments(bitmap: &mut Bitmap, segments: u8, x: usize, y: usize) {
+    let half = DIGIT_HEIGHT / 2;
+    let w = DIGIT_WIDTH;
+    let h = DIGIT_HEIGHT;
+    let s = SEGMENT;
+    // (x, y, width, height) for segments a through g
+    let rects = [
+        (x, y, w, s),
+        (x + w - s, y, s, half),
+        (x + w - s, y + half, s, h - half),
+        (x, y + h - s, w, s),
+        (x, y + half, s, h - half),
+        (x, y, s, half),
+        (x, y + half - s / 2, w, s),
+    ];
+    for (i, (rx, ry, rw, rh)) in rects.into_iter().enumerate() {
+        if segments & (1 << i) != 0 {
+            bitmap.fill_rect(rx, ry, rw, rh);
+        }
+    }
+}
+
+/// Draw text in the 5x7 font at an integer scale
+fn draw_text(bitmap: &mut Bitmap, text: &str, x: usize, y: usize, scale: usize) {
+    for (i, c) in text.chars().enumerate() {
+        let columns = glyph(c);
+        let cx = x + i * GLYPH_WIDTH * scale;
+        for (col, bits) in columns.iter().enumerate() {
+            for row in 0..7 {
+                if bits & (1 << row) != 0 {
+                    bitmap.fill_rect(cx + col * scale, y + row * scale, scale, scale);
+                }
+            }
+        }
+    }
+}
+
+/// Column bitmaps (LSB at top) for the 5x7 font
+pub fn glyph(c: char) -> [u8; 5] {
+    match c.to_ascii_uppercase() {
+        ' ' => [0x00, 0x00, 0x00, 0x00, 0x00],
+        '!' => [0x00, 0x00, 0x5F, 0x00, 0x00],
+        '#' => [0x14, 0x7F, 0x14, 0x7F, 0x14],
+        '%' => [0x23, 0x13, 0x08, 0x64, 0x62],
+        '(' => [0x00, 0x1C, 0x22, 0x41, 0x00],
+        ')' => [0x00, 0x41, 0x22, 0x1C, 0x00],
+        '*' => [0x14, 0x08, 0x3E, 0x08, 0x14],
+        '+' => [0x08, 0x08, 0x3E, 0x08, 0x08],
+        ',' => [0x00, 0x50, 0x30, 0x00, 0x00],
+        '-' => [0x08, 0x08, 0x08, 0x08, 0x08],
+        '.' => [0x00, 0x60, 0x60, 0x00, 0x00],
+        '/' => [0x20, 0x10, 0x08, 0x04, 0x02],
+        '0' => [0x3E, 0x51, 0x49, 0x45, 0x3E],
+        '1' => [0x00, 0x42, 0x7F, 0x40, 0x00],
+        '2' => [0x42, 0x61, 0x51, 0x49, 0x46],
+        '3' => [0x21, 0x41, 0x45, 0x4B, 0x31],
+        '4' => [0x18, 0x14, 0x12, 0x7F, 0x10],
+        '5' => [0x27, 0x45, 0x45, 0x45, 0x39],
+        '6' => [0x3C, 0x4A, 0x49, 0x49, 0x30],
+        '7' => [0x01, 0x71, 0x09, 0x05, 0x03],
+        '8' => [0x36, 0x49, 0x49, 0x49, 0x36],
+        '9' => [0x06, 0x49, 0x49, 0x29, 0x1E],
+        ':' => [0x00, 0x36, 0x36, 0x00, 0x00],
+        '<' => [0x08, 0x14, 0x22, 0x41, 0x00],
+        '=' => [0x14, 0x14, 0x14, 0x14, 0x14],
+        '>' => [0x00, 0x41, 0x22, 0x14, 0x08],
+        'A' => [0x7E, 0x11, 0x11, 0x11, 0x7E],
+        'B' => [0x7F, 0x49, 0x49, 0x49, 0x36],
+        'C' => [0x3E, 0x41, 0x41, 0x41, 0x22],
+        'D' => [0x7F, 0x41, 0x41, 0x22, 0x1C],
+        'E' => [0x7F, 0x49, 0x49, 0x49, 0x41],
+        'F' => [0x7F, 0x09, 0x09, 0x09, 0x01],
+        'G' => [0x3E, 0x41, 0x49, 0x49, 0x7A],
+        'H' => [0x7F, 0x08, 0x08, 0x08, 0x7F],
+        'I' => [0x00, 0x41, 0x7F, 0x41, 0x00],
+        'J' => [0x20, 0x40, 0x41, 0x3F, 0x01],
+        'K' => [0x7F, 0x08, 0x14, 0x22, 0x41],
+        'L' => [0x7F, 0x40, 0x40, 0x40, 0x40],
+        'M' => [0x7F, 0x02, 0x0C, 0x02, 0x7F],
+        'N' => [0x7F, 0x04, 0x08, 0x10, 0x7F],
+        'O' => [0x3E, 0x41, 0x41, 0x41, 0x3E],
+        'P' => [0x7F, 0x09, 0x09, 0x09, 0x06],
+        'Q' => [0x3E, 0x41, 0x51, 0x21, 0x5E],
+        'R' => [0x7F, 0x09, 0x19, 0x29, 0x46],
+        'S' => [0x46, 0x49, 0x49, 0x49, 0x31],
+        'T' => [0x01, 0x01, 0x7F, 0x01, 0x01],
+        'U' => [0x3F, 0x40, 0x40, 0x40, 0x3F],
+        'V' => [0x1F, 0x20, 0x40, 0x20, 0x1F],
+        'W' => [0x3F, 0x40, 0x38, 0x40, 0x3F],
+        'X' => [0x63, 0x14, 0x08, 0x14, 0x63],
+        'Y' => [0x07, 0x08, 0x70, 0x08, 0x07],
+        'Z' => [0x61, 0x51, 0x49, 0x45, 0x43],
+        '_' => [0x40, 0x40, 0x40, 0x40, 0x40],
+        _ => [0x02, 0x01, 0x51, 0x09, 0x06], // '?'
+    }
+}
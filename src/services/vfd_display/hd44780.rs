@@ -0,0 +1,47 @@
+//! HD44780 / US2066 character LCD behind a serial backpack
+
+use super::charset::{CharMap, HD44780_ROM};
+use super::protocol::DisplayProtocol;
+
+/// Backpack prefix: the next byte is passed to the controller as an instruction
+const COMMAND: u8 = 0xFE;
+const CLEAR_DISPLAY: u8 = 0x01;
+const DISPLAY_ON_CURSOR_OFF: u8 = 0x0C;
+const SET_DDRAM_ADDRESS: u8 = 0x80;
+
+/// HD44780-compatible controllers (including US2066 OLED) on SparkFun/Adafruit-style backpacks
+pub struct Hd44780Protocol {
+    /// Characters per line; rows 3 and 4 continue rows 1 and 2 in DDRAM
+    width: usize,
+}
+
+impl Hd44780Protocol {
+    pub fn new(width: usize) -> Self {
+        Self { width }
+    }
+
+    /// DDRAM address of the first cell of a row (0x00, 0x40, then width, 0x40 + width)
+    fn row_address(&self, row: usize) -> usize {
+        let base = if row.is_multiple_of(2) { 0x00 } else { 0x40 };
+        base + (row / 2) * self.width
+    }
+}
+
+impl DisplayProtocol for Hd44780Protocol {
+    fn charset(&self) -> CharMap {
+        CharMap::new(HD44780_ROM)
+    }
+
+    fn init(&self) -> Vec<u8> {
+        vec![COMMAND, DISPLAY_ON_CURSOR_OFF]
+    }
+
+    fn clear(&self) -> Vec<u8> {
+        vec![COMMAND, CLEAR_DISPLAY]
+    }
+
+    fn set_cursor(&self, col: usize, row: usize) -> Option<Vec<u8>> {
+        let address = self.row_address(row) + col;
+        Some(vec![COMMAND, SET_DDRAM_ADDRESS | (address as u8 & 0x7F)])
+    }
+}
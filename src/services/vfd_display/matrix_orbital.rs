@@ -0,0 +1,40 @@
+//! Matrix Orbital command set (0xFE-prefixed commands)
+
+use super::charset::{CharMap, HD44780_ROM};
+use super::protocol::DisplayProtocol;
+
+/// Command prefix byte
+const COMMAND: u8 = 0xFE;
+const CLEAR_SCREEN: u8 = 0x58;
+const GO_HOME: u8 = 0x48;
+const SET_CURSOR_POSITION: u8 = 0x47;
+const AUTO_LINE_WRAP_OFF: u8 = 0x44;
+const AUTO_SCROLL_OFF: u8 = 0x52;
+
+/// Matrix Orbital VFD/LCD modules
+pub struct MatrixOrbitalProtocol;
+
+impl DisplayProtocol for MatrixOrbitalProtocol {
+    fn charset(&self) -> CharMap {
+        CharMap::new(HD44780_ROM)
+    }
+
+    fn init(&self) -> Vec<u8> {
+        // Lines are positioned explicitly, so wrapping and scrolling only get in the way
+        vec![COMMAND, AUTO_LINE_WRAP_OFF, COMMAND, AUTO_SCROLL_OFF]
+    }
+
+    fn clear(&self) -> Vec<u8> {
+        vec![COMMAND, CLEAR_SCREEN, COMMAND, GO_HOME]
+    }
+
+    fn set_cursor(&self, col: usize, row: usize) -> Option<Vec<u8>> {
+        // Matrix Orbital positions are one-based
+        Some(vec![
+            COMMAND,
+            SET_CURSOR_POSITION,
+            (col + 1) as u8,
+            (row + 1) as u8,
+        ])
+    }
+}
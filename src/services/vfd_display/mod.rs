@@ -0,0 +1,1202 @@
+mod charset;
+mod elo;
+mod gp9002;
+mod graphic;
+mod hd44780;
+mod matrix_orbital;
+mod network;
+mod noritake;
+mod pages;
+mod protocol;
+mod screensaver;
+mod test_pattern;
+mod writer;
+
+pub use charset::CharMap;
+pub use graphic::{glyph, Bitmap};
+pub use pages::{
+    beacon_lines, follow_lines, heading_lines, heading_text, watchlist_matches, wrap_words,
+    IdleContent, LocalClock, VfdContent, VfdPage,
+};
+pub use protocol::{DisplayProtocol, ProtocolKind};
+pub use screensaver::ScreensaverStyle;
+
+use graphic::GraphicContent;
+use network::NetworkTarget;
+use screensaver::{IdleAnimation, IdleContext};
+use test_pattern::TestStep;
+use writer::PortWriter;
+
+use crate::error::{Error, Result};
+use crate::models::{AggregatedSpot, BandPlan, FrequencyFormat};
+use std::collections::HashSet;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long a brand-new spot is flashed before the rotation resumes
+const FLASH_TIME: Duration = Duration::from_secs(3);
+
+/// On/off period when flashing on displays without hardware blink
+const FLASH_TOGGLE: Duration = Duration::from_millis(250);
+
+/// Character geometry of the attached display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayGeometry {
+    pub width: usize,
+    pub lines: usize,
+}
+
+impl DisplayGeometry {
+    /// Supported form factors
+    pub const PRESETS: [DisplayGeometry; 5] = [
+        DisplayGeometry {
+            width: 20,
+            lines: 2,
+        },
+        DisplayGeometry {
+            width: 16,
+            lines: 2,
+        },
+        DisplayGeometry {
+            width: 20,
+            lines: 4,
+        },
+        DisplayGeometry {
+            width: 24,
+            lines: 2,
+        },
+        DisplayGeometry {
+            width: 40,
+            lines: 2,
+        },
+    ];
+
+    /// Parse a "WIDTHxLINES" string such as "20x2"
+    pub fn parse(text: &str) -> Option<Self> {
+        let (width, lines) = text
+            .trim()
+            .to_lowercase()
+            .split_once('x')
+            .and_then(|(w, l)| {
+                Some((
+                    w.trim().parse::<usize>().ok()?,
+                    l.trim().parse::<usize>().ok()?,
+                ))
+            })?;
+        Self::PRESETS
+            .into_iter()
+            .find(|g| g.width == width && g.lines == lines)
+    }
+
+    /// Blank lines for this geometry
+    fn blank_lines(self) -> Vec<String> {
+        vec![String::new(); self.lines]
+    }
+}
+
+impl Default for DisplayGeometry {
+    fn default() -> Self {
+        Self::PRESETS[0]
+    }
+}
+
+impl std::fmt::Display for DisplayGeometry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.lines)
+    }
+}
+
+/// VFD Display controller
+pub struct VfdDisplay {
+    /// Serial writes happen on this worker thread
+    writer: PortWriter,
+    port_open: bool,
+    /// Port handed to another program, to be reopened when it is given back
+    released_port: Option<String>,
+    protocol: Box<dyn DisplayProtocol>,
+    protocol_kind: ProtocolKind,
+    /// The driver's character map plus user overrides
+    charset: CharMap,
+    charset_overrides: Vec<(char, u8)>,
+    port_name: String,
+    scroll_index: usize,
+    scroll_interval: Duration,
+    last_update: Instant,
+    /// Scroll spots across line 1 instead of flipping, at this many characters a second
+    marquee_rate: Option<u32>,
+    /// How spot frequencies are written
+    frequency_format: FrequencyFormat,
+    marquee_offset: usize,
+    marquee_last_step: Instant,
+    force_random_mode: bool,
+    random_char_percent: u32,
+    screensaver_style: ScreensaverStyle,
+    screensaver: Box<dyn IdleAnimation>,
+    /// Shown by the bouncing callsign screensaver
+    callsign: String,
+    /// Spots heard in the last 24 hours, for the ticker screensaver
+    spots_24h: usize,
+    /// Shown on the spot page while there are no spots
+    idle_content: IdleContent,
+    /// Which spots the spot page shows
+    content: VfdContent,
+    /// Calls shown in watchlist mode
+    watchlist: Vec<String>,
+    /// Spot selected in the table, for selected-only mode
+    selected: Option<(String, f64)>,
+    /// Local time shown next to UTC on the clock
+    local_clock: LocalClock,
+    /// Brightness (0-100%) for displays that support dimming
+    brightness: u8,
+    geometry: DisplayGeometry,
+    current_lines: Vec<String>,
+    /// Frequency and callsign of the spot at the top of the frame, for graphic displays
+    current_spot: Option<(f64, String)>,
+    /// Frame last sent to the port, for differential updates (None forces a full rewrite)
+    written_lines: Option<Vec<String>>,
+    /// Bitmap last sent to a graphic display
+    written_bitmap: Option<Bitmap>,
+    /// Enabled carousel pages and how long each is shown
+    pages: Vec<(VfdPage, Duration)>,
+    page_index: usize,
+    page_started: Instant,
+    /// Used to label the band summary page
+    band_plan: BandPlan,
+    /// Solar data page content, when available
+    solar_lines: Option<Vec<String>>,
+    my_signal_lines: Option<Vec<String>>,
+    beacon_lines: Option<Vec<String>>,
+    /// Beam heading page content, while a spot is selected
+    heading_lines: Option<Vec<String>>,
+    /// Follow page content, while the logger's frequency is known
+    follow_lines: Option<Vec<String>>,
+    /// Station ID page content
+    station_message: Vec<String>,
+    /// Normal updates are suspended until this time while an alert is shown
+    interrupt_until: Option<Instant>,
+    /// Interrupt the rotation to flash spots not seen before
+    flash_new_spots: bool,
+    /// Spots on the display at the last update (None until the first update)
+    seen_spots: Option<HashSet<String>>,
+    flash: Option<Flash>,
+    /// Remaining test pattern steps and when the current one started
+    test: Option<(Vec<TestStep>, Instant)>,
+}
+
+/// A new spot being flashed
+struct Flash {
+    lines: Vec<String>,
+    started: Instant,
+    /// The display blinks by itself; otherwise the lines are toggled here
+    hardware: bool,
+}
+
+impl VfdDisplay {
+    pub fn new() -> Self {
+        Self {
+            writer: PortWriter::new(),
+            port_open: false,
+            released_port: None,
+            protocol: ProtocolKind::Elo.create(DisplayGeometry::default()),
+            protocol_kind: ProtocolKind::Elo,
+            charset: ProtocolKind::Elo
+                .create(DisplayGeometry::default())
+                .charset(),
+            charset_overrides: Vec::new(),
+            port_name: String::new(),
+            scroll_index: 0,
+            scroll_interval: Duration::from_secs(3),
+            last_update: Instant::now(),
+            marquee_rate: None,
+            frequency_format: FrequencyFormat::default(),
+            marquee_offset: 0,
+            marquee_last_step: Instant::now(),
+            force_random_mode: false,
+            random_char_percent: 20,
+            screensaver_style: ScreensaverStyle::default(),
+            screensaver: ScreensaverStyle::default().create(),
+            callsign: String::new(),
+            spots_24h: 0,
+            idle_content: IdleContent::default(),
+            content: VfdContent::default(),
+            watchlist: Vec::new(),
+            selected: None,
+            local_clock: LocalClock::Off,
+            brightness: 100,
+            geometry: DisplayGeometry::default(),
+            current_lines: DisplayGeometry::default().blank_lines(),
+            current_spot: None,
+            written_lines: None,
+            written_bitmap: None,
+            pages: vec![(VfdPage::Spots, Duration::from_secs(30))],
+            page_index: 0,
+            page_started: Instant::now(),
+            band_plan: BandPlan::default(),
+            solar_lines: None,
+            my_signal_lines: None,
+            beacon_lines: None,
+            heading_lines: None,
+            follow_lines: None,
+            station_message: Vec::new(),
+            interrupt_until: None,
+            flash_new_spots: false,
+            seen_spots: None,
+            flash: None,
+            test: None,
+        }
+    }
+
+    /// Get available serial ports
+    pub fn available_ports() -> Vec<String> {
+        serialport::available_ports()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| p.port_name)
+            .collect()
+    }
+
+    /// Whether a port name is a network display (`tcp://host:port` or `udp://host:port`)
+    pub fn is_network_port(port_name: &str) -> bool {
+        NetworkTarget::parse(port_name).is_some()
+    }
+
+    /// Open a serial port or network display
+    pub fn open(&mut self, port_name: &str) -> Result<()> {
+        self.close();
+
+        let port: Box<dyn std::io::Write + Send> = match NetworkTarget::parse(port_name) {
+            Some(target) => target.connect().map_err(|e| {
+                Error::Display(format!("Failed to connect to {}: {}", port_name, e))
+            })?,
+            None => Box::new(
+                serialport::new(port_name, 9600)
+                    .data_bits(serialport::DataBits::Eight)
+                    .parity(serialport::Parity::None)
+                    .stop_bits(serialport::StopBits::One)
+                    .timeout(Duration::from_millis(1000))
+                    .open()
+                    .map_err(|e| Error::Display(format!("Failed to open {}: {}", port_name, e)))?,
+            ),
+        };
+
+        self.attach_port(port_name, port);
+        Ok(())
+    }
+
+    /// Drive the display through an already opened port, e.g. one recording
+    /// the bytes written in tests
+    pub fn attach_port(&mut self, port_name: &str, port: Box<dyn std::io::Write + Send>) {
+        self.close();
+        self.writer.attach(port);
+        self.port_open = true;
+        self.port_name = port_name.to_string();
+        self.send_init();
+        self.clear();
+    }
+
+    /// Close the serial port
+    pub fn close(&mut self) {
+        if self.port_open {
+            self.clear();
+            self.writer.detach();
+        }
+        self.port_open = false;
+        self.port_name.clear();
+        self.released_port = None;
+    }
+
+    /// Let another program borrow the port; the display keeps updating its
+    /// preview but nothing is written until `reclaim`
+    pub fn release(&mut self) {
+        if !self.port_open {
+            return;
+        }
+        self.writer.detach();
+        self.port_open = false;
+        self.released_port = Some(std::mem::take(&mut self.port_name));
+    }
+
+    /// Reopen a released port and redraw the whole display
+    pub fn reclaim(&mut self) -> Result<()> {
+        match self.released_port.take() {
+            Some(port_name) => self.open(&port_name),
+            None => Ok(()),
+        }
+    }
+
+    /// Whether the port is currently lent to another program
+    pub fn is_released(&self) -> bool {
+        self.released_port.is_some()
+    }
+
+    /// How long the port writer has been stuck in a single write, if it is
+    pub fn write_stalled_for(&self) -> Option<Duration> {
+        self.writer.stalled_for()
+    }
+
+    /// Abandon a wedged writer thread and reopen the port on a fresh one
+    pub fn restart_writer(&mut self) -> Result<()> {
+        self.writer = PortWriter::new();
+        self.port_open = false;
+        let port_name = std::mem::take(&mut self.port_name);
+        if port_name.is_empty() {
+            return Ok(());
+        }
+        self.open(&port_name)
+    }
+
+    /// Check if port is open
+    pub fn is_open(&self) -> bool {
+        self.port_open
+    }
+
+    /// Next error reported by the port writer, if any
+    pub fn take_error(&self) -> Option<Error> {
+        self.writer.try_recv_error().map(Error::Display)
+    }
+
+    /// Get current port name
+    pub fn port_name(&self) -> &str {
+        &self.port_name
+    }
+
+    /// Set scroll interval
+    pub fn set_scroll_interval(&mut self, seconds: u32) {
+        self.scroll_interval = Duration::from_secs(seconds as u64);
+    }
+
+    /// Select the display command set; re-initializes an open display
+    pub fn set_protocol(&mut self, kind: ProtocolKind) {
+        if kind != self.protocol_kind {
+            self.protocol_kind = kind;
+            self.protocol = kind.create(self.geometry);
+            self.rebuild_charset();
+            self.send_init();
+            self.clear();
+        }
+    }
+
+    /// Character mappings applied on top of the driver's own (from a mapping file)
+    pub fn set_charset_overrides(&mut self, overrides: Vec<(char, u8)>) {
+        if overrides != self.charset_overrides {
+            self.charset_overrides = overrides;
+            self.rebuild_charset();
+            // Cells already on the display may now map differently
+            self.written_lines = None;
+        }
+    }
+
+    fn rebuild_charset(&mut self) {
+        self.charset = self.protocol.charset();
+        self.charset.extend(self.charset_overrides.iter().copied());
+    }
+
+    /// Whether the current command set supports brightness control
+    pub fn supports_brightness(&self) -> bool {
+        self.protocol.brightness(self.brightness).is_some()
+    }
+
+    /// Set display brightness (0-100%) where supported
+    pub fn set_brightness(&mut self, percent: u8) {
+        self.brightness = percent.min(100);
+        if let Some(bytes) = self.protocol.brightness(self.brightness) {
+            self.send_bytes(&bytes);
+        }
+    }
+
+    /// Blink the whole display where supported; returns false if the display can't
+    pub fn set_blink(&mut self, enabled: bool) -> bool {
+        match self.protocol.blink(enabled) {
+            Some(bytes) => {
+                self.send_bytes(&bytes);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Flash brand-new spots, interrupting the rotation
+    pub fn set_flash_new_spots(&mut self, enabled: bool) {
+        self.flash_new_spots = enabled;
+        if !enabled {
+            self.end_flash();
+            self.seen_spots = None;
+        }
+    }
+
+    /// Send the protocol's initialization sequence and restore brightness
+    fn send_init(&mut self) {
+        let mut init = self.protocol.init();
+        init.extend(
+            self.protocol
+                .brightness(self.brightness)
+                .unwrap_or_default(),
+        );
+        self.send_bytes(&init);
+    }
+
+    /// Queue raw bytes for the port if open; false if the writer fell behind and dropped them
+    fn send_bytes(&mut self, bytes: &[u8]) -> bool {
+        if !self.port_open || bytes.is_empty() {
+            return true;
+        }
+        self.writer.write(bytes.to_vec())
+    }
+
+    /// Set the display geometry; clears the display
+    pub fn set_geometry(&mut self, geometry: DisplayGeometry) {
+        if geometry != self.geometry {
+            self.geometry = geometry;
+            self.protocol = self.protocol_kind.create(geometry);
+            self.scroll_index = 0;
+            self.clear();
+        }
+    }
+
+    /// Get the display geometry
+    pub fn geometry(&self) -> DisplayGeometry {
+        self.geometry
+    }
+
+    /// Set force random mode
+    pub fn set_force_random_mode(&mut self, enabled: bool) {
+        self.force_random_mode = enabled;
+    }
+
+    /// Set random character duty cycle percentage (0-100)
+    pub fn set_random_char_percent(&mut self, percent: u32) {
+        self.random_char_percent = percent.min(100);
+    }
+
+    /// Select the idle animation
+    pub fn set_screensaver(&mut self, style: ScreensaverStyle) {
+        if style != self.screensaver_style {
+            self.screensaver_style = style;
+            self.screensaver = style.create();
+        }
+    }
+
+    /// Callsign shown by the bouncing callsign screensaver
+    pub fn set_callsign(&mut self, callsign: &str) {
+        self.callsign = callsign.to_string();
+    }
+
+    /// Number of spots heard in the last 24 hours, for the ticker screensaver
+    pub fn set_spots_24h(&mut self, count: usize) {
+        self.spots_24h = count;
+    }
+
+    /// Set how spot frequencies are written
+    pub fn set_frequency_format(&mut self, format: FrequencyFormat) {
+        self.frequency_format = format;
+    }
+
+    /// Scroll spots horizontally at `chars_per_second` instead of flipping pages (None = off)
+    pub fn set_marquee(&mut self, chars_per_second: Option<u32>) {
+        let rate = chars_per_second.map(|r| r.max(1));
+        if rate != self.marquee_rate {
+            self.marquee_rate = rate;
+            self.marquee_offset = 0;
+            self.marquee_last_step = Instant::now();
+            self.last_update = Instant::now() - self.scroll_interval;
+        }
+    }
+
+    /// Set what the spot page shows while there are no spots
+    pub fn set_idle_content(&mut self, content: IdleContent) {
+        self.idle_content = content;
+    }
+
+    /// Set which spots the spot page shows
+    pub fn set_content(&mut self, content: VfdContent) {
+        if content != self.content {
+            self.content = content;
+            self.scroll_index = 0;
+            self.last_update = Instant::now() - self.scroll_interval;
+        }
+    }
+
+    /// Set the calls shown in watchlist mode
+    pub fn set_watchlist(&mut self, watchlist: &[String]) {
+        self.watchlist = watchlist.to_vec();
+    }
+
+    /// Set the spot selected in the table (callsign and frequency)
+    pub fn set_selected_spot(&mut self, spot: Option<(String, f64)>) {
+        self.selected = spot;
+    }
+
+    /// The spots the content mode puts on the spot page
+    fn content_spots(&self, spots: &[AggregatedSpot]) -> Vec<AggregatedSpot> {
+        match self.content {
+            VfdContent::All => spots.to_vec(),
+            VfdContent::Watchlist => spots
+                .iter()
+                .filter(|s| watchlist_matches(&self.watchlist, &s.callsign))
+                .cloned()
+                .collect(),
+            VfdContent::Selected => self
+                .selected
+                .as_ref()
+                .and_then(|(call, khz)| {
+                    spots
+                        .iter()
+                        .find(|s| s.callsign == *call && (s.frequency_khz - khz).abs() < 1.0)
+                })
+                .into_iter()
+                .cloned()
+                .collect(),
+            VfdContent::Newest => spots
+                .iter()
+                .max_by_key(|s| s.last_spotted)
+                .into_iter()
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Set the local time shown next to UTC on the clock
+    pub fn set_local_clock(&mut self, clock: LocalClock) {
+        self.local_clock = clock;
+    }
+
+    /// Get current random char percent
+    #[allow(dead_code)]
+    pub fn random_char_percent(&self) -> u32 {
+        self.random_char_percent
+    }
+
+    /// Clear the display
+    pub fn clear(&mut self) {
+        self.current_lines = self.geometry.blank_lines();
+        self.current_spot = None;
+        self.written_lines = None;
+        self.written_bitmap = None;
+        let bytes = self.protocol.clear();
+        self.send_bytes(&bytes);
+    }
+
+    /// Pad or truncate text to exactly the display width
+    fn format_line(&self, text: &str) -> String {
+        let width = self.geometry.width;
+        format!("{:width$}", text, width = width)
+            .chars()
+            .take(width)
+            .collect()
+    }
+
+    /// Update display state with spots (always runs, even without serial connection)
+    pub fn update(&mut self, spots: &[AggregatedSpot]) {
+        if self.test.is_some() {
+            self.update_test();
+            return;
+        }
+
+        // An interrupt holds the display until it expires, then spots resume immediately
+        if let Some(until) = self.interrupt_until {
+            if Instant::now() < until {
+                return;
+            }
+            self.interrupt_until = None;
+            self.last_update = Instant::now() - self.scroll_interval;
+        }
+
+        // Pages summarize every spot; the spot page shows what the content mode picks
+        let all_spots = spots;
+        let content_spots;
+        let spots = if self.content == VfdContent::All {
+            all_spots
+        } else {
+            content_spots = self.content_spots(all_spots);
+            &content_spots
+        };
+
+        if self.flash_new_spots && !self.force_random_mode {
+            self.detect_new_spot(spots);
+        }
+        if self.flash.is_some() {
+            self.update_flash();
+            return;
+        }
+
+        if !self.force_random_mode {
+            self.advance_page();
+            let page = self.current_page();
+            if page != VfdPage::Spots {
+                self.show_page(page, all_spots);
+                return;
+            }
+        }
+
+        if !self.force_random_mode && spots.is_empty() {
+            let idle_page = match self.idle_content {
+                IdleContent::Random => None,
+                IdleContent::Clock => Some(VfdPage::Clock),
+                IdleContent::StationId => Some(VfdPage::StationId),
+            };
+            if let Some(page) = idle_page.filter(|p| self.page_available(*p)) {
+                self.show_page(page, spots);
+                return;
+            }
+        }
+
+        // The screensaver runs on its own timing
+        if self.force_random_mode || spots.is_empty() {
+            self.current_spot = None;
+            self.update_screensaver();
+            self.write_to_port();
+            return;
+        }
+
+        if let Some(rate) = self.marquee_rate {
+            self.update_marquee(spots, rate);
+            return;
+        }
+
+        // Spot display uses scroll interval
+        let now = Instant::now();
+        if now.duration_since(self.last_update) < self.scroll_interval {
+            return;
+        }
+        self.last_update = now;
+
+        // Update current_lines based on spots, scrolling one line per step when they don't fit
+        let width = self.geometry.width;
+        let lines = self.geometry.lines;
+        if spots.len() <= lines {
+            self.scroll_index = 0;
+        }
+        let start = self.scroll_index;
+        self.current_lines = (0..lines)
+            .map(|i| {
+                if spots.len() > lines {
+                    spots[(start + i) % spots.len()].to_display_string(width, self.frequency_format)
+                } else {
+                    spots
+                        .get(i)
+                        .map(|s| s.to_display_string(width, self.frequency_format))
+                        .unwrap_or_default()
+                }
+            })
+            .collect();
+        self.current_spot = spots
+            .get(start % spots.len())
+            .map(|s| (s.frequency_khz, s.callsign.clone()));
+        if spots.len() > lines {
+            self.scroll_index = (self.scroll_index + 1) % spots.len();
+        }
+
+        self.write_to_port();
+    }
+
+    /// Cycle through the test patterns (all cells on, character ramp, line
+    /// addressing, brightness sweep), then resume normal updates
+    pub fn start_test(&mut self) {
+        let mut steps = test_pattern::test_steps(self.geometry, self.supports_brightness());
+        steps.reverse();
+        self.test = Some((steps, Instant::now()));
+        self.show_test_step();
+    }
+
+    /// Whether a test pattern is running
+    pub fn is_testing(&self) -> bool {
+        self.test.is_some()
+    }
+
+    fn update_test(&mut self) {
+        let Some((ref mut steps, ref mut started)) = self.test else {
+            return;
+        };
+        let done = steps
+            .last()
+            .is_none_or(|step| started.elapsed() >= step.duration);
+        if !done {
+            return;
+        }
+        steps.pop();
+        *started = Instant::now();
+        if steps.is_empty() {
+            self.test = None;
+            // Restore the configured brightness and redraw whatever comes next
+            self.set_brightness(self.brightness);
+            self.written_lines = None;
+            self.last_update = Instant::now() - self.scroll_interval;
+            return;
+        }
+        self.show_test_step();
+    }
+
+    fn show_test_step(&mut self) {
+        let Some(step) = self.test.as_ref().and_then(|(steps, _)| steps.last()) else {
+            return;
+        };
+        let (mut lines, brightness) = (step.lines.clone(), step.brightness);
+        if let Some(bytes) = brightness.and_then(|b| self.protocol.brightness(b)) {
+            self.send_bytes(&bytes);
+        }
+        lines.resize(self.geometry.lines, String::new());
+        self.current_lines = lines;
+        self.current_spot = None;
+        self.write_to_port();
+    }
+
+    /// Start flashing the newest spot that wasn't on the display at the last update
+    fn detect_new_spot(&mut self, spots: &[AggregatedSpot]) {
+        let key = |s: &AggregatedSpot| format!("{}|{:.0}", s.callsign, s.frequency_khz.round());
+        let current: HashSet<String> = spots.iter().map(key).collect();
+        // The first batch after startup isn't news
+        let Some(seen) = self.seen_spots.replace(current) else {
+            return;
+        };
+        let Some(newest) = spots
+            .iter()
+            .filter(|s| !seen.contains(&key(s)))
+            .max_by_key(|s| s.last_spotted)
+        else {
+            return;
+        };
+
+        let width = self.geometry.width;
+        let mut lines = vec![
+            newest.to_display_string(width, self.frequency_format),
+            format!("{:^width$}", "*** NEW ***"),
+        ];
+        lines.resize(self.geometry.lines, String::new());
+        self.current_lines = lines.clone();
+        self.current_spot = Some((newest.frequency_khz, newest.callsign.clone()));
+        self.write_to_port();
+        let hardware = self.set_blink(true);
+        self.flash = Some(Flash {
+            lines,
+            started: Instant::now(),
+            hardware,
+        });
+    }
+
+    /// Toggle the flashing spot in software if needed, and end the flash when it's over
+    fn update_flash(&mut self) {
+        let Some(ref flash) = self.flash else {
+            return;
+        };
+        let elapsed = flash.started.elapsed();
+        if elapsed >= FLASH_TIME {
+            self.end_flash();
+            self.last_update = Instant::now() - self.scroll_interval;
+            return;
+        }
+        if flash.hardware {
+            return;
+        }
+        let visible = (elapsed.as_millis() / FLASH_TOGGLE.as_millis()).is_multiple_of(2);
+        self.current_lines = if visible {
+            flash.lines.clone()
+        } else {
+            self.geometry.blank_lines()
+        };
+        self.write_to_port();
+    }
+
+    fn end_flash(&mut self) {
+        if let Some(flash) = self.flash.take() {
+            if flash.hardware {
+                self.set_blink(false);
+            }
+        }
+    }
+
+    /// Marquee layout: every spot scrolling across line 1, the newest spots below it
+    fn update_marquee(&mut self, spots: &[AggregatedSpot], rate: u32) {
+        let step = Duration::from_secs(1) / rate;
+        let steps = (self.marquee_last_step.elapsed().as_millis() / step.as_millis().max(1)) as u32;
+        if steps == 0 && self.current_spot.is_some() {
+            return;
+        }
+        self.marquee_last_step += step * steps;
+        self.marquee_offset = self.marquee_offset.wrapping_add(steps as usize);
+
+        let width = self.geometry.width;
+        let ticker: Vec<char> = spots
+            .iter()
+            .map(|s| {
+                format!(
+                    "{} {}   ",
+                    s.callsign,
+                    self.frequency_format.format(s.frequency_khz)
+                )
+            })
+            .collect::<String>()
+            .chars()
+            .collect();
+        let start = self.marquee_offset % ticker.len();
+        let mut lines = vec![ticker.iter().cycle().skip(start).take(width).collect()];
+
+        let mut newest: Vec<&AggregatedSpot> = spots.iter().collect();
+        newest.sort_by_key(|s| std::cmp::Reverse(s.last_spotted));
+        lines.extend(
+            newest
+                .iter()
+                .take(self.geometry.lines.saturating_sub(1))
+                .map(|s| s.to_display_string(width, self.frequency_format)),
+        );
+        lines.resize(self.geometry.lines, String::new());
+
+        self.current_lines = lines;
+        self.current_spot = newest
+            .first()
+            .map(|s| (s.frequency_khz, s.callsign.clone()));
+        self.write_to_port();
+    }
+
+    /// Set the carousel pages and their dwell times; an empty list shows only spots
+    pub fn set_pages(&mut self, pages: Vec<(VfdPage, Duration)>) {
+        let pages = if pages.is_empty() {
+            vec![(VfdPage::Spots, Duration::from_secs(30))]
+        } else {
+            pages
+        };
+        if pages != self.pages {
+            self.pages = pages;
+            self.page_index = 0;
+            self.page_started = Instant::now();
+            self.last_update = Instant::now() - self.scroll_interval;
+        }
+    }
+
+    /// Set the band plan used by the band summary page
+    pub fn set_band_plan(&mut self, band_plan: BandPlan) {
+        self.band_plan = band_plan;
+    }
+
+    /// Provide solar data page content (None skips the page)
+    pub fn set_solar_lines(&mut self, lines: Option<Vec<String>>) {
+        self.solar_lines = lines;
+    }
+
+    /// Provide beacon page content (None skips the page)
+    pub fn set_beacon_lines(&mut self, lines: Option<Vec<String>>) {
+        self.beacon_lines = lines;
+    }
+
+    /// Provide beam heading page content (None skips the page)
+    pub fn set_heading_lines(&mut self, lines: Option<Vec<String>>) {
+        self.heading_lines = lines;
+    }
+
+    /// Provide follow page content (None skips the page)
+    pub fn set_follow_lines(&mut self, lines: Option<Vec<String>>) {
+        self.follow_lines = lines;
+    }
+
+    /// Provide "My signal" page content (None skips the page)
+    pub fn set_my_signal_lines(&mut self, lines: Option<Vec<String>>) {
+        self.my_signal_lines = lines;
+    }
+
+    /// Set the station ID message lines (all blank skips the page)
+    pub fn set_station_message(&mut self, lines: &[String]) {
+        self.station_message = lines.to_vec();
+    }
+
+    /// Page currently on the display
+    pub fn current_page(&self) -> VfdPage {
+        self.pages
+            .get(self.page_index)
+            .map(|(page, _)| *page)
+            .unwrap_or(VfdPage::Spots)
+    }
+
+    fn page_available(&self, page: VfdPage) -> bool {
+        match page {
+            VfdPage::Solar => self.solar_lines.is_some(),
+            VfdPage::MySignal => self.my_signal_lines.is_some(),
+            VfdPage::Beacons => self.beacon_lines.is_some(),
+            VfdPage::Heading => self.heading_lines.is_some(),
+            VfdPage::Follow => self.follow_lines.is_some(),
+            VfdPage::StationId => self.station_message.iter().any(|l| !l.trim().is_empty()),
+            _ => true,
+        }
+    }
+
+    /// Move to the next available page once the current one's dwell time is up
+    fn advance_page(&mut self) {
+        let dwell = self
+            .pages
+            .get(self.page_index)
+            .map(|(_, dwell)| *dwell)
+            .unwrap_or_default();
+        let current_available = self.page_available(self.current_page());
+        if self.pages.len() < 2 || (current_available && self.page_started.elapsed() < dwell) {
+            return;
+        }
+
+        for _ in 0..self.pages.len() {
+            self.page_index = (self.page_index + 1) % self.pages.len();
+            if self.page_available(self.current_page()) {
+                break;
+            }
+        }
+        self.page_started = Instant::now();
+        if self.current_page() == VfdPage::Spots {
+            self.last_update = Instant::now() - self.scroll_interval;
+        }
+    }
+
+    /// Render a non-spot page
+    fn show_page(&mut self, page: VfdPage, spots: &[AggregatedSpot]) {
+        let width = self.geometry.width;
+        let mut lines = match page {
+            VfdPage::Spots => return,
+            VfdPage::Clock => {
+                pages::clock_lines(SystemTime::now(), self.local_clock.offset_minutes(), width)
+            }
+            VfdPage::BandSummary => pages::band_summary_lines(spots, &self.band_plan, width),
+            VfdPage::Solar => self.solar_lines.clone().unwrap_or_default(),
+            VfdPage::MySignal => self.my_signal_lines.clone().unwrap_or_default(),
+            VfdPage::Beacons => self.beacon_lines.clone().unwrap_or_default(),
+            VfdPage::Heading => self.heading_lines.clone().unwrap_or_default(),
+            VfdPage::Follow => self.follow_lines.clone().unwrap_or_default(),
+            VfdPage::StationId => self.station_message.clone(),
+        };
+        lines.resize(self.geometry.lines, String::new());
+        self.current_lines = lines;
+        self.current_spot = None;
+        self.write_to_port();
+    }
+
+    /// Show short status items (e.g. startup health) packed onto the display for a while
+    pub fn show_status_page(&mut self, items: &[&str], duration: Duration) {
+        let lines = pages::wrap_words(items, self.geometry.width);
+        self.interrupt(lines, duration);
+    }
+
+    /// Show lines for a while, pausing the normal spot rotation (used for alerts)
+    pub fn interrupt(&mut self, lines: Vec<String>, duration: Duration) {
+        self.show_frame(lines);
+        self.interrupt_until = Some(Instant::now() + duration);
+    }
+
+    /// Show specific lines, bypassing spot scrolling (used by session replay)
+    pub fn show_frame(&mut self, mut lines: Vec<String>) {
+        lines.resize(self.geometry.lines, String::new());
+        self.current_lines = lines;
+        self.current_spot = None;
+        self.write_to_port();
+    }
+
+    /// Write current_lines to serial port if connected
+    fn write_to_port(&mut self) {
+        if !self.port_open {
+            return;
+        }
+        // A failed write may have left anything on the display
+        if self.writer.take_write_failed() {
+            self.written_lines = None;
+            self.written_bitmap = None;
+        }
+
+        if let Some(bitmap) = self.render_bitmap() {
+            if self.written_bitmap.as_ref() != Some(&bitmap) {
+                let bytes = self.protocol.write_bitmap(&bitmap);
+                if self.send_bytes(&bytes) {
+                    self.written_bitmap = Some(bitmap);
+                }
+            }
+            return;
+        }
+
+        let padded: Vec<String> = self
+            .current_lines
+            .iter()
+            .map(|line| self.format_line(line))
+            .collect();
+
+        // Only send changed cells where possible; clearing and rewriting makes some displays flicker
+        let bytes = self
+            .written_lines
+            .as_deref()
+            .and_then(|previous| {
+                self.protocol
+                    .write_changes(previous, &padded, &self.charset)
+            })
+            .unwrap_or_else(|| self.protocol.write_frame(&padded, &self.charset));
+        // A dropped write leaves the display unknown, so the next frame is a full rewrite
+        self.written_lines = self.send_bytes(&bytes).then_some(padded);
+    }
+
+    fn update_screensaver(&mut self) {
+        let ctx = IdleContext {
+            geometry: self.geometry,
+            duty_percent: self.random_char_percent,
+            callsign: &self.callsign,
+            spots_24h: self.spots_24h,
+        };
+        self.current_lines = self.screensaver.frame(&ctx);
+    }
+
+    /// Get current display lines for preview
+    pub fn get_preview(&self) -> Vec<String> {
+        self.current_lines.clone()
+    }
+
+    /// Render the current frame for graphic displays; None on character displays
+    pub fn render_bitmap(&self) -> Option<Bitmap> {
+        let (width, height) = self.protocol.graphic_size()?;
+        let content = match &self.current_spot {
+            Some((frequency_khz, callsign)) => GraphicContent::Spot {
+                frequency_khz: *frequency_khz,
+                callsign,
+            },
+            None => GraphicContent::Text(&self.current_lines),
+        };
+        Some(graphic::render(&content, width, height))
+    }
+
+    /// Get random mode state for preview
+    pub fn is_in_random_mode(&self) -> bool {
+        self.force_random_mode
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    /// Stand-in serial port recording every byte written to it
+    #[derive(Clone, Default)]
+    struct RecordingPort(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for RecordingPort {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl RecordingPort {
+        /// Wait for the writer thread to deliver `text`
+        fn received(&self, text: &str) -> bool {
+            let deadline = Instant::now() + Duration::from_secs(2);
+            while Instant::now() < deadline {
+                let bytes = self.0.lock().unwrap().clone();
+                if String::from_utf8_lossy(&bytes).contains(text) {
+                    return true;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            false
+        }
+    }
+
+    #[test]
+    fn frames_reach_the_port() {
+        let port = RecordingPort::default();
+        let mut display = VfdDisplay::new();
+        display.attach_port("recording", Box::new(port.clone()));
+        assert!(display.is_open());
+        assert_eq!(display.port_name(), "recording");
+
+        display.show_frame(vec!["HELLO".to_string(), "WORLD".to_string()]);
+        assert!(port.received("HELLO"));
+        assert!(port.received("WORLD"));
+    }
+
+    #[test]
+    fn rewrites_the_whole_frame_after_a_failed_write() {
+        /// Fails the first write of the first frame, then records like `RecordingPort`
+        struct FlakyPort(RecordingPort, bool);
+
+        impl Write for FlakyPort {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                if self.1 && String::from_utf8_lossy(buf).contains("WORLD") {
+                    self.1 = false;
+                    return Err(std::io::Error::other("unplugged"));
+                }
+                self.0.write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let port = RecordingPort::default();
+        let mut display = VfdDisplay::new();
+        // Cursor addressing, so unchanged cells are normally skipped
+        display.set_protocol(ProtocolKind::MatrixOrbital);
+        display.attach_port("flaky", Box::new(FlakyPort(port.clone(), true)));
+        display.show_frame(vec!["HELLO".to_string(), "WORLD".to_string()]);
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while display.take_error().is_none() {
+            assert!(
+                Instant::now() < deadline,
+                "the failed write was not reported"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        // Only the second line changed, but the first must be resent too
+        display.show_frame(vec!["HELLO".to_string(), "THERE".to_string()]);
+        assert!(port.received("THERE"));
+        assert!(port.received("HELLO"));
+    }
+
+    #[test]
+    fn nothing_is_written_after_close() {
+        let port = RecordingPort::default();
+        let mut display = VfdDisplay::new();
+        display.attach_port("recording", Box::new(port.clone()));
+        display.show_frame(vec!["FIRST".to_string()]);
+        assert!(port.received("FIRST"));
+
+        display.close();
+        display.show_frame(vec!["SECOND".to_string()]);
+        assert!(!display.is_open());
+        assert_eq!(display.get_preview()[0].trim(), "SECOND");
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!String::from_utf8_lossy(&port.0.lock().unwrap()).contains("SECOND"));
+    }
+
+    fn spot(callsign: &str, frequency_khz: f64) -> AggregatedSpot {
+        AggregatedSpot::from_raw(&crate::models::RawSpot::new(
+            "DL8LAS-#".to_string(),
+            callsign.to_string(),
+            frequency_khz,
+            20,
+            25,
+            "CW".to_string(),
+            crate::models::SpotSource::Skimmer,
+        ))
+    }
+
+    #[test]
+    fn content_modes_pick_spots() {
+        let spots = vec![
+            spot("W1AW", 14025.0),
+            spot("DL1ABC", 7010.0),
+            spot("K3LR", 21030.0),
+        ];
+        let calls = |display: &VfdDisplay| -> Vec<String> {
+            display
+                .content_spots(&spots)
+                .into_iter()
+                .map(|s| s.callsign)
+                .collect()
+        };
+        let mut display = VfdDisplay::new();
+        assert_eq!(calls(&display).len(), 3);
+
+        display.set_content(VfdContent::Watchlist);
+        display.set_watchlist(&["W1AW".to_string(), "DL*".to_string()]);
+        assert_eq!(calls(&display), ["W1AW", "DL1ABC"]);
+
+        display.set_content(VfdContent::Selected);
+        assert!(calls(&display).is_empty());
+        display.set_selected_spot(Some(("K3LR".to_string(), 21030.2)));
+        assert_eq!(calls(&display), ["K3LR"]);
+    }
+}
@@ -0,0 +1,78 @@
+//! Displays reached over the network (e.g. an ESP32 driving the VFD) instead of a
+//! local serial port. The same bytes a serial display would get are sent as a TCP
+//! stream or one UDP datagram per frame update.
+
+use std::io::{self, Write};
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+use std::time::Duration;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A network display address, written as `tcp://host:port` or `udp://host:port`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkTarget {
+    Tcp(String),
+    Udp(String),
+}
+
+impl NetworkTarget {
+    /// Parse a port name; None for serial port names
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.trim();
+        if let Some(address) = name.strip_prefix("tcp://") {
+            Some(NetworkTarget::Tcp(address.to_string()))
+        } else {
+            name.strip_prefix("udp://")
+                .map(|address| NetworkTarget::Udp(address.to_string()))
+        }
+    }
+
+    /// Open a connection that display bytes can be written to
+    pub fn connect(&self) -> io::Result<Box<dyn Write + Send>> {
+        match self {
+            NetworkTarget::Tcp(address) => {
+                let addr = resolve(address)?;
+                let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+                stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+                stream.set_nodelay(true)?;
+                Ok(Box::new(stream))
+            }
+            NetworkTarget::Udp(address) => {
+                let addr = resolve(address)?;
+                let bind = if addr.is_ipv4() {
+                    "0.0.0.0:0"
+                } else {
+                    "[::]:0"
+                };
+                let socket = UdpSocket::bind(bind)?;
+                socket.connect(addr)?;
+                Ok(Box::new(UdpDisplay { socket }))
+            }
+        }
+    }
+}
+
+fn resolve(address: &str) -> io::Result<std::net::SocketAddr> {
+    address.to_socket_addrs()?.next().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No address found for {}", address),
+        )
+    })
+}
+
+/// Sends each write as a single datagram
+struct UdpDisplay {
+    socket: UdpSocket,
+}
+
+impl Write for UdpDisplay {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.socket.send(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+//! Noritake CU-series (CU20025, CU20045) command set
+
+use super::charset::{CharMap, HD44780_ROM};
+use super::protocol::DisplayProtocol;
+
+const ESC: u8 = 0x1B;
+const CLEAR_DISPLAY: u8 = 0x0C;
+const OVERWRITE_MODE: u8 = 0x11;
+const CURSOR_OFF: u8 = 0x14;
+const INITIALIZE: u8 = b'I';
+const CURSOR_POSITION: u8 = b'H';
+const LUMINANCE: u8 = b'L';
+const BLINK: u8 = b'T';
+
+/// Noritake CU-series modules, addressed with ESC sequences
+pub struct NoritakeProtocol {
+    /// Characters per line, used to compute linear cursor addresses
+    width: usize,
+}
+
+impl NoritakeProtocol {
+    pub fn new(width: usize) -> Self {
+        Self { width }
+    }
+}
+
+impl DisplayProtocol for NoritakeProtocol {
+    fn charset(&self) -> CharMap {
+        CharMap::new(HD44780_ROM)
+    }
+
+    fn init(&self) -> Vec<u8> {
+        vec![ESC, INITIALIZE, OVERWRITE_MODE, CURSOR_OFF]
+    }
+
+    fn clear(&self) -> Vec<u8> {
+        vec![CLEAR_DISPLAY]
+    }
+
+    fn set_cursor(&self, col: usize, row: usize) -> Option<Vec<u8>> {
+        // Cells are numbered row by row from 0x00
+        let address = row * self.width + col;
+        Some(vec![ESC, CURSOR_POSITION, address as u8])
+    }
+
+    fn brightness(&self, percent: u8) -> Option<Vec<u8>> {
+        // Four luminance steps: 25%, 50%, 75%, 100%
+        let level = match percent {
+            0..=25 => 0x00,
+            26..=50 => 0x40,
+            51..=75 => 0x80,
+            _ => 0xC0,
+        };
+        Some(vec![ESC, LUMINANCE, level])
+    }
+
+    fn blink(&self, enabled: bool) -> Option<Vec<u8>> {
+        // Blink period in units of ~30 ms; zero stops blinking
+        Some(vec![ESC, BLINK, if enabled { 0x10 } else { 0x00 }])
+    }
+}
@@ -0,0 +1,317 @@
+//! Pages the VFD rotates through
+
+use crate::models::{AggregatedSpot, BandPlan, UtcTime};
+use std::time::{Duration, SystemTime};
+
+/// A screen of content in the VFD carousel
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VfdPage {
+    /// Scrolling spot list (idle screensaver when there are no spots)
+    Spots,
+    /// UTC (and optionally local) time and date
+    Clock,
+    /// Spot counts per band
+    BandSummary,
+    /// Solar indices (skipped while no data is available)
+    Solar,
+    /// User-defined station ID message (skipped while empty)
+    StationId,
+    /// Where RBN hears our own signal (skipped while nobody does)
+    MySignal,
+    /// Beacons currently heard, as a propagation check (skipped while none are)
+    Beacons,
+    /// Beam heading to the selected spot (skipped while none is selected)
+    Heading,
+    /// Spots nearest the contest logger's frequency (skipped while it is unknown)
+    Follow,
+}
+
+impl VfdPage {
+    pub const ALL: [VfdPage; 9] = [
+        VfdPage::Spots,
+        VfdPage::Clock,
+        VfdPage::BandSummary,
+        VfdPage::Solar,
+        VfdPage::StationId,
+        VfdPage::MySignal,
+        VfdPage::Beacons,
+        VfdPage::Heading,
+        VfdPage::Follow,
+    ];
+
+    /// Name used in settings.toml keys
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VfdPage::Spots => "spots",
+            VfdPage::Clock => "clock",
+            VfdPage::BandSummary => "band_summary",
+            VfdPage::Solar => "solar",
+            VfdPage::StationId => "station_id",
+            VfdPage::MySignal => "my_signal",
+            VfdPage::Beacons => "beacons",
+            VfdPage::Heading => "heading",
+            VfdPage::Follow => "follow",
+        }
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            VfdPage::Spots => "Spots",
+            VfdPage::Clock => "Clock",
+            VfdPage::BandSummary => "Band summary",
+            VfdPage::Solar => "Solar data",
+            VfdPage::StationId => "Station ID",
+            VfdPage::MySignal => "My signal",
+            VfdPage::Beacons => "Beacons",
+            VfdPage::Heading => "Beam heading",
+            VfdPage::Follow => "Follow logger",
+        }
+    }
+}
+
+/// What the spot page shows while there are no spots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdleContent {
+    /// The selected screensaver, to keep the phosphor evenly worn
+    #[default]
+    Random,
+    /// The clock page
+    Clock,
+    /// The station ID message
+    StationId,
+}
+
+impl IdleContent {
+    pub const ALL: [IdleContent; 3] = [
+        IdleContent::Random,
+        IdleContent::Clock,
+        IdleContent::StationId,
+    ];
+
+    /// Name used in settings.toml
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IdleContent::Random => "random",
+            IdleContent::Clock => "clock",
+            IdleContent::StationId => "station_id",
+        }
+    }
+
+    /// Parse the settings.toml name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == name.trim())
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            IdleContent::Random => "Screensaver",
+            IdleContent::Clock => "Clock",
+            IdleContent::StationId => "Station ID",
+        }
+    }
+}
+
+/// Which spots the spot page shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VfdContent {
+    /// Every spot that passes the filters
+    #[default]
+    All,
+    /// Only calls on the watchlist
+    Watchlist,
+    /// Only the spot selected in the table
+    Selected,
+    /// Only the most recently heard spot
+    Newest,
+}
+
+impl VfdContent {
+    pub const ALL: [VfdContent; 4] = [
+        VfdContent::All,
+        VfdContent::Watchlist,
+        VfdContent::Selected,
+        VfdContent::Newest,
+    ];
+
+    /// Name used in settings.toml
+    pub fn as_str(self) -> &'static str {
+        match self {
+            VfdContent::All => "all",
+            VfdContent::Watchlist => "watchlist",
+            VfdContent::Selected => "selected",
+            VfdContent::Newest => "newest",
+        }
+    }
+
+    /// Parse the settings.toml name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == name.trim())
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            VfdContent::All => "All filtered spots",
+            VfdContent::Watchlist => "Watchlist only",
+            VfdContent::Selected => "Selected spot only",
+            VfdContent::Newest => "Newest spot only",
+        }
+    }
+}
+
+/// Whether a call matches a watchlist entry (exact, or a prefix ending in '*')
+pub fn watchlist_matches(watchlist: &[String], callsign: &str) -> bool {
+    watchlist.iter().any(|entry| match entry.strip_suffix('*') {
+        Some(prefix) => callsign.starts_with(prefix),
+        None => callsign == entry,
+    })
+}
+
+/// Local time shown next to UTC on the clock page
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalClock {
+    /// UTC only
+    Off,
+    /// The system time zone, following its daylight saving changes
+    System,
+    /// A fixed offset from UTC in minutes
+    Offset(i32),
+}
+
+impl LocalClock {
+    /// Minutes east of UTC right now, or None for UTC only
+    pub fn offset_minutes(self) -> Option<i32> {
+        match self {
+            LocalClock::Off => None,
+            LocalClock::System => Some(chrono::Local::now().offset().local_minus_utc() / 60),
+            LocalClock::Offset(minutes) => Some(minutes),
+        }
+    }
+}
+
+/// Clock page: time on the first line, UTC date on the second, centered.
+/// With a local offset (minutes east of UTC) the first line shows both times,
+/// e.g. "14:05:09Z  10:05L", which fits a 20 column display.
+pub fn clock_lines(
+    now: SystemTime,
+    local_offset_minutes: Option<i32>,
+    width: usize,
+) -> Vec<String> {
+    let t = UtcTime::from_system(now);
+    let time = match local_offset_minutes {
+        Some(offset) => {
+            let shift = Duration::from_secs(u64::from(offset.unsigned_abs()) * 60);
+            let local = if offset >= 0 {
+                now + shift
+            } else {
+                now - shift
+            };
+            let l = UtcTime::from_system(local);
+            format!(
+                "{:02}:{:02}:{:02}Z  {:02}:{:02}L",
+                t.hour, t.minute, t.second, l.hour, l.minute
+            )
+        }
+        None => format!("{:02}:{:02}:{:02} UTC", t.hour, t.minute, t.second),
+    };
+    vec![
+        format!("{:^width$}", time),
+        format!(
+            "{:^width$}",
+            format!(
+                "{} {}-{:02}-{:02}",
+                t.weekday_name(),
+                t.year,
+                t.month,
+                t.day
+            )
+        ),
+    ]
+}
+
+/// Band summary page: "20m:12 40m:5 ..." wrapped to the display
+pub fn band_summary_lines(
+    spots: &[AggregatedSpot],
+    band_plan: &BandPlan,
+    width: usize,
+) -> Vec<String> {
+    let entries: Vec<String> = band_plan
+        .bands()
+        .iter()
+        .filter_map(|band| {
+            let count = spots
+                .iter()
+                .filter(|s| band.contains(s.frequency_khz))
+                .count();
+            (count > 0).then(|| format!("{}:{}", band.name, count))
+        })
+        .collect();
+    if entries.is_empty() {
+        return vec!["No spots".to_string()];
+    }
+    wrap_words(&entries, width)
+}
+
+/// Beacons being heard as `CALL:band` entries, or `None` if there are none
+pub fn beacon_lines(
+    spots: &[AggregatedSpot],
+    band_plan: &BandPlan,
+    width: usize,
+) -> Option<Vec<String>> {
+    let entries: Vec<String> = spots
+        .iter()
+        .filter(|s| s.beacon)
+        .map(|s| match band_plan.for_frequency(s.frequency_khz) {
+            Some(band) => format!("{}:{}", s.callsign, band.name),
+            None => s.callsign.clone(),
+        })
+        .collect();
+    (!entries.is_empty()).then(|| wrap_words(&entries, width))
+}
+
+/// Short and long path headings for a bearing: "HDG 045/225"
+pub fn heading_text(bearing: f64) -> String {
+    let short = bearing.round() as u32 % 360;
+    format!("HDG {:03}/{:03}", short, (short + 180) % 360)
+}
+
+/// Heading page: "CALL HDG 045/225" and any note, wrapped to the display
+pub fn heading_lines(callsign: &str, bearing: f64, note: &str, width: usize) -> Vec<String> {
+    let mut words = vec![callsign.to_string(), heading_text(bearing)];
+    words.extend(note.split_whitespace().map(str::to_string));
+    wrap_words(&words, width)
+}
+
+/// Follow page: the logger's frequency, then the nearest spots with their offset
+pub fn follow_lines(frequency_khz: f64, spots: &[AggregatedSpot], width: usize) -> Vec<String> {
+    let call_width = width.saturating_sub(7);
+    let mut lines = vec![format!("LOG {:.1}", frequency_khz)];
+    lines.extend(spots.iter().map(|spot| {
+        format!(
+            "{:<call_width$}{:>+7.1}",
+            spot.callsign,
+            spot.frequency_khz - frequency_khz
+        )
+    }));
+    lines
+}
+
+/// Pack words into lines no wider than `width`, separated by spaces
+pub fn wrap_words<S: AsRef<str>>(words: &[S], width: usize) -> Vec<String> {
+    let mut lines = vec![String::new()];
+    for word in words {
+        let word = word.as_ref();
+        let line = lines.last_mut().expect("lines is never empty");
+        if line.is_empty() {
+            line.push_str(word);
+        } else if line.len() + 1 + word.len() <= width {
+            line.push(' ');
+            line.push_str(word);
+        } else {
+            lines.push(word.to_string());
+        }
+    }
+    lines
+}
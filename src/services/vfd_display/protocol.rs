@@ -0,0 +1,188 @@
+//! Display command-set abstraction
+
+use super::charset::CharMap;
+use super::elo::EloProtocol;
+use super::gp9002::Gp9002Protocol;
+use super::graphic::Bitmap;
+use super::hd44780::Hd44780Protocol;
+use super::matrix_orbital::MatrixOrbitalProtocol;
+use super::noritake::NoritakeProtocol;
+use super::DisplayGeometry;
+use crate::plugins;
+
+/// Supported display command sets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolKind {
+    /// ELO customer display: form feed to clear, text wraps to the next line
+    Elo,
+    /// Matrix Orbital: 0xFE-prefixed commands
+    MatrixOrbital,
+    /// Noritake CU-series: ESC-prefixed commands
+    Noritake,
+    /// HD44780/US2066 LCD on a serial backpack: 0xFE + controller instruction
+    Hd44780,
+    /// GP9002-class 128x64 graphic VFD: large-digit bitmap frames
+    Gp9002,
+    /// A command set from the plugin registry, by name
+    Plugin(&'static str),
+}
+
+impl ProtocolKind {
+    pub const ALL: [ProtocolKind; 5] = [
+        ProtocolKind::Elo,
+        ProtocolKind::MatrixOrbital,
+        ProtocolKind::Noritake,
+        ProtocolKind::Hd44780,
+        ProtocolKind::Gp9002,
+    ];
+
+    /// Name used in settings.toml
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProtocolKind::Elo => "elo",
+            ProtocolKind::MatrixOrbital => "matrix_orbital",
+            ProtocolKind::Noritake => "noritake",
+            ProtocolKind::Hd44780 => "hd44780",
+            ProtocolKind::Gp9002 => "gp9002",
+            ProtocolKind::Plugin(name) => name,
+        }
+    }
+
+    /// Parse the settings.toml name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|k| k.as_str() == name.trim())
+            .or_else(|| plugins::display_plugin(name).map(|p| ProtocolKind::Plugin(p.name)))
+    }
+
+    /// Built-in command sets followed by those from plugins
+    pub fn available() -> Vec<ProtocolKind> {
+        Self::ALL
+            .into_iter()
+            .chain(
+                plugins::DISPLAY_PLUGINS
+                    .iter()
+                    .map(|p| ProtocolKind::Plugin(p.name)),
+            )
+            .collect()
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            ProtocolKind::Elo => "ELO",
+            ProtocolKind::MatrixOrbital => "Matrix Orbital",
+            ProtocolKind::Noritake => "Noritake CU",
+            ProtocolKind::Hd44780 => "HD44780 LCD",
+            ProtocolKind::Gp9002 => "GP9002 graphic",
+            ProtocolKind::Plugin(name) => plugins::display_plugin(name).map_or(name, |p| p.label),
+        }
+    }
+
+    /// Create the driver for this command set
+    pub fn create(self, geometry: DisplayGeometry) -> Box<dyn DisplayProtocol> {
+        match self {
+            ProtocolKind::Elo => Box::new(EloProtocol),
+            ProtocolKind::MatrixOrbital => Box::new(MatrixOrbitalProtocol),
+            ProtocolKind::Noritake => Box::new(NoritakeProtocol::new(geometry.width)),
+            ProtocolKind::Hd44780 => Box::new(Hd44780Protocol::new(geometry.width)),
+            ProtocolKind::Gp9002 => Box::new(Gp9002Protocol),
+            ProtocolKind::Plugin(name) => match plugins::display_plugin(name) {
+                Some(plugin) => (plugin.create)(geometry),
+                None => Box::new(EloProtocol),
+            },
+        }
+    }
+}
+
+/// Translates display operations into the bytes a particular display understands
+pub trait DisplayProtocol: Send {
+    /// Bytes sent once after the port is opened
+    fn init(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Clear the display and home the cursor
+    fn clear(&self) -> Vec<u8>;
+
+    /// Move the cursor to a zero-based column and row, if the display supports it
+    fn set_cursor(&self, col: usize, row: usize) -> Option<Vec<u8>>;
+
+    /// Set brightness (0-100%), if the display supports it
+    fn brightness(&self, _percent: u8) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Turn whole-display blinking on or off, if the display supports it
+    fn blink(&self, _enabled: bool) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Character codes of the display's font for text that isn't plain ASCII
+    fn charset(&self) -> CharMap {
+        CharMap::default()
+    }
+
+    /// Pixel size for graphic displays; None for character displays
+    fn graphic_size(&self) -> Option<(usize, usize)> {
+        None
+    }
+
+    /// Bytes to show a rendered bitmap on a graphic display
+    fn write_bitmap(&self, _bitmap: &Bitmap) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Bytes to change a frame already on the display into `lines`, rewriting only
+    /// the cells that differ; None if the display can't position the cursor
+    fn write_changes(
+        &self,
+        previous: &[String],
+        lines: &[String],
+        charset: &CharMap,
+    ) -> Option<Vec<u8>> {
+        self.set_cursor(0, 0)?;
+        if previous.len() != lines.len() {
+            return None;
+        }
+
+        let mut bytes = Vec::new();
+        for (row, (old, new)) in previous.iter().zip(lines).enumerate() {
+            let old: Vec<char> = old.chars().collect();
+            let new: Vec<char> = new.chars().collect();
+            if old.len() != new.len() {
+                return None;
+            }
+
+            // Write each run of changed cells after positioning the cursor at its start
+            let mut col = 0;
+            while col < new.len() {
+                if old[col] == new[col] {
+                    col += 1;
+                    continue;
+                }
+                let start = col;
+                while col < new.len() && old[col] != new[col] {
+                    col += 1;
+                }
+                bytes.extend(self.set_cursor(start, row)?);
+                bytes.extend(charset.encode(&new[start..col].iter().collect::<String>()));
+            }
+        }
+        Some(bytes)
+    }
+
+    /// Bytes to show a full frame of lines, each already padded to the display width
+    fn write_frame(&self, lines: &[String], charset: &CharMap) -> Vec<u8> {
+        let mut bytes = self.clear();
+        for (row, line) in lines.iter().enumerate() {
+            // Without cursor addressing we rely on the display wrapping full-width lines
+            if let Some(cursor) = self.set_cursor(0, row) {
+                bytes.extend(cursor);
+            }
+            bytes.extend(charset.encode(line));
+        }
+        bytes
+    }
+}
@@ -0,0 +1,338 @@
+//! Idle animations shown while there are no spots (or when forced)
+//!
+//! Each style keeps the phosphor evenly worn by moving something around the
+//! display instead of leaving it blank or static.
+
+use super::test_pattern::FULL_BLOCK;
+use super::DisplayGeometry;
+use rand::Rng;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Selectable idle animation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScreensaverStyle {
+    /// A random character flashed once a second for the duty cycle
+    #[default]
+    RandomChar,
+    /// Characters falling down the display
+    Rain,
+    /// The station callsign bouncing off the edges
+    BouncingCall,
+    /// A single cell wandering one step at a time
+    PixelWalk,
+    /// Number of spots heard in the last 24 hours scrolling across
+    SpotTicker,
+}
+
+impl ScreensaverStyle {
+    pub const ALL: [ScreensaverStyle; 5] = [
+        ScreensaverStyle::RandomChar,
+        ScreensaverStyle::Rain,
+        ScreensaverStyle::BouncingCall,
+        ScreensaverStyle::PixelWalk,
+        ScreensaverStyle::SpotTicker,
+    ];
+
+    /// Name used in settings.toml
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScreensaverStyle::RandomChar => "random_char",
+            ScreensaverStyle::Rain => "rain",
+            ScreensaverStyle::BouncingCall => "bouncing_call",
+            ScreensaverStyle::PixelWalk => "pixel_walk",
+            ScreensaverStyle::SpotTicker => "spot_ticker",
+        }
+    }
+
+    /// Parse the settings.toml name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|s| s.as_str() == name.trim())
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            ScreensaverStyle::RandomChar => "Random character",
+            ScreensaverStyle::Rain => "Matrix rain",
+            ScreensaverStyle::BouncingCall => "Bouncing callsign",
+            ScreensaverStyle::PixelWalk => "Pixel walk",
+            ScreensaverStyle::SpotTicker => "24h spot ticker",
+        }
+    }
+
+    /// Create the animation for this style
+    pub fn create(self) -> Box<dyn IdleAnimation> {
+        match self {
+            ScreensaverStyle::RandomChar => Box::new(RandomChar::default()),
+            ScreensaverStyle::Rain => Box::new(Rain::default()),
+            ScreensaverStyle::BouncingCall => Box::new(BouncingCall::default()),
+            ScreensaverStyle::PixelWalk => Box::new(PixelWalk::default()),
+            ScreensaverStyle::SpotTicker => Box::new(SpotTicker::default()),
+        }
+    }
+}
+
+/// What an animation may draw on
+pub struct IdleContext<'a> {
+    pub geometry: DisplayGeometry,
+    /// Random character duty cycle (0-100)
+    pub duty_percent: u32,
+    /// Station callsign, for the bouncing callsign
+    pub callsign: &'a str,
+    /// Spots first heard in the last 24 hours
+    pub spots_24h: usize,
+}
+
+/// An idle animation; called on every display update
+pub trait IdleAnimation: Send {
+    /// Lines to show now
+    fn frame(&mut self, ctx: &IdleContext) -> Vec<String>;
+}
+
+/// A random character A-Z or 0-9
+fn random_char(rng: &mut impl Rng) -> char {
+    if rng.gen::<bool>() {
+        rng.gen_range(b'A'..=b'Z') as char
+    } else {
+        rng.gen_range(b'0'..=b'9') as char
+    }
+}
+
+/// Blank lines with `text` placed at a column and row
+fn place(geometry: DisplayGeometry, col: usize, row: usize, text: &str) -> Vec<String> {
+    let mut lines = vec![" ".repeat(geometry.width); geometry.lines];
+    if let Some(line) = lines.get_mut(row) {
+        let mut chars: Vec<char> = line.chars().collect();
+        for (i, c) in text.chars().enumerate() {
+            if let Some(cell) = chars.get_mut(col + i) {
+                *cell = c;
+            }
+        }
+        *line = chars.into_iter().collect();
+    }
+    lines
+}
+
+/// Whole steps of `period` elapsed since `*last`, advancing `*last` by them.
+/// After a long gap (the screensaver wasn't showing) it takes one step instead of catching up.
+fn due_steps(last: &mut Instant, period: Duration) -> u32 {
+    let steps = (last.elapsed().as_millis() / period.as_millis().max(1)) as u32;
+    if steps > 10 {
+        *last = Instant::now();
+        return 1;
+    }
+    *last += period * steps;
+    steps
+}
+
+/// Duty cycle: the character shows for the first N% of each second
+#[derive(Default)]
+struct RandomChar {
+    col: usize,
+    row: usize,
+    character: char,
+    last_second: u64,
+}
+
+impl IdleAnimation for RandomChar {
+    fn frame(&mut self, ctx: &IdleContext) -> Vec<String> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let ms_in_second = (now.as_millis() % 1000) as u32;
+
+        // e.g. 20% duty cycle = first 200ms of each second
+        let should_show = ms_in_second < ctx.duty_percent * 10 && ctx.duty_percent > 0;
+
+        // New second: new character and position
+        if now.as_secs() != self.last_second {
+            self.last_second = now.as_secs();
+            let mut rng = rand::thread_rng();
+            self.character = random_char(&mut rng);
+            self.col = rng.gen_range(0..ctx.geometry.width);
+            self.row = rng.gen_range(0..ctx.geometry.lines);
+        }
+
+        if should_show {
+            place(
+                ctx.geometry,
+                self.col,
+                self.row,
+                &self.character.to_string(),
+            )
+        } else {
+            ctx.geometry.blank_lines()
+        }
+    }
+}
+
+/// Rows of characters shifting down, new ones appearing at the top
+struct Rain {
+    grid: Vec<Vec<char>>,
+    last_step: Instant,
+}
+
+impl Default for Rain {
+    fn default() -> Self {
+        Self {
+            grid: Vec::new(),
+            last_step: Instant::now(),
+        }
+    }
+}
+
+impl IdleAnimation for Rain {
+    fn frame(&mut self, ctx: &IdleContext) -> Vec<String> {
+        let DisplayGeometry { width, lines } = ctx.geometry;
+        if self.grid.len() != lines || self.grid.first().is_some_and(|r| r.len() != width) {
+            self.grid = vec![vec![' '; width]; lines];
+        }
+
+        let mut rng = rand::thread_rng();
+        for _ in 0..due_steps(&mut self.last_step, Duration::from_millis(200)).min(lines as u32) {
+            // Drops fall one row; a few columns start a new drop
+            let top = (0..width)
+                .map(|_| {
+                    if rng.gen_range(0..10) == 0 {
+                        random_char(&mut rng)
+                    } else {
+                        ' '
+                    }
+                })
+                .collect();
+            self.grid.pop();
+            self.grid.insert(0, top);
+        }
+        self.grid.iter().map(|row| row.iter().collect()).collect()
+    }
+}
+
+/// The callsign moving diagonally, reversing at each edge
+struct BouncingCall {
+    col: usize,
+    row: usize,
+    right: bool,
+    down: bool,
+    last_step: Instant,
+}
+
+impl Default for BouncingCall {
+    fn default() -> Self {
+        Self {
+            col: 0,
+            row: 0,
+            right: true,
+            down: true,
+            last_step: Instant::now(),
+        }
+    }
+}
+
+impl IdleAnimation for BouncingCall {
+    fn frame(&mut self, ctx: &IdleContext) -> Vec<String> {
+        let text = if ctx.callsign.is_empty() {
+            "RBN"
+        } else {
+            ctx.callsign
+        };
+        let max_col = ctx.geometry.width.saturating_sub(text.chars().count());
+        let max_row = ctx.geometry.lines - 1;
+
+        for _ in 0..due_steps(&mut self.last_step, Duration::from_millis(400)) {
+            if self.col >= max_col {
+                self.right = false;
+            } else if self.col == 0 {
+                self.right = true;
+            }
+            if self.row >= max_row {
+                self.down = false;
+            } else if self.row == 0 {
+                self.down = true;
+            }
+            self.col = if self.right {
+                (self.col + 1).min(max_col)
+            } else {
+                self.col.saturating_sub(1)
+            };
+            self.row = if self.down {
+                (self.row + 1).min(max_row)
+            } else {
+                self.row.saturating_sub(1)
+            };
+        }
+        place(
+            ctx.geometry,
+            self.col.min(max_col),
+            self.row.min(max_row),
+            text,
+        )
+    }
+}
+
+/// A lit cell taking one random step each second
+struct PixelWalk {
+    col: usize,
+    row: usize,
+    last_step: Instant,
+}
+
+impl Default for PixelWalk {
+    fn default() -> Self {
+        Self {
+            col: 0,
+            row: 0,
+            last_step: Instant::now(),
+        }
+    }
+}
+
+impl IdleAnimation for PixelWalk {
+    fn frame(&mut self, ctx: &IdleContext) -> Vec<String> {
+        let DisplayGeometry { width, lines } = ctx.geometry;
+        let mut rng = rand::thread_rng();
+        for _ in 0..due_steps(&mut self.last_step, Duration::from_secs(1)) {
+            match rng.gen_range(0..4) {
+                0 => self.col = (self.col + 1) % width,
+                1 => self.col = (self.col + width - 1) % width,
+                2 => self.row = (self.row + 1) % lines,
+                _ => self.row = (self.row + lines - 1) % lines,
+            }
+        }
+        self.col %= width;
+        self.row %= lines;
+        place(ctx.geometry, self.col, self.row, &FULL_BLOCK.to_string())
+    }
+}
+
+/// "N spots in 24h" scrolling across the middle line
+struct SpotTicker {
+    offset: usize,
+    last_step: Instant,
+}
+
+impl Default for SpotTicker {
+    fn default() -> Self {
+        Self {
+            offset: 0,
+            last_step: Instant::now(),
+        }
+    }
+}
+
+impl IdleAnimation for SpotTicker {
+    fn frame(&mut self, ctx: &IdleContext) -> Vec<String> {
+        let width = ctx.geometry.width;
+        self.offset = self
+            .offset
+            .wrapping_add(due_steps(&mut self.last_step, Duration::from_millis(250)) as usize);
+
+        // Padded by a screen width so the text scrolls fully off before it repeats
+        let text: Vec<char> = format!("{} spots in 24h", ctx.spots_24h)
+            .chars()
+            .chain(std::iter::repeat_n(' ', width))
+            .collect();
+        let start = self.offset % text.len();
+        let line: String = text.iter().cycle().skip(start).take(width).collect();
+        place(ctx.geometry, 0, (ctx.geometry.lines - 1) / 2, &line)
+    }
+}
@@ -0,0 +1,59 @@
+//! Test patterns for checking wiring, addressing and the driver on a new display
+
+use super::DisplayGeometry;
+use std::time::Duration;
+
+/// Full block, mapped to each driver's solid cell by its character map
+pub const FULL_BLOCK: char = '█';
+
+/// One screen of the test sequence
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestStep {
+    pub lines: Vec<String>,
+    /// Brightness to set while this step is shown
+    pub brightness: Option<u8>,
+    pub duration: Duration,
+}
+
+/// The full sequence: all cells on, a character ramp, a line addressing check,
+/// then a brightness sweep if the display can dim
+pub fn test_steps(geometry: DisplayGeometry, supports_brightness: bool) -> Vec<TestStep> {
+    let width = geometry.width;
+    let all_on = vec![FULL_BLOCK.to_string().repeat(width); geometry.lines];
+    let step = |lines: Vec<String>, brightness, millis| TestStep {
+        lines,
+        brightness,
+        duration: Duration::from_millis(millis),
+    };
+
+    let mut steps = vec![step(all_on.clone(), None, 2000)];
+
+    // Printable ASCII, a screenful at a time
+    let ramp: Vec<char> = (0x20u8..0x7F).map(char::from).collect();
+    for screen in ramp.chunks(width * geometry.lines) {
+        let lines = screen
+            .chunks(width)
+            .map(|line| line.iter().collect())
+            .collect();
+        steps.push(step(lines, None, 1500));
+    }
+
+    // Each line labelled, with a column ruler so missing or shifted cells show up
+    let ruler: String = (1..=width)
+        .map(|col| char::from(b'0' + (col % 10) as u8))
+        .collect();
+    let addressing = (1..=geometry.lines)
+        .map(|row| {
+            let label = format!("L{} ", row);
+            format!("{}{}", label, &ruler[label.len().min(width)..])
+        })
+        .collect();
+    steps.push(step(addressing, None, 3000));
+
+    if supports_brightness {
+        for percent in [100u8, 75, 50, 25, 0, 25, 50, 75, 100] {
+            steps.push(step(all_on.clone(), Some(percent), 500));
+        }
+    }
+    steps
+}
@@ -0,0 +1,150 @@
+//! Port writes on a worker thread so a stalled adapter or network can't block the UI
+
+use crate::services::metrics::METRICS;
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Writes allowed to queue up before new ones are dropped
+const MAX_QUEUED_WRITES: usize = 16;
+
+enum WriterCommand {
+    Attach(Box<dyn Write + Send>),
+    Write(Vec<u8>),
+    Detach,
+}
+
+/// Handle to the port writer thread
+pub struct PortWriter {
+    cmd_tx: mpsc::Sender<WriterCommand>,
+    error_rx: mpsc::Receiver<String>,
+    queued: Arc<AtomicUsize>,
+    /// Set when a write fails, so the display state is known to be lost
+    write_failed: Arc<AtomicBool>,
+    /// When the write in progress started
+    busy_since: Arc<Mutex<Option<Instant>>>,
+}
+
+impl PortWriter {
+    pub fn new() -> Self {
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let (error_tx, error_rx) = mpsc::channel();
+        let queued = Arc::new(AtomicUsize::new(0));
+        let write_failed = Arc::new(AtomicBool::new(false));
+        let busy_since = Arc::new(Mutex::new(None));
+
+        let worker = Worker {
+            error_tx,
+            queued: queued.clone(),
+            write_failed: write_failed.clone(),
+            busy_since: busy_since.clone(),
+        };
+        std::thread::spawn(move || writer_task(cmd_rx, worker));
+
+        Self {
+            cmd_tx,
+            error_rx,
+            queued,
+            write_failed,
+            busy_since,
+        }
+    }
+
+    /// Hand an opened port to the writer, replacing any previous one
+    pub fn attach(&self, port: Box<dyn Write + Send>) {
+        let _ = self.cmd_tx.send(WriterCommand::Attach(port));
+    }
+
+    /// Close the port once queued writes have been sent
+    pub fn detach(&self) {
+        let _ = self.cmd_tx.send(WriterCommand::Detach);
+    }
+
+    /// Queue bytes for the port; returns false if they were dropped because the
+    /// writer has fallen behind
+    pub fn write(&self, bytes: Vec<u8>) -> bool {
+        if self.queued.load(Ordering::Relaxed) >= MAX_QUEUED_WRITES {
+            return false;
+        }
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        self.cmd_tx.send(WriterCommand::Write(bytes)).is_ok()
+    }
+
+    /// How long the current write has been in progress, if one is
+    pub fn stalled_for(&self) -> Option<Duration> {
+        self.busy_since
+            .lock()
+            .ok()
+            .and_then(|busy| busy.map(|since| since.elapsed()))
+    }
+
+    /// Whether a write failed since the last call, leaving the display's
+    /// contents unknown
+    pub fn take_write_failed(&self) -> bool {
+        self.write_failed.swap(false, Ordering::Relaxed)
+    }
+
+    /// Next write error, if any (non-blocking)
+    pub fn try_recv_error(&self) -> Option<String> {
+        self.error_rx.try_recv().ok()
+    }
+}
+
+/// The writer thread's side of the shared state
+struct Worker {
+    error_tx: mpsc::Sender<String>,
+    queued: Arc<AtomicUsize>,
+    write_failed: Arc<AtomicBool>,
+    busy_since: Arc<Mutex<Option<Instant>>>,
+}
+
+fn writer_task(cmd_rx: mpsc::Receiver<WriterCommand>, worker: Worker) {
+    let Worker {
+        error_tx,
+        queued,
+        write_failed,
+        busy_since,
+    } = worker;
+    let mut port: Option<Box<dyn Write + Send>> = None;
+    // Report a failing port once, not on every frame
+    let mut failing = false;
+
+    while let Ok(cmd) = cmd_rx.recv() {
+        match cmd {
+            WriterCommand::Attach(new_port) => {
+                port = Some(new_port);
+                failing = false;
+            }
+            WriterCommand::Detach => port = None,
+            WriterCommand::Write(bytes) => {
+                queued.fetch_sub(1, Ordering::Relaxed);
+                let Some(ref mut port) = port else {
+                    continue;
+                };
+                let set_busy = |since| {
+                    if let Ok(mut busy) = busy_since.lock() {
+                        *busy = since;
+                    }
+                };
+                set_busy(Some(Instant::now()));
+                let result = port.write_all(&bytes);
+                set_busy(None);
+                if result.is_err() {
+                    METRICS.vfd_write_failed();
+                    write_failed.store(true, Ordering::Relaxed);
+                }
+                match result {
+                    Ok(()) => failing = false,
+                    Err(e) if !failing => {
+                        failing = true;
+                        if error_tx.send(format!("VFD write failed: {}", e)).is_err() {
+                            return;
+                        }
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Service-mode watchdog for unattended installs
+//!
+//! The UI thread reports a heartbeat every frame. A monitor thread notices when
+//! frames stop and asks for a repaint; the app checks the feed and serial writer
+//! itself and restarts whichever is wedged. Events are appended to a log file.
+//! When started by systemd (`NOTIFY_SOCKET` set) the heartbeat is also passed on
+//! as `WATCHDOG=1`, so a UI that never recovers gets the process restarted.
+
+use crate::models::UtcTime;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Frames missing for this long count as a stalled UI
+const UI_STALL_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often the monitor thread checks the heartbeat
+const MONITOR_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Watchdog state owned by the UI thread
+pub struct Watchdog {
+    started: Instant,
+    /// Milliseconds since `started` at the last frame
+    heartbeat: Arc<AtomicU64>,
+    log_path: Option<PathBuf>,
+    systemd: Option<SystemdNotifier>,
+}
+
+impl Watchdog {
+    /// Start the monitor thread; `on_ui_stall` is called from it when frames stop
+    pub fn start(log_path: Option<PathBuf>, on_ui_stall: impl Fn() + Send + 'static) -> Self {
+        let started = Instant::now();
+        let heartbeat = Arc::new(AtomicU64::new(0));
+
+        let monitor_heartbeat = heartbeat.clone();
+        let monitor_log = log_path.clone();
+        std::thread::spawn(move || {
+            let mut stalled = false;
+            loop {
+                std::thread::sleep(MONITOR_INTERVAL);
+                let last = Duration::from_millis(monitor_heartbeat.load(Ordering::Relaxed));
+                let silent = started.elapsed().saturating_sub(last);
+                if silent >= UI_STALL_TIMEOUT {
+                    if !stalled {
+                        stalled = true;
+                        log_event(
+                            monitor_log.as_deref(),
+                            &format!("UI stalled for {}s, requesting repaint", silent.as_secs()),
+                        );
+                    }
+                    on_ui_stall();
+                } else if stalled {
+                    stalled = false;
+                    log_event(monitor_log.as_deref(), "UI recovered");
+                }
+            }
+        });
+
+        let systemd = SystemdNotifier::from_env();
+        if let Some(ref notifier) = systemd {
+            notifier.notify("READY=1");
+        }
+
+        let watchdog = Self {
+            started,
+            heartbeat,
+            log_path,
+            systemd,
+        };
+        watchdog.log("Watchdog started");
+        watchdog
+    }
+
+    /// Record that the UI produced a frame
+    pub fn frame(&mut self) {
+        self.heartbeat
+            .store(self.started.elapsed().as_millis() as u64, Ordering::Relaxed);
+        if let Some(ref mut notifier) = self.systemd {
+            notifier.keep_alive();
+        }
+    }
+
+    /// Append an event to the service log
+    pub fn log(&self, event: &str) {
+        log_event(self.log_path.as_deref(), event);
+    }
+}
+
+fn log_event(path: Option<&Path>, event: &str) {
+    let line = format!("{} {}", UtcTime::from_system(SystemTime::now()), event);
+    eprintln!("{}", line);
+    let Some(path) = path else {
+        return;
+    };
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+    {
+        let _ = writeln!(file, "{}", line);
+    }
+}
+
+/// sd_notify(3) over the socket systemd passes in `NOTIFY_SOCKET`
+struct SystemdNotifier {
+    #[cfg(unix)]
+    socket: std::os::unix::net::UnixDatagram,
+    #[cfg(unix)]
+    address: std::os::unix::net::SocketAddr,
+    /// Half the WatchdogSec systemd expects pings within
+    interval: Option<Duration>,
+    last_ping: Instant,
+}
+
+impl SystemdNotifier {
+    #[cfg(unix)]
+    fn from_env() -> Option<Self> {
+        use std::os::unix::net::{SocketAddr, UnixDatagram};
+
+        let path = std::env::var("NOTIFY_SOCKET").ok()?;
+        let address = match path.strip_prefix('@') {
+            #[cfg(target_os = "linux")]
+            Some(name) => {
+                use std::os::linux::net::SocketAddrExt;
+                SocketAddr::from_abstract_name(name).ok()?
+            }
+            #[cfg(not(target_os = "linux"))]
+            Some(_) => return None,
+            None => SocketAddr::from_pathname(&path).ok()?,
+        };
+        let interval = std::env::var("WATCHDOG_USEC")
+            .ok()
+            .and_then(|usec| usec.parse::<u64>().ok())
+            .map(|usec| Duration::from_micros(usec / 2));
+
+        Some(Self {
+            socket: UnixDatagram::unbound().ok()?,
+            address,
+            interval,
+            last_ping: Instant::now(),
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn from_env() -> Option<Self> {
+        None
+    }
+
+    fn notify(&self, state: &str) {
+        #[cfg(unix)]
+        let _ = self.socket.send_to_addr(state.as_bytes(), &self.address);
+        #[cfg(not(unix))]
+        let _ = state;
+    }
+
+    fn keep_alive(&mut self) {
+        if let Some(interval) = self.interval {
+            if self.last_ping.elapsed() >= interval {
+                self.last_ping = Instant::now();
+                self.notify("WATCHDOG=1");
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! Server side of the WebSocket spot stream. The HTTP API reads the upgrade
+//! request; tungstenite does the rest of RFC 6455 (accept key, framing, and
+//! answering the client's pings and close).
+
+use std::io::{ErrorKind, Write};
+use std::net::TcpStream;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::frame::coding::CloseCode;
+use tungstenite::protocol::{CloseFrame, Role};
+use tungstenite::{Message, WebSocket};
+
+/// Idle time after which a ping is sent, so dead clients are noticed
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long a read waits for the client before queued messages are sent
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Channels of the connected clients; each receives every message sent
+pub type Subscribers = Arc<Mutex<Vec<mpsc::Sender<String>>>>;
+
+/// Send a message to every connected client, forgetting those that went away
+pub fn broadcast(subscribers: &Subscribers, message: &str) {
+    if let Ok(mut subscribers) = subscribers.lock() {
+        subscribers.retain(|tx| tx.send(message.to_string()).is_ok());
+    }
+}
+
+/// Complete the handshake and forward broadcast messages until the client
+/// closes the connection or `stop` is set
+pub fn serve(mut stream: TcpStream, key: &str, subscribers: &Subscribers, stop: &AtomicBool) {
+    let handshake = format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        derive_accept_key(key.trim().as_bytes())
+    );
+    if stream.write_all(handshake.as_bytes()).is_err()
+        || stream.set_read_timeout(Some(POLL_INTERVAL)).is_err()
+    {
+        return;
+    }
+    let mut socket = WebSocket::from_raw_socket(stream, Role::Server, None);
+
+    let (tx, rx) = mpsc::channel();
+    if let Ok(mut subscribers) = subscribers.lock() {
+        subscribers.push(tx);
+    }
+
+    let mut last_sent = Instant::now();
+    while !stop.load(Ordering::Relaxed) {
+        // The client has nothing to say; reading answers its pings and
+        // acknowledges its close, after which reads fail
+        match socket.read() {
+            Ok(_) => {}
+            Err(tungstenite::Error::Io(e))
+                if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(_) => return,
+        }
+        loop {
+            let message = match rx.try_recv() {
+                Ok(message) => Message::text(message),
+                Err(mpsc::TryRecvError::Empty) if last_sent.elapsed() >= PING_INTERVAL => {
+                    Message::Ping(Default::default())
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+                Err(mpsc::TryRecvError::Disconnected) => return,
+            };
+            if socket.send(message).is_err() {
+                return;
+            }
+            last_sent = Instant::now();
+        }
+    }
+    let _ = socket.close(Some(CloseFrame {
+        code: CloseCode::Away,
+        reason: Default::default(),
+    }));
+    let _ = socket.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+
+    #[test]
+    fn streams_messages_and_answers_the_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let subscribers = Subscribers::default();
+        let stop = Arc::new(AtomicBool::new(false));
+        let server = {
+            let subscribers = subscribers.clone();
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                let (stream, _) = listener.accept().unwrap();
+                let mut key = String::new();
+                for line in BufReader::new(&stream).lines() {
+                    let line = line.unwrap();
+                    if line.is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.strip_prefix("Sec-WebSocket-Key:") {
+                        key = value.trim().to_string();
+                    }
+                }
+                serve(stream, &key, &subscribers, &stop);
+            })
+        };
+
+        let stream = TcpStream::connect(address).unwrap();
+        let (mut client, _) =
+            tungstenite::client(format!("ws://{}/stream", address), stream).unwrap();
+        while subscribers.lock().unwrap().is_empty() {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        broadcast(&subscribers, "{\"callsign\":\"W1AW\"}");
+        assert_eq!(
+            client.read().unwrap(),
+            Message::text("{\"callsign\":\"W1AW\"}")
+        );
+
+        client.send(Message::Ping("hi".into())).unwrap();
+        assert_eq!(client.read().unwrap(), Message::Pong("hi".into()));
+
+        // The server acknowledges a close and hangs up
+        client.close(None).unwrap();
+        assert!(matches!(client.read(), Ok(Message::Close(_))));
+        server.join().unwrap();
+    }
+}
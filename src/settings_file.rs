@@ -0,0 +1,892 @@
+//! On-disk format of the settings file
+//!
+//! settings.toml is a serialized `SettingsFile`: a `version`, then one table
+//! per section. Lists are TOML arrays and keyed values (dial frequencies,
+//! antennas) tables. Radio profiles are `[[radio.profiles]]` tables, carousel
+//! pages `[pages.<page>]` tables, and each configuration profile a
+//! `[[profiles.saved]]` table nesting the values it switches by section. Keys
+//! missing from a file take their values from the defaults passed in, so
+//! older and hand-trimmed files still load. A settings.ini from an older
+//! version is read once with `read_ini` and migrated.
+
+use crate::error::{Error, Result};
+use crate::models::Band;
+use configparser::ini::Ini;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use toml::{Table, Value};
+
+/// Schema version written to new files; files from a newer version are refused
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Declare a settings section along with its patch: the same fields, each
+/// optional, for profiles that switch only some of them
+macro_rules! section {
+    (
+        $(#[$meta:meta])*
+        pub struct $name:ident / $patch:ident {
+            $($(#[$field_meta:meta])* pub $field:ident: $ty:ty,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+        pub struct $name {
+            $($(#[$field_meta])* pub $field: $ty,)*
+        }
+
+        #[doc = concat!("The values of `", stringify!($name), "` a profile sets")]
+        #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+        pub struct $patch {
+            $(
+                #[serde(default, skip_serializing_if = "Option::is_none")]
+                pub $field: Option<$ty>,
+            )*
+        }
+
+        impl $name {
+            /// Every value, as a patch
+            pub fn patch(&self) -> $patch {
+                $patch {
+                    $($field: Some(self.$field.clone()),)*
+                }
+            }
+
+            /// Take over the values `patch` sets
+            pub fn apply(&mut self, patch: &$patch) {
+                $(
+                    if let Some(value) = &patch.$field {
+                        self.$field = value.clone();
+                    }
+                )*
+            }
+        }
+
+        impl $patch {
+            /// Whether the patch sets nothing
+            pub fn is_empty(&self) -> bool {
+                $(self.$field.is_none())&&*
+            }
+        }
+    };
+}
+
+/// Everything stored in settings.toml
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingsFile {
+    pub version: u32,
+    pub connection: ConnectionSettings,
+    pub ui: UiSettings,
+    pub display: DisplaySettings,
+    pub filters: FilterSettings,
+    pub data: DataSettings,
+    pub propagation: PropagationSettings,
+    pub contest: ContestSettings,
+    pub alerts: AlertSettings,
+    pub network: NetworkSettings,
+    pub bands: BandSettings,
+    pub radio: RadioSettings,
+    pub service: ServiceSettings,
+    pub pages: PagesSettings,
+    pub mqtt: MqttSettings,
+    pub api: ApiSettings,
+    pub n1mm: N1mmSettings,
+    pub history: HistorySettings,
+    pub scripting: ScriptingSettings,
+    pub profiles: ProfilesSettings,
+}
+
+/// A named frequency range: a band, window or tuning segment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BandEntry {
+    pub name: String,
+    pub low_khz: f64,
+    pub high_khz: f64,
+}
+
+impl From<&Band> for BandEntry {
+    fn from(band: &Band) -> Self {
+        Self {
+            name: band.name.clone(),
+            low_khz: band.low_khz,
+            high_khz: band.high_khz,
+        }
+    }
+}
+
+impl BandEntry {
+    /// The range as a band, if it is a usable one
+    pub fn to_band(&self) -> std::result::Result<Band, String> {
+        let name = self.name.trim();
+        if name.is_empty()
+            || !self.low_khz.is_finite()
+            || !self.high_khz.is_finite()
+            || self.low_khz >= self.high_khz
+        {
+            return Err(format!(
+                "Invalid band entry: {} {}-{}",
+                name, self.low_khz, self.high_khz
+            ));
+        }
+        Ok(Band {
+            name: name.to_string(),
+            low_khz: self.low_khz,
+            high_khz: self.high_khz,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConnectionSettings {
+    pub callsign: String,
+    pub locator: String,
+    pub auto_connect: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiSettings {
+    pub theme: String,
+    /// "#rrggbb", or empty for the theme's own
+    pub accent_color: String,
+    pub scale: f64,
+    pub preview_color: String,
+    pub preview_dot_matrix: bool,
+    pub preview_glow: bool,
+    pub row_color_age: bool,
+    pub row_color_snr: bool,
+    pub row_color_mode: bool,
+    pub sort_by_trend: bool,
+    pub group_by_band: bool,
+    pub age_indicator: String,
+    pub frequency_unit: String,
+    pub number_locale: String,
+}
+
+section! {
+    pub struct DisplaySettings / DisplayPatch {
+        pub serial_port: String,
+        pub marquee: bool,
+        pub marquee_chars_per_second: u32,
+        pub random_char_percent: u32,
+        pub screensaver: String,
+        pub flash_new_spots: bool,
+        pub idle_content: String,
+        pub content: String,
+        pub watchlist: Vec<String>,
+        pub show_local_time: bool,
+        /// Left out of the file when the system time zone is used
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub local_offset_minutes: Option<i32>,
+        pub handoff_file: String,
+        pub network_display: String,
+        pub charset_file: String,
+        pub geometry: String,
+        pub protocol: String,
+        pub brightness: u32,
+    }
+}
+
+section! {
+    pub struct FilterSettings / FilterPatch {
+        pub min_snr: i32,
+        pub adaptive_snr: bool,
+        pub adaptive_keep_percent: u32,
+        pub max_age_minutes: u32,
+        pub scroll_interval_seconds: u32,
+        pub source_policy: String,
+        pub blocked_spotters: Vec<String>,
+        pub ignored_calls: Vec<String>,
+        pub show_beacons: bool,
+        pub hide_dupes: bool,
+        pub busted_calls: String,
+        pub merge_tolerance_hz: u32,
+        pub frequency_windows: Vec<BandEntry>,
+        pub refresh_ms: u32,
+        /// Hz keyed by skimmer callsign
+        pub calibration_offsets: BTreeMap<String, f64>,
+        pub calibration_learning: bool,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataSettings {
+    pub update_interval_hours: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PropagationSettings {
+    pub refresh_minutes: u32,
+}
+
+section! {
+    pub struct ContestSettings / ContestPatch {
+        /// Empty when contest mode is off
+        pub name: String,
+    }
+}
+
+section! {
+    pub struct AlertSettings / AlertPatch {
+        pub away_idle_minutes: u32,
+        pub most_wanted_rank: u32,
+        pub needed_entities: bool,
+        pub run_guard_hz: u32,
+        pub run_busy_flash: bool,
+        pub run_busy_sound: String,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkSettings {
+    pub low_bandwidth: bool,
+    pub low_bandwidth_commands: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BandSettings {
+    pub table: Vec<BandEntry>,
+}
+
+/// The `[radio]` table: the active radio configuration, tuning rules and the
+/// saved radio profiles
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RadioSettings {
+    #[serde(flatten)]
+    pub control: RadioControlSettings,
+    pub band_boost_seconds: u32,
+    pub tune_segments: Vec<BandEntry>,
+    pub locked_bands: Vec<String>,
+    pub confirm_qsy_khz: u32,
+    /// kHz keyed by mode
+    pub dial_frequencies: BTreeMap<String, Vec<f64>>,
+    pub active_profile: String,
+    pub profiles: Vec<RadioProfileSettings>,
+}
+
+/// The values of `RadioSettings` a profile sets (all but the radio profiles)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RadioPatch {
+    #[serde(flatten)]
+    pub control: RadioControlPatch,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub band_boost_seconds: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tune_segments: Option<Vec<BandEntry>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locked_bands: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm_qsy_khz: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dial_frequencies: Option<BTreeMap<String, Vec<f64>>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+}
+
+impl RadioSettings {
+    /// Every value a profile sets, as a patch
+    pub fn patch(&self) -> RadioPatch {
+        RadioPatch {
+            control: self.control.patch(),
+            band_boost_seconds: Some(self.band_boost_seconds),
+            tune_segments: Some(self.tune_segments.clone()),
+            locked_bands: Some(self.locked_bands.clone()),
+            confirm_qsy_khz: Some(self.confirm_qsy_khz),
+            dial_frequencies: Some(self.dial_frequencies.clone()),
+            active_profile: Some(self.active_profile.clone()),
+        }
+    }
+
+    /// Take over the values `patch` sets
+    pub fn apply(&mut self, patch: &RadioPatch) {
+        self.control.apply(&patch.control);
+        if let Some(seconds) = patch.band_boost_seconds {
+            self.band_boost_seconds = seconds;
+        }
+        if let Some(segments) = &patch.tune_segments {
+            self.tune_segments = segments.clone();
+        }
+        if let Some(bands) = &patch.locked_bands {
+            self.locked_bands = bands.clone();
+        }
+        if let Some(khz) = patch.confirm_qsy_khz {
+            self.confirm_qsy_khz = khz;
+        }
+        if let Some(dials) = &patch.dial_frequencies {
+            self.dial_frequencies = dials.clone();
+        }
+        if let Some(name) = &patch.active_profile {
+            self.active_profile = name.clone();
+        }
+    }
+}
+
+impl RadioPatch {
+    /// Whether the patch sets nothing
+    pub fn is_empty(&self) -> bool {
+        self.control.is_empty()
+            && self.band_boost_seconds.is_none()
+            && self.tune_segments.is_none()
+            && self.locked_bands.is_none()
+            && self.confirm_qsy_khz.is_none()
+            && self.dial_frequencies.is_none()
+            && self.active_profile.is_none()
+    }
+}
+
+section! {
+    /// Settings a radio profile switches
+    pub struct RadioControlSettings / RadioControlPatch {
+        pub enabled: bool,
+        pub backend: String,
+        pub rigctld_host: String,
+        pub rigctld_port: u16,
+        pub omnirig_rig: u8,
+        pub commander_host: String,
+        pub commander_port: u16,
+        pub flex_host: String,
+        pub flex_slice: u8,
+        pub passband_cw: u32,
+        pub passband_ssb: u32,
+        pub passband_rtty: u32,
+        pub passband_data: u32,
+        /// Backend commands, each with an optional "MODE:" prefix
+        pub tune_steps: Vec<String>,
+        pub antenna_output: String,
+        pub antenna_target: String,
+        pub antenna_command: String,
+        /// Antenna number keyed by band name
+        pub antennas: BTreeMap<String, u8>,
+    }
+}
+
+/// One `[[radio.profiles]]` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RadioProfileSettings {
+    pub name: String,
+    #[serde(flatten)]
+    pub control: RadioControlSettings,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceSettings {
+    pub watchdog: bool,
+    pub feed_stall_minutes: u32,
+}
+
+/// The `[pages]` table, with a `[pages.<page>]` table per carousel page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PagesSettings {
+    pub station_id_line1: String,
+    pub station_id_line2: String,
+    pub show_spot_note: bool,
+    /// Keyed by `VfdPage::as_str`
+    #[serde(flatten)]
+    pub carousel: BTreeMap<String, CarouselPageSettings>,
+}
+
+/// The values of `PagesSettings` a profile sets
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PagesPatch {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub station_id_line1: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub station_id_line2: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub show_spot_note: Option<bool>,
+    #[serde(flatten)]
+    pub carousel: BTreeMap<String, CarouselPagePatch>,
+}
+
+impl PagesSettings {
+    /// Every value, as a patch
+    pub fn patch(&self) -> PagesPatch {
+        PagesPatch {
+            station_id_line1: Some(self.station_id_line1.clone()),
+            station_id_line2: Some(self.station_id_line2.clone()),
+            show_spot_note: Some(self.show_spot_note),
+            carousel: self
+                .carousel
+                .iter()
+                .map(|(page, settings)| (page.clone(), settings.patch()))
+                .collect(),
+        }
+    }
+
+    /// Take over the values `patch` sets
+    pub fn apply(&mut self, patch: &PagesPatch) {
+        if let Some(line) = &patch.station_id_line1 {
+            self.station_id_line1 = line.clone();
+        }
+        if let Some(line) = &patch.station_id_line2 {
+            self.station_id_line2 = line.clone();
+        }
+        if let Some(show) = patch.show_spot_note {
+            self.show_spot_note = show;
+        }
+        for (page, page_patch) in &patch.carousel {
+            if let Some(settings) = self.carousel.get_mut(page) {
+                settings.apply(page_patch);
+            }
+        }
+    }
+}
+
+impl PagesPatch {
+    /// Whether the patch sets nothing
+    pub fn is_empty(&self) -> bool {
+        self.station_id_line1.is_none()
+            && self.station_id_line2.is_none()
+            && self.show_spot_note.is_none()
+            && self.carousel.values().all(CarouselPagePatch::is_empty)
+    }
+}
+
+section! {
+    pub struct CarouselPageSettings / CarouselPagePatch {
+        pub enabled: bool,
+        pub dwell_seconds: u32,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub frame_topic: String,
+    pub spot_topic: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiSettings {
+    pub enabled: bool,
+    pub bind: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct N1mmSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistorySettings {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptingSettings {
+    pub script: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProfilesSettings {
+    pub active: String,
+    pub saved: Vec<SavedProfile>,
+}
+
+/// One `[[profiles.saved]]` table: the values the profile switches, by
+/// section. A profile written by hand may set as few of them as it likes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedProfile {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "FilterPatch::is_empty")]
+    pub filters: FilterPatch,
+    #[serde(default, skip_serializing_if = "DisplayPatch::is_empty")]
+    pub display: DisplayPatch,
+    #[serde(default, skip_serializing_if = "PagesPatch::is_empty")]
+    pub pages: PagesPatch,
+    #[serde(default, skip_serializing_if = "ContestPatch::is_empty")]
+    pub contest: ContestPatch,
+    #[serde(default, skip_serializing_if = "AlertPatch::is_empty")]
+    pub alerts: AlertPatch,
+    #[serde(default, skip_serializing_if = "RadioPatch::is_empty")]
+    pub radio: RadioPatch,
+}
+
+impl SavedProfile {
+    /// A profile switching to the filter, display, page, contest, alert and
+    /// radio settings in `settings`
+    pub fn capture(name: &str, settings: &SettingsFile) -> Self {
+        Self {
+            name: name.to_string(),
+            filters: settings.filters.patch(),
+            display: without_hardware(settings.display.patch()),
+            pages: settings.pages.patch(),
+            contest: settings.contest.patch(),
+            alerts: settings.alerts.patch(),
+            radio: settings.radio.patch(),
+        }
+    }
+
+    /// Lay the profile's values over `settings`
+    pub fn apply(&self, settings: &mut SettingsFile) {
+        settings.filters.apply(&self.filters);
+        settings
+            .display
+            .apply(&without_hardware(self.display.clone()));
+        settings.pages.apply(&self.pages);
+        settings.contest.apply(&self.contest);
+        settings.alerts.apply(&self.alerts);
+        settings.radio.apply(&self.radio);
+    }
+}
+
+/// A display patch without the settings tied to the hardware rather than a
+/// way of operating, which profiles leave alone
+fn without_hardware(display: DisplayPatch) -> DisplayPatch {
+    DisplayPatch {
+        serial_port: None,
+        handoff_file: None,
+        network_display: None,
+        charset_file: None,
+        geometry: None,
+        protocol: None,
+        ..display
+    }
+}
+
+/// Read settings.toml, filling in anything it leaves out from `defaults`
+pub fn read(path: &Path, defaults: &SettingsFile) -> Result<SettingsFile> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| Error::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+    let mut table: Table = text
+        .parse()
+        .map_err(|e| Error::Config(format!("Failed to parse {}: {}", path.display(), e)))?;
+
+    // Early TOML files called the version schema_version
+    if let Some(version) = table.remove("schema_version") {
+        table.entry("version").or_insert(version);
+    }
+    let version = table
+        .get("version")
+        .and_then(Value::as_integer)
+        .unwrap_or(SCHEMA_VERSION.into());
+    if version > SCHEMA_VERSION.into() {
+        return Err(Error::Config(format!(
+            "Settings file is from a newer version (schema {}, this version reads {})",
+            version, SCHEMA_VERSION
+        )));
+    }
+
+    with_defaults(table, defaults)
+        .map_err(|e| Error::Config(format!("Failed to parse {}: {}", path.display(), e)))
+}
+
+/// Write settings.toml
+pub fn write(settings: &SettingsFile, path: &Path) -> Result<()> {
+    let text = toml::to_string_pretty(settings)
+        .map_err(|e| Error::Config(format!("Failed to write config: {}", e)))?;
+    std::fs::write(path, text).map_err(|e| Error::Config(format!("Failed to write config: {}", e)))
+}
+
+/// Read a settings.ini written by an older version. Values that don't parse
+/// as their setting's type are left at their defaults, as the INI reader did.
+pub fn read_ini(path: &Path, defaults: &SettingsFile) -> Result<SettingsFile> {
+    let mut ini = Ini::new();
+    // Values the app wrote may hold ';' and '#' (tune steps, colors); only
+    // whole-line comments are comments
+    ini.set_inline_comment_symbols(Some(&[]));
+    ini.load(path)
+        .map_err(|e| Error::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+    from_ini(&ini, defaults)
+        .map_err(|e| Error::Config(format!("Failed to convert {}: {}", path.display(), e)))
+}
+
+/// Lay the INI sections out as settings.toml tables
+fn from_ini(ini: &Ini, defaults: &SettingsFile) -> std::result::Result<SettingsFile, String> {
+    let template = to_table(defaults)?;
+    let radio_template = to_table(&defaults.radio.control)?;
+    let mut root = Table::new();
+    let mut radio_profiles = BTreeMap::new();
+    let mut profiles = BTreeMap::new();
+
+    for (section, values) in ini.get_map_ref() {
+        let values = values
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_deref().unwrap_or_default()));
+
+        // Profiles were [radio_profile_N] and [profile_N] sections
+        if let Some(n) = numbered(section, "radio_profile_") {
+            let profile: &mut Table = radio_profiles.entry(n).or_default();
+            for (key, value) in values {
+                if let Some(value) = legacy_value(key, value, radio_template.get(key)) {
+                    profile.insert(key.to_string(), value);
+                }
+            }
+        } else if let Some(n) = numbered(section, "profile_") {
+            let profile: &mut Table = profiles.entry(n).or_default();
+            for (key, value) in values {
+                match key.split_once('.') {
+                    Some((inner, inner_key)) => {
+                        legacy_insert(profile, &template, inner, inner_key, value);
+                    }
+                    None => {
+                        profile.insert(key.to_string(), Value::String(value.to_string()));
+                    }
+                }
+            }
+        } else {
+            for (key, value) in values {
+                legacy_insert(&mut root, &template, section, key, value);
+            }
+        }
+    }
+
+    if !radio_profiles.is_empty() {
+        let tables = radio_profiles.into_values().map(Value::Table).collect();
+        nested_table(&mut root, "radio").insert("profiles".to_string(), Value::Array(tables));
+    }
+    if !profiles.is_empty() {
+        let tables = profiles.into_values().map(Value::Table).collect();
+        nested_table(&mut root, "profiles").insert("saved".to_string(), Value::Array(tables));
+    }
+    with_defaults(root, defaults)
+}
+
+/// Put an INI value into `root` as the type its setting has in `template`.
+/// Carousel pages were flat <page>_enabled / <page>_dwell_seconds keys; they
+/// move into the page's table.
+fn legacy_insert(root: &mut Table, template: &Table, section: &str, key: &str, value: &str) {
+    let section_template = template.get(section);
+    let (table, key, key_template) = match page_key(section, key) {
+        Some((page, key)) => (
+            nested_table(nested_table(root, section), page),
+            key,
+            section_template
+                .and_then(|t| t.get(page))
+                .and_then(|t| t.get(key)),
+        ),
+        None => (
+            nested_table(root, section),
+            key,
+            section_template.and_then(|t| t.get(key)),
+        ),
+    };
+    if let Some(value) = legacy_value(key, value, key_template) {
+        table.insert(key.to_string(), value);
+    }
+}
+
+/// A carousel page key of the old `[pages]` section, split into page and key
+fn page_key<'a>(section: &str, key: &'a str) -> Option<(&'a str, &'a str)> {
+    if section != "pages" {
+        return None;
+    }
+    ["enabled", "dwell_seconds"].into_iter().find_map(|suffix| {
+        let page = key.strip_suffix(suffix)?.strip_suffix('_')?;
+        Some((page, suffix))
+    })
+}
+
+/// N from a section named `<prefix>N`
+fn numbered(section: &str, prefix: &str) -> Option<u32> {
+    section.strip_prefix(prefix)?.parse().ok()
+}
+
+/// An INI value as the type its setting has in `template`, or None if it
+/// doesn't parse as that type
+fn legacy_value(key: &str, value: &str, template: Option<&Value>) -> Option<Value> {
+    let value = value.trim();
+    match template {
+        Some(Value::String(_)) => Some(Value::String(value.to_string())),
+        Some(Value::Boolean(_)) => value.to_lowercase().parse().ok().map(Value::Boolean),
+        Some(Value::Integer(_)) => value.parse().ok().map(Value::Integer),
+        Some(Value::Float(_)) => value.parse().ok().map(Value::Float),
+        Some(Value::Array(_) | Value::Table(_)) => legacy_list(key, value),
+        _ => Some(typed(value)),
+    }
+}
+
+/// A delimited INI value as the array or table that replaced it
+fn legacy_list(key: &str, value: &str) -> Option<Value> {
+    let split = |separators: &[char]| {
+        Value::Array(
+            value
+                .split(separators)
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(|item| Value::String(item.to_string()))
+                .collect(),
+        )
+    };
+    match key {
+        // NAME:LOW-HIGH;...
+        "table" | "frequency_windows" | "tune_segments" => {
+            let bands: Vec<BandEntry> = Band::parse_list(value)
+                .ok()?
+                .iter()
+                .map(BandEntry::from)
+                .collect();
+            Value::try_from(bands).ok()
+        }
+        "watchlist" => Some(split(&[',', ' '])),
+        "blocked_spotters" | "ignored_calls" | "locked_bands" => Some(split(&[','])),
+        "low_bandwidth_commands" | "tune_steps" => Some(split(&[';'])),
+        // CALL=Hz,...
+        "calibration_offsets" => {
+            legacy_pairs(value, ',', '=', |hz| hz.parse().ok().map(Value::Float))
+        }
+        // band=number,...
+        "antennas" => legacy_pairs(value, ',', '=', |n| {
+            n.parse::<u8>().ok().map(|n| Value::Integer(n.into()))
+        }),
+        // MODE:kHz,kHz,...;...
+        "dial_frequencies" => legacy_pairs(value, ';', ':', |dials| {
+            dials
+                .split(',')
+                .map(|dial| dial.trim().parse().ok().map(Value::Float))
+                .collect::<Option<Vec<_>>>()
+                .map(Value::Array)
+        }),
+        _ => None,
+    }
+}
+
+/// `key<pair>value` entries separated by `separator` as a table, or None if
+/// an entry doesn't parse
+fn legacy_pairs(
+    value: &str,
+    separator: char,
+    pair: char,
+    parse: impl Fn(&str) -> Option<Value>,
+) -> Option<Value> {
+    let mut table = Table::new();
+    for entry in value
+        .split(separator)
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+    {
+        let (key, value) = entry.split_once(pair)?;
+        table.insert(key.trim().to_string(), parse(value.trim())?);
+    }
+    Some(Value::Table(table))
+}
+
+/// Parse `table` as settings, with `defaults` under it
+fn with_defaults(
+    table: Table,
+    defaults: &SettingsFile,
+) -> std::result::Result<SettingsFile, String> {
+    let mut root = to_table(defaults)?;
+    let radio_defaults = to_table(&defaults.radio.control)?;
+    merge(&mut root, table);
+
+    // Each radio profile falls back to the default radio settings
+    if let Some(Value::Array(profiles)) = root
+        .get_mut("radio")
+        .and_then(Value::as_table_mut)
+        .and_then(|radio| radio.get_mut("profiles"))
+    {
+        for profile in profiles.iter_mut().filter_map(Value::as_table_mut) {
+            let mut filled = radio_defaults.clone();
+            merge(&mut filled, std::mem::take(profile));
+            *profile = filled;
+        }
+    }
+
+    Value::Table(root).try_into().map_err(|e| e.to_string())
+}
+
+/// Serialize settings (or part of them) as a TOML table
+pub fn to_table(value: &impl Serialize) -> std::result::Result<Table, String> {
+    Table::try_from(value).map_err(|e| e.to_string())
+}
+
+/// Lay `over` on top of `base`, table by table
+fn merge(base: &mut Table, over: Table) {
+    for (key, value) in over {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(over)) => merge(base, over),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// The table at `key`, created if missing
+fn nested_table<'a>(table: &'a mut Table, key: &str) -> &'a mut Table {
+    let entry = table
+        .entry(key.to_string())
+        .or_insert_with(|| Value::Table(Table::new()));
+    if !entry.is_table() {
+        *entry = Value::Table(Table::new());
+    }
+    entry.as_table_mut().expect("entry was just made a table")
+}
+
+/// An INI value with its type, when it reads back unchanged
+fn typed(value: &str) -> Value {
+    if let Ok(flag) = value.parse::<bool>() {
+        return Value::Boolean(flag);
+    }
+    if let Ok(n) = value.parse::<i64>() {
+        if n.to_string() == value {
+            return Value::Integer(n);
+        }
+    }
+    if let Ok(x) = value.parse::<f64>() {
+        if x.is_finite() && x.to_string() == value {
+            return Value::Float(x);
+        }
+    }
+    Value::String(value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_profile_value_types() {
+        assert_eq!(typed("true"), Value::Boolean(true));
+        assert_eq!(typed("8073"), Value::Integer(8073));
+        assert_eq!(typed("7047.5"), Value::Float(7047.5));
+        assert_eq!(typed("007"), Value::String("007".to_string()));
+        assert_eq!(typed("inf"), Value::String("inf".to_string()));
+        assert_eq!(typed("W6JSV"), Value::String("W6JSV".to_string()));
+    }
+
+    #[test]
+    fn converts_delimited_ini_values() {
+        let value = |text: &str| {
+            format!("value = {}", text)
+                .parse::<Table>()
+                .unwrap()
+                .remove("value")
+                .unwrap()
+        };
+        assert_eq!(
+            legacy_list("watchlist", "W1AW, DL* K1ABC"),
+            Some(value(r#"["W1AW", "DL*", "K1ABC"]"#))
+        );
+        assert_eq!(
+            legacy_list("tune_steps", "J 0;CW:L AGC 6;"),
+            Some(value(r#"["J 0", "CW:L AGC 6"]"#))
+        );
+        assert_eq!(
+            legacy_list("tune_segments", "40m:7000-7125"),
+            Some(value(
+                r#"[{ name = "40m", low_khz = 7000.0, high_khz = 7125.0 }]"#
+            ))
+        );
+        assert_eq!(
+            legacy_list("calibration_offsets", "DL1ABC-#=-40, K1TTT-#=12.5"),
+            Some(value(r#"{ "DL1ABC-#" = -40.0, "K1TTT-#" = 12.5 }"#))
+        );
+        assert_eq!(
+            legacy_list("antennas", "40m=1,20m=2"),
+            Some(value("{ 40m = 1, 20m = 2 }"))
+        );
+        assert_eq!(
+            legacy_list("dial_frequencies", "FT8:7074,14074;FT4:7047.5"),
+            Some(value("{ FT8 = [7074.0, 14074.0], FT4 = [7047.5] }"))
+        );
+        // Values the INI reader couldn't parse keep their defaults
+        assert_eq!(legacy_list("antennas", "40m=one"), None);
+        assert_eq!(legacy_list("frequency_windows", "40m:7125-7000"), None);
+    }
+}
@@ -0,0 +1,7 @@
+//! Custom egui widgets
+
+mod vfd_preview;
+mod world_map;
+
+pub use vfd_preview::{Phosphor, PreviewStyle, VfdPreview};
+pub use world_map::{MapMarker, WorldMap};
@@ -0,0 +1,222 @@
+//! On-screen preview of the VFD, drawn to look like the real glass
+
+use crate::services::{glyph, Bitmap};
+use eframe::egui;
+
+/// Text size of the plain (non dot-matrix) preview
+const TEXT_SIZE: f32 = 16.0;
+
+/// Distance between dot centers in the dot-matrix preview
+const DOT_PITCH: f32 = 2.5;
+
+/// Screen pixels per graphic display pixel
+const BITMAP_SCALE: f32 = 2.0;
+
+/// Phosphor color of the preview
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Phosphor {
+    #[default]
+    Green,
+    /// The blue-green of most Noritake and Futaba tubes
+    Cyan,
+    Amber,
+}
+
+impl Phosphor {
+    pub const ALL: [Phosphor; 3] = [Phosphor::Green, Phosphor::Cyan, Phosphor::Amber];
+
+    /// Name used in settings.toml
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Phosphor::Green => "green",
+            Phosphor::Cyan => "cyan",
+            Phosphor::Amber => "amber",
+        }
+    }
+
+    /// Parse the settings.toml name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == name.trim())
+    }
+
+    /// Human-readable label for the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            Phosphor::Green => "Green",
+            Phosphor::Cyan => "Cyan",
+            Phosphor::Amber => "Amber",
+        }
+    }
+
+    fn color(self) -> egui::Color32 {
+        match self {
+            Phosphor::Green => egui::Color32::from_rgb(0, 255, 0),
+            Phosphor::Cyan => egui::Color32::from_rgb(64, 255, 224),
+            Phosphor::Amber => egui::Color32::from_rgb(255, 176, 0),
+        }
+    }
+}
+
+/// How the preview is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PreviewStyle {
+    pub phosphor: Phosphor,
+    /// Draw characters as 5x7 dots instead of a monospace font
+    pub dot_matrix: bool,
+    /// Soft glow around lit segments and dark scanlines across the glass
+    pub glow: bool,
+}
+
+enum Content<'a> {
+    /// Text lines padded or cut to the display width
+    Lines(&'a [String], usize),
+    /// Framebuffer of a graphic display
+    Bitmap(&'a Bitmap),
+}
+
+/// The VFD preview widget
+pub struct VfdPreview<'a> {
+    content: Content<'a>,
+    style: PreviewStyle,
+}
+
+impl<'a> VfdPreview<'a> {
+    /// Text lines as they appear on a character display of the given width
+    pub fn lines(lines: &'a [String], width: usize, style: PreviewStyle) -> Self {
+        Self {
+            content: Content::Lines(lines, width),
+            style,
+        }
+    }
+
+    /// A graphic display frame at double scale
+    pub fn bitmap(bitmap: &'a Bitmap, style: PreviewStyle) -> Self {
+        Self {
+            content: Content::Bitmap(bitmap),
+            style,
+        }
+    }
+
+    /// Lit (and, in dot-matrix mode, unlit) dots as (x, y, lit) in dot units
+    fn dots(&self) -> (egui::Vec2, Vec<(usize, usize, bool)>) {
+        let mut dots = Vec::new();
+        match self.content {
+            Content::Lines(lines, width) => {
+                for (row, line) in lines.iter().enumerate() {
+                    let chars = line.chars().chain(std::iter::repeat(' ')).take(width);
+                    for (i, c) in chars.enumerate() {
+                        for (col, bits) in glyph(c).iter().enumerate() {
+                            for y in 0..7 {
+                                dots.push((i * 6 + col, row * 8 + y, bits & (1 << y) != 0));
+                            }
+                        }
+                    }
+                }
+                let size = egui::vec2((width * 6) as f32, (lines.len() * 8) as f32);
+                (size, dots)
+            }
+            Content::Bitmap(bitmap) => {
+                for y in 0..bitmap.height {
+                    for x in 0..bitmap.width {
+                        if bitmap.get(x, y) {
+                            dots.push((x, y, true));
+                        }
+                    }
+                }
+                (egui::vec2(bitmap.width as f32, bitmap.height as f32), dots)
+            }
+        }
+    }
+
+    fn paint_dots(&self, ui: &mut egui::Ui) -> egui::Response {
+        let color = self.style.phosphor.color();
+        let (scale, round) = match self.content {
+            Content::Lines(..) => (DOT_PITCH, true),
+            Content::Bitmap(_) => (BITMAP_SCALE, false),
+        };
+        let (size, dots) = self.dots();
+        let (response, painter) = ui.allocate_painter(size * scale, egui::Sense::hover());
+        let origin = response.rect.min;
+        for (x, y, lit) in dots {
+            let min = origin + egui::vec2(x as f32, y as f32) * scale;
+            let cell = egui::Rect::from_min_size(min, egui::Vec2::splat(scale));
+            let fill = if lit {
+                color
+            } else {
+                // Unlit segments still show faintly through the glass
+                color.gamma_multiply(0.08)
+            };
+            if lit && self.style.glow {
+                painter.circle_filled(cell.center(), scale, color.gamma_multiply(0.15));
+            }
+            if round {
+                painter.circle_filled(cell.center(), scale * 0.4, fill);
+            } else {
+                painter.rect_filled(cell, 0.0, fill);
+            }
+        }
+        response
+    }
+
+    fn paint_text(&self, ui: &mut egui::Ui, lines: &[String], width: usize) -> egui::Response {
+        let color = self.style.phosphor.color();
+        let text = lines
+            .iter()
+            .map(|line| {
+                format!("{:width$}", line, width = width)
+                    .chars()
+                    .take(width)
+                    .collect()
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        let galley = ui
+            .painter()
+            .layout_no_wrap(text, egui::FontId::monospace(TEXT_SIZE), color);
+        let (response, painter) = ui.allocate_painter(galley.size(), egui::Sense::hover());
+        let origin = response.rect.min;
+        if self.style.glow {
+            let halo = color.gamma_multiply(0.2);
+            for offset in [
+                egui::vec2(-1.0, 0.0),
+                egui::vec2(1.0, 0.0),
+                egui::vec2(0.0, -1.0),
+                egui::vec2(0.0, 1.0),
+            ] {
+                painter.galley_with_override_text_color(origin + offset, galley.clone(), halo);
+            }
+        }
+        painter.galley(origin, galley, color);
+        response
+    }
+}
+
+impl egui::Widget for VfdPreview<'_> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        egui::Frame::new()
+            .fill(egui::Color32::BLACK)
+            .inner_margin(egui::Margin::same(8))
+            .corner_radius(egui::CornerRadius::same(4))
+            .show(ui, |ui| {
+                let response = match self.content {
+                    Content::Lines(lines, width) if !self.style.dot_matrix => {
+                        self.paint_text(ui, lines, width)
+                    }
+                    _ => self.paint_dots(ui),
+                };
+                if self.style.glow {
+                    // Faint dark scanlines every other screen pixel row
+                    let rect = response.rect;
+                    let shade = egui::Color32::from_black_alpha(70);
+                    let mut y = rect.top();
+                    while y < rect.bottom() {
+                        ui.painter()
+                            .hline(rect.x_range(), y, egui::Stroke::new(1.0, shade));
+                        y += 2.0;
+                    }
+                }
+                response
+            })
+            .inner
+    }
+}
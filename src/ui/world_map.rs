@@ -0,0 +1,110 @@
+//! A simple world map: equirectangular, with the Maidenhead field grid for
+//! orientation instead of coastlines
+
+use eframe::egui;
+
+/// Background of the map
+const SEA: egui::Color32 = egui::Color32::from_rgb(12, 24, 40);
+
+/// A point plotted on the map
+#[derive(Debug, Clone)]
+pub struct MapMarker {
+    /// Degrees north
+    pub latitude: f64,
+    /// Degrees east
+    pub longitude: f64,
+    /// Radius in points
+    pub radius: f32,
+    pub color: egui::Color32,
+    /// Shown on hover
+    pub label: String,
+}
+
+/// The map widget; `show` reports which marker was clicked
+pub struct WorldMap<'a> {
+    markers: &'a [MapMarker],
+}
+
+impl<'a> WorldMap<'a> {
+    pub fn new(markers: &'a [MapMarker]) -> Self {
+        Self { markers }
+    }
+
+    /// Draw the map at the available width; returns the clicked marker's index
+    pub fn show(self, ui: &mut egui::Ui) -> Option<usize> {
+        let width = ui.available_width().max(200.0);
+        let (response, painter) =
+            ui.allocate_painter(egui::vec2(width, width / 2.0), egui::Sense::click());
+        let rect = response.rect;
+        let to_screen = |latitude: f64, longitude: f64| {
+            egui::pos2(
+                rect.left() + ((longitude + 180.0) / 360.0) as f32 * rect.width(),
+                rect.top() + ((90.0 - latitude) / 180.0) as f32 * rect.height(),
+            )
+        };
+
+        painter.rect_filled(rect, 2.0, SEA);
+        let line = egui::Stroke::new(1.0, egui::Color32::from_rgb(35, 55, 80));
+        for field in 0..=18 {
+            let x = rect.left() + field as f32 / 18.0 * rect.width();
+            painter.vline(x, rect.y_range(), line);
+            let y = rect.top() + field as f32 / 18.0 * rect.height();
+            painter.hline(rect.x_range(), y, line);
+        }
+        let axis = egui::Stroke::new(1.0, egui::Color32::from_rgb(60, 90, 120));
+        painter.hline(rect.x_range(), rect.center().y, axis);
+        painter.vline(rect.center().x, rect.y_range(), axis);
+
+        // Field labels, when there is room for them
+        if rect.width() >= 360.0 {
+            let font = egui::FontId::monospace(9.0);
+            let color = egui::Color32::from_rgb(60, 85, 110);
+            for lon_field in 0..18u8 {
+                for lat_field in 0..18u8 {
+                    let label = format!(
+                        "{}{}",
+                        (b'A' + lon_field) as char,
+                        (b'A' + lat_field) as char
+                    );
+                    let center = to_screen(
+                        lat_field as f64 * 10.0 - 85.0,
+                        lon_field as f64 * 20.0 - 170.0,
+                    );
+                    painter.text(
+                        center,
+                        egui::Align2::CENTER_CENTER,
+                        label,
+                        font.clone(),
+                        color,
+                    );
+                }
+            }
+        }
+
+        for marker in self.markers {
+            let center = to_screen(marker.latitude, marker.longitude);
+            painter.circle_filled(center, marker.radius, marker.color);
+        }
+
+        // The marker nearest the pointer, if the pointer is on it
+        let hovered = response.hover_pos().and_then(|pos| {
+            self.markers
+                .iter()
+                .enumerate()
+                .map(|(i, m)| {
+                    (
+                        i,
+                        to_screen(m.latitude, m.longitude).distance(pos) - m.radius,
+                    )
+                })
+                .filter(|(_, gap)| *gap <= 3.0)
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i)
+        });
+        let clicked = hovered.filter(|_| response.clicked());
+        if let Some(i) = hovered {
+            response.on_hover_text(&self.markers[i].label);
+        }
+        clicked
+    }
+}